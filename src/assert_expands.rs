@@ -0,0 +1,150 @@
+use crate::{
+	duplicate_impl,
+	error::Error,
+	pretty_errors::ASSERT_EXPANDS,
+	token_iter::{is_ident, TokenIter},
+	Result, SubstitutionGroup,
+};
+use proc_macro::{Delimiter, Spacing, Span, TokenStream, TokenTree};
+
+/// The number of tokens shown around the first difference.
+const CONTEXT_TOKENS: usize = 8;
+
+/// Implements `assert_duplicate_expands`.
+///
+/// Duplicates the body according to the invocation and compares the result to
+/// the expected tokens, ignoring spans and spacing (unless `expect exactly`).
+/// Expands to nothing if they match.
+pub(crate) fn assert_expands(stream: TokenStream) -> Result<TokenStream>
+{
+	let mut iter = TokenIter::new(stream, SubstitutionGroup::empty(), std::iter::empty());
+	let (invocation, _) = iter
+		.next_group(Some(Delimiter::Bracket))
+		.map_err(|err| err.hint(ASSERT_EXPANDS))?;
+	let (body, _) = iter
+		.next_group(Some(Delimiter::Brace))
+		.map_err(|err| err.hint(ASSERT_EXPANDS))?;
+	iter.expect_simple(|t| is_ident(t, Some("expect")), Some("'expect'"))
+		.map_err(|err| err.hint(ASSERT_EXPANDS))?;
+	let exact = iter
+		.extract_simple(|t| is_ident(t, Some("exactly")), |_| (), None)
+		.is_ok();
+	let (expected, expected_span) = iter
+		.next_group(Some(Delimiter::Brace))
+		.map_err(|err| err.hint(ASSERT_EXPANDS))?;
+	if let Some(token) = iter.next_fallible()?
+	{
+		return Err(Error::new("Unexpected token.")
+			.span(token.span())
+			.found(token.describe()));
+	}
+
+	let expansion = duplicate_impl(invocation.to_token_stream(), body.to_token_stream())?;
+
+	let mut found = Vec::new();
+	flatten(expansion, exact, &mut found);
+	let mut wanted = Vec::new();
+	flatten(expected.to_token_stream(), exact, &mut wanted);
+
+	match found
+		.iter()
+		.zip(wanted.iter())
+		.position(|((found, _), (wanted, _))| found != wanted)
+		.or_else(|| (found.len() != wanted.len()).then(|| found.len().min(wanted.len())))
+	{
+		None => Ok(TokenStream::new()),
+		Some(index) =>
+		{
+			let span = wanted.get(index).map_or(expected_span, |(_, span)| *span);
+			Err(Error::new(format!(
+				"The expansion differs from the expected tokens at token {}.",
+				index + 1
+			))
+			.span(span)
+			.hint(format!(
+				"Expected: {}\nFound:    {}",
+				excerpt(&wanted, index),
+				excerpt(&found, index)
+			)))
+		},
+	}
+}
+
+/// Flattens the given tokens into the text of each token, where delimited
+/// groups are given by their delimiters around their contents and
+/// None-delimited groups by only their contents.
+///
+/// If `exact`, None-delimited groups are given by `⟦` and `⟧` around their
+/// contents, and punctuation joined to the next token by `(joint)` after it.
+fn flatten(stream: TokenStream, exact: bool, result: &mut Vec<(String, Span)>)
+{
+	for token in stream
+	{
+		match token
+		{
+			TokenTree::Group(group) =>
+			{
+				let (open, close) = match group.delimiter()
+				{
+					Delimiter::Parenthesis => ("(", ")"),
+					Delimiter::Brace => ("{", "}"),
+					Delimiter::Bracket => ("[", "]"),
+					Delimiter::None if exact => ("⟦", "⟧"),
+					Delimiter::None => ("", ""),
+				};
+				if !open.is_empty()
+				{
+					result.push((open.into(), group.span_open()));
+				}
+				flatten(group.stream(), exact, result);
+				if !close.is_empty()
+				{
+					result.push((close.into(), group.span_close()));
+				}
+			},
+			TokenTree::Punct(p) if exact && p.spacing() == Spacing::Joint =>
+			{
+				result.push((format!("{}(joint)", p), p.span()))
+			},
+			token => result.push((token.to_string(), token.span())),
+		}
+	}
+}
+
+/// Describes the tokens around the given index, marking the end with
+/// `<end>`.
+fn excerpt(tokens: &[(String, Span)], index: usize) -> String
+{
+	let start = index.saturating_sub(CONTEXT_TOKENS);
+	let end = (index + CONTEXT_TOKENS).min(tokens.len());
+	let mut result = if start > 0
+	{
+		String::from("... ")
+	}
+	else
+	{
+		String::new()
+	};
+	for (i, (token, _)) in tokens.iter().enumerate().take(end).skip(start)
+	{
+		if i == index
+		{
+			result.push_str(">>");
+		}
+		result.push_str(token);
+		result.push(' ');
+	}
+	if end < tokens.len()
+	{
+		result.push_str("...");
+	}
+	else
+	{
+		if index >= tokens.len()
+		{
+			result.push_str(">>");
+		}
+		result.push_str("<end>");
+	}
+	result
+}
@@ -0,0 +1,249 @@
+use crate::{error::Error, new_group, pretty_errors::EXPAND, token_iter::string_value, Result};
+use proc_macro::{Delimiter, Literal, Span, TokenStream, TokenTree};
+use std::path::PathBuf;
+
+/// The name of the macro expanding its argument eagerly in an invocation.
+const EXPAND_NAME: &str = "expand";
+
+/// Replaces every `expand!(..)` in the given invocation by the expansion of
+/// the macro it is given.
+///
+/// Proc macros can't invoke other macros, so only those the crate can expand
+/// itself are supported: `concat!`, `stringify!`, and `include!`.
+pub(crate) fn expand_eager(stream: TokenStream) -> Result<TokenStream>
+{
+	let mut tokens = stream.into_iter().peekable();
+	let mut result = TokenStream::new();
+	while let Some(token) = tokens.next()
+	{
+		match token
+		{
+			TokenTree::Ident(ident)
+				if ident.to_string() == EXPAND_NAME
+					&& matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '!') =>
+			{
+				let bang = tokens.next().unwrap();
+				match tokens.next()
+				{
+					Some(TokenTree::Group(group)) if group.delimiter() != Delimiter::None =>
+					{
+						result.extend(expand_macro(group.stream(), group.span())?);
+					},
+					_ =>
+					{
+						return Err(Error::new(format!(
+							"Expected the macro to expand in parentheses after '{}!'.",
+							EXPAND_NAME
+						))
+						.span(bang.span())
+						.hint(EXPAND))
+					},
+				}
+			},
+			TokenTree::Group(group) =>
+			{
+				result.extend(Some(TokenTree::Group(new_group(
+					group.delimiter(),
+					expand_eager(group.stream())?,
+					group.span(),
+				))));
+			},
+			token => result.extend(Some(token)),
+		}
+	}
+	Ok(result)
+}
+
+/// Expands the given macro invocation (e.g. `include!("rows.in")`), where
+/// `span` is that of the `expand!` argument.
+fn expand_macro(stream: TokenStream, span: Span) -> Result<TokenStream>
+{
+	let mut tokens = stream.into_iter();
+	let (name, args) = match (tokens.next(), tokens.next(), tokens.next(), tokens.next())
+	{
+		(
+			Some(TokenTree::Ident(name)),
+			Some(TokenTree::Punct(bang)),
+			Some(TokenTree::Group(args)),
+			None,
+		) if bang.as_char() == '!' => (name, args),
+		_ =>
+		{
+			return Err(
+				Error::new("Expected a macro invocation, e.g. 'include!(\"rows.in\")'.")
+					.span(span)
+					.hint(EXPAND),
+			)
+		},
+	};
+	match name.to_string().as_str()
+	{
+		"include" =>
+		{
+			let path = expand_eager(args.stream())?;
+			let (path, path_span) = match &path.into_iter().collect::<Vec<_>>()[..]
+			{
+				[TokenTree::Literal(lit)] => (string_value(lit), lit.span()),
+				_ => (None, args.span()),
+			};
+			let path = path.ok_or_else(|| {
+				Error::new("Expected the path of a file.")
+					.span(path_span)
+					.hint(EXPAND)
+			})?;
+			let full_path = std::env::var_os("CARGO_MANIFEST_DIR")
+				.map_or_else(PathBuf::new, PathBuf::from)
+				.join(&path);
+			let contents: TokenStream = std::fs::read_to_string(full_path)
+				.map_err(|err| {
+					Error::new(format!("Failed to read '{}': {}", path, err))
+						.span(path_span)
+						.hint(EXPAND)
+				})?
+				.parse()
+				.map_err(|_| {
+					Error::new(format!("Invalid tokens in '{}'.", path)).span(path_span)
+				})?;
+			expand_eager(contents)
+		},
+		"stringify" =>
+		{
+			let mut lit = Literal::string(&args.stream().to_string());
+			lit.set_span(name.span());
+			Ok(TokenTree::Literal(lit).into())
+		},
+		"concat" =>
+		{
+			let mut text = String::new();
+			for mut arg in split_arguments(expand_eager(args.stream())?)
+			{
+				// Like with 'concat!', the arguments may themselves be supported macros
+				if matches!(&arg[..], [TokenTree::Ident(_), TokenTree::Punct(bang), TokenTree::Group(_)]
+					if bang.as_char() == '!')
+				{
+					let span = arg[0].span();
+					arg = expand_macro(arg.into_iter().collect(), span)?
+						.into_iter()
+						.collect();
+				}
+				match &arg[..]
+				{
+					[TokenTree::Literal(lit)] =>
+					{
+						text += &escaped_contents(lit).unwrap_or_else(|| literal_text(lit));
+					},
+					[TokenTree::Punct(minus), TokenTree::Literal(lit)]
+						if minus.as_char() == '-' =>
+					{
+						text.push('-');
+						text += &literal_text(lit);
+					},
+					[TokenTree::Ident(ident)]
+						if ident.to_string() == "true" || ident.to_string() == "false" =>
+					{
+						text += &ident.to_string();
+					},
+					_ =>
+					{
+						let span = arg.first().map_or(args.span(), TokenTree::span);
+						return Err(Error::new("Expected a literal to concatenate.")
+							.span(span)
+							.hint(EXPAND));
+					},
+				}
+			}
+			let mut lit: Literal = format!("\"{}\"", text).parse().map_err(|_| {
+				Error::new("Concatenation produced an invalid string.")
+					.span(name.span())
+					.hint(EXPAND)
+			})?;
+			lit.set_span(name.span());
+			Ok(TokenTree::Literal(lit).into())
+		},
+		other =>
+		{
+			Err(Error::new(format!(
+				"'{}!' can't be expanded by '{}!', which only supports 'concat!', 'stringify!', \
+				 and 'include!'.",
+				other, EXPAND_NAME
+			))
+			.span(name.span())
+			.hint(EXPAND))
+		},
+	}
+}
+
+/// Splits the given tokens at every top-level `,`, ignoring a trailing one.
+fn split_arguments(stream: TokenStream) -> Vec<Vec<TokenTree>>
+{
+	let mut result = vec![Vec::new()];
+	for token in stream
+	{
+		match token
+		{
+			TokenTree::Punct(p) if p.as_char() == ',' => result.push(Vec::new()),
+			token => result.last_mut().unwrap().push(token),
+		}
+	}
+	if result.last().map_or(false, Vec::is_empty)
+	{
+		result.pop();
+	}
+	result
+}
+
+/// The contents of the given literal, escaped for a string literal, if it is
+/// a string, raw string, or character literal.
+fn escaped_contents(lit: &Literal) -> Option<String>
+{
+	let text = lit.to_string();
+	if let Some(raw) = text.strip_prefix('r')
+	{
+		let raw = raw.trim_matches('#');
+		let raw = raw.strip_prefix('"')?.strip_suffix('"')?;
+		Some(raw.replace('\\', "\\\\").replace('"', "\\\""))
+	}
+	else if let Some(string) = text.strip_prefix('"')
+	{
+		string.strip_suffix('"').map(String::from)
+	}
+	else if let Some(character) = text.strip_prefix('\'')
+	{
+		let character = character.strip_suffix('\'')?;
+		Some(
+			if character == "\""
+			{
+				"\\\"".into()
+			}
+			else
+			{
+				character.into()
+			},
+		)
+	}
+	else
+	{
+		None
+	}
+}
+
+/// The text of the given numeric literal without its suffix (e.g. `1` for
+/// `1u8`), like `concat!` gives it.
+fn literal_text(lit: &Literal) -> String
+{
+	let text = lit.to_string();
+	for suffix in [
+		"u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+		"f32", "f64",
+	]
+	{
+		if let Some(number) = text.strip_suffix(suffix)
+		{
+			if !number.is_empty() && !text.starts_with("0x")
+			{
+				return number.trim_end_matches('_').to_string();
+			}
+		}
+	}
+	text
+}
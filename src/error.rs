@@ -4,6 +4,10 @@ use proc_macro2::Span as Span2;
 #[cfg(feature = "pretty_errors")]
 use proc_macro2_diagnostics::{Diagnostic, Level};
 
+/// The maximum number of characters of the found token(s) to include in the
+/// panic message.
+const MAX_FOUND_LENGTH: usize = 32;
+
 /// Used to report errors.
 ///
 /// When 'pretty_errors' isn't enabled, simply includes a basic message.
@@ -17,13 +21,35 @@ pub struct Error
 	/// Will always be reported (first).
 	msg: String,
 
+	/// Whether the error should be reported even when the parser could try
+	/// parsing the input differently.
+	fatal: bool,
+
+	/// A short rendering of the token(s) that caused the error.
+	///
+	/// Included in the panic message, as it has no span to show them.
+	found: Option<String>,
+
+	/// A description of what was expected instead of the token(s) found.
+	///
+	/// Included in the panic message, as it has no hint.
+	expected: Option<String>,
+
 	/// The source of the error
+	///
+	/// If none is given, the call site is used.
 	#[cfg(feature = "pretty_errors")]
-	span: Span,
+	span: Option<Span>,
 
 	/// Additional error details and help
 	#[cfg(feature = "pretty_errors")]
 	hint: String,
+
+	/// A note pointing at other relevant code
+	///
+	/// Boxed, as it is rarely given and errors are returned everywhere.
+	#[cfg(feature = "pretty_errors")]
+	note: Option<Box<(Span, String)>>,
 }
 
 impl Error
@@ -35,13 +61,22 @@ impl Error
 		{
 			Self {
 				msg: msg.into(),
-				span: Span::call_site(),
+				fatal: false,
+				found: None,
+				expected: None,
+				span: None,
 				hint: "".to_string(),
+				note: None,
 			}
 		}
 		#[cfg(not(feature = "pretty_errors"))]
 		{
-			Self { msg: msg.into() }
+			Self {
+				msg: msg.into(),
+				fatal: false,
+				found: None,
+				expected: None,
+			}
 		}
 	}
 
@@ -54,7 +89,22 @@ impl Error
 	{
 		#[cfg(feature = "pretty_errors")]
 		{
-			self.span = span;
+			self.span = Some(span);
+		}
+		self
+	}
+
+	/// Adds a span to the error if it doesn't have one yet and returns it.
+	///
+	/// If `pretty_errors` is disabled, does nothing.
+	#[cfg(feature = "nested_invocation")]
+	#[allow(unused_variables)]
+	#[allow(unused_mut)]
+	pub fn span_if_unset(mut self, span: Span) -> Self
+	{
+		#[cfg(feature = "pretty_errors")]
+		{
+			self.span.get_or_insert(span);
 		}
 		self
 	}
@@ -73,36 +123,220 @@ impl Error
 		self
 	}
 
-	/// Returns the source span of the error
-	/// (or a stub value if the `pretty_errors` feature is disabled).
-	pub fn get_span(&self) -> Span
+	/// Adds a note with the given span to the error, replacing any previous
+	/// one, and returns it.
+	///
+	/// If `pretty_errors` is disabled, does nothing.
+	#[allow(unused_variables)]
+	#[allow(unused_mut)]
+	#[cfg_attr(not(feature = "nested_invocation"), allow(dead_code))]
+	pub fn note(mut self, span: Span, note: impl Into<String>) -> Self
 	{
 		#[cfg(feature = "pretty_errors")]
 		{
-			self.span
-		}
-		#[cfg(not(feature = "pretty_errors"))]
-		{
-			Span::call_site()
+			self.note = Some(Box::new((span, note.into())));
 		}
+		self
+	}
+
+	/// Adds the token(s) that caused the error and returns it.
+	///
+	/// Long renderings are shortened.
+	pub fn found(mut self, found: impl ToString) -> Self
+	{
+		let found = found.to_string();
+		self.found = Some(
+			if found.chars().count() > MAX_FOUND_LENGTH
+			{
+				found.chars().take(MAX_FOUND_LENGTH).collect::<String>() + "..."
+			}
+			else
+			{
+				found
+			},
+		);
+		self
 	}
 
-	/// Returns the message of the error.
+	/// Adds a description of what was expected instead and returns it.
+	pub fn expected(mut self, expected: impl Into<String>) -> Self
+	{
+		self.expected = Some(expected.into());
+		self
+	}
+
+	/// Replaces the message of the error, keeping everything else, and returns
+	/// it.
+	pub fn rephrase(mut self, msg: impl Into<String>) -> Self
+	{
+		self.msg = msg.into();
+		self
+	}
+
+	/// Marks the error as fatal and returns it.
+	///
+	/// A fatal error must be reported instead of trying to parse the input
+	/// differently.
+	pub fn fatal(mut self) -> Self
+	{
+		self.fatal = true;
+		self
+	}
+
+	/// Whether the error is fatal.
+	pub fn is_fatal(&self) -> bool
+	{
+		self.fatal
+	}
+
+	/// Adds the given context before the error's message and returns it.
+	pub fn context(mut self, context: impl AsRef<str>) -> Self
+	{
+		self.msg = format!("{}{}", context.as_ref(), self.msg);
+		self
+	}
+
+	/// Returns the message of the error, including what was found and expected
+	/// if known.
 	#[cfg(not(feature = "pretty_errors"))]
 	pub fn into_panic_message(self) -> String
 	{
-		self.msg
+		let details = match (self.found, self.expected)
+		{
+			(Some(found), Some(expected)) => format!("found '{}', expected {}", found, expected),
+			(Some(found), None) => format!("found '{}'", found),
+			(None, Some(expected)) => format!("expected {}", expected),
+			(None, None) => return self.msg,
+		};
+		format!(
+			"{} ({}).",
+			self.msg.strip_suffix('.').unwrap_or(&self.msg),
+			details
+		)
 	}
 
 	#[cfg(feature = "pretty_errors")]
 	/// Converts the error into a [`Diagnostic`] ready for emitting.
 	pub fn into_diagnostic(self) -> Diagnostic
 	{
-		let mut diagnostic = Diagnostic::spanned(Span2::from(self.span), Level::Error, self.msg);
-		if !self.hint.is_empty()
+		self.into_diagnostic_with_level(Level::Error)
+	}
+
+	#[cfg(feature = "pretty_errors")]
+	/// Converts the error into a [`Diagnostic`] of the given level (e.g. a
+	/// warning) ready for emitting.
+	pub fn into_diagnostic_with_level(self, level: Level) -> Diagnostic
+	{
+		let span = self.span.unwrap_or_else(Span::call_site);
+		#[cfg(feature = "nightly_spans")]
+		let hint = match excerpt(span)
+		{
+			Some(excerpt) if self.hint.is_empty() => excerpt,
+			Some(excerpt) => format!("{}\n{}", self.hint.trim_end(), excerpt),
+			None => self.hint,
+		};
+		#[cfg(not(feature = "nightly_spans"))]
+		let hint = self.hint;
+		let mut diagnostic = Diagnostic::spanned(Span2::from(span), level, self.msg);
+		if let Some(note) = self.note
 		{
-			diagnostic = diagnostic.help(self.hint);
+			let (span, note) = *note;
+			diagnostic = diagnostic.span_note(Span2::from(span), note);
+		}
+		if !hint.is_empty()
+		{
+			diagnostic = diagnostic.help(hint);
 		}
 		diagnostic
 	}
 }
+
+/// Renders the line of the invocation's source containing the start of the
+/// given span, with the span marked (see `render_excerpt`).
+///
+/// Returns `None` if the source can't be read, e.g. if the span isn't in a
+/// local file.
+#[cfg(feature = "nightly_spans")]
+#[rustversion::since(1.88)]
+fn excerpt(span: Span) -> Option<String>
+{
+	let (start, end) = (span.start(), span.end());
+	let source = std::fs::read_to_string(span.local_file()?).ok()?;
+	let line = source.lines().nth(start.line().checked_sub(1)?)?;
+	let end_column = if end.line() == start.line()
+	{
+		end.column()
+	}
+	else
+	{
+		// Only the first line is shown, so mark the rest of it
+		line.chars().count() + 1
+	};
+	Some(render_excerpt(
+		start.line(),
+		line,
+		start.column(),
+		end_column,
+	))
+}
+
+/// Compilers before 1.88 can't locate spans in the source, so no excerpt is
+/// rendered.
+#[cfg(feature = "nightly_spans")]
+#[rustversion::before(1.88)]
+fn excerpt(_: Span) -> Option<String>
+{
+	None
+}
+
+/// Renders the given line of source with the given line number, marking the
+/// characters from the given start column up to the given end column (both
+/// 1-indexed) with '^'. At least one character is marked.
+///
+/// Tabs are rendered as 4 spaces, like the compiler does.
+#[cfg(feature = "nightly_spans")]
+fn render_excerpt(line_number: usize, line: &str, start_column: usize, end_column: usize)
+	-> String
+{
+	let width = |c: char| if c == '\t' { 4 } else { 1 };
+	let (before, marked): (Vec<_>, Vec<_>) = line
+		.chars()
+		.enumerate()
+		.filter(|(idx, _)| *idx + 1 < end_column.max(start_column + 1))
+		.map(|(idx, c)| (idx + 1 < start_column, c))
+		.partition(|(before, _)| *before);
+	let number = line_number.to_string();
+	format!(
+		"In the invocation:\n{0} |\n{1} | {2}\n{0} | {3}{4}",
+		" ".repeat(number.len()),
+		number,
+		line.replace('\t', "    "),
+		" ".repeat(before.iter().map(|(_, c)| width(*c)).sum()),
+		"^".repeat(marked.iter().map(|(_, c)| width(*c)).sum::<usize>().max(1)),
+	)
+}
+
+#[cfg(all(test, feature = "nightly_spans"))]
+mod tests
+{
+	use super::render_excerpt;
+
+	#[test]
+	fn render_excerpt_marks_span()
+	{
+		assert_eq!(
+			render_excerpt(5, "\tsecond [Wrapper<first>];", 18, 23),
+			"In the invocation:\n  |\n5 |     second [Wrapper<first>];\n  |                     \
+			 ^^^^^"
+		);
+	}
+
+	#[test]
+	fn render_excerpt_marks_at_least_one_character()
+	{
+		assert_eq!(
+			render_excerpt(12, "name;", 5, 5),
+			"In the invocation:\n   |\n12 | name;\n   |     ^"
+		);
+	}
+}
@@ -84,6 +84,121 @@
 //! The code inside substitutions can be arbitrary, as long as the expanded code
 //! is valid.
 //!
+//! Alternatively, `=>` can be used instead of `;` after the substitution
+//! identifiers and between the substitution groups, which some find makes it
+//! clearer where the rows begin. The same separator must be used throughout
+//! the invocation, except that the last group may still end with `;`:
+//!
+//! ```
+//! # trait IsMax {fn is_max(&self) -> bool;}
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   int_type  max_value =>
+//!   [ u8 ]    [ 255 ] =>
+//!   [ u16 ]   [ 65_535 ] =>
+//!   [ u32 ]   [ 4_294_967_295 ];
+//! )]
+//! impl IsMax for int_type {
+//!   fn is_max(&self) -> bool {
+//!     *self == max_value
+//!   }
+//! }
+//! # assert!(!42u8.is_max());
+//! # assert!(!42u16.is_max());
+//! # assert!(!42u32.is_max());
+//! ```
+//!
+//! Substitutions may be enclosed in `{}` instead of `[]`, which can be easier
+//! to read when the substitution itself uses brackets (e.g. array types). The
+//! delimiters don't become part of the substitution and can be mixed freely:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   name      array_type;
+//!   [Bytes]   { [u8; 4] };
+//!   {Words}   { [u16; 2] };
+//! )]
+//! struct name(array_type);
+//!
+//! # assert_eq!(std::mem::size_of::<Bytes>(), 4);
+//! # assert_eq!(std::mem::size_of::<Words>(), 4);
+//! ```
+//!
+//! The same goes for substitutions in the verbose syntax, global
+//! substitutions, and the arguments of parameterized substitutions.
+//!
+//! The `keep` keyword can be given instead of a substitution to keep the
+//! substitution identifier as-is, e.g. for a duplicate that should use the
+//! identifier itself:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   method     reference(ty);
+//!   [get]      [&ty];
+//!   [get_mut]  [&mut ty];
+//!   keep       [&ty];
+//! )]
+//! fn method(value: reference([u8])) -> reference([u8]) {
+//!   value
+//! }
+//! # assert_eq!(*method(&1), 1);
+//! ```
+//!
+//! In the verbose syntax, `keep` follows the substitution identifier, e.g.
+//! `[method keep]`. The kept identifier has the span of each of its uses and
+//! isn't substituted again by the [`recursive`](#invocation-options) option.
+//! `keep` can't keep the identifier of a parameterized substitution. A
+//! substitution of `[_]` is just `_`, like any other substitution.
+//!
+//! Identifiers immediately preceded by `$` are never substituted, as they are
+//! metavariables of a declarative macro (e.g. `$name` or `$crate`). This means
+//! `macro_rules!` definitions can be duplicated without their metavariables
+//! clashing with substitution identifiers.
+//!
+//! Doc comments (or other `#[doc]` attributes) before a substitution group
+//! document its duplicate, i.e. they are prepended to the duplicate made with
+//! the group. This also works for groups in the verbose syntax:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   name;
+//!   /// Tuned for small integers.
+//!   [Small];
+//!   /// Tuned for large integers.
+//!   [Large];
+//! )]
+//! pub struct name(u64);
+//! ```
+//!
+//! A row starting with `except` excludes the substitution groups with the
+//! same substitutions (ignoring spacing), e.g. to remove some of the
+//! combinations generated by [nested invocations](#nested-invocation):
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! # pub struct Wrapper<T>(T);
+//! #[duplicate_item(
+//!   src     dst;
+//!   [u8]    [u16];
+//!   [u16]   [u16];
+//!   [u16]   [u8];
+//!   except  [u16] [u16];
+//! )]
+//! impl From<Wrapper<src>> for Wrapper<dst> {
+//!   fn from(value: Wrapper<src>) -> Self {
+//!     Wrapper(value.0 as dst)
+//!   }
+//! }
+//! ```
+//!
+//! Each excluded group must be the same as at least one substitution group.
+//! Excluding every group is an error, unless the
+//! [`allow_empty`](#invocation-options) option is given, in which case the
+//! item is removed. Exclusion is only available in the short syntax.
+//!
 //! ## Parameterized Substitution
 //!
 //! Say we have a struct that wraps a vector and we want to give
@@ -142,7 +257,8 @@
 //! method declaration, we give it different types as arguments to construct
 //! either shared or mutable references.
 //! E.g. `reference([Self])` becomes `&Self` in the first duplicate and `&mut
-//! Self` in the second. An argument can be any code snippet inside `[]`.
+//! Self` in the second. An argument can be any code snippet inside `[]` (or
+//! `{}`).
 //!
 //! A substitution identifier can take any number of parameters.
 //! We can use this if we need to also provide the references with a lifetime:
@@ -193,6 +309,47 @@
 //! own. Our solution is therefore a hacking of the system and not a property of
 //! `duplicate_item` itself.
 //!
+//! Substitution identifiers can likewise be used as lifetimes in the
+//! duplicated code, e.g. `&'lt u8`. If the substitution is a whole lifetime
+//! (e.g. `['a]` or `['static]`) it replaces the lifetime, while an empty
+//! substitution (`[]`) removes it. Otherwise, the substitution is prefixed by
+//! the `'`, e.g. `[a]` produces `'a`:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   name      lt;
+//!   [named]   ['a];
+//!   [stat]    ['static];
+//!   [elided]  [];
+//! )]
+//! fn name<'a>(x: &'lt u8) -> &'lt u8 {
+//!   x
+//! }
+//! ```
+//!
+//! Visibilities can be substituted too, including by no visibility (i.e.
+//! private). Since an item must start with a valid visibility for
+//! [`duplicate_item`] to accept it, use the placeholder `pub(in vis)`, where
+//! `vis` is a substitution identifier. The whole placeholder is replaced by the
+//! substitution:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   name      vis;
+//!   [Private] [];
+//!   [Public]  [pub];
+//!   [Crate]   [pub(crate)];
+//! )]
+//! pub(in vis) struct name {
+//!   pub(in vis) field: u8,
+//! }
+//! ```
+//!
+//! This also works with [`duplicate`], where `vis` can also be used on its
+//! own.
+//!
 //! ## Nested Invocation
 //!
 //! Imagine we have the following trait with the method `is_negative` that
@@ -287,6 +444,8 @@
 //! remove the last bit of repetition:
 //!
 //! ```
+//! # #[cfg(feature="nested_invocation")] // Ensure test is only run if feature is on
+//! # {
 //! # trait IsNegative { fn is_negative(&self) -> bool;}
 //! # use duplicate::duplicate_item;
 //! #[duplicate_item(
@@ -316,6 +475,7 @@
 //! assert!(!42i8.is_negative());
 //! assert!(!42i16.is_negative());
 //! assert!(!42i32.is_negative());
+//! # }
 //! ```
 //!
 //! We use `duplicate!{..}` to invoke the macro inside itself.
@@ -325,6 +485,9 @@
 //! invocation, and actually expands to it as an intermediate step before
 //! expanding the outer-most invocation.
 //!
+//! Nested invocations need the `nested_invocation` feature, which is enabled
+//! by default (see [Crate Features](#crate-features)).
+//!
 //! Deeper levels of nested invocation are possible and work as expected.
 //! There is no limit on the depth of nesting, however, as might be clear from
 //! our example, it can get complicated to read.
@@ -335,6 +498,8 @@
 //! do the following:
 //!
 //! ```
+//! # #[cfg(feature="nested_invocation")] // Ensure test is only run if feature is on
+//! # {
 //! # trait IsNegative { fn is_negative(&self) -> bool;}
 //! # use duplicate::duplicate_item;
 //! #[duplicate_item(
@@ -357,12 +522,14 @@
 //! # assert!(!42u16.is_negative());
 //! # assert!(!42u32.is_negative());
 //! # assert!(!42i8.is_negative());
+//! # }
 //! ```
 //!
 //! In general, nested invocations can be used anywhere. However, note that
 //! nested invocations are only recognized by the identifier `duplicate`,
 //! followed by `!`, followed by a delimiter within which the nested invocation
-//! is. Therefore, care must be taken to ensure the surrounding code is correct
+//! is (other names can be added with the `nested_names` option). Therefore,
+//! care must be taken to ensure the surrounding code is correct
 //! after the expansion. E.g. maybe `;` is needed after the invocation, or
 //! commas must be produced by the nested invocation itself as part of a list.
 //!
@@ -441,6 +608,68 @@
 //! in the annotated example, the third group has the `max_value` identifier
 //! before `int_type` without having any effect on the expanded code.
 //!
+//! The exception is identifiers declared `optional`, which only the groups
+//! needing them have to define. An optional identifier may only be used
+//! where the groups that don't define it never reach, e.g. inside the argument
+//! of a parameterized substitution that those groups drop:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   [
+//!     name                [Checked]
+//!     when_checked(code)  [code]
+//!     optional limit      [100]
+//!   ]
+//!   [
+//!     name                [Unchecked]
+//!     when_checked(code)  []
+//!   ]
+//! )]
+//! fn name(value: u32) -> u32 {
+//!   when_checked([assert!(value <= limit)]);
+//!   value
+//! }
+//! # assert_eq!(Unchecked(200), 200);
+//! ```
+//!
+//! Using an optional identifier in a duplicate whose group doesn't define it
+//! is an error.
+//!
+//! Identifiers declared with `~` are case-preserving: they are also
+//! substituted where they are written in snake_case, PascalCase, or
+//! SCREAMING_SNAKE_CASE, by their substitution converted to the same case.
+//! This saves substitution identifiers that are just case variants of each
+//! other. The substitution must be a single identifier that is still one in
+//! every case (unlike e.g. `__`, which is empty in snake_case), and the
+//! identifier can't have parameters. Global substitutions can also be
+//! case-preserving. Case-preserving identifiers require the
+//! `module_disambiguation` feature, which converts between the cases:
+//!
+//! ```
+//! # #[cfg(feature="module_disambiguation")] // Ensure test is only run if feature is on
+//! # {
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   [ ~backend [sqlite] ]
+//!   [ ~backend [postgres] ]
+//! )]
+//! mod backend {
+//!   pub struct Backend;
+//!   pub const BACKEND: &str = stringify!(backend);
+//!   pub fn open() -> Backend {
+//!     Backend
+//!   }
+//! }
+//! # let _: postgres::Postgres = postgres::open();
+//! # assert_eq!(sqlite::SQLITE, "sqlite");
+//! # }
+//! ```
+//!
+//! The module is named `sqlite` in the first duplicate and defines `Sqlite` and
+//! `SQLITE`. Only whole identifiers are substituted, so e.g. `open_backend`
+//! would be kept as is.
+//!
 //! The verbose syntax is not very concise but it has some advantages over
 //! the short syntax in regards to readability. Using many identifiers and
 //! long substitutions can quickly become unwieldy in the short syntax.
@@ -454,6 +683,8 @@
 //! follows:
 //!
 //! ```
+//! # #[cfg(feature="nested_invocation")] // Ensure test is only run if feature is on
+//! # {
 //! # trait IsNegative { fn is_negative(&self) -> bool;}
 //! # use duplicate::duplicate_item;
 //! #[duplicate_item(
@@ -484,6 +715,7 @@
 //! assert!(!42i8.is_negative());
 //! assert!(!42i16.is_negative());
 //! assert!(!42i32.is_negative());
+//! # }
 //! ```
 //!
 //! It's important to notice that the nested invocation doesn't know it
@@ -492,6 +724,9 @@
 //! (`int_type_nested`) than in the code (`int_type`), because otherwise the
 //! nested invocation would substitute the substitution identifier too, instead
 //! of only substituting in the nested invocation's substitute.
+//! Nested invocations in a header that declare a substitution identifier of
+//! the enclosing invocation are therefore warned about by the
+//! `nested_shadowing` lint (see `lints`).
 //!
 //! Nested invocations must produce the syntax of their
 //! parent invocation. However, each invocation's private syntax is free
@@ -502,6 +737,39 @@
 //! [`macro@substitute`] is also available when using nested invocation, with
 //! the same behavior.
 //!
+//! Substitution groups produced independently, e.g. by two nested
+//! invocations, can be combined pairwise with `zip` followed by two `{}`
+//! groups, each containing substitution groups in either syntax. The first
+//! groups of each side are combined into the first substitution group, the
+//! second into the second, and so on. Both sides must therefore produce the
+//! same number of substitution groups, and may not substitute the same
+//! identifiers:
+//!
+//! ```
+//! # #[cfg(feature="nested_invocation")] // Ensure test is only run if feature is on
+//! # {
+//! # use duplicate::duplicate_item;
+//! # trait IsMax {fn is_max(&self) -> bool;}
+//! #[duplicate_item(
+//!   zip {
+//!     duplicate!{[ ty; [u8]; [u16] ] [ int_type [ty] ]}
+//!   } {
+//!     duplicate!{[ max; [255]; [65_535] ] [ max_value [max] ]}
+//!   }
+//! )]
+//! impl IsMax for int_type {
+//!   fn is_max(&self) -> bool {
+//!     *self == max_value
+//!   }
+//! }
+//! # assert!(255u8.is_max());
+//! # assert!(!42u16.is_max());
+//! # }
+//! ```
+//!
+//! `zip` comes after any [global substitutions](#global-substitutions) and
+//! takes the place of the substitution groups.
+//!
 //! ## Global Substitutions
 //!
 //! Say we have a function that takes two types as inputs and returns the same
@@ -596,18 +864,1070 @@
 //! }
 //! ```
 //!
-//! Here we duplicate the function to use either shared or mutable reference,
-//! while reusing `typ1` and `typ2` in both duplicates.
+//! Here we duplicate the function to use either shared or mutable reference,
+//! while reusing `typ1` and `typ2` in both duplicates.
+//!
+//! The following additional rules apply when using global substitutions:
+//!
+//! * All global substitutions must come before any short or verbose syntax
+//!   substitution groups.
+//! * Global substitution variable are __not__ substituted inside the bodies of
+//!   following substitutions. If that is needed, multiple invocations or the
+//!   [`recursive`](#invocation-options) option can be used.
+//! * All global substitutions must be separated by `;`, also when followed by
+//!   substitution groups.
+//! * [Nested invocations](#nested-invocation) following the global
+//!   substitutions in the invocation are substituted by them before being
+//!   expanded, like nested invocations in the body. E.g. a global substitution
+//!   can give a list of types that nested invocations producing substitution
+//!   groups use.
+//!
+//! ## Aliases
+//!
+//! A substitution identifier can be given an alias, which is substituted the
+//! same way as the identifier it is an alias of. This allows using short names
+//! in the body without making the invocation harder to read, or vice versa:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   alias t = int_type;
+//!   name          int_type;
+//!   [double_u8]   [u8];
+//!   [double_u16]  [u16];
+//! )]
+//! fn name(value: int_type) -> t {
+//!   value * 2
+//! }
+//! # fn main() { assert_eq!(double_u8(3), 6); }
+//! ```
+//!
+//! Aliases are declared as `alias name = ident;` after any
+//! [invocation options](#invocation-options) and before any global
+//! substitutions. An alias cannot be a substitution identifier itself and
+//! can be used wherever the identifier it is an alias of can, including as a
+//! global substitution or with arguments.
+//!
+//! ## Accumulators
+//!
+//! An accumulator is substituted in each duplicate by a fragment repeated for
+//! every substitution group up to and including the duplicate's, each
+//! repetition substituted with its group. This makes e.g. implementations for
+//! tuples of growing arity:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! trait Arity {
+//!   const ARITY: usize;
+//! }
+//! #[duplicate_item(
+//!   acc types += [ty,];
+//!   ty  count;
+//!   [A] [1];
+//!   [B] [2];
+//!   [C] [3];
+//! )]
+//! impl<types> Arity for (types) {
+//!   const ARITY: usize = count;
+//! }
+//! # fn main() { assert_eq!(<(u8, u16, u32) as Arity>::ARITY, 3); }
+//! ```
+//!
+//! Accumulators are declared as `acc name += [fragment];` after any
+//! [aliases](#aliases). An accumulator cannot be a substitution identifier
+//! itself and can't be used with [in-place
+//! duplication](#in-place-duplication).
+//!
+//! ## Environment Variables
+//!
+//! Instead of a substitution in brackets, `env(NAME)` substitutes the value of
+//! the environment variable `NAME` at the time of expansion, while
+//! `env(NAME, [default])` uses `default` if the variable isn't set:
+//!
+//! ```
+//! # use duplicate::substitute_item;
+//! #[substitute_item(
+//!   int_type env(DUPLICATE_DOC_INT_TYPE, [u64]);
+//! )]
+//! fn widen(value: u8) -> int_type {
+//!   value as int_type
+//! }
+//! # assert_eq!(widen(42), 42u64);
+//! ```
+//!
+//! This can be used for substitutions without arguments, be they global or in
+//! substitution groups of either syntax. Note that the compiler doesn't know
+//! the invocation depends on the environment variable, so changing only the
+//! variable may not cause recompilation.
+//!
+//! ## Eager Expansion
+//!
+//! Apart from nested invocations, macros in an invocation aren't expanded
+//! before it is parsed. `expand!(..)` anywhere in an invocation is replaced by
+//! the expansion of the macro it is given, which is done by the crate itself,
+//! so only the following macros are supported:
+//!
+//! * `include!("path")`: The tokens of the file at `path`, relative to the
+//!   invoking crate's manifest directory (not the invoking file, like
+//!   [`rows_from`](#invocation-options)).
+//! * `concat!(..)`: A string literal concatenating the given literals, like [`concat!`](https://doc.rust-lang.org/std/macro.concat.html).
+//! * `stringify!(..)`: A string literal of the given tokens, like [`stringify!`](https://doc.rust-lang.org/std/macro.stringify.html).
+//!
+//! Any other macro is an error. This allows e.g. sharing substitution groups
+//! between invocations:
+//!
+//! ```ignore
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   int_type  max_value;
+//!   [u8]      [255];
+//!   expand!(include!("tables/int_types.rows"))
+//! )]
+//! impl IsMax for int_type {
+//!   fn is_max(&self) -> bool {
+//!     *self == max_value
+//!   }
+//! }
+//! ```
+//!
+//! Like with environment variables, the compiler doesn't know the invocation
+//! depends on an included file, so changing only the file may not cause
+//! recompilation.
+//!
+//! ## Pattern Substitution
+//!
+//! Sometimes what varies between duplicates isn't an identifier but a short
+//! sequence of tokens in the body, like `Self::Assoc`. Instead of replacing it
+//! with a substitution identifier, the sequence itself can be substituted by
+//! declaring `pattern [tokens] => [substitution]`:
+//!
+//! ```
+//! # trait Trait { type Assoc; fn get(&self) -> Self::Assoc; }
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   [
+//!     name                      [shift_left]
+//!     pattern [Self::Assoc]  => [<T as Trait>::Assoc]
+//!     pattern [<<]           => [<<]
+//!   ]
+//!   [
+//!     name                      [shift_right]
+//!     pattern [Self::Assoc]  => [u32]
+//!     pattern [<<]           => [>>]
+//!   ]
+//! )]
+//! fn name<T: Trait<Assoc = u32>>(value: T) -> Self::Assoc {
+//!   value.get() << 2
+//! }
+//! # impl Trait for u32 { type Assoc = u32; fn get(&self) -> u32 { *self } }
+//! # assert_eq!(shift_left(1u32), 4);
+//! # assert_eq!(shift_right(8u32), 2);
+//! ```
+//!
+//! Patterns can be declared in verbose syntax substitution groups, where all
+//! groups must declare the same patterns, and as global substitutions, which
+//! end with `;`. A pattern matches the same identifiers, literals, and
+//! punctuation in the same order, where punctuation must also be joined the
+//! same way (e.g. `<<` doesn't match `< <`), except after its last token. A
+//! pattern can't contain groups delimited by `()`, `[]`, or `{}`, so a match
+//! never includes only part of a group. Where more than one pattern matches,
+//! the longest match is substituted. Matches take precedence over substitution
+//! identifiers, so the tokens of a match are never substituted otherwise.
+//!
+//! ## Invocation Options
+//!
+//! An invocation may start with options changing how it is expanded. Each
+//! option is its name followed by `;` and all options must come before any
+//! global substitutions or substitution groups. When duplicating, an option
+//! name followed by `;` may also be the only substitution identifier of a short
+//! syntax invocation (e.g. `columns; [u8]; [u16];`). It is then taken to be the
+//! substitution identifier if the rest of the invocation are valid short syntax
+//! substitution groups for it, and an option otherwise.
+//!
+//! The following options are available:
+//!
+//! * `attr_strings`: Substitution identifiers are also substituted inside
+//!   string literals in attributes. Only whole words are substituted and only
+//!   using substitution identifiers that take no arguments:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   attr_strings;
+//!   name    backend;
+//!   [Alpha] [alpha];
+//!   [Beta]  [beta];
+//! )]
+//! #[doc(alias = "backend")]
+//! struct name();
+//! ```
+//!
+//! The `Alpha` struct gets the alias `"alpha"` while `Beta` gets `"beta"`.
+//! String literals outside attributes are never substituted in.
+//!
+//! * `allow_empty`: The invocation may have no substitution groups, in which
+//!   case nothing is output, effectively removing the item. This is useful when
+//!   the substitution groups are produced by a nested invocation that may
+//!   produce none. Without the option, at least one substitution group must be
+//!   given. Only available when duplicating:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   allow_empty;
+//!   name;
+//! )]
+//! struct name();
+//! ```
+//!
+//! No struct is produced. With [in-place duplication](#in-place-duplication),
+//! only the marked fragment is removed.
+//!
+//! * `repeat(N)`: Makes `N` duplicates without any substitution groups. The
+//!   duplicates only differ by the [built-in
+//!   identifiers](#built-in-identifiers) like `__index__`. May be followed by
+//!   global substitutions but not by substitution groups. Only available when
+//!   duplicating:
+//!
+//! ```
+//! # use duplicate::duplicate;
+//! duplicate!{
+//!   [
+//!     repeat(4);
+//!     ty [u32];
+//!   ]
+//!   fn __concat__(get_, __index__)() -> ty {
+//!     __index__
+//!   }
+//! }
+//! # fn main() { assert_eq!(get_3(), 3) }
+//! ```
+//!
+//! * `interleave(by_item)`: When duplicating multiple items (with
+//!   [`duplicate!`](macro@duplicate)), all duplicates of each item are output
+//!   before the next item, instead of all items of each duplicate before the
+//!   next duplicate (`interleave(by_group)`, the default). This can be useful
+//!   when the order of the items matters. If the body can't be split into
+//!   items, it is duplicated as a whole. Only available when duplicating.
+//!
+//! * `columns`: The short syntax is given column by column instead of row by
+//!   row. Each substitution identifier is followed by `:` and its substitution
+//!   for every substitution group, ending with `;`. This makes it easier to add
+//!   a substitution identifier to many substitution groups. All identifiers
+//!   must have the same number of substitutions. Only available when
+//!   duplicating:
+//!
+//! ```
+//! # trait IsMax {fn is_max(&self) -> bool;}
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   columns;
+//!   int_type:  [u8]  [u16]    [u32];
+//!   max_value: [255] [65_535] [4_294_967_295];
+//! )]
+//! impl IsMax for int_type {
+//!   fn is_max(&self) -> bool {
+//!     *self == max_value
+//!   }
+//! }
+//! # assert!(!42u8.is_max());
+//! # assert!(!42u16.is_max());
+//! # assert!(!42u32.is_max());
+//! ```
+//!
+//! * `rows_from("path")`: Additional short syntax substitution groups are read
+//!   from the file at the given path, relative to the directory of the invoking
+//!   crate's `Cargo.toml`. The substitution identifiers are still given in the
+//!   invocation, and the file's substitution groups follow any given in the
+//!   invocation. E.g. with a file `tables/int_types.dup` containing `[u16]
+//!   [65_535]; [u32] [4_294_967_295];`, the following is equivalent to the
+//!   previous example:
+//!
+//! ```ignore
+//! #[duplicate_item(
+//!   rows_from("tables/int_types.dup");
+//!   int_type max_value;
+//!   [u8] [255];
+//! )]
+//! impl IsMax for int_type {
+//!   fn is_max(&self) -> bool {
+//!     *self == max_value
+//!   }
+//! }
+//! ```
+//!
+//! Errors in the file are reported with the path and the number of the
+//! substitution group they are in. The path may be any string literal, e.g. a
+//! raw string. Note that the compiler doesn't know the invocation depends on
+//! the file, so changing only the file doesn't cause recompilation. Where
+//! items are allowed, an item including the file makes the compiler track it,
+//! e.g. `const _: &[u8] = include_bytes!("../tables/int_types.dup");` in a
+//! file of the `src` directory. Only available when duplicating with the short
+//! syntax.
+//!
+//! * `continues`: The substitution groups of the short syntax are continued by
+//!   `duplicate_item_rows` attributes following the invocation, which are
+//!   consumed by it. Only the attributes before any other attribute (e.g.
+//!   another invocation stacked on the item) continue the invocation. Each
+//!   attribute contains any number of substitution groups, which follow those
+//!   given in the invocation (and precede any from `rows_from`). This allows
+//!   splitting long invocations, which can be easier to review. Only available
+//!   when duplicating with the short syntax:
+//!
+//! ```
+//! # trait IsMax {fn is_max(&self) -> bool;}
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   continues;
+//!   int_type max_value;
+//! )]
+//! #[duplicate_item_rows([u8] [255];)]
+//! #[duplicate_item_rows(
+//!   [u16] [65_535];
+//!   [u32] [4_294_967_295];
+//! )]
+//! impl IsMax for int_type {
+//!   fn is_max(&self) -> bool {
+//!     *self == max_value
+//!   }
+//! }
+//! # assert!(!42u8.is_max());
+//! # assert!(!42u16.is_max());
+//! # assert!(!42u32.is_max());
+//! ```
+//!
+//! * `strict`: Identifiers that differ from a substitution identifier only by
+//!   case (e.g. `Int_type` when the substitution identifier is `int_type`) are
+//!   errors instead of being left unchanged, as they are likely typos:
+//!
+//! ```compile_fail
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   strict;
+//!   int_type;
+//!   [u8];
+//! )]
+//! fn zero() -> Int_type {
+//!   0
+//! }
+//! ```
+//!
+//!   Nested invocations shadowing substitution identifiers are errors too. This
+//!   is the same as denying the `case_mismatch` and `nested_shadowing` lints
+//!   (see `lints`).
+//!
+//! * `lints(...)`: Sets the levels of lints, which check for likely mistakes in
+//!   invocations that still expand. Each level (`allow`, `warn`, or `deny`) is
+//!   followed by the names of the lints to give that level in parentheses, e.g.
+//!   `lints(deny(case_mismatch));`. Allowed lints are ignored, warnings are
+//!   only emitted by nightly compilers with `pretty_errors` enabled, and denied
+//!   lints fail the expansion with an error. The available lints are:
+//!
+//!   * `case_mismatch` (allowed by default): Identifiers that differ from a
+//!     substitution identifier only by case.
+//!   * `empty_substitution` (allowed by default): Empty substitutions in the
+//!     substitution groups of the short syntax and empty arguments of
+//!     parameterized substitutions, e.g. a forgotten `[]`.
+//!   * `nested_shadowing` (warned about by default): Substitution identifiers
+//!     of nested invocations in the invocation that are also substitution
+//!     identifiers of the invocation, which the nested invocations substitute
+//!     too.
+//!   * `single_group` (warned about by default): Duplicating invocations that
+//!     give only one substitution group and no global substitutions, which
+//!     should probably use `substitute_item` or `substitute!` instead. Groups
+//!     from `continues`, `rows_from`, or nested invocations, and invocations
+//!     using `expect` or `repeat`, aren't reported.
+//!
+//! * `module_name(index)`: With the `module_disambiguation` feature, modules
+//!   (and the other names it disambiguates) are postfixed with the index of
+//!   each duplicate's substitution group instead of a substitution. With
+//!   `module_name(auto)`, the index is only used if no substitution identifier
+//!   is suitable. Only available when duplicating:
+//!
+//! ```
+//! # #[cfg(feature="module_disambiguation")]
+//! # mod wrapper {
+//! #[duplicate::duplicate_item(
+//!   module_name(index);
+//!   int_type;
+//!   [Vec<u8>];
+//!   [Option<u16>];
+//! )]
+//! mod module {
+//!   pub type Alias = int_type;
+//! }
+//! # pub fn check(_: module_0::Alias, _: module_1::Alias) {}
+//! # }
+//! ```
+//!
+//!   With `module_name(sanitize(ident))`, the substitutions of the
+//!   substitution identifier `ident` are instead sanitized into postfixes by
+//!   joining their identifiers and literals in snake case with `_`, ignoring
+//!   any other tokens. The postfixes must start with a letter and be unique:
+//!
+//! ```
+//! # #[cfg(feature="module_disambiguation")]
+//! # mod wrapper {
+//! #[duplicate::duplicate_item(
+//!   module_name(sanitize(int_type));
+//!   int_type;
+//!   [Vec<u8>];
+//!   [Option<u16>];
+//! )]
+//! mod module {
+//!   pub type Alias = int_type;
+//! }
+//! # pub fn check(_: module_vec_u8::Alias, _: module_option_u16::Alias) {}
+//! # }
+//! ```
+//!
+//! * `module_names([..], ..)`: With the `module_disambiguation` feature, names
+//!   each duplicate's module (or `macro_rules!` macro) exactly, instead of
+//!   postfixing the name. The names are given in `[]` (or `{}`), one for each
+//!   substitution group, and must be unique identifiers. Other names that are
+//!   disambiguated, like struct fields, are postfixed with them. Only available
+//!   when duplicating:
+//!
+//! ```
+//! # #[cfg(feature="module_disambiguation")]
+//! # mod wrapper {
+//! #[duplicate::duplicate_item(
+//!   module_names([codec_u8], [codec_u16]);
+//!   int_type;
+//!   [u8];
+//!   [u16];
+//! )]
+//! mod module {
+//!   pub type Alias = int_type;
+//! }
+//! # pub fn check(_: codec_u8::Alias, _: codec_u16::Alias) {}
+//! # }
+//! ```
+//!
+//! * `explain_module_names`: With the `module_disambiguation` and
+//!   `pretty_errors` features, a note names the substitution identifier (or
+//!   other naming) module disambiguation uses and the module name of each
+//!   duplicate. Since the first suitable substitution identifier is chosen,
+//!   reordering them can change the names, which the note makes visible. Like
+//!   other notes, it is only given by nightly compilers. Only available when
+//!   duplicating.
+//!
+//! * `max_duplicates(N)`: The invocation may make at most `N` duplicates,
+//!   instead of the default of 10000, which guards against accidentally making
+//!   so many that the build seems to hang. Invocations may also output at most
+//!   1000000 tokens. The defaults can be changed for all invocations with the
+//!   `DUPLICATE_MAX_DUPLICATES` and `DUPLICATE_MAX_OUTPUT_TOKENS` environment
+//!   variables, though changing those doesn't make cargo rebuild. Only
+//!   available when duplicating:
+//!
+//! ```compile_fail
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   max_duplicates(2);
+//!   int_type;
+//!   [u8];
+//!   [u16];
+//!   [u32]; // Error: more than 2 duplicates
+//! )]
+//! pub type Alias = int_type;
+//! ```
+//!
+//! * `expect(N)`: The invocation must end up with exactly `N` substitution
+//!   groups, including any produced by nested invocations, continued rows, or
+//!   `rows_from`, otherwise it fails with an error. This guards against a
+//!   change to e.g. a nested table silently changing how many duplicates are
+//!   made. Only available when duplicating:
+//!
+//! ```compile_fail
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   expect(3);
+//!   int_type;
+//!   [u8];
+//!   [u16]; // Error: expected 3 substitution groups, found 2
+//! )]
+//! pub type Alias = int_type;
+//! ```
+//!
+//! * `require(idents)`: Every substitution group must define exactly the given
+//!   comma-separated substitution identifiers. Otherwise, the invocation fails
+//!   with an error naming the substitution group and the missing or extra
+//!   identifier, before anything is duplicated. This is useful when the groups
+//!   are produced by nested invocations, as e.g. a misspelled identifier in
+//!   them is otherwise reported in tokens that aren't in the source. Only
+//!   available when duplicating:
+//!
+//! ```compile_fail
+//! # use duplicate::duplicate;
+//! duplicate!{
+//!   [
+//!     require(int_type, implementation);
+//!     duplicate!{
+//!       [ty; [u8]; [u16]]
+//!       [
+//!         int_type      [ty]
+//!         implemenation [ty::MAX] // Error: misspelled required identifier
+//!       ]
+//!     }
+//!   ]
+//!   pub const MAX: int_type = implementation;
+//! }
+//! ```
+//!
+//! * `recursive`: Substitution identifiers in the output of substitutions are
+//!   also substituted, using the substitution group of the duplicate and the
+//!   global substitutions. Without the option, the output of a substitution is
+//!   used as is. A substitution may not end up using its own substitution
+//!   identifier, which is an error:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   recursive;
+//!   list [Vec<element>];
+//!   name     element       inner;
+//!   [Bytes]  [[inner; 2]]  [u8];
+//!   [Words]  [[inner; 4]]  [u16];
+//! )]
+//! pub type name = list;
+//! # let _: (Bytes, Words) = (Vec::<[u8; 2]>::new(), Vec::<[u16; 4]>::new());
+//! ```
+//!
+//!   `Bytes` is `Vec<[u8; 2]>` and `Words` is `Vec<[u16; 4]>`.
+//!
+//! * `protect_inner`: The headers of inner `duplicate_item` and
+//!   `substitute_item` attributes in the item (and of `duplicate!` and
+//!   `substitute!` without the [`nested_invocation`](#nested_invocation)
+//!   feature) aren't substituted. Neither are the substitution identifiers they
+//!   declare, in the item the attribute applies to, which ends with its first
+//!   `;` or `{}`. This lets inner invocations use the same substitution
+//!   identifiers as the outer one:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! # pub struct Small(u8);
+//! # pub struct Large(u64);
+//! #[duplicate_item(
+//!   protect_inner;
+//!   name     ty;
+//!   [Small]  [u8];
+//!   [Large]  [u64];
+//! )]
+//! impl name {
+//!   pub fn get(&self) -> ty {
+//!     self.0
+//!   }
+//!   #[duplicate_item(
+//!     method    ty;
+//!     [as_i32]  [i32];
+//!     [as_f64]  [f64];
+//!   )]
+//!   pub fn method(&self) -> ty {
+//!     self.0 as ty
+//!   }
+//! }
+//! # assert_eq!(Small(4).as_f64(), 4.0);
+//! ```
+//!
+//! * `positions(ident@position, ...)`: With the [`positions`](#positions)
+//!   feature, the item is parsed and the substitution identifier `ident` is
+//!   only substituted in the given position, which is one of:
+//!
+//!   * `type`: Types.
+//!   * `method`: The names of methods in method calls and of functions defined
+//!     in `impl` and `trait` blocks.
+//!   * `expr`: Expressions, except the types, patterns, and field names in
+//!     them.
+//!
+//!   An identifier can be given more than once to substitute it in more
+//!   positions, while identifiers that aren't given are substituted in all
+//!   positions:
+//!
+//! ```
+//! # #[cfg(feature="positions")] // Ensure test is only run if feature is on
+//! # {
+//! # struct Wrapper<T> { value: T }
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   positions(value@type);
+//!   value;
+//!   [u8];
+//!   [u64];
+//! )]
+//! impl Wrapper<value> {
+//!   fn get(&self) -> value {
+//!     let value: value = self.value;
+//!     value
+//!   }
+//! }
+//! # assert_eq!(Wrapper { value: 42u8 }.get(), 42);
+//! # }
+//! ```
+//!
+//!   Only the types are substituted, not the variable or field named `value`.
+//!   The contents of macro invocations can't be parsed, so identifiers in them
+//!   are substituted in all positions. If the item can't be parsed (e.g.
+//!   because it uses parameterized substitution identifiers where syntax is
+//!   expected), all positions are substituted and a warning is emitted (only
+//!   on nightly with `pretty_errors`).
+//!
+//! * `validate`: With the [`validate`](#validate) feature, the output of each
+//!   duplicate is parsed as items before it is emitted. If a duplicate isn't
+//!   valid, the error names its substitution group and the substitutions it
+//!   used, instead of the compiler pointing into the output of the invocation.
+//!   Valid duplicates are emitted unchanged:
+//!
+//! ```compile_fail
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   validate;
+//!   name     ty;
+//!   [Small]  [u8];
+//!   [Large]  [u64 u8]; // Error: The duplicate of substitution group 2 isn't valid Rust
+//! )]
+//! pub type name = ty;
+//! ```
+//!
+//! * `unique_names`: It is an error for two duplicates to define items with the
+//!   same name, instead of the compiler reporting that the name is defined
+//!   multiple times in the output of the invocation. The error names the item
+//!   and the substitution groups of the two duplicates. Only the names of the
+//!   items outside of other items are compared, found by the keyword before
+//!   them (e.g. `fn`), so e.g. the items of modules named differently in each
+//!   duplicate may have the same names. Items with a `cfg` attribute are
+//!   skipped, as not all of them might be compiled. Only available when
+//!   duplicating:
+//!
+//! ```compile_fail
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   unique_names;
+//!   ty;
+//!   [u8];
+//!   [u64];
+//! )]
+//! fn check(value: ty) {} // Error: The duplicates of substitution groups 1 and 2 both define 'check'
+//! ```
+//!
+//! * `wrap_in(name)` or `wrap_in(vis name)`: Puts all duplicates in a single
+//!   module with the given name and visibility (e.g. `pub(crate)`), which
+//!   starts with `use super::*;` so the duplicates can use what the module of
+//!   the invocation can. `super::` in the duplicates therefore refers to that
+//!   module's module. The wrapping module is emitted once, so it isn't
+//!   disambiguated, but modules in the duplicates still are by [module
+//!   disambiguation](#module_disambiguation). Only available when duplicating:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! pub struct Wrapper<T>(T);
+//! #[duplicate_item(
+//!   wrap_in(pub generated);
+//!   name    ty;
+//!   [Small] [u8];
+//!   [Large] [u64];
+//! )]
+//! pub type name = Wrapper<ty>;
+//! # fn main() {
+//! # let _: generated::Small = Wrapper(1u8);
+//! # let _: generated::Large = Wrapper(1u64);
+//! # }
+//! ```
+//!
+//! * `defer_as(name)` or `defer_as(pub name)`: Instead of emitting the
+//!   duplicates, emits a `macro_rules!` macro with the given name that expands
+//!   to them, so they are only compiled where it is invoked, e.g. in downstream
+//!   crates that each need only some of them. The substitutions are still done
+//!   by the invocation; only the emission is deferred. With `pub`, the macro is
+//!   `#[macro_export]`ed. Paths in the duplicates are resolved where the macro
+//!   is invoked, and the duplicates can't contain `$`. With `wrap_in`, the
+//!   macro expands to the wrapping module. Only available when duplicating:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[macro_use]
+//! mod core {
+//!   # use duplicate::duplicate_item;
+//!   #[duplicate_item(
+//!     defer_as(instantiate_backends);
+//!     name       id;
+//!     [Sqlite]   [1];
+//!     [Postgres] [2];
+//!   )]
+//!   pub struct name(pub [u8; id]);
+//! }
+//! mod backends {
+//!   instantiate_backends!();
+//! }
+//! # fn main() {
+//! # let _ = backends::Sqlite([0]);
+//! # let _ = backends::Postgres([0, 0]);
+//! # }
+//! ```
+//!
+//! * `nested_names(dup, sub)`: With the
+//!   [`nested_invocation`](#nested_invocation) feature, invocations of `dup!`
+//!   and `sub!` in the invocation and the code are also expanded as nested
+//!   `duplicate!` and `substitute!` invocations, respectively. This is needed
+//!   when the macros are re-exported under other names, e.g. by a facade crate.
+//!   A warning is emitted (only on nightly with `pretty_errors`) if a name is
+//!   also a substitution identifier, as it isn't substituted where it starts a
+//!   nested invocation.
+//!
+//! * `maybe_async` or `maybe_async(predicate)`: Makes two duplicates, the first
+//!   as written and the second without its asynchrony. In the second, `async`
+//!   is removed (along with the `move` of `async move {..}` blocks), as is
+//!   `.await`, and `impl Future<Output = T>` is replaced by `T`, including any
+//!   bounds following it (e.g. `+ Send`). Either no substitution groups or two
+//!   must be given, e.g. to name the duplicates differently. With a
+//!   configuration predicate, the items of the first duplicate are only
+//!   compiled if it holds (`#[cfg(predicate)]`) and those of the second if it
+//!   doesn't, so they may have the same names. Only available when duplicating
+//!   and can't be combined with `repeat` or in-place duplication:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   maybe_async;
+//!   name          read;
+//!   [load_async]  [read_async];
+//!   [load_sync]   [read_sync];
+//! )]
+//! async fn name(id: u32) -> u32 {
+//!   read(id).await + 1
+//! }
+//! # async fn read_async(id: u32) -> u32 { id }
+//! # fn read_sync(id: u32) -> u32 { id }
+//! assert_eq!(load_sync(1), 2);
+//!
+//! #[duplicate_item(maybe_async(feature = "async"))]
+//! pub async fn double(x: u32) -> u32 {
+//!   let doubled = async move { x * 2 };
+//!   doubled.await
+//! }
+//! ```
+//!
+//! * `auto_cfg(key = ident)`: Each duplicate's items are only compiled if the
+//!   configuration option `key` has the value that `ident` is substituted by in
+//!   the duplicate's substitution group (`#[cfg(key = "value")]`), so
+//!   duplicates may have the same names. The substitution must be a single
+//!   identifier or literal, where a string literal's contents are used as the
+//!   value. Only available when duplicating and can't be used with in-place
+//!   duplication:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   auto_cfg(target_os = os);
+//!   os         sep;
+//!   [windows]  ['\\'];
+//!   [linux]    ['/'];
+//!   ["macos"]  ['/'];
+//! )]
+//! pub const SEPARATOR: char = sep;
+//! ```
+//!
+//! ## In-Place Duplication
+//!
+//! Some things cannot be duplicated by annotating them, e.g. enum variants, as
+//! macros cannot be invoked in their position. Instead, the item containing
+//! them can be annotated and the fragment to duplicate marked with
+//! `#[duplicate::here]`:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   name    ty;
+//!   [Small] [u8];
+//!   [Large] [u64];
+//! )]
+//! enum Value {
+//!   Empty,
+//!   #[duplicate::here]
+//!   name(ty),
+//! }
+//!
+//! #[duplicate_item(
+//!   name    ty;
+//!   [Small] [u8];
+//!   [Large] [u64];
+//! )]
+//! fn to_u64(value: Value) -> u64 {
+//!   match value {
+//!     Value::Empty => 0,
+//!     #[duplicate::here]
+//!     Value::name(v) => v as u64,
+//!   }
+//! }
+//! # assert_eq!(to_u64(Value::Large(42)), 42);
+//! ```
+//!
+//! Which expands to:
+//!
+//! ```
+//! enum Value {
+//!   Empty,
+//!   Small(u8),
+//!   Large(u64),
+//! }
+//!
+//! fn to_u64(value: Value) -> u64 {
+//!   match value {
+//!     Value::Empty => 0,
+//!     Value::Small(v) => v as u64,
+//!     Value::Large(v) => v as u64,
+//!   }
+//! }
+//! # assert_eq!(to_u64(Value::Large(42)), 42);
+//! ```
+//!
+//! When an item contains a marker, the item itself is not duplicated. Instead,
+//! the marked fragment, which ends at the next `,` (or the end of the enclosing
+//! delimiters), is duplicated once for each substitution group, separated by
+//! `,`. The rest of the item only has global substitutions applied.
+//! Markers in the body of a nested invocation belong to the nested invocation.
+//!
+//! Struct fields, function parameters, and fields in struct expressions can be
+//! duplicated the same way. Duplicated named fields (i.e. `name: ..`) must get
+//! unique names, so if a field's name isn't a substitution identifier, the
+//! `module_disambiguation` feature is used to give each duplicate a unique name
+//! (see [`module_disambiguation`](#module_disambiguation)):
+//!
+//! ```
+//! # #[cfg(feature="module_disambiguation")] // Ensure test is only run if feature is on
+//! # {
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   int_type;
+//!   [u8];
+//!   [u16];
+//! )]
+//! struct Values {
+//!   #[duplicate::here]
+//!   value: int_type,
+//! }
+//! let values = Values { value_u8: 1, value_u16: 2 };
+//! # }
+//! ```
+//!
+//! ## File Templates
+//!
+//! Code to duplicate can also be kept in a file of its own using
+//! [`duplicate_file`] and [`substitute_file`]. They take the path of the file,
+//! relative to the directory of the invoking crate's `Cargo.toml`, and the
+//! invocation, and emit the duplicates of the file's contents where they are
+//! called:
+//!
+//! ```ignore
+//! duplicate_file!("templates/backend.rs", [
+//!   backend    label;
+//!   [Sqlite]   ["sqlite"];
+//!   [Postgres] ["postgres"];
+//! ]);
+//! ```
+//!
+//! Errors reading or tokenizing the file are reported with its path. Unlike
+//! with `rows_from`, the compiler recompiles the invocation when the file
+//! changes.
+//!
+//! [`duplicate_file`]: macro.duplicate_file.html
+//! [`substitute_file`]: macro.substitute_file.html
+//!
+//! ## Built-in Identifiers
+//!
+//! The following identifiers are substituted in all duplicates, unless they
+//! are used as substitution identifiers:
+//!
+//! * `__index__`: The index of the duplicate's substitution group, starting
+//!   from 0, as an integer literal.
+//! * `__concat__(..)`: Concatenates its comma-separated arguments into one
+//!   identifier. The arguments may use substitution identifiers but must only
+//!   produce identifiers and integers.
+//!
+//! Together, they can give in-place duplicates unique names:
+//!
+//! ```
+//! # use duplicate::duplicate_item;
+//! #[duplicate_item(
+//!   ty;
+//!   [u8];
+//!   [u16];
+//!   [u32];
+//! )]
+//! fn sum(#[duplicate::here] __concat__(arg, __index__): ty) -> u64 {
+//!   [#[duplicate::here] __concat__(arg, __index__) as u64].iter().sum()
+//! }
+//! assert_eq!(sum(1, 2, 3), 6);
+//! ```
+//!
+//! Here, the function's parameters are `arg0: u8`, `arg1: u16`, and
+//! `arg2: u32`.
+//!
+//! * `__for_each__([..])`: Duplicates the code fragment in its `[]` (or `{}`)
+//!   in place, once for each substitution group, like [in-place
+//!   duplication](#in-place-duplication). The duplicates are separated by `,`,
+//!   and the last is followed by `,` only if the fragment ends with one. This
+//!   is useful e.g. for the fields of struct expressions, which only
+//!   [`duplicate!`](macro@duplicate) can duplicate this way, since
+//!   [`duplicate_item`](attr.duplicate_item.html) requires the item to be valid
+//!   Rust syntax:
+//!
+//! ```
+//! # use duplicate::duplicate;
+//! struct Example { small: u8, large: u64 }
+//!
+//! duplicate!{
+//!   [
+//!     member  ty;
+//!     [small] [u8];
+//!     [large] [u64];
+//!   ]
+//!   fn example() -> Example {
+//!     Example { __for_each__([member: 0 as ty,]) }
+//!   }
+//! }
+//! # assert_eq!(example().large, 0);
+//! ```
+//!
+//! * `__hash__`: A short identifier derived from the substitutions of the
+//!   duplicate's substitution group, which is the same every time the group is
+//!   expanded. Unlike `__index__`, it doesn't change when substitution groups
+//!   are added, removed, or reordered, so it can give duplicates names that
+//!   stay the same as the invocation is edited. Substitution groups with the
+//!   same substitutions (e.g. with [`repeat`](#invocation-options)) get the
+//!   same identifier, so combine it with `__index__` in that case:
+//!
+//! ```
+//! # use duplicate::duplicate;
+//! duplicate!{
+//!   [
+//!     ty;
+//!     [u8];
+//!     [u16];
+//!   ]
+//!   fn __concat__(zero_, __hash__)() -> ty {
+//!     0
+//!   }
+//! }
+//! ```
+//!
+//! * `__concat_str__(..)`: Concatenates its comma-separated arguments into one
+//!   string literal. String literals contribute their contents, while other
+//!   arguments, which may use substitution identifiers and the other built-in
+//!   identifiers, contribute their tokens as text, like
+//!   [`stringify!`](https://doc.rust-lang.org/std/macro.stringify.html). This
+//!   is useful e.g. for messages, documentation, and the names of tests:
+//!
+//! ```
+//! # use duplicate::duplicate;
+//! duplicate!{
+//!   [
+//!     ty;
+//!     [u8];
+//!     [u16];
+//!   ]
+//!   #[doc = __concat_str__("The name of ", ty, " (duplicate ", __index__, ").")]
+//!   fn __concat__(name_, ty)() -> &'static str {
+//!     __concat_str__("impl for ", ty, " backend")
+//!   }
+//! }
+//! assert_eq!(name_u16(), "impl for u16 backend");
+//! ```
+//!
+//! * `__item__`: The name of the duplicated item (or of the first item of the
+//!   code given to [`duplicate!`](macro@duplicate)) in each duplicate, which
+//!   must be a function, struct, enum, union, trait, type alias, constant,
+//!   static, module, or `macro_rules!` declaration. It is the name after
+//!   substitution and, for modules, after
+//!   [disambiguation](#module_disambiguation). This is useful for companion
+//!   code, such as implementations for the item or the names of its tests:
 //!
-//! The following additional rules apply when using global substitutions:
+//! ```
+//! # use duplicate::duplicate;
+//! duplicate!{
+//!   [
+//!     name    ty;
+//!     [Small] [u8];
+//!     [Large] [u64];
+//!   ]
+//!   pub struct name(ty);
+//!   impl Default for __item__ {
+//!     fn default() -> Self {
+//!       __item__(0)
+//!     }
+//!   }
+//!   fn __concat__(default_, __item__)() -> __item__ {
+//!     Default::default()
+//!   }
+//! }
+//! assert_eq!(default_Large().0, 0);
+//! ```
 //!
-//! * All global substitutions must come before any short or verbose syntax
-//!   substitution groups.
-//! * Global substitution variable are __not__ substituted inside the bodies of
-//!   following substitutions. If that is needed, multiple invocations can be
-//!   used.
-//! * All global substitutions must be separated by `;`, also when followed by
-//!   substitution groups.
+//! * `__first__([..])` and `__last__([..])`: The code fragment in the `[]` (or
+//!   `{}`), with its substitutions, in the first or last duplicate,
+//!   respectively, and nothing in the others. This is useful for code that must
+//!   only be generated once, before or after the duplicates:
+//!
+//! ```
+//! # use duplicate::duplicate;
+//! duplicate!{
+//!   [
+//!     name     ty;
+//!     [SMALL]  [u8];
+//!     [LARGE]  [u64];
+//!   ]
+//!   __first__([pub struct Sizes;])
+//!   pub const name: usize = std::mem::size_of::<ty>();
+//!   __last__([pub const COUNT: usize = __index__ + 1;])
+//! }
+//! assert_eq!(COUNT, 2);
+//! ```
+//!
+//! * `__all__(ident)` and `__all__(ident, [..])`: The substitutions of `ident`
+//!   in all substitution groups, separated by `,` or the tokens in the `[]` (or
+//!   `{}`). Groups that don't define an [optional](#verbose-syntax) `ident` are
+//!   skipped. It is substituted the same in every duplicate, so it is usually
+//!   combined with `__first__` or `__last__` to list all values once:
+//!
+//! ```
+//! # use duplicate::duplicate;
+//! duplicate!{
+//!   [
+//!     name     ty;
+//!     [SMALL]  [u8];
+//!     [LARGE]  [u64];
+//!   ]
+//!   pub const name: usize = std::mem::size_of::<ty>();
+//!   __last__([pub const ALL: [usize; 2] = [__all__(name)];])
+//! }
+//! assert_eq!(ALL, [1, 8]);
+//! ```
+//!
+//! * `__group_sub__(ident, index)`: The substitution of `ident` in the
+//!   substitution group with the given index. An index with a sign (e.g. `+1`
+//!   or `-1`) is relative to the duplicate's substitution group and wraps
+//!   around at either end, so `+1` gives the first group in the last duplicate.
+//!   An index without a sign is absolute and must be less than the number of
+//!   substitution groups. This allows duplicates to refer to their neighbours:
+//!
+//! ```
+//! # use duplicate::duplicate;
+//! pub struct Wrapper<T>(T);
+//! duplicate!{
+//!   [
+//!     int_type;
+//!     [u8];
+//!     [u16];
+//!     [u32];
+//!   ]
+//!   // From u8 to u16, u16 to u32, and u32 to u8
+//!   impl From<Wrapper<int_type>> for Wrapper<__group_sub__(int_type, +1)> {
+//!     fn from(value: Wrapper<int_type>) -> Self {
+//!       Wrapper(value.0 as _)
+//!     }
+//!   }
+//! }
+//! assert_eq!(Wrapper::<u16>::from(Wrapper(1u8)).0, 1);
+//! ```
 //!
 //! # Crate Features
 //!
@@ -673,7 +1993,14 @@
 //! produce a single identifier and nothing else. Those identifiers are then
 //! converted to snake case, and postfixed to the original module's name,
 //! e.g., `module  + u8 = module_u8`. The first suitable substitution
-//! identifier is chosen.
+//! identifier is chosen, skipping any whose identifiers aren't all different
+//! in snake case, as they wouldn't give unique names.
+//!
+//! If no such substitution identifier exists, the [`module_name`
+//! option](#invocation-options) can be used to postfix the index of each
+//! duplicate's substitution group instead, e.g. `module_0`, `module_1`, and
+//! `module_2`, or to sanitize the substitutions of a given substitution
+//! identifier, e.g. `Vec<u8>` into `module_vec_u8`.
 //!
 //! Notes:
 //!
@@ -682,18 +2009,83 @@
 //!   without bumping the major version.
 //! * Only the name of the module is substituted with the disambiguated name.
 //!   Any matching identifier in the body of the module is ignored.
+//! * `macro_rules!` macros are disambiguated the same way as modules, e.g.
+//!   duplicating `macro_rules! make {..}` results in `make_u8`, `make_u16`, and
+//!   `make_u32`. Substitution is still performed in the macro's rules, except
+//!   for metavariables (identifiers following `$`).
+//! * Struct fields duplicated in place (see [In-Place
+//!   Duplication](#in-place-duplication)) are disambiguated the same way, e.g.
+//!   `value: int_type` results in `value_u8: u8` and `value_u16: u16`.
+//! * Attributes between the invocation and the module (e.g. doc comments or
+//!   attributes following a `cfg_attr`-wrapped invocation) are skipped when
+//!   looking for the module's name.
 //!
 //! ### `pretty_errors`
 //! __More Detailed Error Messages__ (Enabled by default)
 //!
 //! Enabling this feature will make error messages indicate exactly where the
 //! offending code is. Without this feature, error messages will not provide
-//! detailed location indicators for errors.
+//! detailed location indicators for errors, but still mention the offending
+//! token where possible, e.g. `Unexpected token (found ';', expected a
+//! substitution identifier).`
+//!
+//! On nightly compilers, notes are additionally given for invocations that
+//! compile but could be improved, e.g. `duplicate_item` with only one
+//! substitution group, for which `substitute_item` is suggested.
 //!
 //! This feature is has no effect on expansion. Therefore, libraries are advised
 //! to keep this feature off (note that it's enabled by default)
 //! to avoid forcing it on users.
 //!
+//! ### `nested_invocation`
+//! __Nested Invocations__ (Enabled by default)
+//!
+//! Enables [nested invocations](#nested-invocation), i.e. `duplicate!` and
+//! `substitute!` being expanded by the invocation they are in. Without this
+//! feature, they are passed through like any other tokens, which avoids the
+//! overhead of looking for them and allows using other macros named
+//! `duplicate` or `substitute` in the code being duplicated.
+//!
+//! ### `positions`
+//! __Substitution Positions__ (Disabled by default)
+//!
+//! Enables the [`positions`](#invocation-options) invocation option, which
+//! restricts substitution identifiers to being substituted in some positions
+//! of the item, like types. This requires parsing the item with the `syn`
+//! crate, which this feature adds as a dependency.
+//!
+//! ### `validate`
+//! __Validating Duplicates__ (Disabled by default)
+//!
+//! Enables the [`validate`](#invocation-options) invocation option, which
+//! parses the output of each duplicate before emitting it to give errors
+//! naming the substitution group of invalid duplicates. Like `positions`, this
+//! adds the `syn` crate as a dependency.
+//!
+//! ### `nightly_spans`
+//! __Source Excerpts in Errors__ (Disabled by default)
+//!
+//! Enabling this feature (which also enables `pretty_errors`) adds an excerpt
+//! of the invocation to the help of errors, showing the line of the offending
+//! code with it marked. This needs compilers that can locate code in its
+//! source file (nightly, or stable 1.88 and later). On older compilers, or if
+//! the source file can't be read, errors are given without the excerpt.
+//!
+//! ### `trace`
+//! __Expansion Metrics__ (Disabled by default)
+//!
+//! Enabling this feature makes every invocation print a line to the compiler's
+//! standard error with its number of substitution groups, input and output
+//! tokens, nested invocations expanded, and the time taken, e.g.:
+//!
+//! ```text
+//! duplicate trace: 'duplicate' invocation with 3 substitution groups, 25 input tokens, 42 output tokens, and 0 nested invocations took 153.1µs
+//! ```
+//!
+//! This can help find the invocations that slow down compilation. Nested
+//! invocations print their own line too. Without this feature, no measuring
+//! is compiled into the crate.
+//!
 //! # Disclaimer
 //!
 //! This crate does not try to justify or condone the usage of code duplication
@@ -708,22 +2100,35 @@
 
 extern crate proc_macro;
 
+mod assert_expands;
 mod crate_readme_test;
+mod eager;
 mod error;
+mod limits;
+mod lint;
 #[cfg(feature = "module_disambiguation")]
 mod module_disambiguation;
 mod parse;
+#[cfg(feature = "positions")]
+mod positions;
 mod pretty_errors;
 mod substitute;
 mod token_iter;
+#[cfg(feature = "trace")]
+mod trace;
+#[cfg(feature = "validate")]
+mod validate;
 
 use crate::{
 	error::Error,
 	token_iter::{is_ident, Token, TokenIter},
 };
 use parse::*;
-use proc_macro::{Delimiter, Group, Ident, Span, TokenStream};
-use std::{collections::HashMap, iter::empty};
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use std::{
+	collections::{HashMap, HashSet},
+	iter::empty,
+};
 use substitute::*;
 
 /// Duplicates the item and substitutes specific identifiers for different code
@@ -762,7 +2167,9 @@ use substitute::*;
 /// Then a `;` seperated list of substitution groups must be given (at least 1
 /// group). Every group is a list of substitutions, one for each substitution
 /// identifier given in the first line.
-/// The substitutions must be enclosed in `[]` but are otherwise
+/// Alternatively, `=>` can be used instead of `;` throughout, except that the
+/// last group may still end with `;`.
+/// The substitutions must be enclosed in `[]` or `{}` but are otherwise
 /// free.
 ///
 /// # Verbose Syntax
@@ -839,7 +2246,7 @@ use substitute::*;
 /// each use. In the declaration a list of identifiers is given, which can be
 /// used in its substitutions. When using the identifier, argument code snippets
 /// must be given in a comma separated list, with each argument being inclosed
-/// in `[]`.
+/// in `[]` or `{}`.
 ///
 /// Parameterized substitution is also available for the verbose syntax:
 ///
@@ -869,6 +2276,8 @@ use substitute::*;
 ///
 /// # Nested Invocation
 /// ```
+/// # #[cfg(feature="nested_invocation")] // Ensure test is only run if feature is on
+/// # {
 /// use duplicate::duplicate_item;
 /// trait IsNegative {
 ///   fn is_negative(&self) -> bool;
@@ -894,6 +2303,7 @@ use substitute::*;
 /// assert!(!42u16.is_negative());
 /// assert!(!42u32.is_negative());
 /// assert!(!42i8.is_negative());
+/// # }
 /// ```
 ///
 /// This implements `IsNegative` 4 times:
@@ -940,6 +2350,8 @@ use substitute::*;
 /// Nested invocation is also available for the verbose syntax:
 ///
 /// ```
+/// # #[cfg(feature="nested_invocation")] // Ensure test is only run if feature is on
+/// # {
 /// use duplicate::duplicate_item;
 /// trait IsNegative {
 ///   fn is_negative(&self) -> bool;
@@ -968,6 +2380,7 @@ use substitute::*;
 /// assert!(!42u16.is_negative());
 /// assert!(!42u32.is_negative());
 /// assert!(!42i8.is_negative());
+/// # }
 /// ```
 ///
 /// ## Global Substitution
@@ -1058,6 +2471,48 @@ pub fn substitute_item(attr: TokenStream, item: TokenStream) -> TokenStream
 	}
 }
 
+/// Continues the substitution groups of a [`duplicate_item`] invocation that
+/// has the `continues` option.
+///
+/// Must follow the [`duplicate_item`] invocation it continues, which consumes
+/// it. On its own, it only produces an error.
+///
+/// ```
+/// # trait IsMax {fn is_max(&self) -> bool;}
+/// use duplicate::duplicate_item;
+///
+/// #[duplicate_item(
+///   continues;
+///   int_type  max_value;
+///   [ u8 ]    [ 255 ];
+/// )]
+/// #[duplicate_item_rows(
+///   [ u16 ]   [ 65_535 ];
+///   [ u32 ]   [ 4_294_967_295 ];
+/// )]
+/// impl IsMax for int_type {
+///   fn is_max(&self) -> bool {
+///     *self == max_value
+///   }
+/// }
+/// # assert!(!42u8.is_max());
+/// # assert!(!42u16.is_max());
+/// # assert!(!42u32.is_max());
+/// ```
+///
+/// [`duplicate_item`]: attr.duplicate_item.html
+#[proc_macro_attribute]
+pub fn duplicate_item_rows(_: TokenStream, _: TokenStream) -> TokenStream
+{
+	emit_error(
+		Error::new(
+			"'duplicate_item_rows' must follow a 'duplicate_item' invocation with the 'continues' \
+			 option.",
+		)
+		.hint(pretty_errors::CONTINUES),
+	)
+}
+
 /// Duplicates the given code and substitutes specific identifiers
 /// for different code snippets in each duplicate.
 ///
@@ -1129,6 +2584,49 @@ pub fn substitute_item(attr: TokenStream, item: TokenStream) -> TokenStream
 /// # assert!(!42u32.is_max());
 /// ```
 ///
+/// The invocation can instead be given after the code to duplicate, preceded by
+/// `@`, such that long code isn't preceded by its substitution groups:
+/// ```
+/// use duplicate::duplicate;
+/// duplicate!{
+///   pub struct name(ty);
+///   @ [
+///     name    ty;
+///     [Small] [u8];
+///     [Large] [u64];
+///   ]
+/// }
+/// # let _ = (Small(1), Large(2));
+/// ```
+/// The postfix form is only used if the code doesn't start with a `[]`, in
+/// which case that is the invocation, even if the code ends with `@ [...]`.
+/// Nested invocations may also use either form.
+///
+/// The code can also be split into template sections, given as `template`
+/// followed by a name and the section's code within `{}`. All sections are
+/// duplicated with the same substitution groups, and all the duplicates of a
+/// section come before those of the next. A section may give its own global
+/// substitutions within `[]` after its name, which only apply to it:
+/// ```
+/// use duplicate::duplicate;
+/// duplicate!{
+///   [
+///     name    ty;
+///     [Small] [u8];
+///     [Large] [u64];
+///   ]
+///   template struct_def {
+///     pub struct name(ty);
+///   }
+///   template constructor [ value [42]; ] {
+///     impl name {
+///       pub fn new() -> Self { name(value) }
+///     }
+///   }
+/// }
+/// # assert_eq!(Large::new().0, 42);
+/// ```
+///
 /// For more details on about invocations and features see [`duplicate_item`].
 ///
 /// [`duplicate_item`]: attr.duplicate_item.html
@@ -1177,18 +2675,124 @@ pub fn duplicate(stream: TokenStream) -> TokenStream
 ///
 /// The global substitutions (`typ1` and `typ2`) are substituted in both
 /// their occurrences. Global substitutions are `;` separated.
+///
+/// Like with [`duplicate`], the invocation may instead follow the code,
+/// preceded by `@`.
+///
+/// [`duplicate`]: macro.duplicate.html
 #[proc_macro]
 pub fn substitute(stream: TokenStream) -> TokenStream
 {
 	inline_macro_impl(stream, substitute_impl)
 }
 
+/// Duplicates the contents of a file.
+///
+/// This is a version of [`duplicate`] where the code to duplicate is read from
+/// a file instead of being given to the macro. The first argument is the path
+/// of the file, relative to the directory of the invoking crate's
+/// `Cargo.toml`, and the second is the duplication invocation within brackets:
+///
+/// ```ignore
+/// use duplicate::duplicate_file;
+///
+/// duplicate_file!("templates/backend.rs", [
+///   backend    label;
+///   [Sqlite]   ["sqlite"];
+///   [Postgres] ["postgres"];
+/// ]);
+/// ```
+///
+/// The file must contain valid Rust tokens, which are duplicated as if they had
+/// been given to [`duplicate`] after the invocation. The duplicates are emitted
+/// where `duplicate_file` is called, together with an anonymous constant
+/// that includes the file, such that the compiler recompiles the invocation
+/// when the file changes. Therefore, `duplicate_file` can only be called where
+/// items are allowed.
+///
+/// [`duplicate`]: macro.duplicate.html
+#[proc_macro]
+pub fn duplicate_file(stream: TokenStream) -> TokenStream
+{
+	file_macro_impl(stream, duplicate_impl)
+}
+
+/// Substitutes identifiers in the contents of a file.
+///
+/// This is a version of [`substitute`] where the code to substitute in is read
+/// from a file, just like [`duplicate_file`] is for [`duplicate`]:
+///
+/// ```ignore
+/// use duplicate::substitute_file;
+///
+/// substitute_file!("templates/backend.rs", [
+///   backend [Sqlite];
+///   label ["sqlite"];
+/// ]);
+/// ```
+///
+/// [`substitute`]: macro.substitute.html
+/// [`duplicate_file`]: macro.duplicate_file.html
+/// [`duplicate`]: macro.duplicate.html
+#[proc_macro]
+pub fn substitute_file(stream: TokenStream) -> TokenStream
+{
+	file_macro_impl(stream, substitute_impl)
+}
+
+/// Asserts that an invocation expands to the expected code.
+///
+/// Takes a duplication invocation within brackets, the code to duplicate within
+/// braces, and `expect` followed by the expected code within braces. The code
+/// is duplicated as by [`duplicate`] and compared to the expected code token by
+/// token, ignoring spacing, and the macro expands to nothing if they match.
+/// Otherwise, it fails to compile with an error showing where the two first
+/// differ:
+///
+/// ```
+/// use duplicate::assert_duplicate_expands;
+///
+/// assert_duplicate_expands!{
+///   [
+///     name    ty;
+///     [Small] [u8];
+///     [Large] [u64];
+///   ]
+///   {
+///     pub struct name(ty);
+///   }
+///   expect {
+///     pub struct Small(u8);
+///     pub struct Large(u64);
+///   }
+/// }
+/// ```
+///
+/// This allows crates that build on `duplicate`, e.g. with `macro_rules!`
+/// macros generating invocations, to test the invocations they produce.
+///
+/// With `expect exactly`, the spacing of punctuation (e.g. `::` versus `: :`)
+/// and invisible groups (e.g. from `macro_rules!` fragments) must match too.
+/// Invisible groups are shown as `⟦...⟧` and punctuation joined to the next
+/// token as followed by `(joint)`.
+///
+/// [`duplicate`]: macro.duplicate.html
+#[proc_macro]
+pub fn assert_duplicate_expands(stream: TokenStream) -> TokenStream
+{
+	match assert_expands::assert_expands(stream)
+	{
+		Ok(result) => result,
+		Err(err) => emit_error(err),
+	}
+}
+
 /// A result that specified where in the token stream the error occured
 /// and is accompanied by a message.
 type Result<T> = std::result::Result<T, Error>;
 
 /// Parses an inline macro invocation where the invocation syntax is within
-/// initial brackets.
+/// initial brackets, or trailing brackets preceded by `@`.
 ///
 /// Extracts the invocation syntax and body to be duplicated/substituted
 /// and passes them to the given function.
@@ -1197,18 +2801,18 @@ fn inline_macro_impl(
 	f: fn(TokenStream, TokenStream) -> Result<TokenStream>,
 ) -> TokenStream
 {
-	let empty_globals = SubstitutionGroup::new();
-	let mut iter = TokenIter::new(stream, &empty_globals, empty());
+	let iter = TokenIter::new(stream, SubstitutionGroup::empty(), empty());
 
-	let result = match iter.next_group(Some(Delimiter::Bracket))
+	let result = match split_inline_invocation(iter)
 	{
-		Ok((invocation, _)) =>
+		Ok((invocation, body)) => f(invocation, body),
+		Err(err) =>
 		{
-			let invocation_body = invocation.to_token_stream();
-
-			f(invocation_body, iter.to_token_stream())
+			Err(err.hint(
+				"Expected invocation within brackets, either before the code: [...] or after it: \
+				 @ [...]",
+			))
 		},
-		Err(err) => Err(err.hint("Expected invocation within brackets: [...]")),
 	};
 
 	match result
@@ -1218,22 +2822,179 @@ fn inline_macro_impl(
 	}
 }
 
+/// Parses a file macro invocation, where the path of the file is followed by
+/// the invocation syntax within brackets.
+///
+/// Passes the invocation syntax and the file's contents to the given function
+/// and appends a constant including the file, so the compiler tracks it.
+fn file_macro_impl(
+	stream: TokenStream,
+	f: fn(TokenStream, TokenStream) -> Result<TokenStream>,
+) -> TokenStream
+{
+	let result = parse_file_invocation(stream).and_then(|(invocation, contents, path)| {
+		let mut result = f(invocation, contents)?;
+		if let Some(path) = path.to_str()
+		{
+			let tracking: TokenStream = format!(
+				"const _: &[u8] = include_bytes!({});",
+				Literal::string(path)
+			)
+			.parse()
+			.unwrap();
+			result.extend(tracking);
+		}
+		Ok(result)
+	});
+
+	match result
+	{
+		Ok(result) => result,
+		Err(err) => emit_error(err),
+	}
+}
+
 /// Implements the duplicate macros.
-fn duplicate_impl(attr: TokenStream, item: TokenStream) -> Result<TokenStream>
+fn duplicate_impl(attr: TokenStream, mut item: TokenStream) -> Result<TokenStream>
+{
+	#[cfg(feature = "trace")]
+	let trace = trace::Trace::start("duplicate", &attr, &item);
+	let header = attr.clone();
+	let dup_def = parse_duplicate_invocation(attr, &mut item)?;
+	check_single_group(&header, &dup_def)?;
+
+	let result = match extract_template_sections(&item)?
+	{
+		None => duplicate_with_globals(item, &dup_def, &dup_def.global_substitutions),
+		Some(sections) =>
+		{
+			// Section-major: all duplicates of a section precede the next section's
+			sections
+				.into_iter()
+				.try_fold(TokenStream::new(), |mut output, (_, locals, code)| {
+					let globals = section_global_substitutions(&dup_def, locals)?;
+					output.extend(duplicate_with_globals(code, &dup_def, &globals)?);
+					Ok(output)
+				})
+		},
+	};
+	let result = match &dup_def.options.wrap_in
+	{
+		Some((visibility, name)) => result.map(|output| wrap_in_module(output, visibility, name)),
+		None => result,
+	};
+	let result = match &dup_def.options.defer_as
+	{
+		Some((exported, name)) => result.and_then(|output| defer_as_macro(output, *exported, name)),
+		None => result,
+	};
+	#[cfg(feature = "trace")]
+	trace.report(dup_def.duplications.len(), &result);
+	result
+}
+
+/// Emits the given duplicates as the expansion of a `macro_rules!` macro with
+/// the given name, which is `#[macro_export]`ed if `exported`
+/// (`defer_as(..)`).
+fn defer_as_macro(duplicates: TokenStream, exported: bool, name: &Ident) -> Result<TokenStream>
+{
+	if let Some(dollar) = find_dollar(duplicates.clone())
+	{
+		return Err(Error::new(
+			"The duplicates can't contain '$' with 'defer_as', as they become the body of a \
+			 'macro_rules!' macro.",
+		)
+		.span(dollar)
+		.hint(pretty_errors::DEFER_AS));
+	}
+	let mut result = TokenStream::new();
+	if exported
+	{
+		result.extend("#[macro_export]".parse::<TokenStream>().unwrap());
+	}
+	let mut rule: TokenStream = "() =>".parse().unwrap();
+	rule.extend([
+		TokenTree::Group(new_group(Delimiter::Brace, duplicates, name.span())),
+		TokenTree::Punct(Punct::new(';', Spacing::Alone)),
+	]);
+	result.extend([
+		TokenTree::Ident(Ident::new("macro_rules", name.span())),
+		TokenTree::Punct(Punct::new('!', Spacing::Alone)),
+		TokenTree::Ident(name.clone()),
+		TokenTree::Group(new_group(Delimiter::Brace, rule, name.span())),
+	]);
+	Ok(result)
+}
+
+/// The span of the first `$` in the given tokens, if any.
+fn find_dollar(stream: TokenStream) -> Option<Span>
+{
+	stream.into_iter().find_map(|token| {
+		match token
+		{
+			TokenTree::Punct(p) if p.as_char() == '$' => Some(p.span()),
+			TokenTree::Group(group) => find_dollar(group.stream()),
+			_ => None,
+		}
+	})
+}
+
+/// Puts the given duplicates in a module with the given visibility and name
+/// that imports everything from the invocation's module (`wrap_in(..)`).
+fn wrap_in_module(duplicates: TokenStream, visibility: &TokenStream, name: &Ident) -> TokenStream
 {
-	let dup_def = parse_duplicate_invocation(attr)?;
+	let mut body: TokenStream = "use super::*;".parse().unwrap();
+	body.extend(duplicates);
+	let mut result = visibility.clone();
+	result.extend([
+		TokenTree::Ident(Ident::new("mod", name.span())),
+		TokenTree::Ident(name.clone()),
+		TokenTree::Group(new_group(Delimiter::Brace, body, name.span())),
+	]);
+	result
+}
 
-	duplicate_and_substitute(
+/// Duplicates the given item for each substitution group of the invocation,
+/// using the given global substitutions.
+fn duplicate_with_globals(
+	item: TokenStream,
+	dup_def: &DuplicationDefinition,
+	global_substitutions: &SubstitutionGroup,
+) -> Result<TokenStream>
+{
+	#[cfg(feature = "positions")]
+	let (item, hidden) = dup_def.options.positions.hide(item);
+	let result = duplicate_and_substitute(
 		item,
-		&dup_def.global_substitutions,
+		&dup_def.options,
+		global_substitutions,
 		dup_def.duplications.iter(),
-	)
+	);
+	#[cfg(feature = "positions")]
+	let result = result.map(|output| positions::Positions::restore(output, &hidden));
+	result
 }
 
 /// Implements the substitute macros
 fn substitute_impl(attr: TokenStream, item: TokenStream) -> Result<TokenStream>
 {
-	duplicate_and_substitute(item, &parse_global_substitutions_only(attr)?, empty())
+	#[cfg(feature = "trace")]
+	let trace = trace::Trace::start("substitute", &attr, &item);
+	let dup_def = parse_global_substitutions_only(attr)?;
+
+	#[cfg(feature = "positions")]
+	let (item, hidden) = dup_def.options.positions.hide(item);
+	let result = duplicate_and_substitute(
+		item,
+		&dup_def.options,
+		&dup_def.global_substitutions,
+		empty(),
+	);
+	#[cfg(feature = "positions")]
+	let result = result.map(|output| positions::Positions::restore(output, &hidden));
+	#[cfg(feature = "trace")]
+	trace.report(0, &result);
+	result
 }
 
 /// Terminates with an error and produces the given message.
@@ -1252,10 +3013,52 @@ fn emit_error(err: Error) -> TokenStream
 	}
 }
 
-#[derive(Debug)]
+/// Emits a note with the given message and hint at the given span without
+/// failing compilation.
+///
+/// Notes can only be emitted by nightly compilers, so on stable nothing is
+/// emitted.
+#[cfg(all(feature = "pretty_errors", feature = "module_disambiguation"))]
+fn emit_note(span: Span, msg: &str, hint: &str)
+{
+	use proc_macro2_diagnostics::{Diagnostic, Level};
+	// On stable, the diagnostic isn't emitted but returned as tokens that would
+	// fail compilation, so those are dropped
+	let _ = Diagnostic::spanned(proc_macro2::Span::from(span), Level::Note, msg)
+		.help(hint)
+		.emit_as_item_tokens();
+}
+
+/// Emits the given error as a warning without failing compilation.
+///
+/// Like notes, warnings can only be emitted by nightly compilers and with
+/// `pretty_errors`, so otherwise nothing is emitted.
+#[allow(unused_variables)]
+fn emit_warning(err: Error)
+{
+	#[cfg(feature = "pretty_errors")]
+	{
+		// On stable, the diagnostic isn't emitted but returned as tokens that would
+		// fail compilation, so those are dropped
+		let _ = err
+			.into_diagnostic_with_level(proc_macro2_diagnostics::Level::Warning)
+			.emit_as_item_tokens();
+	}
+}
+
+#[derive(Debug, Clone)]
 struct SubstitutionGroup
 {
 	substitutions: HashMap<String, Substitution>,
+	/// Optional substitution identifiers that other groups define but this one
+	/// doesn't.
+	absent: HashSet<String>,
+	/// The patterns whose substitutions are in `substitutions`, keyed by
+	/// `Pattern::key`.
+	patterns: Vec<Pattern>,
+	/// The doc attributes (e.g. doc comments) given before the group in the
+	/// invocation, which are prepended to its duplicate.
+	docs: Vec<TokenStream>,
 	#[cfg(feature = "module_disambiguation")]
 	identifier_order: Vec<String>,
 }
@@ -1266,11 +3069,27 @@ impl SubstitutionGroup
 	{
 		Self {
 			substitutions: HashMap::new(),
+			absent: HashSet::new(),
+			patterns: Vec::new(),
+			docs: Vec::new(),
 			#[cfg(feature = "module_disambiguation")]
 			identifier_order: Vec::new(),
 		}
 	}
 
+	/// Returns a group without substitutions, e.g. for the global
+	/// substitutions of an iterator over tokens that don't need them.
+	///
+	/// The group is shared, so it is only created once per thread.
+	fn empty() -> &'static Self
+	{
+		thread_local! {
+			// Leaked, as the compiler's threads live as long as the process anyway
+			static EMPTY: &'static SubstitutionGroup = Box::leak(Box::new(SubstitutionGroup::new()));
+		}
+		EMPTY.with(|empty| *empty)
+	}
+
 	fn add_substitution(&mut self, ident: Ident, subst: Substitution) -> Result<()>
 	{
 		if self
@@ -1293,11 +3112,47 @@ impl SubstitutionGroup
 		}
 	}
 
+	/// Adds the substitution of the given pattern.
+	///
+	/// Patterns aren't substitution identifiers, so module disambiguation
+	/// doesn't consider them.
+	fn add_pattern(&mut self, pattern: Pattern, subst: Substitution) -> Result<()>
+	{
+		if self.substitutions.insert(pattern.key(), subst).is_some()
+		{
+			Err(Error::new("Pattern assigned multiple substitutions").span(pattern.span()))
+		}
+		else
+		{
+			self.patterns.push(pattern);
+			Ok(())
+		}
+	}
+
+	fn patterns(&self) -> impl Iterator<Item = &Pattern>
+	{
+		self.patterns.iter()
+	}
+
 	fn substitution_of(&self, ident: &String) -> Option<&Substitution>
 	{
 		self.substitutions.get(ident)
 	}
 
+	/// Marks the given optional substitution identifier as not defined by this
+	/// group.
+	fn add_absent(&mut self, ident: String)
+	{
+		self.absent.insert(ident);
+	}
+
+	/// Whether the given identifier is an optional substitution identifier
+	/// that this group doesn't define.
+	fn is_absent(&self, ident: &String) -> bool
+	{
+		self.absent.contains(ident)
+	}
+
 	fn identifiers(&self) -> impl Iterator<Item = &String>
 	{
 		self.substitutions.keys()
@@ -1309,6 +3164,20 @@ impl SubstitutionGroup
 			.map(move |ident| (ident, self.substitution_of(ident).unwrap().argument_count()))
 	}
 
+	/// Adds the substitutions of the given group, which must not substitute
+	/// any of the same identifiers or patterns.
+	fn merge(&mut self, other: Self)
+	{
+		self.substitutions.extend(other.substitutions);
+		self.absent.extend(other.absent);
+		self.patterns.extend(other.patterns);
+		self.docs.extend(other.docs);
+		#[cfg(feature = "module_disambiguation")]
+		{
+			self.identifier_order.extend(other.identifier_order);
+		}
+	}
+
 	#[cfg(feature = "module_disambiguation")]
 	fn identifiers_ordered(&self) -> impl Iterator<Item = &String>
 	{
@@ -1316,26 +3185,172 @@ impl SubstitutionGroup
 	}
 }
 
+/// Options given at the start of an invocation, changing how it is expanded.
+#[derive(Debug, Default)]
+struct InvocationOptions
+{
+	/// Whether identifiers are also substituted inside string literals in
+	/// attributes (`attr_strings`).
+	attr_strings: bool,
+	/// Whether the invocation may have no substitution groups, in which case
+	/// nothing is output (`allow_empty`).
+	allow_empty: bool,
+	/// The number of duplicates to make without substitution groups and the
+	/// span of the count (`repeat(N)`).
+	repeat: Option<(usize, Span)>,
+	/// Whether to output all duplicates of each item before the next item,
+	/// instead of each duplicate of all items before the next duplicate
+	/// (`interleave(by_item)`).
+	interleave_by_item: bool,
+	/// Whether the short syntax lists the substitutions of each identifier on
+	/// its own line (`columns`).
+	columns: bool,
+	/// The path of a file with additional short syntax substitution groups and
+	/// the span of the path (`rows_from("path")`).
+	rows_from: Option<(String, Span)>,
+	/// The span of the option if the substitution groups are continued by
+	/// `duplicate_item_rows` attributes on the item (`continues`).
+	continues: Option<Span>,
+	/// Whether identifiers differing from a substitution identifier only by
+	/// case and nested invocations shadowing substitution identifiers are
+	/// errors (`strict`).
+	strict: bool,
+	/// The levels of lints given by the invocation (`lints(...)`).
+	lints: lint::LintLevels,
+	/// How module disambiguation postfixes module names
+	/// (`module_name(...)`).
+	module_name: ModuleNaming,
+	/// The number of duplicates the invocation may make and the span of the
+	/// number (`max_duplicates(N)`).
+	max_duplicates: Option<(usize, Span)>,
+	/// The number of substitution groups the invocation must end up with and
+	/// the span of the number (`expect(N)`).
+	expect: Option<(usize, Span)>,
+	/// The substitution identifiers every substitution group must define and
+	/// the span of the option's arguments (`require(idents)`).
+	require: Option<(Vec<String>, Span)>,
+	/// Whether a note explains how module disambiguation names the module in
+	/// each duplicate (`explain_module_names`).
+	#[cfg_attr(
+		not(all(feature = "module_disambiguation", feature = "pretty_errors")),
+		allow(dead_code)
+	)]
+	explain_module_names: bool,
+	/// Whether the output of substitutions is substituted again, so it may use
+	/// other substitution identifiers (`recursive`).
+	recursive: bool,
+	/// Whether the headers of inner `duplicate_item` and `substitute_item`
+	/// invocations are left unsubstituted, along with the substitution
+	/// identifiers they declare (`protect_inner`).
+	protect_inner: bool,
+	/// Whether it is an error for two duplicates to define an item with the
+	/// same name (`unique_names`).
+	unique_names: bool,
+	/// The visibility and name of the module all duplicates are put in, if
+	/// any (`wrap_in(..)`).
+	wrap_in: Option<(TokenStream, Ident)>,
+	/// Whether the macro the duplicates are emitted in is exported and its
+	/// name, if any (`defer_as(..)`).
+	defer_as: Option<(bool, Ident)>,
+	/// The configuration predicate choosing between the async and the sync
+	/// duplicate, if any, and the span of the option, if an async and a sync
+	/// duplicate are made (`maybe_async(..)`).
+	maybe_async: Option<(Option<(TokenStream, Span)>, Span)>,
+	/// The configuration option each duplicate is gated on and the substitution
+	/// identifier whose substitution gives the option's value
+	/// (`auto_cfg(key = ident)`).
+	auto_cfg: Option<(Ident, Ident)>,
+	/// The positions substitution identifiers are restricted to
+	/// (`positions(...)`).
+	#[cfg(feature = "positions")]
+	positions: positions::Positions,
+	/// Whether the output of each duplicate is parsed as items before it is
+	/// emitted (`validate`).
+	#[cfg(feature = "validate")]
+	validate: bool,
+	/// Additional names of the `duplicate!` and `substitute!` macros whose
+	/// invocations are expanded as nested invocations (`nested_names(..)`).
+	#[cfg(feature = "nested_invocation")]
+	nested_names: token_iter::NestedNames,
+	/// The accumulators declared after the options (`acc name += [..];`), each
+	/// substituted in a duplicate by its fragment substituted with every
+	/// substitution group up to and including the duplicate's.
+	accumulators: Vec<(Ident, TokenStream)>,
+}
+
+/// How module disambiguation chooses the postfix of each duplicate's module
+/// name.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(not(feature = "module_disambiguation"), allow(dead_code))]
+enum ModuleNaming
+{
+	/// Use a substitution identifier whose substitutions are all a single
+	/// identifier, failing if none is found.
+	#[default]
+	Identifier,
+	/// Use the index of each duplicate's substitution group
+	/// (`module_name(index)`).
+	Index,
+	/// Use a substitution identifier like `Identifier` if one is found,
+	/// otherwise the index like `Index` (`module_name(auto)`).
+	Auto,
+	/// Use the substitutions of the given substitution identifier, sanitized
+	/// into identifiers (`module_name(sanitize(ident))`).
+	Sanitize(Ident),
+	/// Use the given name of each duplicate's module, by the index of its
+	/// substitution group, and the span of the names (`module_names(...)`).
+	Names(Vec<Ident>, Span),
+}
+
+/// What to postfix a name with to disambiguate it in each duplicate.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "module_disambiguation"), allow(dead_code))]
+enum Postfix
+{
+	/// The substitution of the given substitution identifier, whose
+	/// substitutions are all a single identifier.
+	Identifier(String),
+	/// The index of the duplicate's substitution group.
+	Index,
+	/// The given postfix of each duplicate, by the index of its substitution
+	/// group.
+	Sanitized(Vec<String>),
+	/// The given name of each duplicate, by the index of its substitution
+	/// group, which replaces module names instead of being postfixed on them.
+	Names(Vec<String>),
+}
+
 /// Defines how duplication should happen.
 struct DuplicationDefinition
 {
+	pub options: InvocationOptions,
 	pub global_substitutions: SubstitutionGroup,
 	pub duplications: Vec<SubstitutionGroup>,
 }
 
-/// Checks whether item is a module and whether it then needs disambiguation.
+/// Checks whether item is a module (or `macro_rules!` macro) and whether it
+/// then needs disambiguation.
 ///
 /// Returns the identifier of the found module (if found) and the substitution
 /// identifier that should be used to disambiguate it in each duplicate.
 /// Returns none if no disambiguation is needed.
+#[allow(unused_variables)]
 pub(crate) fn disambiguate_module<'a>(
 	item: &TokenStream,
 	sub_groups: impl Iterator<Item = &'a SubstitutionGroup> + Clone,
-) -> Result<Option<(Ident, String)>>
+	naming: &ModuleNaming,
+) -> Result<Option<(Ident, Postfix)>>
 {
 	let mut sub_groups = sub_groups.peekable();
 
-	match (sub_groups.peek(), get_module_name(&item))
+	// Macros may shadow each other, so without module disambiguation they are
+	// duplicated as-is, while with it they are given unique names like modules
+	#[cfg(feature = "module_disambiguation")]
+	let name = get_module_name(item).or_else(|| get_macro_rules_name(item));
+	#[cfg(not(feature = "module_disambiguation"))]
+	let name = get_module_name(item);
+
+	match (sub_groups.peek(), name)
 	{
 		(Some(sub), Some(ref module)) if sub.substitution_of(&module.to_string()).is_none() =>
 		{
@@ -1354,7 +3369,7 @@ pub(crate) fn disambiguate_module<'a>(
 				let span = module.span();
 				Ok(Some((
 					module.clone(),
-					crate::module_disambiguation::find_simple(sub_groups, span)?,
+					crate::module_disambiguation::find_simple(sub_groups, span, naming)?,
 				)))
 			}
 		},
@@ -1368,9 +3383,12 @@ pub(crate) fn disambiguate_module<'a>(
 /// If not, returns None.
 fn get_module_name(item: &TokenStream) -> Option<Ident>
 {
-	let empty_globals = SubstitutionGroup::new();
-	let mut iter = TokenIter::new(item.clone(), &empty_globals, std::iter::empty());
+	let mut iter = TokenIter::new(item.clone(), SubstitutionGroup::empty(), std::iter::empty());
 
+	// Attributes may precede the module, e.g. doc comments or those following a
+	// 'cfg_attr'-wrapped invocation, and so may its visibility
+	iter.skip_attributes().ok()?;
+	iter.skip_visibility().ok()?;
 	iter.expect_simple(|t| is_ident(t, Some("mod")), None)
 		.ok()?;
 
@@ -1379,6 +3397,26 @@ fn get_module_name(item: &TokenStream) -> Option<Ident>
 	Some(module)
 }
 
+/// Extract the name of the macro assuming the given item is a `macro_rules!`
+/// declaration.
+///
+/// If not, returns None.
+#[cfg(feature = "module_disambiguation")]
+fn get_macro_rules_name(item: &TokenStream) -> Option<Ident>
+{
+	let mut iter = TokenIter::new(item.clone(), SubstitutionGroup::empty(), std::iter::empty());
+
+	iter.skip_attributes().ok()?;
+	iter.expect_simple(|t| is_ident(t, Some("macro_rules")), None)
+		.ok()?;
+	iter.expect_simple(|t| crate::token_iter::is_punct(t, '!'), None)
+		.ok()?;
+
+	let name = iter.extract_identifier(None).ok()?;
+	iter.next_group(None).ok()?;
+	Some(name)
+}
+
 /// Creates a new group with the given span correctly set as the group's span.
 ///
 /// Use this function instead of creating the group manually, as forgetting
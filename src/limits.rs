@@ -0,0 +1,119 @@
+use crate::{
+	error::Error,
+	pretty_errors::{EXPECT, LIMITS},
+	DuplicationDefinition, Result,
+};
+use proc_macro::{TokenStream, TokenTree};
+
+/// The number of duplicates an invocation may make by default.
+const DEFAULT_MAX_DUPLICATES: usize = 10_000;
+
+/// The number of tokens an invocation may output by default.
+const DEFAULT_MAX_OUTPUT_TOKENS: usize = 1_000_000;
+
+/// The environment variable overriding `DEFAULT_MAX_DUPLICATES`.
+const MAX_DUPLICATES_VAR: &str = "DUPLICATE_MAX_DUPLICATES";
+
+/// The environment variable overriding `DEFAULT_MAX_OUTPUT_TOKENS`.
+const MAX_OUTPUT_TOKENS_VAR: &str = "DUPLICATE_MAX_OUTPUT_TOKENS";
+
+/// Returns an error if the given invocation makes more duplicates than allowed
+/// by its `max_duplicates` option, or otherwise by the environment or default.
+pub(crate) fn check_duplicates(dup_def: &DuplicationDefinition) -> Result<()>
+{
+	let count = dup_def.duplications.len();
+	let (max, span) = match dup_def.options.max_duplicates
+	{
+		Some((max, span)) => (max, Some(span)),
+		None => (from_env(MAX_DUPLICATES_VAR, DEFAULT_MAX_DUPLICATES)?, None),
+	};
+	if count > max
+	{
+		let error = Error::new(format!(
+			"Invocation makes {} duplicates, more than the maximum of {}.",
+			count, max
+		))
+		.hint(LIMITS);
+		return Err(span.into_iter().fold(error, Error::span));
+	}
+	Ok(())
+}
+
+/// Returns an error if the given invocation doesn't make the number of
+/// duplicates given by its `expect` option, if any.
+pub(crate) fn check_expected_duplicates(dup_def: &DuplicationDefinition) -> Result<()>
+{
+	match dup_def.options.expect
+	{
+		Some((expected, span)) if expected != dup_def.duplications.len() =>
+		{
+			Err(Error::new(format!(
+				"Expected {} substitution groups, found {}.",
+				expected,
+				dup_def.duplications.len()
+			))
+			.span(span)
+			.hint(EXPECT))
+		},
+		_ => Ok(()),
+	}
+}
+
+/// Returns the number of tokens an invocation may output, as given by the
+/// environment or default.
+pub(crate) fn max_output_tokens() -> Result<usize>
+{
+	from_env(MAX_OUTPUT_TOKENS_VAR, DEFAULT_MAX_OUTPUT_TOKENS)
+}
+
+/// Returns an error if the given number of tokens output by an invocation is
+/// more than the given maximum.
+pub(crate) fn check_output_tokens(count: usize, max: usize) -> Result<()>
+{
+	if count > max
+	{
+		return Err(Error::new(format!(
+			"Invocation outputs {} tokens, more than the maximum of {}.",
+			count, max
+		))
+		.hint(LIMITS));
+	}
+	Ok(())
+}
+
+/// Returns the limit given by the environment variable with the given name, or
+/// the given default if it isn't set.
+fn from_env(var: &str, default: usize) -> Result<usize>
+{
+	match std::env::var(var)
+	{
+		Ok(value) =>
+		{
+			value.trim().parse().map_err(|_| {
+				Error::new(format!(
+					"Environment variable '{}' must be a number, but is '{}'.",
+					var, value
+				))
+				.hint(LIMITS)
+			})
+		},
+		Err(_) => Ok(default),
+	}
+}
+
+/// Counts the tokens in the given stream, including those in groups.
+///
+/// Delimited groups count as one token besides their contents.
+pub(crate) fn count_tokens(stream: TokenStream) -> usize
+{
+	stream
+		.into_iter()
+		.map(|token| {
+			match token
+			{
+				TokenTree::Group(group) => 1 + count_tokens(group.stream()),
+				_ => 1,
+			}
+		})
+		.sum()
+}
@@ -0,0 +1,186 @@
+use crate::{
+	error::Error,
+	token_iter::{SubGroupIter, TokenIter},
+	InvocationOptions, Result,
+};
+use proc_macro::{Delimiter, Span, TokenStream};
+use std::collections::HashMap;
+
+/// The severity of a lint, i.e. what to do when its check finds something.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LintLevel
+{
+	/// Ignore it.
+	Allow,
+	/// Emit a warning (only on nightly and with `pretty_errors`).
+	Warn,
+	/// Fail with an error.
+	Deny,
+}
+
+/// The identifiers differing from a substitution identifier only by case.
+pub(crate) const CASE_MISMATCH: &str = "case_mismatch";
+
+/// Substitutions and arguments that are empty.
+pub(crate) const EMPTY_SUBSTITUTION: &str = "empty_substitution";
+
+/// Substitution identifiers of nested invocations in a header that are also
+/// substitution identifiers of the enclosing invocation.
+pub(crate) const NESTED_SHADOWING: &str = "nested_shadowing";
+
+/// Duplicating invocations giving only one substitution group and no global
+/// substitutions, which should probably only substitute instead.
+pub(crate) const SINGLE_GROUP: &str = "single_group";
+
+/// The names of all lints with their default level.
+const LINTS: &[(&str, LintLevel)] = &[
+	(CASE_MISMATCH, LintLevel::Allow),
+	(EMPTY_SUBSTITUTION, LintLevel::Allow),
+	(NESTED_SHADOWING, LintLevel::Warn),
+	(SINGLE_GROUP, LintLevel::Warn),
+];
+
+/// The lint levels configured by an invocation (`lints(...)`).
+#[derive(Debug, Default)]
+pub(crate) struct LintLevels
+{
+	levels: HashMap<&'static str, LintLevel>,
+}
+
+impl LintLevels
+{
+	/// Returns the level of the given lint in the invocation with the given
+	/// options.
+	pub(crate) fn level(options: &InvocationOptions, lint: &str) -> LintLevel
+	{
+		if let Some(level) = options.lints.levels.get(lint)
+		{
+			return *level;
+		}
+		if options.strict && (lint == CASE_MISMATCH || lint == NESTED_SHADOWING)
+		{
+			return LintLevel::Deny;
+		}
+		LINTS
+			.iter()
+			.find(|(name, _)| *name == lint)
+			.map(|(_, level)| *level)
+			.expect("unknown lint")
+	}
+}
+
+/// Reports the given finding of the given lint according to its level in the
+/// invocation with the given options.
+///
+/// Returns the finding as an error if the lint is denied.
+pub(crate) fn report(options: &InvocationOptions, lint: &str, finding: Error) -> Result<()>
+{
+	match LintLevels::level(options, lint)
+	{
+		LintLevel::Allow => Ok(()),
+		LintLevel::Warn =>
+		{
+			crate::emit_warning(finding);
+			Ok(())
+		},
+		LintLevel::Deny => Err(finding),
+	}
+}
+
+/// Reports the given substitution (or argument, if `what` says so) with the
+/// given span of its brackets if it is empty, per the `empty_substitution`
+/// lint.
+pub(crate) fn check_empty_substitution(
+	options: &InvocationOptions,
+	substitution: &TokenStream,
+	span: Span,
+	what: &str,
+) -> Result<()>
+{
+	if substitution.is_empty()
+	{
+		report(
+			options,
+			EMPTY_SUBSTITUTION,
+			Error::new(format!("Empty {}.", what))
+				.span(span)
+				.hint(crate::pretty_errors::EMPTY_SUBSTITUTION),
+		)
+	}
+	else
+	{
+		Ok(())
+	}
+}
+
+/// Validates the arguments of the `lints` option, which are lint levels each
+/// followed by a parenthesized list of lint names, separated by ','.
+pub(crate) fn validate_lint_levels<'a, T: SubGroupIter<'a>>(
+	mut args: TokenIter<'a, T>,
+) -> Result<LintLevels>
+{
+	let mut result = LintLevels::default();
+	while args.has_next()?
+	{
+		let level_ident = args.extract_identifier(Some("'allow', 'warn', or 'deny'"))?;
+		let level = match level_ident.to_string().as_str()
+		{
+			"allow" => LintLevel::Allow,
+			"warn" => LintLevel::Warn,
+			"deny" => LintLevel::Deny,
+			_ =>
+			{
+				return Err(Error::new("Unknown lint level.")
+					.span(level_ident.span())
+					.hint(crate::pretty_errors::LINTS))
+			},
+		};
+		let (mut names, _) = args
+			.next_group(Some(Delimiter::Parenthesis))
+			.map_err(|err| err.hint(crate::pretty_errors::LINTS))?;
+		while names.has_next()?
+		{
+			let name_ident = names.extract_identifier(Some("a lint name"))?;
+			let name = validate_lint_name(&name_ident.to_string(), name_ident.span())?;
+			if result.levels.insert(name, level).is_some()
+			{
+				return Err(
+					Error::new(format!("Lint '{}' given multiple levels.", name))
+						.span(name_ident.span()),
+				);
+			}
+			if names.has_next()?
+			{
+				names.expect_comma()?;
+			}
+		}
+		if args.has_next()?
+		{
+			args.expect_comma()?;
+		}
+	}
+	Ok(result)
+}
+
+/// Returns the given lint name if it is the name of a lint, otherwise returns
+/// an error listing the valid names.
+fn validate_lint_name(name: &str, span: Span) -> Result<&'static str>
+{
+	LINTS
+		.iter()
+		.map(|(lint, _)| *lint)
+		.find(|lint| *lint == name)
+		.ok_or_else(|| {
+			Error::new(format!(
+				"Unknown lint '{}'. Valid lints are: {}.",
+				name,
+				LINTS
+					.iter()
+					.map(|(lint, _)| *lint)
+					.collect::<Vec<_>>()
+					.join(", ")
+			))
+			.span(span)
+			.hint(crate::pretty_errors::LINTS)
+		})
+}
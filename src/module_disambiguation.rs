@@ -1,57 +1,213 @@
 use crate::{
 	error::Error,
 	token_iter::{is_ident, SubGroupIter},
-	Result, SubstitutionGroup, TokenIter,
+	ModuleNaming, Postfix, Result, SubstitutionGroup, TokenIter,
 };
 use heck::ToSnakeCase;
 use proc_macro::{Ident, Span, TokenStream, TokenTree};
+use std::collections::HashSet;
 
 /// Finds a substitution identifier whose substitutions only contain one
-/// identifier and nothing else for all duplicates.
+/// identifier and nothing else for all duplicates, and whose identifiers are
+/// unique once converted to snake case. The first such substitution
+/// identifier is chosen.
+///
+/// Depending on the naming given by the invocation, the index of each
+/// duplicate's substitution group is used instead, either always or if no such
+/// substitution identifier is found.
 pub(crate) fn find_simple<'a>(
 	substitutions: impl Iterator<Item = &'a SubstitutionGroup> + Clone,
 	mod_span: Span,
-) -> Result<String>
+	naming: &ModuleNaming,
+) -> Result<Postfix>
 {
+	match naming
+	{
+		ModuleNaming::Sanitize(ident) => return sanitize_all(substitutions, ident, mod_span),
+		ModuleNaming::Names(names, _) =>
+		{
+			return Ok(Postfix::Names(
+				names.iter().map(|name| name.to_string()).collect(),
+			))
+		},
+		_ => (),
+	}
 	let mut substitutions = substitutions.peekable();
-	if substitutions.peek().is_none()
+	if substitutions.peek().is_none() || matches!(naming, ModuleNaming::Index)
 	{
-		// No duplications are made, so either the module doesn't need disambiguation
+		// If no duplications are made, either the module doesn't need disambiguation
 		// (as even with global substitutions only 1 duplicate will be made)
 		// or the invocation will fails somewhere else (from the lack of substitution
 		// groups)
-		return Ok("".into());
+		return Ok(Postfix::Index);
 	}
+	// The first substitution identifier whose postfixes weren't unique, with the
+	// postfix given by more than one substitution
+	let mut collision = None;
 	'outer: for ident in substitutions.peek().unwrap().identifiers_ordered()
 	{
+		let mut postfixes = HashSet::new();
 		for group in substitutions.clone()
 		{
-			let substitution = group.substitution_of(ident).unwrap();
-			if substitution.substitutes_identifier().is_none()
+			match group
+				.substitution_of(ident)
+				.and_then(|substitution| substitution.substitutes_identifier())
 			{
-				continue 'outer;
+				Some(postfix_ident) =>
+				{
+					let postfix = postfix_ident.to_string().to_snake_case();
+					if !postfixes.insert(postfix.clone())
+					{
+						collision.get_or_insert((ident.clone(), postfix));
+						continue 'outer;
+					}
+				},
+				// Not a simple identifier or optional and not defined by this group
+				None => continue 'outer,
 			}
 		}
-		return Ok(ident.clone());
+		return Ok(Postfix::Identifier(ident.clone()));
 	}
-	Err(Error::new(
+	if matches!(naming, ModuleNaming::Auto)
+	{
+		return Ok(Postfix::Index);
+	}
+	let error = Error::new(
 		"Was unable to find a suitable substitution identifier to postfix on the module's \
 		 name.\nHint: If a substitution identifier's substitutions all consist of a single \
-		 identifier and nothing, they will automatically be postfixed on the module name to make \
-		 them unique.",
+		 identifier and nothing else, and are all different, they will automatically be postfixed \
+		 on the module name to make them unique. Otherwise, use the 'module_name(auto)' option to \
+		 postfix the index of each duplicate's substitution group instead.",
 	)
-	.span(mod_span))
+	.span(mod_span);
+	Err(match collision
+	{
+		Some((ident, postfix)) =>
+		{
+			error.hint(format!(
+				"The substitutions of '{}' can't be used, as more than one would postfix the \
+				 module name with '{}'.\n{}",
+				ident,
+				postfix,
+				crate::pretty_errors::MODULE_POSTFIX_COLLISION
+			))
+		},
+		None => error,
+	})
+}
+
+/// Sanitizes the substitutions of the given substitution identifier into the
+/// postfixes of each duplicate (see `sanitize`).
+///
+/// Returns an error if a substitution can't be sanitized or two substitutions
+/// are sanitized into the same postfix.
+fn sanitize_all<'a>(
+	substitutions: impl Iterator<Item = &'a SubstitutionGroup>,
+	ident: &Ident,
+	mod_span: Span,
+) -> Result<Postfix>
+{
+	let mut postfixes = Vec::new();
+	let mut unique = HashSet::new();
+	for group in substitutions
+	{
+		let substitution = group
+			.substitution_of(&ident.to_string())
+			.filter(|substitution| substitution.argument_count() == 0)
+			.ok_or_else(|| {
+				Error::new(format!(
+					"'{}' must be a substitution identifier without parameters that all \
+					 substitution groups define, to be used for module names.",
+					ident
+				))
+				.span(ident.span())
+			})?;
+		let stream = substitution.apply_simple(ident.span())?;
+		let span = stream
+			.clone()
+			.into_iter()
+			.next()
+			.map_or(mod_span, |t| t.span());
+		let postfix = sanitize(stream);
+		if !postfix.starts_with(|c: char| c.is_ascii_alphabetic())
+		{
+			return Err(Error::new(format!(
+				"Substitution can't be sanitized into a module name, as it produced '{}'.",
+				postfix
+			))
+			.span(span)
+			.hint(crate::pretty_errors::SANITIZE));
+		}
+		if !unique.insert(postfix.clone())
+		{
+			return Err(Error::new(format!(
+				"Substitution is sanitized into '{}' like a previous substitution, so the module \
+				 names wouldn't be unique.",
+				postfix
+			))
+			.span(span)
+			.hint(crate::pretty_errors::SANITIZE));
+		}
+		postfixes.push(postfix);
+	}
+	Ok(Postfix::Sanitized(postfixes))
+}
+
+/// Renders the given tokens as the snake case of their identifiers and
+/// alphanumeric literals, ignoring punctuation and delimiters.
+///
+/// E.g. `Vec<u8>` becomes `vec_u8` and `&'static str` becomes `static_str`.
+fn sanitize(stream: TokenStream) -> String
+{
+	fn words(stream: TokenStream, result: &mut Vec<String>)
+	{
+		for token in stream
+		{
+			match token
+			{
+				TokenTree::Ident(ident) =>
+				{
+					let ident = ident.to_string();
+					// Raw identifiers only keep their name
+					result.push(ident.strip_prefix("r#").unwrap_or(&ident).to_snake_case());
+				},
+				TokenTree::Literal(lit) =>
+				{
+					let lit: String = lit
+						.to_string()
+						.chars()
+						.filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+						.collect();
+					result.push(lit.to_snake_case());
+				},
+				TokenTree::Group(group) => words(group.stream(), result),
+				TokenTree::Punct(_) => (),
+			}
+		}
+	}
+	let mut result = Vec::new();
+	words(stream, &mut result);
+	result.retain(|word| !word.is_empty());
+	result.join("_")
 }
 
 /// If the next token is the 'mod' keyword, substitutes the following module
 /// name with its disambiguation, returning 'mod' plus the disambiguation.
+///
+/// Likewise, if the next tokens are 'macro_rules!', substitutes the following
+/// macro name, returning 'macro_rules!' plus the disambiguation.
+///
+/// Returns nothing otherwise, so the caller substitutes the next token as
+/// usual and tries again, which leaves any attributes and visibility before
+/// the keyword in place.
 pub(crate) fn try_substitute_mod<'a, T: SubGroupIter<'a>>(
 	// If Some(), then tries to disambiguate, otherwise doesn't.
 	//
-	// First is the module name to disambiguate, then the substitution identifier to use
-	// for disambiguation.
-	mod_and_postfix_sub: &Option<(Ident, String)>,
+	// First is the module name to disambiguate, then what to postfix it with.
+	mod_and_postfix_sub: &Option<(Ident, Postfix)>,
 	substitutions: &SubstitutionGroup,
+	// The index of the substitution group
+	index: usize,
 	// The item being substituted. Will consume 'mod' and the following name if successful
 	item_iter: &mut TokenIter<'a, T>,
 ) -> TokenStream
@@ -59,24 +215,122 @@ pub(crate) fn try_substitute_mod<'a, T: SubGroupIter<'a>>(
 	let mut result = TokenStream::new();
 	if let Some((mod_name, mod_sub_ident)) = mod_and_postfix_sub
 	{
-		if let Ok(mod_keyword) = item_iter.extract_simple(|t| is_ident(t, Some("mod")), |t| t, None)
+		if let Ok(mod_keyword) = item_iter.extract_simple(
+			|t| is_ident(t, Some("mod")) || is_ident(t, Some("macro_rules")),
+			|t| t,
+			None,
+		)
 		{
-			result.extend(Some(mod_keyword).into_iter());
+			let is_macro = is_ident(&mod_keyword, Some("macro_rules"));
+			result.extend(Some(mod_keyword));
+			if is_macro
+			{
+				// Consume '!'
+				let bang = item_iter.next_fallible().unwrap().unwrap();
+				result.extend(Some(TokenTree::from(bang)));
+			}
 
 			// Consume mod name (since we will replace it)
 			let mod_name_t = item_iter.next_fallible().unwrap().unwrap();
 
-			let postfix = substitutions
-				.substitution_of(&mod_sub_ident)
-				.unwrap()
-				.substitutes_identifier()
-				.unwrap()
-				.to_string()
-				.to_snake_case();
-			let replacement_name = mod_name.to_string() + "_" + &postfix;
+			let replacement_name =
+				disambiguated_name(mod_name, mod_sub_ident, substitutions, index);
 			let replacement = Ident::new(&replacement_name, TokenTree::from(mod_name_t).span());
 			result.extend(Some(TokenTree::Ident(replacement)).into_iter());
 		}
 	}
 	result
 }
+
+/// Returns the name of the given module in the duplicate using the given
+/// substitution group with the given index.
+pub(crate) fn disambiguated_name(
+	mod_name: &Ident,
+	postfix: &Postfix,
+	substitutions: &SubstitutionGroup,
+	index: usize,
+) -> String
+{
+	match postfix
+	{
+		Postfix::Names(names) => names[index].clone(),
+		postfix => mod_name.to_string() + "_" + &postfix_of(substitutions, postfix, index),
+	}
+}
+
+/// Emits a note naming how the given module is disambiguated and its name in
+/// each duplicate (given the `explain_module_names` option).
+#[cfg(feature = "pretty_errors")]
+pub(crate) fn explain_module_names<'a>(
+	mod_name: &Ident,
+	postfix: &Postfix,
+	naming: &ModuleNaming,
+	sub_groups: impl Iterator<Item = &'a SubstitutionGroup>,
+)
+{
+	let how = match (postfix, naming)
+	{
+		(Postfix::Identifier(ident), _) =>
+		{
+			format!(
+				"the substitutions of the substitution identifier '{}'",
+				ident
+			)
+		},
+		(Postfix::Index, _) => "the index of each substitution group".to_string(),
+		(Postfix::Sanitized(_), ModuleNaming::Sanitize(ident)) =>
+		{
+			format!(
+				"the sanitized substitutions of the substitution identifier '{}'",
+				ident
+			)
+		},
+		(Postfix::Sanitized(_), _) => "the sanitized substitutions".to_string(),
+		(Postfix::Names(_), _) => "the names given by 'module_names'".to_string(),
+	};
+	let names = sub_groups
+		.enumerate()
+		.map(|(index, substitutions)| {
+			format!(
+				"Substitution group {}: {}",
+				index + 1,
+				disambiguated_name(mod_name, postfix, substitutions, index)
+			)
+		})
+		.collect::<Vec<_>>()
+		.join("\n");
+	crate::emit_note(
+		mod_name.span(),
+		&format!("Module '{}' is disambiguated using {}.", mod_name, how),
+		&names,
+	);
+}
+
+/// Returns the postfix to disambiguate a name with in the duplicate using the
+/// given substitution group with the given index.
+///
+/// The postfix is either the index, the snake case of the identifier
+/// substituted for the substitution identifier found using `find_simple`, or
+/// the postfix (or name) given for the duplicate.
+pub(crate) fn postfix_of(
+	substitutions: &SubstitutionGroup,
+	postfix: &Postfix,
+	index: usize,
+) -> String
+{
+	match postfix
+	{
+		Postfix::Identifier(postfix_sub) =>
+		{
+			substitutions
+				.substitution_of(postfix_sub)
+				.unwrap()
+				.substitutes_identifier()
+				.unwrap()
+				.to_string()
+				.to_snake_case()
+		},
+		Postfix::Index => index.to_string(),
+		Postfix::Sanitized(postfixes) | Postfix::Names(postfixes) => postfixes[index].clone(),
+	}
+}
@@ -1,28 +1,134 @@
 use crate::{
-	duplicate_impl,
+	eager::expand_eager,
 	error::Error,
+	limits::{check_duplicates, check_expected_duplicates},
+	lint::{check_empty_substitution, report, validate_lint_levels, SINGLE_GROUP},
 	pretty_errors::{
-		GLOBAL_SUB_SEMICOLON, NO_GROUPS, NO_GROUPS_HINT, NO_INVOCATION, SHORT_SYNTAX_NO_GROUPS,
-		VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS, VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS_ARGS,
+		ACCUMULATOR, ALIAS, AUTO_CFG, CASE_PRESERVING, COLUMNS, CONTINUES, DEFER_AS,
+		ENV_SUBSTITUTION, EXCEPT, EXPECT, FILE_MACRO, GLOBAL_SUB_OR_VERBOSE_GROUP,
+		GLOBAL_SUB_SEMICOLON, KEEP, LIMITS, MAYBE_ASYNC, MODULE_NAMES, NESTED_NAMES, NO_GROUPS,
+		NO_GROUPS_HINT, NO_INVOCATION, PATTERN, REPEAT_COUNT, REQUIRE, ROWS_FROM,
+		SHORT_SYNTAX_ARROWS, SHORT_SYNTAX_MISSING_SEPARATOR, SHORT_SYNTAX_NO_GROUPS,
+		TEMPLATE_SECTIONS, VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS,
+		VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS_ARGS, WRAP_IN, ZIP,
 	},
-	substitute::Substitution,
-	substitute_impl,
-	token_iter::{get_ident, is_ident, is_semicolon, SubGroupIter, Token, TokenIter},
-	DuplicationDefinition, Result, SubstitutionGroup,
+	substitute::{same_tokens, Pattern, Substitution},
+	token_iter::{
+		get_ident, is_ident, is_punct, is_semicolon, string_value, SubGroupIter, Token, TokenIter,
+	},
+	DuplicationDefinition, InvocationOptions, ModuleNaming, Result, SubstitutionGroup,
+};
+#[cfg(feature = "nested_invocation")]
+use crate::{
+	lint::{LintLevel, LintLevels, NESTED_SHADOWING},
+	new_group,
+	token_iter::{NESTED_DUPLICATE_NAME, NESTED_SUBSTITUTE_NAME},
 };
-use proc_macro::{Delimiter, Ident, Span, TokenStream, TokenTree};
-use std::collections::HashSet;
+#[cfg(feature = "module_disambiguation")]
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use proc_macro::{Delimiter, Ident, Spacing, Span, TokenStream, TokenTree};
+use std::{collections::HashSet, iter::once, path::PathBuf};
+
+/// The names of all invocation options.
+const OPTION_NAMES: &[&str] = &[
+	"attr_strings",
+	"allow_empty",
+	"repeat",
+	"interleave",
+	"columns",
+	"rows_from",
+	"continues",
+	"strict",
+	"lints",
+	"module_name",
+	"module_names",
+	"explain_module_names",
+	"max_duplicates",
+	"expect",
+	"require",
+	"recursive",
+	"protect_inner",
+	"positions",
+	"validate",
+	"unique_names",
+	"wrap_in",
+	"defer_as",
+	"nested_names",
+	"maybe_async",
+	"auto_cfg",
+];
+
+/// The names of invocation options that only apply when duplicating.
+const DUPLICATION_OPTION_NAMES: &[&str] = &[
+	"allow_empty",
+	"repeat",
+	"interleave",
+	"columns",
+	"rows_from",
+	"continues",
+	"module_name",
+	"module_names",
+	"explain_module_names",
+	"max_duplicates",
+	"expect",
+	"require",
+	"unique_names",
+	"wrap_in",
+	"defer_as",
+	"maybe_async",
+	"auto_cfg",
+];
+
+/// The name of the attributes continuing the substitution groups of an
+/// invocation with the `continues` option.
+const CONTINUED_ROWS_NAME: &str = "duplicate_item_rows";
+
+/// The keyword combining the substitution groups of two lists pairwise.
+const ZIP_NAME: &str = "zip";
+
+/// The keyword starting a substitution group to exclude in the short syntax.
+const EXCEPT_NAME: &str = "except";
 
-/// Parses all global substitutions, returning them.
+/// The keyword given instead of a substitution to keep its substitution
+/// identifier as-is.
+const KEEP_NAME: &str = "keep";
+
+/// The names of invocation options that take arguments in parentheses.
+const OPTIONS_WITH_ARGUMENTS: &[&str] = &[
+	"repeat",
+	"interleave",
+	"rows_from",
+	"lints",
+	"module_name",
+	"module_names",
+	"max_duplicates",
+	"expect",
+	"require",
+	"positions",
+	"nested_names",
+	"auto_cfg",
+	"wrap_in",
+	"defer_as",
+];
+
+/// The names of invocation options that may take arguments in parentheses.
+const OPTIONS_WITH_OPTIONAL_ARGUMENTS: &[&str] = &["maybe_async"];
+
+/// Parses any invocation options followed by all global substitutions,
+/// returning them without any duplications.
 ///
 /// If there are other tokens than global substitutions, returns an error.
-pub(crate) fn parse_global_substitutions_only(attr: TokenStream) -> Result<SubstitutionGroup>
+pub(crate) fn parse_global_substitutions_only(attr: TokenStream) -> Result<DuplicationDefinition>
 {
-	let empty_global = SubstitutionGroup::new();
-	let mut iter = TokenIter::new(attr, &empty_global, std::iter::empty());
+	let attr = expand_eager(attr)?;
+	#[cfg(feature = "nested_invocation")]
+	let header = attr.clone();
+	let mut iter = TokenIter::new(attr, SubstitutionGroup::empty(), std::iter::empty());
+	let options = validate_invocation_options(&mut iter, false)?;
+	let aliases = validate_aliases(&mut iter)?;
 	let global_substitutions = validate_global_substitutions(&mut iter)?;
 
-	if let Ok(None) = iter.peek()
+	if iter.peek()?.is_none()
 	{
 		// Accept global substitutions on their own
 		if global_substitutions.substitutions.is_empty()
@@ -34,11 +140,33 @@ pub(crate) fn parse_global_substitutions_only(attr: TokenStream) -> Result<Subst
 		}
 		else
 		{
-			Ok(global_substitutions)
+			let mut dup_def = DuplicationDefinition {
+				options,
+				global_substitutions,
+				duplications: Vec::new(),
+			};
+			apply_aliases(&aliases, &mut dup_def)?;
+			#[cfg(feature = "nested_invocation")]
+			check_nested_shadowing(header, &dup_def)?;
+			#[cfg(feature = "nested_invocation")]
+			check_nested_names(&dup_def);
+			#[cfg(feature = "nested_invocation")]
+			check_nested_names(&dup_def);
+			Ok(dup_def)
 		}
 	}
 	else
 	{
+		if looks_like_substitution_groups(iter.clone())
+		{
+			return Err(Error::new(
+				"'substitute_item' and 'substitute!' only accept global substitutions, not \
+				 substitution groups. Try 'duplicate_item' or 'duplicate!'.",
+			)
+			.span(iter.peek()?.unwrap().span())
+			.hint(crate::pretty_errors::SUBSTITUTE_GROUPS));
+		}
+
 		// There are more tokens, just try to get another substitution and return its
 		// error
 		#[cfg_attr(not(feature = "pretty_errors"), allow(unused_mut))]
@@ -46,10 +174,7 @@ pub(crate) fn parse_global_substitutions_only(attr: TokenStream) -> Result<Subst
 
 		#[cfg(feature = "pretty_errors")]
 		{
-			if validate_short_get_identifiers(&mut iter.clone()).is_ok()
-				|| validate_verbose_invocation(&mut iter)
-					.map(|res| res.is_some())
-					.unwrap_or(false)
+			if validate_short_get_identifiers(&mut iter).is_ok()
 			{
 				err = err.hint(
 					"Only global substitutions are allowed. Try 'duplicate' or 'duplicate_item'.",
@@ -60,6 +185,36 @@ pub(crate) fn parse_global_substitutions_only(attr: TokenStream) -> Result<Subst
 	}
 }
 
+/// Whether the given tokens are substitution groups in either syntax, like a
+/// duplicate invocation would have after its global substitutions.
+fn looks_like_substitution_groups<'a, T: SubGroupIter<'a>>(mut iter: TokenIter<'a, T>) -> bool
+{
+	if let Ok(Some(_)) = validate_verbose_invocation(&mut iter.clone())
+	{
+		return true;
+	}
+	match validate_short_get_identifiers(&mut iter)
+	{
+		Ok((idents, arrows)) =>
+		{
+			let mut result: ShortSubstitutions = idents
+				.into_iter()
+				.map(|(ident, args)| (ident, args, Vec::new()))
+				.collect();
+			validate_short_get_all_substitution_goups(
+				iter,
+				&mut result,
+				&mut Vec::new(),
+				&mut Vec::new(),
+				arrows,
+				&InvocationOptions::default(),
+			)
+			.is_ok() && !result[0].2.is_empty()
+		},
+		Err(_) => false,
+	}
+}
+
 /// Parses the invocation of duplicate, returning all the substitutions that
 /// should be made to code.
 ///
@@ -67,347 +222,2232 @@ pub(crate) fn parse_global_substitutions_only(attr: TokenStream) -> Result<Subst
 /// substitutions that should be applied to all duplicates but don't on their
 /// own indicate a duplicate. Then comes a list of substitution groups, each of
 /// which indicates on duplicate.
-pub(crate) fn parse_duplicate_invocation(attr: TokenStream) -> Result<DuplicationDefinition>
+///
+/// `item` is the item the invocation is on. With the `continues` option, the
+/// `duplicate_item_rows` attributes continuing the invocation are removed from
+/// it (see `extract_continued_rows`).
+pub(crate) fn parse_duplicate_invocation(
+	attr: TokenStream,
+	item: &mut TokenStream,
+) -> Result<DuplicationDefinition>
 {
-	let empty_global = SubstitutionGroup::new();
-	let mut iter = TokenIter::new(attr, &empty_global, std::iter::empty());
+	let attr = expand_eager(attr)?;
+	#[cfg(feature = "nested_invocation")]
+	let header = attr.clone();
+	let mut iter = TokenIter::new(attr, SubstitutionGroup::empty(), std::iter::empty());
+	let options = validate_invocation_options(&mut iter, true)?;
+	let aliases = validate_aliases(&mut iter)?;
+	let accumulators = validate_accumulators(&mut iter)?;
 	let global_substitutions = validate_global_substitutions(&mut iter)?;
+	// Nested invocations in the rest of the invocation are substituted with the
+	// global substitutions before they are expanded, like those in the code
+	#[cfg(feature = "nested_invocation")]
+	let globals = global_substitutions.clone();
+	#[cfg(feature = "nested_invocation")]
+	iter.set_global_subs(&globals);
 
-	if let (Ok(None), false) = (iter.peek(), global_substitutions.substitutions.is_empty())
+	// Without 'continues', any 'duplicate_item_rows' attributes are left to report
+	// themselves, as they may continue another invocation stacked on the item
+	let mut rows = Vec::new();
+	if let Some(span) = &options.continues
 	{
-		// Do not accept no duplicates
-		Err(Error::new(NO_GROUPS).hint(NO_GROUPS_HINT))
+		let (continued, rest) = extract_continued_rows(std::mem::take(item));
+		*item = rest;
+		if continued.is_empty()
+		{
+			return Err(Error::new(format!(
+				"No '{}' attributes continue the invocation.",
+				CONTINUED_ROWS_NAME
+			))
+			.span(*span)
+			.hint(CONTINUES));
+		}
+		rows = continued;
 	}
-	else if let Some(dups) = validate_verbose_invocation(&mut iter)?
+
+	let mut dup_def = parse_duplications(iter, options, global_substitutions, rows)?;
+	check_required_identifiers(&dup_def)?;
+	apply_aliases(&aliases, &mut dup_def)?;
+	check_accumulators(&accumulators, &dup_def)?;
+	dup_def.options.accumulators = accumulators;
+	if let ModuleNaming::Names(names, span) = &dup_def.options.module_name
 	{
-		Ok(DuplicationDefinition {
-			global_substitutions,
-			duplications: dups,
-		})
+		if names.len() != dup_def.duplications.len()
+		{
+			return Err(Error::new(format!(
+				"'module_names' gives {} module names for {} substitution groups.",
+				names.len(),
+				dup_def.duplications.len()
+			))
+			.span(*span)
+			.hint(MODULE_NAMES));
+		}
 	}
-	else
+	if let Some((_, span)) = &dup_def.options.maybe_async
 	{
-		// Otherwise, try short syntax
-		let substitutions = validate_short_attr(iter)?;
-		let mut reorder = Vec::new();
-
-		for _ in 0..substitutions[0].2.len()
+		if dup_def.duplications.len() != 2
 		{
-			reorder.push(SubstitutionGroup::new());
+			return Err(Error::new(format!(
+				"'maybe_async' takes either no substitution groups or two, one for the async and \
+				 one for the sync duplicate, found {}.",
+				dup_def.duplications.len()
+			))
+			.span(*span)
+			.hint(MAYBE_ASYNC));
 		}
-
-		for (ident, args, subs) in substitutions
+	}
+	if let Some((_, ident)) = &dup_def.options.auto_cfg
+	{
+		let name = ident.to_string();
+		if dup_def
+			.global_substitutions
+			.substitution_of(&name)
+			.is_none()
 		{
-			for (idx, sub) in subs.into_iter().enumerate()
+			if let Some(index) = dup_def
+				.duplications
+				.iter()
+				.position(|group| group.substitution_of(&name).is_none())
 			{
-				let substitution = Substitution::new(
-					&args,
-					TokenIter::new(sub, &SubstitutionGroup::new(), std::iter::empty()),
-				);
-				if let Ok(substitution) = substitution
-				{
-					reorder[idx].add_substitution(
-						Ident::new(&ident.clone(), Span::call_site()),
-						substitution,
-					)?;
-				}
-				else
-				{
-					return Err(Error::new(
-						"Duplicate internal error: Failed at creating substitution",
-					));
-				}
+				return Err(Error::new(format!(
+					"'auto_cfg' uses the substitution identifier '{}', which substitution group \
+					 {} doesn't define.",
+					name,
+					index + 1
+				))
+				.span(ident.span())
+				.hint(AUTO_CFG));
 			}
 		}
-
-		Ok(DuplicationDefinition {
-			global_substitutions,
-			duplications: reorder,
-		})
 	}
+	check_duplicates(&dup_def)?;
+	check_expected_duplicates(&dup_def)?;
+	#[cfg(feature = "nested_invocation")]
+	check_nested_shadowing(header, &dup_def)?;
+	Ok(dup_def)
 }
 
-/// Validates global substitutions and returns a substitution group with them.
+/// Returns an error if a substitution group of the given invocation doesn't
+/// define exactly the substitution identifiers given by its `require` option,
+/// if any.
 ///
-/// When it fails to validate a global substitution, it might return the next
-/// identifier that the iterator produced optionally followed by the next group
-/// too. The two must therefore be assumed to precede any tokentrees returned by
-/// the iterator after calling this function.
-/// This may happen if the global substitutions are followed by short-syntax,
-/// which starts the same way as a global substitution.
-fn validate_global_substitutions<'a, T: SubGroupIter<'a>>(
-	iter: &mut TokenIter<'a, T>,
-) -> Result<SubstitutionGroup>
+/// Groups produced by nested invocations have no useful spans, so errors name
+/// the group and are reported at the option instead.
+fn check_required_identifiers(dup_def: &DuplicationDefinition) -> Result<()>
 {
-	let mut sub_group = SubstitutionGroup::new();
-	while let Ok((ident, sub)) = extract_inline_substitution(iter)
+	let (required, span) = match &dup_def.options.require
 	{
-		sub_group.add_substitution(ident, sub)?;
-
-		if iter.has_next()?
+		Some(require) => require,
+		None => return Ok(()),
+	};
+	for (index, group) in dup_def.duplications.iter().enumerate()
+	{
+		let missing = required
+			.iter()
+			.find(|ident| group.substitution_of(ident).is_none());
+		let extra = group
+			.identifiers()
+			.filter(|ident| !required.contains(ident))
+			.min();
+		let error = match (missing, extra)
 		{
-			iter.expect_semicolon()
-				.map_err(|err| err.hint(GLOBAL_SUB_SEMICOLON))?;
-		}
+			(Some(missing), Some(extra)) =>
+			{
+				format!(
+					"Substitution group {} defines '{}' instead of the required substitution \
+					 identifier '{}'.",
+					index + 1,
+					extra,
+					missing
+				)
+			},
+			(Some(missing), None) =>
+			{
+				format!(
+					"Substitution group {} doesn't define the required substitution identifier \
+					 '{}'.",
+					index + 1,
+					missing
+				)
+			},
+			(None, Some(extra)) =>
+			{
+				format!(
+					"Substitution group {} defines '{}', which isn't a required substitution \
+					 identifier.",
+					index + 1,
+					extra
+				)
+			},
+			(None, None) => continue,
+		};
+		return Err(Error::new(error).span(*span).hint(REQUIRE));
 	}
-	Ok(sub_group)
+	Ok(())
 }
 
-/// Validates that a duplicate invocation uses the verbose syntax, and returns
-/// all the substitutions that should be made.
-///
-/// Returns 'Some' if the tokens given definitely represent the use of verbose
-/// syntax, even though it might still contain errors.
-/// Returns 'None' if an error occurred before verbose syntax was recognized
-fn validate_verbose_invocation<'a, T: SubGroupIter<'a>>(
-	iter: &mut TokenIter<'a, T>,
-) -> Result<Option<Vec<SubstitutionGroup>>>
+/// Parses the substitution groups of a duplicate invocation, given the
+/// options and global substitutions that precede them.
+fn parse_duplications<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+	options: InvocationOptions,
+	global_substitutions: SubstitutionGroup,
+	rows: Vec<(TokenStream, Span)>,
+) -> Result<DuplicationDefinition>
 {
-	if let Ok(Some(Token::Group(Delimiter::Bracket, _, _))) = iter.peek()
+	let verbose = next_is_verbose_group(&mut iter)?;
+	let zip = next_is_zip(&iter);
+	let short = !verbose && !zip && !options.columns && options.repeat.is_none();
+	if let (Some((_, span)), false) = (&options.rows_from, short)
 	{
-		let mut sub_groups = Vec::new();
+		return Err(
+			Error::new("'rows_from' can only be used with the short syntax.")
+				.span(*span)
+				.hint(ROWS_FROM),
+		);
+	}
+	if let (Some(span), false) = (&options.continues, short)
+	{
+		return Err(
+			Error::new("'continues' can only be used with the short syntax.")
+				.span(*span)
+				.hint(CONTINUES),
+		);
+	}
 
-		let mut substitution_ids = None;
-		while iter.has_next()?
+	if let Some((count, span)) = options.repeat
+	{
+		if let Some(token) = iter.next_fallible()?
 		{
-			let (body, span) = iter.next_group(Some(Delimiter::Bracket)).map_err(|err| {
-				err.hint(
-					"When using verbose syntax, a substitutions must be enclosed in a \
-					 group.\nExample:\n..\n[\n\tidentifier1 [ substitution1 ]\n\tidentifier2 [ \
-					 substitution2 ]\n]",
-				)
-			})?;
-			sub_groups.push(extract_verbose_substitutions(
-				body,
-				span,
-				&substitution_ids,
-			)?);
-			if None == substitution_ids
-			{
-				substitution_ids = Some(
-					sub_groups[0]
-						.identifiers_with_args()
-						.map(|(ident, count)| (ident.clone(), count))
-						.collect(),
-				)
-			}
+			return Err(Error::new(
+				"Substitution groups cannot be given together with the 'repeat' option.",
+			)
+			.span(token.span())
+			.found(token.describe())
+			.hint(REPEAT_COUNT));
 		}
-		Ok(Some(sub_groups))
+		if count == 0 && !options.allow_empty
+		{
+			return Err(Error::new("Must repeat at least once.")
+				.span(span)
+				.hint("Use the 'allow_empty' option to remove the item instead."));
+		}
+		Ok(DuplicationDefinition {
+			options,
+			global_substitutions,
+			duplications: (0..count).map(|_| SubstitutionGroup::new()).collect(),
+		})
+	}
+	else if options.maybe_async.is_some() && !iter.has_next()?
+	{
+		// The async and the sync duplicate
+		Ok(DuplicationDefinition {
+			options,
+			global_substitutions,
+			duplications: vec![SubstitutionGroup::new(), SubstitutionGroup::new()],
+		})
+	}
+	else if options.allow_empty && !iter.has_next()?
+	{
+		// Explicitly no duplicates
+		Ok(DuplicationDefinition {
+			options,
+			global_substitutions,
+			duplications: Vec::new(),
+		})
+	}
+	else if let (Ok(None), false) = (iter.peek(), global_substitutions.substitutions.is_empty())
+	{
+		// Do not accept no duplicates
+		Err(Error::new(NO_GROUPS).hint(NO_GROUPS_HINT))
+	}
+	else if zip && !options.columns
+	{
+		Ok(DuplicationDefinition {
+			options,
+			global_substitutions,
+			duplications: validate_zip(iter)?,
+		})
+	}
+	else if let Some(dups) = validate_verbose_invocation(&mut iter)?
+	{
+		Ok(DuplicationDefinition {
+			options,
+			global_substitutions,
+			duplications: dups,
+		})
+	}
+	else if options.columns
+	{
+		let substitutions = validate_columns(iter, &options)?;
+		Ok(DuplicationDefinition {
+			options,
+			global_substitutions,
+			duplications: short_substitution_groups(substitutions, Vec::new())?,
+		})
 	}
 	else
 	{
-		Ok(None)
+		// Otherwise, try short syntax
+		let (substitutions, docs) = validate_short_attr(iter, &options, rows)?;
+		Ok(DuplicationDefinition {
+			options,
+			global_substitutions,
+			duplications: short_substitution_groups(substitutions, docs)?,
+		})
 	}
 }
 
-/// Extracts a substitution identifier followed by
-/// an optional parameter list, followed by a substitution.
-fn extract_inline_substitution<'a, T: SubGroupIter<'a>>(
-	stream: &mut TokenIter<'a, T>,
-) -> Result<(Ident, Substitution)>
+/// Whether the next tokens, after any doc attributes, are a verbose syntax
+/// substitution group.
+fn next_is_verbose_group<'a, T: SubGroupIter<'a>>(iter: &mut TokenIter<'a, T>) -> Result<bool>
 {
-	let ident = stream.extract_identifier(Some("a substitution identifier"))?;
-	let param_group = stream.next_group(Some(Delimiter::Parenthesis));
-	let substitution = stream.next_group(Some(Delimiter::Bracket));
+	match iter.peek()?
+	{
+		Some(Token::Group(Delimiter::Bracket, _, _)) => Ok(true),
+		Some(Token::Simple(t)) if is_punct(t, '#') =>
+		{
+			let mut iter = iter.clone();
+			iter.extract_doc_attributes()?;
+			Ok(matches!(
+				iter.peek()?,
+				Some(Token::Group(Delimiter::Bracket, _, _))
+			))
+		},
+		_ => Ok(false),
+	}
+}
 
-	if let Ok((params, span)) = param_group
+/// Whether the next tokens are `zip` followed by two groups delimited by '{}'.
+///
+/// A single group would instead be a global substitution of `zip`.
+fn next_is_zip<'a, T: SubGroupIter<'a>>(iter: &TokenIter<'a, T>) -> bool
+{
+	let mut iter = iter.clone();
+	matches!(iter.next_fallible(), Ok(Some(Token::Simple(TokenTree::Ident(ident))))
+		if ident.to_string() == ZIP_NAME)
+		&& (0..2).all(|_| {
+			matches!(
+				iter.next_fallible(),
+				Ok(Some(Token::Group(Delimiter::Brace, _, _)))
+			)
+		})
+}
+
+/// Validates `zip` followed by two groups delimited by '{}', each containing
+/// substitution groups in the short or verbose syntax (possibly produced by
+/// nested invocations).
+///
+/// Returns the substitution groups of both sides combined pairwise, i.e. the
+/// first group of each side combined, then the second, etc.
+fn validate_zip<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+) -> Result<Vec<SubstitutionGroup>>
+{
+	let zip = iter.extract_identifier(None)?;
+	let (left, _) = iter.next_group(Some(Delimiter::Brace))?;
+	let (right, right_span) = iter
+		.next_group(Some(Delimiter::Brace))
+		.map_err(|err| err.hint(ZIP))?;
+	if let Some(token) = iter.next_fallible()?
 	{
-		// Found parameters, now get substitution
-		substitution
-			.and_then(|(sub, _)| {
-				extract_argument_list(params.clone())
-					.map(|args| Substitution::new(&args, sub).unwrap())
-					.or_else(|err| Err(err))
-			})
-			.or_else(|err| {
-				stream.push_front(Token::Group(Delimiter::Parenthesis, params, span));
-				Err(err)
-			})
+		return Err(Error::new("Unexpected token after 'zip'.")
+			.span(token.span())
+			.found(token.describe())
+			.hint(ZIP));
 	}
-	else
+	let left = validate_zip_side(left)?;
+	let right = validate_zip_side(right)?;
+
+	if left.len() != right.len()
 	{
-		// No parameters, get substitution
-		substitution
-			.map(|(sub, _)| Substitution::new_simple(sub.process_all()))
-			.map_err(|old_err| Error::new("Expected '(' or '['.").span(old_err.get_span()))
+		return Err(Error::new(format!(
+			"'zip' combines {} substitution groups with {} substitution groups.",
+			left.len(),
+			right.len()
+		))
+		.span(zip.span())
+		.hint(ZIP));
 	}
-	.or_else(|err| {
-		stream.push_front(Token::Simple(TokenTree::Ident(ident.clone())));
-		Err(err)
-	})
-	.map(|result| (ident, result))
+	left.into_iter()
+		.zip(right)
+		.map(|(mut group, right_group)| {
+			if let Some(ident) = right_group
+				.identifiers()
+				.chain(right_group.absent.iter())
+				.find(|ident| group.substitution_of(ident).is_some() || group.is_absent(ident))
+			{
+				return Err(Error::new(format!(
+					"'{}' is substituted by both sides of 'zip'.",
+					ident
+				))
+				.span(right_span)
+				.hint(ZIP));
+			}
+			group.merge(right_group);
+			Ok(group)
+		})
+		.collect()
 }
 
-/// Extracts a substitution group in the verbose syntax.
-fn extract_verbose_substitutions<'a, T: SubGroupIter<'a>>(
+/// Validates one side of `zip`, which are substitution groups in the short or
+/// verbose syntax.
+fn validate_zip_side<'a, T: SubGroupIter<'a>>(
 	mut iter: TokenIter<'a, T>,
-	iter_span: Span,
-	existing: &Option<HashSet<(String, usize)>>,
-) -> Result<SubstitutionGroup>
+) -> Result<Vec<SubstitutionGroup>>
 {
-	if !iter.has_next()?
+	if let Some(groups) = validate_verbose_invocation(&mut iter)?
 	{
-		return Err(Error::new(NO_GROUPS).span(iter_span));
+		Ok(groups)
+	}
+	else
+	{
+		let (substitutions, docs) =
+			validate_short_attr(iter, &InvocationOptions::default(), Vec::new())?;
+		short_substitution_groups(substitutions, docs)
 	}
+}
 
-	// Map idents to string reference so we can use HashSet::difference
-	let expected_idents: HashSet<_> = existing.as_ref().map_or(HashSet::new(), |idents| {
-		idents
-			.iter()
-			.map(|(ident, count)| (ident, count.clone()))
-			.collect()
-	});
+/// The substitution identifiers of a short syntax invocation, each with its
+/// arguments and its substitution for every substitution group, which is `None`
+/// if the group keeps the identifier as-is (`keep`).
+type ShortSubstitutions = Vec<(Ident, Vec<String>, Vec<Option<TokenStream>>)>;
 
-	let mut substitutions = SubstitutionGroup::new();
-	let mut stream = iter;
+/// The doc attributes given before each substitution group of a short syntax
+/// invocation.
+type ShortDocs = Vec<Vec<TokenStream>>;
 
-	while stream.has_next()?
+/// The substitution groups excluded by a short syntax invocation (`except`),
+/// each with the span of its `except`.
+type ShortExclusions = Vec<(Vec<Option<TokenStream>>, Span)>;
+
+/// The substitution identifiers of a short syntax invocation, each with its
+/// arguments.
+type ShortIdentifiers = Vec<(Ident, Vec<String>)>;
+
+/// Converts the substitutions of each substitution identifier, as given by the
+/// short syntax, into substitution groups, each with its given doc attributes.
+fn short_substitution_groups(
+	substitutions: ShortSubstitutions,
+	docs: ShortDocs,
+) -> Result<Vec<SubstitutionGroup>>
+{
+	let mut reorder = Vec::new();
+
+	for _ in 0..substitutions[0].2.len()
 	{
-		#[allow(unused_mut)]
-		let mut hint: Option<&str> = None;
+		reorder.push(SubstitutionGroup::new());
+	}
+	for (group, docs) in reorder.iter_mut().zip(docs)
+	{
+		group.docs = docs;
+	}
 
-		#[cfg(feature = "pretty_errors")]
+	for (ident, args, subs) in substitutions
+	{
+		for (idx, sub) in subs.into_iter().enumerate()
 		{
-			if stream.has_next_semicolon()?
+			let substitution = match sub
 			{
-				hint = Some(crate::pretty_errors::VERBOSE_SEMICOLON);
-			}
+				None => Substitution::new_keep(ident.clone()),
+				Some(sub) =>
+				{
+					Substitution::new(
+						&args,
+						TokenIter::new(sub, SubstitutionGroup::empty(), std::iter::empty()),
+					)?
+				},
+			};
+			reorder[idx].add_substitution(ident.clone(), substitution)?;
 		}
+	}
+
+	Ok(reorder)
+}
 
-		let (ident, substitution) = extract_inline_substitution(&mut stream)
-			.map_err(|err| hint.into_iter().fold(err, |err, hint| err.hint(hint)))?;
-		if !expected_idents.is_empty()
-			&& !expected_idents.contains(&(&ident.to_string(), substitution.argument_count()))
+/// Validates any invocation options and returns them.
+///
+/// Options must come first in an invocation and each is an option name,
+/// followed by its arguments in parentheses if it takes any, followed by a `;`
+/// (unless it is the last thing in the invocation).
+/// An identifier that is not followed by a `;` is not considered an option,
+/// leaving it to be parsed as e.g. a global substitution.
+///
+/// If not `duplicating`, options that only apply to duplication are rejected.
+fn validate_invocation_options<'a, T: SubGroupIter<'a>>(
+	iter: &mut TokenIter<'a, T>,
+	duplicating: bool,
+) -> Result<InvocationOptions>
+{
+	let mut options = InvocationOptions::default();
+	let mut given = HashSet::new();
+	while let Some(Token::Simple(TokenTree::Ident(ident))) = iter.peek()?
+	{
+		let ident = ident.clone();
+		let name = ident.to_string();
+		if !OPTION_NAMES.contains(&name.as_str())
 		{
-			let (msg, _hint) = if expected_idents
-				.iter()
-				.find(|(i, _)| **i == ident.to_string())
-				.is_some()
+			break;
+		}
+		let start = iter.clone();
+		iter.next_fallible()?;
+		let mut arguments = None;
+		if OPTIONS_WITH_ARGUMENTS.contains(&name.as_str())
+			|| OPTIONS_WITH_OPTIONAL_ARGUMENTS.contains(&name.as_str())
+		{
+			if let Some(Token::Group(Delimiter::Parenthesis, _, _)) = iter.peek()?
 			{
-				(
-					"Wrong argument count for substitution identifier.",
-					VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS_ARGS,
-				)
+				arguments = Some(iter.next_group(Some(Delimiter::Parenthesis))?);
 			}
-			else
+			else if OPTIONS_WITH_ARGUMENTS.contains(&name.as_str())
 			{
-				(
-					"Unexpected substitution identifier.",
-					VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS,
-				)
-			};
-			return Err(Error::new(msg).span(ident.span()).hint(_hint));
+				iter.push_front(Token::Simple(TokenTree::Ident(ident)));
+				break;
+			}
 		}
-		substitutions.add_substitution(ident, substitution)?;
-	}
+		if iter.has_next()? && !iter.has_next_semicolon()?
+		{
+			if let Some((args, span)) = arguments
+			{
+				iter.push_front(Token::Group(Delimiter::Parenthesis, args, span));
+			}
+			iter.push_front(Token::Simple(TokenTree::Ident(ident)));
+			break;
+		}
+		// Option names are also valid substitution identifiers, so a short syntax
+		// header of just an option name (e.g. 'columns; [u8]; [u16];') keeps
+		// declaring the identifier, unless a verbose substitution group follows
+		if duplicating && looks_like_substitution_groups(start.clone())
+		{
+			let mut rest = iter.clone();
+			rest.next_fallible()?;
+			if !matches!(validate_verbose_invocation(&mut rest), Ok(Some(_)))
+			{
+				*iter = start;
+				break;
+			}
+		}
+		if !given.insert(name.clone())
+		{
+			return Err(
+				Error::new(format!("Option '{}' given multiple times.", name)).span(ident.span()),
+			);
+		}
+		if given.contains("module_name") && given.contains("module_names")
+		{
+			return Err(Error::new(
+				"Options 'module_name' and 'module_names' can't both be given.",
+			)
+			.span(ident.span()));
+		}
+		if given.contains("repeat") && given.contains("maybe_async")
+		{
+			return Err(
+				Error::new("Options 'repeat' and 'maybe_async' can't both be given.")
+					.span(ident.span())
+					.hint(MAYBE_ASYNC),
+			);
+		}
+		if !duplicating && DUPLICATION_OPTION_NAMES.contains(&name.as_str())
+		{
+			return Err(Error::new(format!(
+				"Option '{}' can only be used when duplicating.",
+				name
+			))
+			.span(ident.span())
+			.hint("Try 'duplicate' or 'duplicate_item'."));
+		}
+		match name.as_str()
+		{
+			"attr_strings" => options.attr_strings = true,
+			"allow_empty" => options.allow_empty = true,
+			"columns" => options.columns = true,
+			"strict" => options.strict = true,
+			"explain_module_names" => options.explain_module_names = true,
+			"recursive" => options.recursive = true,
+			"protect_inner" => options.protect_inner = true,
+			"unique_names" => options.unique_names = true,
+			"positions" =>
+			{
+				#[cfg(feature = "positions")]
+				{
+					options.positions = crate::positions::validate_positions(arguments.unwrap().0)?;
+				}
+				#[cfg(not(feature = "positions"))]
+				{
+					return Err(
+						Error::new("Option 'positions' requires the 'positions' feature.")
+							.span(ident.span())
+							.hint(crate::pretty_errors::POSITIONS),
+					);
+				}
+			},
+			"validate" =>
+			{
+				#[cfg(feature = "validate")]
+				{
+					options.validate = true;
+				}
+				#[cfg(not(feature = "validate"))]
+				{
+					return Err(
+						Error::new("Option 'validate' requires the 'validate' feature.")
+							.span(ident.span())
+							.hint(crate::pretty_errors::VALIDATE),
+					);
+				}
+			},
+			"lints" => options.lints = validate_lint_levels(arguments.unwrap().0)?,
+			"continues" => options.continues = Some(ident.span()),
+			"auto_cfg" =>
+			{
+				let (mut args, _) = arguments.unwrap();
+				let key = args
+					.extract_identifier(Some("a configuration option"))
+					.map_err(|err| err.hint(AUTO_CFG))?;
+				args.expect_simple(|t| is_punct(t, '='), Some("'='"))
+					.map_err(|err| err.hint(AUTO_CFG))?;
+				let ident = args
+					.extract_identifier(Some("a substitution identifier"))
+					.map_err(|err| err.hint(AUTO_CFG))?;
+				if let Some(token) = args.next_fallible()?
+				{
+					return Err(Error::new("Unexpected token.")
+						.span(token.span())
+						.found(token.describe())
+						.hint(AUTO_CFG));
+				}
+				options.auto_cfg = Some((key, ident));
+			},
+			"maybe_async" =>
+			{
+				let predicate = match arguments
+				{
+					Some((args, span)) =>
+					{
+						let predicate = args.to_token_stream();
+						if predicate.is_empty()
+						{
+							return Err(Error::new("Expected a configuration predicate.")
+								.span(span)
+								.hint(MAYBE_ASYNC));
+						}
+						Some((predicate, span))
+					},
+					None => None,
+				};
+				options.maybe_async = Some((predicate, ident.span()));
+			},
+			"repeat" =>
+			{
+				let (mut args, span) = arguments.unwrap();
+				options.repeat = Some(validate_count(
+					&mut args,
+					span,
+					"the number of duplicates to make",
+					REPEAT_COUNT,
+				)?);
+			},
+			"max_duplicates" =>
+			{
+				let (mut args, span) = arguments.unwrap();
+				options.max_duplicates = Some(validate_count(
+					&mut args,
+					span,
+					"the maximum number of duplicates",
+					LIMITS,
+				)?);
+			},
+			"expect" =>
+			{
+				let (mut args, span) = arguments.unwrap();
+				options.expect = Some(validate_count(
+					&mut args,
+					span,
+					"the number of substitution groups",
+					EXPECT,
+				)?);
+			},
+			"require" =>
+			{
+				let (args, span) = arguments.unwrap();
+				let idents = extract_argument_list(args).map_err(|err| err.hint(REQUIRE))?;
+				if idents.is_empty()
+				{
+					return Err(Error::new("Expected substitution identifiers.")
+						.span(span)
+						.hint(REQUIRE));
+				}
+				options.require = Some((idents, span));
+			},
+			"defer_as" =>
+			{
+				let (args, span) = arguments.unwrap();
+				options.defer_as = Some(validate_defer_as(args.to_token_stream(), span)?);
+			},
+			"wrap_in" =>
+			{
+				let (args, span) = arguments.unwrap();
+				options.wrap_in = Some(validate_wrap_in(args.to_token_stream(), span)?);
+			},
+			"rows_from" =>
+			{
+				let (mut args, span) = arguments.unwrap();
+				let path = match args.next_fallible()?
+				{
+					Some(Token::Simple(TokenTree::Literal(lit))) =>
+					{
+						string_value(&lit).map(|path| (path, lit.span()))
+					},
+					_ => None,
+				}
+				.ok_or_else(|| {
+					Error::new("Expected the path of a file with substitution groups.")
+						.span(span)
+						.hint(ROWS_FROM)
+				})?;
+				if let Some(token) = args.next_fallible()?
+				{
+					return Err(Error::new("Unexpected token.")
+						.span(token.span())
+						.found(token.describe()));
+				}
+				options.rows_from = Some(path);
+			},
+			"interleave" =>
+			{
+				let (mut args, _) = arguments.unwrap();
+				let order = args.extract_identifier(Some("'by_item' or 'by_group'"))?;
+				options.interleave_by_item = match order.to_string().as_str()
+				{
+					"by_item" => true,
+					"by_group" => false,
+					_ =>
+					{
+						return Err(Error::new("Unknown interleaving.")
+							.span(order.span())
+							.hint("Expected 'by_item' or 'by_group'."))
+					},
+				};
+				if let Some(token) = args.next_fallible()?
+				{
+					return Err(Error::new("Unexpected token.")
+						.span(token.span())
+						.found(token.describe()));
+				}
+			},
+			"module_name" =>
+			{
+				let (mut args, _) = arguments.unwrap();
+				let naming =
+					args.extract_identifier(Some("'index', 'auto', or 'sanitize(ident)'"))?;
+				options.module_name = match naming.to_string().as_str()
+				{
+					"index" => ModuleNaming::Index,
+					"auto" => ModuleNaming::Auto,
+					"sanitize" =>
+					{
+						let (mut ident_iter, _) = args.next_group(Some(Delimiter::Parenthesis))?;
+						let ident =
+							ident_iter.extract_identifier(Some("a substitution identifier"))?;
+						if let Some(token) = ident_iter.next_fallible()?
+						{
+							return Err(Error::new("Unexpected token.")
+								.span(token.span())
+								.found(token.describe()));
+						}
+						ModuleNaming::Sanitize(ident)
+					},
+					_ =>
+					{
+						return Err(Error::new("Unknown module naming.")
+							.span(naming.span())
+							.hint("Expected 'index', 'auto', or 'sanitize(ident)'."))
+					},
+				};
+				if let Some(token) = args.next_fallible()?
+				{
+					return Err(Error::new("Unexpected token.")
+						.span(token.span())
+						.found(token.describe()));
+				}
+			},
+			"nested_names" =>
+			{
+				#[cfg(feature = "nested_invocation")]
+				{
+					let (args, span) = arguments.unwrap();
+					let names =
+						extract_argument_list(args).map_err(|err| err.hint(NESTED_NAMES))?;
+					let names = match <[String; 2]>::try_from(names)
+					{
+						Ok([duplicate_name, substitute_name]) =>
+						{
+							Some(std::rc::Rc::new((duplicate_name, substitute_name)))
+						},
+						Err(_) =>
+						{
+							return Err(Error::new(
+								"Expected the names of 'duplicate' and 'substitute', separated by \
+								 ','.",
+							)
+							.span(span)
+							.hint(NESTED_NAMES))
+						},
+					};
+					// The names also apply to the rest of the invocation
+					iter.set_nested_names(names.clone());
+					options.nested_names = names;
+				}
+				#[cfg(not(feature = "nested_invocation"))]
+				{
+					return Err(Error::new(
+						"Option 'nested_names' requires the 'nested_invocation' feature.",
+					)
+					.span(ident.span())
+					.hint(NESTED_NAMES));
+				}
+			},
+			"module_names" =>
+			{
+				let (args, span) = arguments.unwrap();
+				options.module_name = ModuleNaming::Names(validate_module_names(args)?, span);
+			},
+			_ => unreachable!(),
+		}
+		if iter.has_next()?
+		{
+			iter.expect_semicolon()?;
+		}
+	}
+	Ok(options)
+}
+
+/// Validates the arguments of the `module_names` option, which are unique
+/// identifiers each enclosed in '[]' or '{}', separated by ','.
+fn validate_module_names<'a, T: SubGroupIter<'a>>(mut args: TokenIter<'a, T>)
+	-> Result<Vec<Ident>>
+{
+	let mut names: Vec<Ident> = Vec::new();
+	while args.has_next()?
+	{
+		let (mut name_iter, _) = args
+			.next_substitution()
+			.map_err(|err| err.hint(MODULE_NAMES))?;
+		let name = name_iter
+			.extract_identifier(Some("a module name"))
+			.map_err(|err| err.hint(MODULE_NAMES))?;
+		if let Some(token) = name_iter.next_fallible()?
+		{
+			return Err(Error::new("Expected only a module name.")
+				.span(token.span())
+				.found(token.describe())
+				.hint(MODULE_NAMES));
+		}
+		if names
+			.iter()
+			.any(|other| other.to_string() == name.to_string())
+		{
+			return Err(
+				Error::new(format!("Module name '{}' given multiple times.", name))
+					.span(name.span())
+					.hint(MODULE_NAMES),
+			);
+		}
+		names.push(name);
+		if args.has_next()?
+		{
+			args.expect_comma()?;
+		}
+	}
+	Ok(names)
+}
+
+/// Validates the arguments of an option taking a count (e.g. `repeat`), which
+/// must be a single integer literal, and returns the count and its span.
+///
+/// `what` describes the count and `hint` how to use the option.
+fn validate_count<'a, T: SubGroupIter<'a>>(
+	args: &mut TokenIter<'a, T>,
+	group_span: Span,
+	what: &str,
+	hint: &str,
+) -> Result<(usize, Span)>
+{
+	let not_literal = |span| {
+		Error::new(format!("Expected {}.", what))
+			.span(span)
+			.hint(hint)
+	};
+	let count = match args.next_fallible()?
+	{
+		Some(Token::Simple(TokenTree::Literal(lit))) =>
+		{
+			let count = lit
+				.to_string()
+				.parse::<usize>()
+				.map_err(|_| not_literal(lit.span()).found(&lit))?;
+			(count, lit.span())
+		},
+		Some(token) => return Err(not_literal(token.span()).found(token.describe())),
+		None => return Err(not_literal(group_span)),
+	};
+	if let Some(token) = args.next_fallible()?
+	{
+		return Err(Error::new(format!("Unexpected token after {}.", what))
+			.span(token.span())
+			.found(token.describe())
+			.hint(hint));
+	}
+	Ok(count)
+}
+
+/// Validates the arguments of the `defer_as` option, which are the name of the
+/// macro to emit the duplicates in, optionally preceded by `pub`, and returns
+/// whether it was and the name.
+fn validate_defer_as(args: TokenStream, span: Span) -> Result<(bool, Ident)>
+{
+	let tokens: Vec<_> = args.into_iter().collect();
+	match &tokens[..]
+	{
+		[TokenTree::Ident(name)] if name.to_string() != "pub" => Ok((false, name.clone())),
+		[vis, TokenTree::Ident(name)] if is_ident(vis, Some("pub")) => Ok((true, name.clone())),
+		_ =>
+		{
+			Err(
+				Error::new("Expected the name of the macro, optionally preceded by 'pub'.")
+					.span(tokens.first().map_or(span, TokenTree::span))
+					.hint(DEFER_AS),
+			)
+		},
+	}
+}
+
+/// Validates the arguments of the `wrap_in` option, which are the name of the
+/// module to wrap the duplicates in, optionally preceded by its visibility,
+/// and returns the visibility and name.
+fn validate_wrap_in(args: TokenStream, span: Span) -> Result<(TokenStream, Ident)>
+{
+	let mut tokens: Vec<_> = args.into_iter().collect();
+	let name = match tokens.pop()
+	{
+		Some(TokenTree::Ident(name)) if name.to_string() != "pub" => name,
+		token =>
+		{
+			return Err(
+				Error::new("Expected the name of the module to wrap the duplicates in.")
+					.span(token.map_or(span, |t| t.span()))
+					.hint(WRAP_IN),
+			)
+		},
+	};
+	match &tokens[..]
+	{
+		[] => (),
+		[vis] if is_ident(vis, Some("pub")) => (),
+		[vis, TokenTree::Group(group)]
+			if is_ident(vis, Some("pub")) && group.delimiter() == Delimiter::Parenthesis =>
+		{},
+		[token, ..] =>
+		{
+			return Err(Error::new("Expected a visibility before the module name.")
+				.span(token.span())
+				.hint(WRAP_IN))
+		},
+	}
+	Ok((tokens.into_iter().collect(), name))
+}
+
+/// Validates any substitution identifier aliases and returns them as pairs of
+/// the alias and the substitution identifier it is an alias of.
+///
+/// Aliases come after any invocation options and each is of the form
+/// `alias name = ident;`.
+/// Like for options, tokens that don't take that form are left for e.g. short
+/// syntax to parse.
+fn validate_aliases<'a, T: SubGroupIter<'a>>(
+	iter: &mut TokenIter<'a, T>,
+) -> Result<Vec<(Ident, Ident)>>
+{
+	let mut aliases: Vec<(Ident, Ident)> = Vec::new();
+	loop
+	{
+		let alias_keyword = match iter.extract_simple(|t| is_ident(t, Some("alias")), |t| t, None)
+		{
+			Ok(alias_keyword) => alias_keyword,
+			Err(err) if err.is_fatal() => return Err(err),
+			Err(_) => break,
+		};
+		let alias = match iter.peek()?
+		{
+			Some(Token::Simple(TokenTree::Ident(alias))) => alias.clone(),
+			_ =>
+			{
+				iter.push_front(Token::Simple(alias_keyword));
+				break;
+			},
+		};
+		iter.next_fallible()?;
+		if !matches!(iter.peek()?, Some(Token::Simple(TokenTree::Punct(p))) if p.as_char() == '=')
+		{
+			iter.push_front(Token::Simple(TokenTree::Ident(alias)));
+			iter.push_front(Token::Simple(alias_keyword));
+			break;
+		}
+		iter.next_fallible()?;
+		let ident = iter.extract_identifier(Some("the substitution identifier to alias"))?;
+
+		if aliases
+			.iter()
+			.any(|(other, _)| other.to_string() == alias.to_string())
+		{
+			return Err(
+				Error::new(format!("Alias '{}' declared multiple times.", alias))
+					.span(alias.span()),
+			);
+		}
+		aliases.push((alias, ident));
+		if iter.has_next()?
+		{
+			iter.expect_semicolon()?;
+		}
+	}
+	Ok(aliases)
+}
+
+/// Adds the given aliases to the global substitutions and every substitution
+/// group, each using the same substitution as the identifier it is an alias
+/// of.
+///
+/// Returns an error if an alias is already a substitution identifier or if
+/// the aliased identifier isn't a substitution identifier.
+fn apply_aliases(aliases: &[(Ident, Ident)], dup_def: &mut DuplicationDefinition) -> Result<()>
+{
+	for (alias, ident) in aliases
+	{
+		let (alias_name, ident_name) = (alias.to_string(), ident.to_string());
+		let mut found = false;
+		for group in Some(&mut dup_def.global_substitutions)
+			.into_iter()
+			.chain(dup_def.duplications.iter_mut())
+		{
+			if group.substitution_of(&alias_name).is_some()
+			{
+				return Err(Error::new(format!(
+					"Alias '{}' is already a substitution identifier.",
+					alias_name
+				))
+				.span(alias.span())
+				.hint(ALIAS));
+			}
+			if let Some(substitution) = group.substitution_of(&ident_name).cloned()
+			{
+				group.add_substitution(alias.clone(), substitution)?;
+				found = true;
+			}
+		}
+		if !found
+		{
+			return Err(Error::new(format!(
+				"Alias of '{}', which is not a substitution identifier.",
+				ident_name
+			))
+			.span(ident.span())
+			.hint(ALIAS));
+		}
+	}
+	Ok(())
+}
+
+/// Validates any accumulator declarations and returns them as pairs of the
+/// accumulator and the fragment it accumulates.
+///
+/// Accumulators come after any aliases and each is of the form
+/// `acc name += [fragment];`. Like for aliases, tokens that don't take that
+/// form are left for e.g. short syntax to parse.
+fn validate_accumulators<'a, T: SubGroupIter<'a>>(
+	iter: &mut TokenIter<'a, T>,
+) -> Result<Vec<(Ident, TokenStream)>>
+{
+	let mut accumulators: Vec<(Ident, TokenStream)> = Vec::new();
+	loop
+	{
+		let acc_keyword = match iter.extract_simple(|t| is_ident(t, Some("acc")), |t| t, None)
+		{
+			Ok(acc_keyword) => acc_keyword,
+			Err(err) if err.is_fatal() => return Err(err),
+			Err(_) => break,
+		};
+		let name = match iter.peek()?
+		{
+			Some(Token::Simple(TokenTree::Ident(name))) => name.clone(),
+			_ =>
+			{
+				iter.push_front(Token::Simple(acc_keyword));
+				break;
+			},
+		};
+		iter.next_fallible()?;
+		if !matches!(iter.peek()?, Some(Token::Simple(TokenTree::Punct(p)))
+			if p.as_char() == '+' && p.spacing() == Spacing::Joint)
+		{
+			iter.push_front(Token::Simple(TokenTree::Ident(name)));
+			iter.push_front(Token::Simple(acc_keyword));
+			break;
+		}
+		iter.next_fallible()?;
+		iter.expect_simple(|t| is_punct(t, '='), Some("'+='"))
+			.map_err(|err| err.hint(ACCUMULATOR))?;
+		let (fragment, _) = iter
+			.next_substitution()
+			.map_err(|err| err.hint(ACCUMULATOR))?;
+
+		if accumulators
+			.iter()
+			.any(|(other, _)| other.to_string() == name.to_string())
+		{
+			return Err(
+				Error::new(format!("Accumulator '{}' declared multiple times.", name))
+					.span(name.span())
+					.hint(ACCUMULATOR),
+			);
+		}
+		accumulators.push((name, fragment.to_token_stream()));
+		if iter.has_next()?
+		{
+			iter.expect_semicolon()?;
+		}
+	}
+	Ok(accumulators)
+}
+
+/// Returns an error if any of the given accumulators is already a
+/// substitution identifier (or alias) of the invocation.
+fn check_accumulators(
+	accumulators: &[(Ident, TokenStream)],
+	dup_def: &DuplicationDefinition,
+) -> Result<()>
+{
+	for (name, _) in accumulators
+	{
+		let name_string = name.to_string();
+		if Some(&dup_def.global_substitutions)
+			.into_iter()
+			.chain(dup_def.duplications.iter())
+			.any(|group| group.substitution_of(&name_string).is_some())
+		{
+			return Err(Error::new(format!(
+				"Accumulator '{}' is already a substitution identifier.",
+				name_string
+			))
+			.span(name.span())
+			.hint(ACCUMULATOR));
+		}
+	}
+	Ok(())
+}
+
+/// Validates global substitutions and returns a substitution group with them.
+///
+/// When it fails to validate a global substitution, it might return the next
+/// identifier that the iterator produced optionally followed by the next group
+/// too. The two must therefore be assumed to precede any tokentrees returned by
+/// the iterator after calling this function.
+/// This may happen if the global substitutions are followed by short-syntax,
+/// which starts the same way as a global substitution.
+fn validate_global_substitutions<'a, T: SubGroupIter<'a>>(
+	iter: &mut TokenIter<'a, T>,
+) -> Result<SubstitutionGroup>
+{
+	let mut sub_group = SubstitutionGroup::new();
+	loop
+	{
+		// Nested invocations following global substitutions are left to be expanded
+		// with them (see 'parse_duplicate_invocation')
+		#[cfg(feature = "nested_invocation")]
+		if !sub_group.substitutions.is_empty() && iter.next_is_nested()
+		{
+			break;
+		}
+		if next_is_zip(iter)
+		{
+			break;
+		}
+		if let Some((pattern, sub)) = extract_pattern_substitution(iter)?
+		{
+			sub_group.add_pattern(pattern, sub)?;
+		}
+		else if extract_case_preserving_marker(iter)?
+		{
+			let (ident, sub) = extract_inline_substitution(iter)?;
+			add_case_preserving_substitution(&mut sub_group, ident, sub)?;
+		}
+		else
+		{
+			// A short syntax header may declare a parameterized substitution identifier
+			// named 'env' (e.g. 'name env(x);'), which isn't a substitution from the
+			// environment
+			if next_is_env_substitution(iter.clone())
+				&& looks_like_substitution_groups(iter.clone())
+			{
+				break;
+			}
+			let (ident, sub) = match extract_inline_substitution(iter)
+			{
+				Ok(substitution) => substitution,
+				Err(err) if err.is_fatal() => return Err(err),
+				Err(_) => break,
+			};
+			sub_group.add_substitution(ident, sub)?;
+		}
+
+		if iter.has_next()?
+		{
+			if let Some(span) = next_substitution_without_semicolon(iter.clone())
+			{
+				return Err(Error::new(
+					"Expected ';' between global substitutions or '[]' around a verbose \
+					 substitution group.",
+				)
+				.span(span)
+				.hint(GLOBAL_SUB_OR_VERBOSE_GROUP));
+			}
+			iter.expect_semicolon().map_err(|err| {
+				if err.is_fatal()
+				{
+					err
+				}
+				else
+				{
+					err.hint(GLOBAL_SUB_SEMICOLON)
+				}
+			})?;
+		}
+	}
+	Ok(sub_group)
+}
+
+/// If the next tokens are another substitution identifier with its
+/// substitution, returns the span of the identifier.
+///
+/// Used after a global substitution, where this means either the global
+/// substitutions are missing ';' or they were meant as a verbose substitution
+/// group but are missing the surrounding '[]'.
+fn next_substitution_without_semicolon<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+) -> Option<Span>
+{
+	extract_inline_substitution(&mut iter)
+		.ok()
+		.map(|(ident, _)| ident.span())
+}
+
+/// Validates that a duplicate invocation uses the verbose syntax, and returns
+/// all the substitutions that should be made.
+///
+/// Returns 'Some' if the tokens given definitely represent the use of verbose
+/// syntax, even though it might still contain errors.
+/// Returns 'None' if an error occurred before verbose syntax was recognized
+fn validate_verbose_invocation<'a, T: SubGroupIter<'a>>(
+	iter: &mut TokenIter<'a, T>,
+) -> Result<Option<Vec<SubstitutionGroup>>>
+{
+	if let Ok(true) = next_is_verbose_group(iter)
+	{
+		let mut sub_groups = Vec::new();
+
+		let mut substitution_ids = None;
+		let mut optional = HashSet::new();
+		while iter.has_next()?
+		{
+			let docs = iter.extract_doc_attributes()?;
+			let (body, span) = iter.next_group(Some(Delimiter::Bracket)).map_err(|err| {
+				err.hint(
+					"When using verbose syntax, a substitutions must be enclosed in a \
+					 group.\nExample:\n..\n[\n\tidentifier1 [ substitution1 ]\n\tidentifier2 [ \
+					 substitution2 ]\n]",
+				)
+			})?;
+			let (mut group, group_optional) =
+				extract_verbose_substitutions(body, span, &substitution_ids)?;
+			group.docs = docs;
+			sub_groups.push(group);
+			if None == substitution_ids
+			{
+				substitution_ids = Some(
+					sub_groups[0]
+						.identifiers_with_args()
+						.filter(|(ident, _)| !group_optional.contains(*ident))
+						.map(|(ident, count)| (ident.clone(), count))
+						.collect(),
+				)
+			}
+			optional.extend(group_optional);
+		}
+
+		// Mark optional identifiers not defined by a group
+		for group in sub_groups.iter_mut()
+		{
+			for ident in optional.iter()
+			{
+				if group.substitution_of(ident).is_none()
+				{
+					group.add_absent(ident.clone());
+				}
+			}
+		}
+		Ok(Some(sub_groups))
+	}
+	else
+	{
+		Ok(None)
+	}
+}
+
+/// Extracts a substitution identifier followed by
+/// an optional parameter list, followed by a substitution.
+fn extract_inline_substitution<'a, T: SubGroupIter<'a>>(
+	stream: &mut TokenIter<'a, T>,
+) -> Result<(Ident, Substitution)>
+{
+	let ident = stream.extract_identifier(Some("a substitution identifier"))?;
+	if let Some(value) = extract_env_substitution(stream)?
+	{
+		return Ok((ident, Substitution::new_simple(value)));
+	}
+	let param_group = match stream.next_group(Some(Delimiter::Parenthesis))
+	{
+		Err(err) if err.is_fatal() => return Err(err),
+		param_group => param_group,
+	};
+	let substitution = stream.next_substitution();
+
+	if let Ok((params, span)) = param_group
+	{
+		// Found parameters, now get substitution
+		substitution
+			.and_then(|(sub, _)| {
+				// Errors in the substitution (e.g. from nested invocations) mean this is
+				// a substitution, just an invalid one
+				extract_argument_list(params.clone())
+					.and_then(|args| Substitution::new(&args, sub).map_err(Error::fatal))
+			})
+			.or_else(|err| {
+				stream.push_front(Token::Group(Delimiter::Parenthesis, params, span));
+				Err(err)
+			})
+	}
+	else
+	{
+		// No parameters, get substitution
+		match substitution
+		{
+			Ok((sub, _)) =>
+			{
+				sub.process_all()
+					.map(Substitution::new_simple)
+					.map_err(Error::fatal)
+			},
+			Err(err) if err.is_fatal() => Err(err),
+			Err(old_err) => Err(old_err.rephrase("Expected '(' or '['.")),
+		}
+	}
+	.or_else(|err| {
+		stream.push_front(Token::Simple(TokenTree::Ident(ident.clone())));
+		Err(err)
+	})
+	.map(|result| (ident, result))
+}
+
+/// If the next token is the `keep` keyword, given instead of a substitution to
+/// keep its substitution identifier as-is, consumes it and returns its span.
+fn extract_keep_keyword<'a, T: SubGroupIter<'a>>(
+	stream: &mut TokenIter<'a, T>,
+) -> Result<Option<Span>>
+{
+	match stream.peek()?
+	{
+		Some(Token::Simple(t)) if is_ident(t, Some(KEEP_NAME)) =>
+		{
+			let span = t.span();
+			stream.next_fallible()?;
+			Ok(Some(span))
+		},
+		_ => Ok(None),
+	}
+}
+
+/// If the next tokens are a substitution identifier followed by the `keep`
+/// keyword, consumes them and returns the identifier.
+fn extract_kept_identifier<'a, T: SubGroupIter<'a>>(
+	stream: &mut TokenIter<'a, T>,
+) -> Result<Option<Ident>>
+{
+	let mut lookahead = stream.clone();
+	if let Ok(ident) = lookahead.extract_identifier(None)
+	{
+		if extract_keep_keyword(&mut lookahead)?.is_some()
+		{
+			*stream = lookahead;
+			return Ok(Some(ident));
+		}
+	}
+	Ok(None)
+}
+
+/// Returns an error if the given substitution identifier, kept with the `keep`
+/// at the given span, has parameters, as its arguments would have nowhere to
+/// go.
+fn check_keep_arguments(ident: &Ident, args: &[String], span: Span) -> Result<()>
+{
+	if args.is_empty()
+	{
+		Ok(())
+	}
+	else
+	{
+		Err(Error::new(format!(
+			"'keep' can't keep the parameterized substitution identifier '{}'.",
+			ident
+		))
+		.span(span)
+		.hint(KEEP))
+	}
+}
+
+/// If the next tokens are a pattern followed by its substitution (`pattern
+/// [tokens] => [substitution]`), consumes and returns them.
+///
+/// Without the `=>`, `pattern` is taken to be a substitution identifier. After
+/// it, errors are fatal, as nothing else can start that way.
+fn extract_pattern_substitution<'a, T: SubGroupIter<'a>>(
+	stream: &mut TokenIter<'a, T>,
+) -> Result<Option<(Pattern, Substitution)>>
+{
+	let keyword = match stream.extract_simple(|t| is_ident(t, Some("pattern")), |t| t, None)
+	{
+		Ok(keyword) => keyword,
+		Err(err) if err.is_fatal() => return Err(err),
+		Err(_) => return Ok(None),
+	};
+	let (tokens, span) = match stream.peek()?
+	{
+		Some(Token::Group(Delimiter::Bracket, _, _)) =>
+		{
+			stream.next_group(Some(Delimiter::Bracket))?
+		},
+		_ =>
+		{
+			stream.push_front(Token::Simple(keyword));
+			return Ok(None);
+		},
+	};
+	if !stream.extract_arrow()?
+	{
+		stream.push_front(Token::Group(Delimiter::Bracket, tokens, span));
+		stream.push_front(Token::Simple(keyword));
+		return Ok(None);
+	}
+
+	let pattern = Pattern::new(tokens.process_all()?, span).map_err(Error::fatal)?;
+	let (sub, _) = stream
+		.next_substitution()
+		.map_err(|err| err.hint(PATTERN).fatal())?;
+	let sub = sub.process_all().map_err(Error::fatal)?;
+	Ok(Some((pattern, Substitution::new_simple(sub))))
+}
+
+/// If the next tokens are `env(NAME)` or `env(NAME, [default])`, consumes them
+/// and returns the value of the environment variable `NAME` as tokens, or the
+/// default if the variable isn't set.
+///
+/// Errors are fatal, as `env(` can't start anything else where a substitution
+/// is expected.
+fn extract_env_substitution<'a, T: SubGroupIter<'a>>(
+	stream: &mut TokenIter<'a, T>,
+) -> Result<Option<TokenStream>>
+{
+	let keyword = match stream.extract_simple(|t| is_ident(t, Some("env")), |t| t, None)
+	{
+		Ok(keyword) => keyword,
+		Err(err) if err.is_fatal() => return Err(err),
+		Err(_) => return Ok(None),
+	};
+	let (mut args, span) = match stream.peek()?
+	{
+		Some(Token::Group(Delimiter::Parenthesis, _, _)) =>
+		{
+			stream.next_group(Some(Delimiter::Parenthesis))?
+		},
+		_ =>
+		{
+			stream.push_front(Token::Simple(keyword));
+			return Ok(None);
+		},
+	};
+
+	let name = args
+		.extract_identifier(Some("the name of an environment variable"))
+		.map_err(|err| err.hint(ENV_SUBSTITUTION).fatal())?;
+	let default = if args.has_next()?
+	{
+		args.expect_comma().map_err(|err| err.fatal())?;
+		let (default, _) = args
+			.next_substitution()
+			.map_err(|err| err.hint(ENV_SUBSTITUTION).fatal())?;
+		if let Some(token) = args.next_fallible()?
+		{
+			return Err(Error::new("Unexpected token.")
+				.span(token.span())
+				.found(token.describe())
+				.hint(ENV_SUBSTITUTION)
+				.fatal());
+		}
+		Some(default.to_token_stream())
+	}
+	else
+	{
+		None
+	};
+
+	match (std::env::var(name.to_string()), default)
+	{
+		(Ok(value), _) =>
+		{
+			value.parse().map(Some).map_err(|_| {
+				Error::new(format!(
+					"Environment variable '{}' doesn't contain valid tokens.",
+					name
+				))
+				.span(name.span())
+				.fatal()
+			})
+		},
+		(Err(_), Some(default)) => Ok(Some(default)),
+		(Err(_), None) =>
+		{
+			Err(
+				Error::new(format!("Environment variable '{}' is not set.", name))
+					.span(span)
+					.hint(ENV_SUBSTITUTION)
+					.fatal(),
+			)
+		},
+	}
+}
+
+/// Whether the next tokens are a substitution identifier followed by a
+/// substitution from the environment (`ident env(..)`).
+fn next_is_env_substitution<'a, T: SubGroupIter<'a>>(mut iter: TokenIter<'a, T>) -> bool
+{
+	matches!(
+		iter.next_fallible(),
+		Ok(Some(Token::Simple(TokenTree::Ident(_))))
+	) && matches!(iter.next_fallible(), Ok(Some(Token::Simple(t))) if is_ident(&t, Some("env")))
+		&& matches!(
+			iter.peek(),
+			Ok(Some(Token::Group(Delimiter::Parenthesis, _, _)))
+		)
+}
+
+/// Extracts a substitution group in the verbose syntax.
+///
+/// Also returns the substitution identifiers the group declares optional
+/// (`optional ident [sub]`), which are exempt from having to be in `existing`.
+fn extract_verbose_substitutions<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+	iter_span: Span,
+	existing: &Option<HashSet<(String, usize)>>,
+) -> Result<(SubstitutionGroup, HashSet<String>)>
+{
+	if !iter.has_next()?
+	{
+		return Err(Error::new(NO_GROUPS).span(iter_span));
+	}
+
+	// Map idents to string reference so we can use HashSet::difference
+	let expected_idents: HashSet<_> = existing.as_ref().map_or(HashSet::new(), |idents| {
+		idents
+			.iter()
+			.map(|(ident, count)| (ident, count.clone()))
+			.collect()
+	});
+
+	let mut substitutions = SubstitutionGroup::new();
+	let mut optional = HashSet::new();
+	let mut stream = iter;
+
+	while stream.has_next()?
+	{
+		#[allow(unused_mut)]
+		let mut hint: Option<&str> = None;
+
+		#[cfg(feature = "pretty_errors")]
+		{
+			if stream.has_next_semicolon()?
+			{
+				hint = Some(crate::pretty_errors::VERBOSE_SEMICOLON);
+			}
+		}
+
+		let is_optional = extract_optional_keyword(&mut stream)?;
+		let case_preserving = extract_case_preserving_marker(&mut stream)?;
+		let (name, span, arg_count) = if case_preserving
+		{
+			let (ident, substitution) = extract_inline_substitution(&mut stream)?;
+			let span = ident.span();
+			// Keyed by the snake_case identifier it adds, however it is written
+			let name = add_case_preserving_substitution(&mut substitutions, ident, substitution)?;
+			(name, span, 0)
+		}
+		else if let Some((pattern, substitution)) = extract_pattern_substitution(&mut stream)?
+		{
+			let key = (pattern.key(), pattern.span(), substitution.argument_count());
+			substitutions.add_pattern(pattern, substitution)?;
+			key
+		}
+		else
+		{
+			let (ident, substitution) = match extract_kept_identifier(&mut stream)?
+			{
+				Some(ident) => (ident.clone(), Substitution::new_keep(ident)),
+				None =>
+				{
+					extract_inline_substitution(&mut stream)
+						.map_err(|err| hint.into_iter().fold(err, |err, hint| err.hint(hint)))?
+				},
+			};
+			let key = (
+				ident.to_string(),
+				ident.span(),
+				substitution.argument_count(),
+			);
+			substitutions.add_substitution(ident, substitution)?;
+			key
+		};
+		if is_optional
+		{
+			optional.insert(name.clone());
+		}
+		else if !expected_idents.is_empty() && !expected_idents.contains(&(&name, arg_count))
+		{
+			let (msg, _hint) = if expected_idents.iter().find(|(i, _)| **i == name).is_some()
+			{
+				(
+					"Wrong argument count for substitution identifier.",
+					VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS_ARGS,
+				)
+			}
+			else
+			{
+				(
+					"Unexpected substitution identifier.",
+					VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS,
+				)
+			};
+			return Err(Error::new(msg).span(span).hint(_hint));
+		}
+	}
+
+	// Check no substitution idents are missing
+	let found_idents: HashSet<_> = substitutions
+		.identifiers_with_args()
+		.filter(|(ident, _)| !optional.contains(*ident))
+		.collect();
+	let missing: Vec<_> = expected_idents.difference(&found_idents).collect();
+
+	if missing.len() > 0
+	{
+		let mut hint = String::new();
+		#[cfg(feature = "pretty_errors")]
+		{
+			hint += "Missing";
+
+			hint += " substitution for:";
+			for ident in missing
+			{
+				hint += " '";
+				hint += &ident.0.to_string();
+				hint += "'";
+			}
+			hint += "\n";
+		}
+		hint += VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS;
+
+		return Err(Error::new("Incomplete substitution group.")
+			.span(iter_span)
+			.hint(hint));
+	}
+
+	Ok((substitutions, optional))
+}
+
+/// Consumes the `optional` keyword if it precedes a substitution identifier,
+/// returning whether it did.
+///
+/// `optional` is otherwise taken to be a substitution identifier itself.
+fn extract_optional_keyword<'a, T: SubGroupIter<'a>>(stream: &mut TokenIter<'a, T>)
+	-> Result<bool>
+{
+	match stream.extract_simple(|t| is_ident(t, Some("optional")), |t| t, None)
+	{
+		Ok(keyword) =>
+		{
+			if let Some(Token::Simple(TokenTree::Ident(_))) = stream.peek()?
+			{
+				return Ok(true);
+			}
+			stream.push_front(Token::Simple(keyword));
+			Ok(false)
+		},
+		Err(err) if err.is_fatal() => Err(err),
+		Err(_) => Ok(false),
+	}
+}
+
+/// Consumes the `~` declaring a case-preserving substitution identifier
+/// (`~ident [sub]`), returning whether it did.
+fn extract_case_preserving_marker<'a, T: SubGroupIter<'a>>(
+	stream: &mut TokenIter<'a, T>,
+) -> Result<bool>
+{
+	match stream.extract_simple(|t| is_punct(t, '~'), |t| t, None)
+	{
+		Ok(_) => Ok(true),
+		Err(err) if err.is_fatal() => Err(err),
+		Err(_) => Ok(false),
+	}
+}
+
+/// Adds the given case-preserving substitution identifier (`~ident [sub]`) to
+/// the given group in snake_case, PascalCase, and SCREAMING_SNAKE_CASE, each
+/// substituted by the substitution converted to the same case.
+///
+/// Returns the identifier in snake_case, or an error if the substitution isn't
+/// a single identifier.
+#[cfg(feature = "module_disambiguation")]
+fn add_case_preserving_substitution(
+	group: &mut SubstitutionGroup,
+	ident: Ident,
+	substitution: Substitution,
+) -> Result<String>
+{
+	let substituted = substitution
+		.substitutes_identifier()
+		.filter(|_| substitution.argument_count() == 0)
+		.ok_or_else(|| {
+			Error::new(format!(
+				"Case-preserving substitution identifier '{}' must be substituted by a single \
+				 identifier.",
+				ident
+			))
+			.span(ident.span())
+			.hint(CASE_PRESERVING)
+		})?;
+	let span = substituted.span();
+	let (name, substituted) = (ident.to_string(), substituted.to_string());
+	let (name, substituted) = (
+		name.strip_prefix("r#").unwrap_or(&name),
+		substituted.strip_prefix("r#").unwrap_or(&substituted),
+	);
+	let cases = [
+		(
+			ToSnakeCase::to_snake_case as fn(&str) -> String,
+			"snake_case",
+		),
+		(ToUpperCamelCase::to_upper_camel_case, "PascalCase"),
+		(
+			ToShoutySnakeCase::to_shouty_snake_case,
+			"SCREAMING_SNAKE_CASE",
+		),
+	];
+	// Converting may leave nothing (e.g. of '__') or a leading digit (e.g. of '_1')
+	let convert = |name: &str, case: fn(&str) -> String, case_name: &str, span: Span| {
+		let converted = case(name);
+		if converted.is_empty() || converted.starts_with(|c: char| c.is_ascii_digit())
+		{
+			Err(Error::new(format!(
+				"'{}' in {} is '{}', which is not a valid identifier.",
+				name, case_name, converted
+			))
+			.span(span)
+			.hint(CASE_PRESERVING))
+		}
+		else
+		{
+			Ok(Ident::new(&converted, span))
+		}
+	};
+	let mut added = Vec::new();
+	for (case, case_name) in cases
+	{
+		let variant = convert(name, case, case_name, ident.span())?;
+		let substitution = convert(substituted, case, case_name, span)?;
+		// Short names may be the same in several cases (e.g. 'X1')
+		if added.contains(&variant.to_string())
+		{
+			continue;
+		}
+		added.push(variant.to_string());
+		group.add_substitution(
+			variant,
+			Substitution::new_simple(TokenTree::Ident(substitution).into()),
+		)?;
+	}
+	Ok(added.swap_remove(0))
+}
+
+/// Returns an error, as case-preserving substitution identifiers can't be
+/// converted between cases without the `module_disambiguation` feature.
+#[cfg(not(feature = "module_disambiguation"))]
+fn add_case_preserving_substitution(
+	_: &mut SubstitutionGroup,
+	ident: Ident,
+	_: Substitution,
+) -> Result<String>
+{
+	Err(Error::new(
+		"Case-preserving substitution identifiers ('~') require the 'module_disambiguation' \
+		 feature.",
+	)
+	.span(ident.span())
+	.hint(CASE_PRESERVING))
+}
+
+/// Validates a duplicate invocation using the short syntax and returns the
+/// substitution that should be made.
+///
+/// Any substitution groups in the given continued `rows` follow those in the
+/// invocation, followed by any in the file given by the `rows_from` option.
+/// Unless `allow_empty`, at least one substitution group must be given.
+///
+/// Also returns the doc attributes given before each substitution group.
+fn validate_short_attr<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+	options: &InvocationOptions,
+	rows: Vec<(TokenStream, Span)>,
+) -> Result<(ShortSubstitutions, ShortDocs)>
+{
+	let (idents, arrows) = validate_short_get_identifiers(&mut iter)?;
+	let mut result: Vec<_> = idents
+		.into_iter()
+		.map(|(ident, args)| (ident, args, Vec::new()))
+		.collect();
+	let mut docs = Vec::new();
+	let mut exclusions = Vec::new();
+	validate_short_get_all_substitution_goups(
+		iter,
+		&mut result,
+		&mut docs,
+		&mut exclusions,
+		arrows,
+		options,
+	)?;
+	for (rows, _) in rows
+	{
+		validate_short_get_all_substitution_goups(
+			TokenIter::new(rows, SubstitutionGroup::empty(), std::iter::empty()),
+			&mut result,
+			&mut docs,
+			&mut exclusions,
+			arrows,
+			options,
+		)?;
+	}
+	if let Some(path) = &options.rows_from
+	{
+		validate_rows_from(path, &mut result, &mut docs, &mut exclusions, options)?;
+	}
+	let allow_empty = options.allow_empty;
+	let any_groups = !result[0].2.is_empty();
+	exclude_short_groups(&mut result, &mut docs, exclusions)?;
+
+	if result[0].2.is_empty() && !allow_empty
+	{
+		if any_groups
+		{
+			return Err(Error::new("All substitution groups are excluded.").hint(EXCEPT));
+		}
+		Err(Error::new(NO_GROUPS).hint(SHORT_SYNTAX_NO_GROUPS))
+	}
+	else
+	{
+		Ok((result, docs))
+	}
+}
+
+/// Removes the substitution groups (and their doc attributes) that are the
+/// same as any of the given excluded groups, comparing the substitution of
+/// each substitution identifier.
+///
+/// Returns an error if an excluded group is the same as none of the groups.
+fn exclude_short_groups(
+	result: &mut ShortSubstitutions,
+	docs: &mut ShortDocs,
+	exclusions: ShortExclusions,
+) -> Result<()>
+{
+	let mut excluded = vec![false; result[0].2.len()];
+	for (exclusion, span) in exclusions
+	{
+		let mut found = false;
+		for (index, excluded) in excluded.iter_mut().enumerate()
+		{
+			if result
+				.iter()
+				.zip(exclusion.iter())
+				.all(|((_, _, streams), sub)| {
+					match (&streams[index], sub)
+					{
+						(Some(stream), Some(sub)) => same_tokens(stream, sub),
+						(stream, sub) => stream.is_none() && sub.is_none(),
+					}
+				})
+			{
+				*excluded = true;
+				found = true;
+			}
+		}
+		if !found
+		{
+			return Err(Error::new(
+				"The excluded substitution group is the same as none of the substitution groups.",
+			)
+			.span(span)
+			.hint(EXCEPT));
+		}
+	}
+	for (_, _, streams) in result.iter_mut()
+	{
+		let mut excluded = excluded.iter();
+		streams.retain(|_| !excluded.next().unwrap());
+	}
+	let mut excluded = excluded.iter();
+	docs.retain(|_| !excluded.next().unwrap());
+	Ok(())
+}
+
+/// Assuming use of the short syntax, gets the initial list of substitution
+/// identifiers.
+///
+/// Also returns whether the list ended with `=>` instead of `;`, in which case
+/// the substitution groups are also separated by `=>`.
+fn validate_short_get_identifiers<'a, T: SubGroupIter<'a>>(
+	mut iter: &mut TokenIter<'a, T>,
+) -> Result<(ShortIdentifiers, bool)>
+{
+	let mut result = Vec::new();
+	loop
+	{
+		if !result.is_empty() && iter.extract_arrow()?
+		{
+			return Ok((result, true));
+		}
+		match iter.extract_simple(
+			|t| is_ident(t, None) || (is_semicolon(t) && !result.is_empty()),
+			|t| get_ident(t),
+			Some(
+				if result.is_empty()
+				{
+					NO_INVOCATION
+				}
+				else
+				{
+					"substitution_identifier or ';'"
+				},
+			),
+		)?
+		{
+			Some(ident) =>
+			{
+				result.push((ident, validate_short_get_identifier_arguments(&mut iter)?))
+			},
+			None => return Ok((result, false)),
+		}
+	}
+}
 
-	// Check no substitution idents are missing
-	let found_idents: HashSet<_> = substitutions.identifiers_with_args().collect();
-	let missing: Vec<_> = expected_idents.difference(&found_idents).collect();
+/// Assuming use of the short syntax, gets the list of identifier arguments.
+fn validate_short_get_identifier_arguments<'a, T: SubGroupIter<'a>>(
+	iter: &mut TokenIter<'a, T>,
+) -> Result<Vec<String>>
+{
+	match iter.next_group(Some(Delimiter::Parenthesis))
+	{
+		Ok((group, _)) => extract_argument_list(group),
+		Err(err) if err.is_fatal() => Err(err),
+		Err(_) => Ok(Vec::new()),
+	}
+}
 
-	if missing.len() > 0
+/// Validates a duplicate invocation using the column layout of the short
+/// syntax (given the `columns` option) and returns the substitutions that
+/// should be made, like `validate_short_attr`.
+///
+/// Each column is a substitution identifier, followed by `:` and all its
+/// substitutions, one for each substitution group.
+fn validate_columns<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+	options: &InvocationOptions,
+) -> Result<ShortSubstitutions>
+{
+	let mut result: ShortSubstitutions = Vec::new();
+	while iter.has_next()?
 	{
-		let mut hint = String::new();
-		#[cfg(feature = "pretty_errors")]
+		let ident = iter.extract_identifier(Some("a substitution identifier"))?;
+		let args = validate_short_get_identifier_arguments(&mut iter)?;
+		iter.extract_simple(|t| is_punct(t, ':'), |_| (), Some("':'"))
+			.map_err(|err| err.hint(COLUMNS))?;
+
+		let mut substitutions = Vec::new();
+		loop
 		{
-			hint += "Missing";
+			if let Some(span) = extract_keep_keyword(&mut iter)?
+			{
+				check_keep_arguments(&ident, &args, span)?;
+				substitutions.push(None);
+				continue;
+			}
+			if !iter.next_is_substitution()?
+			{
+				break;
+			}
+			let (group, span) = iter.next_substitution()?;
+			let substitution = group.to_token_stream();
+			check_empty_substitution(options, &substitution, span, "substitution")?;
+			substitutions.push(Some(substitution));
+		}
 
-			hint += " substitution for:";
-			for ident in missing
+		if let Some((first, _, first_subs)) = result.first()
+		{
+			if first_subs.len() != substitutions.len()
 			{
-				hint += " '";
-				hint += &ident.0.to_string();
-				hint += "'";
+				let ((short, short_count), (long, long_count)) = if first_subs.len()
+					< substitutions.len()
+				{
+					(
+						(first.to_string(), first_subs.len()),
+						(ident.to_string(), substitutions.len()),
+					)
+				}
+				else
+				{
+					(
+						(ident.to_string(), substitutions.len()),
+						(first.to_string(), first_subs.len()),
+					)
+				};
+				return Err(Error::new(format!(
+					"Column '{}' has {} substitutions but column '{}' has {}.",
+					short, short_count, long, long_count
+				))
+				.span(ident.span())
+				.hint(COLUMNS));
 			}
-			hint += "\n";
 		}
-		hint += VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS;
+		result.push((ident, args, substitutions));
 
-		return Err(Error::new("Incomplete substitution group.")
-			.span(iter_span)
-			.hint(hint));
+		if iter.has_next()?
+		{
+			iter.expect_semicolon().map_err(|err| err.hint(COLUMNS))?;
+		}
 	}
 
-	Ok(substitutions)
+	match result.first()
+	{
+		None => Err(Error::new(NO_GROUPS).hint(COLUMNS)),
+		Some((_, _, subs)) if subs.is_empty() && !options.allow_empty =>
+		{
+			Err(Error::new(NO_GROUPS).hint(COLUMNS))
+		},
+		_ => Ok(result),
+	}
 }
 
-/// Validates a duplicate invocation using the short syntax and returns the
-/// substitution that should be made.
-fn validate_short_attr<'a, T: SubGroupIter<'a>>(
-	mut iter: TokenIter<'a, T>,
-) -> Result<Vec<(String, Vec<String>, Vec<TokenStream>)>>
+/// Reads the short syntax substitution groups in the file at the given path
+/// (relative to the invoking crate's manifest directory) and inserts them into
+/// the given vec.
+///
+/// Errors in the file are reported at the path with the file and number of the
+/// substitution group they are in, as the file's tokens have no useful spans.
+fn validate_rows_from(
+	path: &(String, Span),
+	result: &mut ShortSubstitutions,
+	docs: &mut ShortDocs,
+	exclusions: &mut ShortExclusions,
+	options: &InvocationOptions,
+) -> Result<()>
 {
-	let idents = validate_short_get_identifiers(&mut iter)?;
-	let mut result: Vec<_> = idents
-		.into_iter()
-		.map(|(ident, args)| (ident, args, Vec::new()))
-		.collect();
-	validate_short_get_all_substitution_goups(iter, &mut result)?;
+	let (path, span) = path;
+	let full_path = std::env::var_os("CARGO_MANIFEST_DIR")
+		.map_or_else(PathBuf::new, PathBuf::from)
+		.join(path);
+	let context = |row: Option<usize>| {
+		match row
+		{
+			Some(row) => format!("In '{}', substitution group {}: ", path, row),
+			None => format!("In '{}': ", path),
+		}
+	};
 
-	if result[0].2.is_empty()
-	{
-		Err(Error::new(NO_GROUPS).hint(SHORT_SYNTAX_NO_GROUPS))
-	}
-	else
-	{
-		Ok(result)
-	}
+	let rows: TokenStream = std::fs::read_to_string(&full_path)
+		.map_err(|err| {
+			Error::new(format!(
+				"Failed to read substitution groups from '{}': {}",
+				path, err
+			))
+			.span(*span)
+			.hint(ROWS_FROM)
+		})?
+		.parse()
+		.map_err(|_| {
+			Error::new("Invalid tokens.")
+				.context(context(None))
+				.span(*span)
+		})?;
+
+	let rows_before = result[0].2.len();
+	validate_short_get_all_substitution_goups(
+		TokenIter::new(rows, SubstitutionGroup::empty(), std::iter::empty()),
+		result,
+		docs,
+		exclusions,
+		false,
+		options,
+	)
+	.map_err(|err| {
+		// The last identifier has substitutions only for complete groups
+		let row = result.last().unwrap().2.len() - rows_before + 1;
+		err.context(context(Some(row))).span(*span)
+	})
 }
 
-/// Assuming use of the short syntax, gets the initial list of substitution
-/// identifiers.
-fn validate_short_get_identifiers<'a, T: SubGroupIter<'a>>(
-	mut iter: &mut TokenIter<'a, T>,
-) -> Result<Vec<(String, Vec<String>)>>
+/// Splits the tokens given to a function-like macro into the invocation and the
+/// body.
+///
+/// The invocation is either the leading bracket group (prefix form) or, if the
+/// tokens don't start with a bracket group, a trailing bracket group preceded
+/// by `@` (postfix form). A body that starts with a bracket group therefore
+/// can't be given in postfix form, and a body ending with `@ [...]` is only
+/// kept whole in prefix form.
+pub(crate) fn split_inline_invocation<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+) -> Result<(TokenStream, TokenStream)>
 {
-	let mut result = Vec::new();
-	while let Some(ident) = iter.extract_simple(
-		|t| is_ident(t, None) || (is_semicolon(t) && !result.is_empty()),
-		|t| get_ident(t),
-		Some(
-			if result.is_empty()
+	match iter.next_group(Some(Delimiter::Bracket))
+	{
+		Ok((invocation, _)) => Ok((invocation.to_token_stream(), iter.to_token_stream())),
+		Err(err) =>
+		{
+			let mut body: Vec<_> = iter.to_token_stream().into_iter().collect();
+			match (body.pop(), body.pop())
 			{
-				NO_INVOCATION
+				(Some(TokenTree::Group(invocation)), Some(TokenTree::Punct(at)))
+					if invocation.delimiter() == Delimiter::Bracket && at.as_char() == '@' =>
+				{
+					Ok((invocation.stream(), body.into_iter().collect()))
+				},
+				_ => Err(err),
 			}
-			else
-			{
-				"substitution_identifier or ';'"
-			},
-		),
-	)?
-	{
-		result.push((
-			ident.to_string(),
-			validate_short_get_identifier_arguments(&mut iter)?,
-		));
+		},
 	}
-	Ok(result)
 }
 
-/// Assuming use of the short syntax, gets the list of identifier arguments.
-fn validate_short_get_identifier_arguments<'a, T: SubGroupIter<'a>>(
-	iter: &mut TokenIter<'a, T>,
-) -> Result<Vec<String>>
+/// Parses the arguments of the file macros, i.e. the path of a file followed by
+/// the invocation within brackets.
+///
+/// Returns the invocation, the tokens of the file, and the file's full path.
+pub(crate) fn parse_file_invocation(
+	stream: TokenStream,
+) -> Result<(TokenStream, TokenStream, PathBuf)>
 {
-	if let Ok((group, _)) = iter.next_group(Some(Delimiter::Parenthesis))
+	let mut iter = TokenIter::new(stream, SubstitutionGroup::empty(), std::iter::empty());
+	let (path, span) = iter
+		.extract_simple(
+			|t| matches!(t, TokenTree::Literal(_)),
+			|t| {
+				let path = match &t
+				{
+					TokenTree::Literal(lit) => string_value(lit),
+					_ => None,
+				};
+				(path, t.span())
+			},
+			Some("the path of a file"),
+		)
+		.map_err(|err| err.hint(FILE_MACRO))?;
+	let path = path.ok_or_else(|| {
+		Error::new("Expected the path of a file.")
+			.span(span)
+			.hint(FILE_MACRO)
+	})?;
+	iter.expect_comma().map_err(|err| err.hint(FILE_MACRO))?;
+	let (invocation, _) = iter
+		.next_group(Some(Delimiter::Bracket))
+		.map_err(|err| err.hint(FILE_MACRO))?;
+	if let Some(token) = iter.next_fallible()?
 	{
-		let result = extract_argument_list(group)?;
-		return Ok(result);
+		return Err(Error::new("Unexpected token.")
+			.span(token.span())
+			.found(token.describe()));
 	}
-	Ok(Vec::new())
+
+	let full_path = std::env::var_os("CARGO_MANIFEST_DIR")
+		.map_or_else(PathBuf::new, PathBuf::from)
+		.join(&path);
+	let contents: TokenStream = std::fs::read_to_string(&full_path)
+		.map_err(|err| {
+			Error::new(format!("Failed to read '{}': {}", path, err))
+				.span(span)
+				.hint(FILE_MACRO)
+		})?
+		.parse()
+		.map_err(|_| Error::new(format!("Invalid tokens in '{}'.", path)).span(span))?;
+
+	Ok((invocation.to_token_stream(), contents, full_path))
 }
 
 /// Gets all substitution groups in the short syntax and inserts
-/// them into the given vec.
+/// them into the given vec, and the doc attributes before each into `docs`.
+///
+/// If `arrows`, the groups are separated by `=>` instead of `;`, where the last
+/// group may still end with `;`.
 fn validate_short_get_all_substitution_goups<'a, T: SubGroupIter<'a>>(
 	mut iter: TokenIter<'a, T>,
-	result: &mut Vec<(String, Vec<String>, Vec<TokenStream>)>,
+	result: &mut ShortSubstitutions,
+	docs: &mut ShortDocs,
+	exclusions: &mut ShortExclusions,
+	arrows: bool,
+	options: &InvocationOptions,
 ) -> Result<()>
 {
 	while iter.has_next()?
 	{
-		for (_, _, streams) in result.iter_mut()
+		let group_docs = iter.extract_doc_attributes()?;
+		let except = match iter.peek()?
 		{
+			Some(Token::Simple(t)) if is_ident(t, Some(EXCEPT_NAME)) => Some(t.span()),
+			_ => None,
+		};
+		let mut excluded = Vec::new();
+		if except.is_some()
+		{
+			iter.next_fallible()?;
+		}
+		else
+		{
+			docs.push(group_docs);
+		}
+		for (ident, args, streams) in result.iter_mut()
+		{
+			let streams = if except.is_some()
+			{
+				&mut excluded
+			}
+			else
+			{
+				streams
+			};
 			#[allow(unused_mut)]
 			let mut error = crate::pretty_errors::SHORT_SYNTAX_MISSING_SUB_BRACKET;
 			#[cfg(feature = "pretty_errors")]
@@ -418,53 +2458,504 @@ fn validate_short_get_all_substitution_goups<'a, T: SubGroupIter<'a>>(
 				}
 			}
 
-			let (group, _) = iter
-				.next_group(Some(Delimiter::Bracket))
-				.map_err(|err| err.hint(error))?;
-			streams.push(group.to_token_stream());
+			if let Some(span) = extract_keep_keyword(&mut iter)?
+			{
+				check_keep_arguments(ident, args, span)?;
+				streams.push(None);
+				continue;
+			}
+			if let Some(value) = extract_env_substitution(&mut iter)?
+			{
+				streams.push(Some(value));
+				continue;
+			}
+			let (group, span) = iter.next_substitution().map_err(|err| err.hint(error))?;
+			let substitution = group.to_token_stream();
+			check_empty_substitution(options, &substitution, span, "substitution")?;
+			streams.push(Some(substitution));
+		}
+		if let Some(span) = except
+		{
+			exclusions.push((excluded, span));
 		}
 
 		if iter.has_next()?
 		{
+			if let Some(span) = next_row_without_separator(iter.clone(), result.len())?
+			{
+				return Err(Error::new(format!(
+					"Possibly missing '{}' between substitution groups.",
+					if arrows { "=>" } else { ";" }
+				))
+				.span(span)
+				.hint(SHORT_SYNTAX_MISSING_SEPARATOR));
+			}
 			#[cfg(feature = "pretty_errors")]
 			{
-				if let Ok((_, span)) = iter.next_group(Some(Delimiter::Bracket))
+				if let Ok((_, span)) = iter.next_substitution()
 				{
 					return Err(Error::new("Unexpected delimiter.")
 						.span(span)
 						.hint(crate::pretty_errors::SHORT_SYNTAX_SUBSTITUTION_COUNT));
 				}
 			}
-			iter.expect_semicolon()?;
+			let span = iter.peek()?.map_or_else(Span::call_site, Token::span);
+			if iter.extract_arrow()?
+			{
+				if !arrows
+				{
+					return Err(Error::new(
+						"Substitution groups must be separated by ';' like the substitution \
+						 identifiers.",
+					)
+					.span(span)
+					.hint(SHORT_SYNTAX_ARROWS));
+				}
+			}
+			else if arrows
+			{
+				iter.expect_simple(is_semicolon, Some("'=>' or ';'"))?;
+				if iter.has_next()?
+				{
+					return Err(Error::new(
+						"Substitution groups must be separated by '=>' like the substitution \
+						 identifiers.",
+					)
+					.span(span)
+					.hint(SHORT_SYNTAX_ARROWS));
+				}
+			}
+			else
+			{
+				iter.expect_semicolon()?;
+			}
 		}
 	}
 	Ok(())
 }
 
+/// A template section of a `duplicate!` body: its name, section-local global
+/// substitutions (if given), and code.
+pub(crate) type TemplateSection = (Ident, Option<(TokenStream, Span)>, TokenStream);
+
+/// Extracts the template sections (`template name [locals] { code }`) of a
+/// `duplicate!` body.
+///
+/// Returns `None` if the body doesn't start with a template section, in which
+/// case it is duplicated as a whole.
+pub(crate) fn extract_template_sections(item: &TokenStream)
+	-> Result<Option<Vec<TemplateSection>>>
+{
+	let mut start = item.clone().into_iter();
+	match (start.next(), start.next(), start.next())
+	{
+		(Some(keyword), Some(TokenTree::Ident(_)), Some(TokenTree::Group(group)))
+			if is_ident(&keyword, Some("template"))
+				&& (group.delimiter() == Delimiter::Brace
+					|| group.delimiter() == Delimiter::Bracket) =>
+		{},
+		_ => return Ok(None),
+	}
+
+	let mut sections: Vec<TemplateSection> = Vec::new();
+	let mut iter = TokenIter::new(item.clone(), SubstitutionGroup::empty(), std::iter::empty());
+	while iter.has_next()?
+	{
+		iter.expect_simple(|t| is_ident(t, Some("template")), Some("'template'"))
+			.map_err(|err| err.hint(TEMPLATE_SECTIONS))?;
+		let name = iter
+			.extract_identifier(Some("the name of the template section"))
+			.map_err(|err| err.hint(TEMPLATE_SECTIONS))?;
+		if sections
+			.iter()
+			.any(|(other, _, _)| other.to_string() == name.to_string())
+		{
+			return Err(Error::new(format!(
+				"Template section '{}' is defined more than once.",
+				name
+			))
+			.span(name.span())
+			.hint(TEMPLATE_SECTIONS));
+		}
+		let locals = match iter.peek()?
+		{
+			Some(Token::Group(Delimiter::Bracket, _, _)) =>
+			{
+				let (locals, span) = iter.next_group(Some(Delimiter::Bracket))?;
+				Some((locals.to_token_stream(), span))
+			},
+			_ => None,
+		};
+		let (code, _) = iter
+			.next_group(Some(Delimiter::Brace))
+			.map_err(|err| err.hint(TEMPLATE_SECTIONS))?;
+		sections.push((name, locals, code.to_token_stream()));
+	}
+	Ok(Some(sections))
+}
+
+/// Gets the global substitutions of a template section: those of the
+/// invocation together with the section-local ones, if given.
+pub(crate) fn section_global_substitutions(
+	dup_def: &DuplicationDefinition,
+	locals: Option<(TokenStream, Span)>,
+) -> Result<SubstitutionGroup>
+{
+	let mut globals = dup_def.global_substitutions.clone();
+	if let Some((locals, span)) = locals
+	{
+		let locals = parse_global_substitutions_only(locals)?.global_substitutions;
+		if let Some(ident) = locals.identifiers().find(|ident| {
+			dup_def
+				.duplications
+				.iter()
+				.chain(once(&dup_def.global_substitutions))
+				.any(|group| group.substitution_of(ident).is_some())
+		})
+		{
+			return Err(Error::new(format!(
+				"Section-local global substitution '{}' is also a substitution identifier of the \
+				 invocation.",
+				ident
+			))
+			.span(span)
+			.hint(TEMPLATE_SECTIONS));
+		}
+		globals.merge(locals);
+	}
+	Ok(globals)
+}
+
+/// Extracts the `duplicate_item_rows(...)` attributes (optionally given as
+/// `duplicate::duplicate_item_rows(...)`) at the start of the item, up to the
+/// first other attribute, which may be another invocation the following ones
+/// continue.
+///
+/// Returns the body and span of each extracted attribute, followed by the item
+/// without them.
+fn extract_continued_rows(item: TokenStream) -> (Vec<(TokenStream, Span)>, TokenStream)
+{
+	let mut rows = Vec::new();
+	let mut rest = TokenStream::new();
+	let mut iter = item.into_iter().peekable();
+	while let Some(TokenTree::Punct(hash)) = iter.peek()
+	{
+		if hash.as_char() != '#'
+		{
+			break;
+		}
+		let hash = iter.next().unwrap();
+		match iter.next()
+		{
+			Some(TokenTree::Group(attr)) if attr.delimiter() == Delimiter::Bracket =>
+			{
+				match get_continued_rows(attr.stream())
+				{
+					Some(body) => rows.push((body, attr.span())),
+					None =>
+					{
+						rest.extend([hash, TokenTree::Group(attr)]);
+						break;
+					},
+				}
+			},
+			t =>
+			{
+				rest.extend(once(hash).chain(t));
+				break;
+			},
+		}
+	}
+	rest.extend(iter);
+	(rows, rest)
+}
+
+/// If the given attribute body is `duplicate_item_rows(...)` or
+/// `duplicate::duplicate_item_rows(...)`, returns the body of its parentheses.
+fn get_continued_rows(attr: TokenStream) -> Option<TokenStream>
+{
+	let mut tokens: Vec<_> = attr.into_iter().collect();
+	if let [.., TokenTree::Ident(name), TokenTree::Group(rows)] = tokens.as_slice()
+	{
+		if name.to_string() == CONTINUED_ROWS_NAME && rows.delimiter() == Delimiter::Parenthesis
+		{
+			let rows = rows.stream();
+			tokens.truncate(tokens.len() - 2);
+			let path: String = tokens.iter().map(ToString::to_string).collect();
+			return ["", "duplicate::", "::duplicate::"]
+				.contains(&path.as_str())
+				.then_some(rows);
+		}
+	}
+	None
+}
+
+/// Assuming a substitution group of the short syntax has just been completed,
+/// checks whether the following substitutions look like more substitution
+/// groups, i.e. the separator after the group was likely forgotten.
+///
+/// That is the case if there are more than one substitution identifiers and
+/// the number of substitutions before the next separator is a multiple of it.
+/// If so, returns the span of the first substitution after the completed
+/// group.
+fn next_row_without_separator<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+	identifier_count: usize,
+) -> Result<Option<Span>>
+{
+	let mut first = None;
+	let mut count = 0;
+	while iter.next_is_substitution()?
+	{
+		let (_, span) = iter.next_substitution()?;
+		first.get_or_insert(span);
+		count += 1;
+	}
+	if identifier_count > 1 && count % identifier_count == 0
+	{
+		Ok(first)
+	}
+	else
+	{
+		Ok(None)
+	}
+}
+
 /// Invokes a nested invocation of duplicate, assuming the
 /// next group is the body of call to `duplicate` (`is_duplicate`) or
 /// `substitute`(`!is_duplicate`)
+#[cfg(feature = "nested_invocation")]
 pub(crate) fn invoke_nested<'a, T: SubGroupIter<'a>>(
 	iter: &mut TokenIter<'a, T>,
 	is_duplicate: bool,
 ) -> Result<TokenStream>
 {
-	let (mut nested_body_iter, _) = iter.next_group(None)?;
+	let (nested_body_iter, span) = iter.next_group(None)?;
 
-	let (nested_invocation, _) = nested_body_iter.next_group(Some(Delimiter::Bracket))?;
+	// Errors without a span would otherwise point at the outer-most invocation
+	let (nested_invocation, nested_body) =
+		split_inline_invocation(nested_body_iter).map_err(|err| err.span_if_unset(span))?;
+	#[cfg(feature = "trace")]
+	crate::trace::nested_invocation();
 	(if is_duplicate
 	{
-		duplicate_impl
+		crate::duplicate_impl
 	}
 	else
 	{
-		substitute_impl
-	})(
-		nested_invocation.to_token_stream(),
-		nested_body_iter.to_token_stream(),
+		crate::substitute_impl
+	})(nested_invocation, nested_body)
+	.map_err(|err| err.span_if_unset(span))
+}
+
+/// Reports the substitution identifiers of nested invocations in the given
+/// header that are also substitution identifiers of the invocation it defines,
+/// per the `nested_shadowing` lint.
+///
+/// Nested invocations don't know they aren't the outer-most invocation, so
+/// they substitute such identifiers where the enclosing invocation's were
+/// meant to be.
+#[cfg(feature = "nested_invocation")]
+fn check_nested_shadowing(header: TokenStream, dup_def: &DuplicationDefinition) -> Result<()>
+{
+	if LintLevels::level(&dup_def.options, NESTED_SHADOWING) == LintLevel::Allow
+	{
+		return Ok(());
+	}
+	let mut nested_headers = Vec::new();
+	let outer_tokens = without_nested_headers(header, &mut nested_headers);
+	let outer: HashSet<_> = dup_def
+		.global_substitutions
+		.identifiers()
+		.chain(
+			dup_def
+				.duplications
+				.iter()
+				.flat_map(|group| group.identifiers()),
+		)
+		.collect();
+
+	for (nested_header, is_duplicate) in nested_headers
+	{
+		// Errors in the nested invocation were reported when it was expanded
+		let nested = match if is_duplicate
+		{
+			parse_duplicate_invocation(nested_header.clone(), &mut TokenStream::new())
+		}
+		else
+		{
+			parse_global_substitutions_only(nested_header.clone())
+		}
+		{
+			Ok(nested) => nested,
+			Err(_) => continue,
+		};
+		let mut shadowing: Vec<_> = nested
+			.global_substitutions
+			.identifiers()
+			.chain(
+				nested
+					.duplications
+					.iter()
+					.flat_map(|group| group.identifiers()),
+			)
+			.filter(|ident| outer.contains(ident))
+			.collect::<HashSet<_>>()
+			.into_iter()
+			.collect();
+		shadowing.sort();
+
+		for ident in shadowing
+		{
+			let mut err = Error::new(format!(
+				"Substitution identifier '{}' of the nested invocation is also a substitution \
+				 identifier of the enclosing invocation.",
+				ident
+			))
+			.span(find_identifier(&nested_header, ident).unwrap_or_else(Span::call_site))
+			.hint(crate::pretty_errors::NESTED_SHADOWING);
+			if let Some(span) = find_identifier(&outer_tokens, ident)
+			{
+				err = err.note(span, "The enclosing invocation's identifier is used here.");
+			}
+			report(&dup_def.options, NESTED_SHADOWING, err)?;
+		}
+	}
+	Ok(())
+}
+
+/// Reports the invocation with the given header if it gives only one
+/// substitution group and no global substitutions, per the `single_group` lint.
+///
+/// Only groups given literally by the invocation are reported, as those from
+/// `continues`, `rows_from`, or nested invocations may intentionally be one,
+/// as may a group count given by `expect`.
+pub(crate) fn check_single_group(
+	#[allow(unused_variables)] header: &TokenStream,
+	dup_def: &DuplicationDefinition,
+) -> Result<()>
+{
+	let options = &dup_def.options;
+	if dup_def.duplications.len() != 1
+		|| !dup_def.global_substitutions.substitutions.is_empty()
+		|| options.repeat.is_some()
+		|| options.continues.is_some()
+		|| options.rows_from.is_some()
+		|| options.expect.is_some()
+	{
+		return Ok(());
+	}
+	#[cfg(feature = "nested_invocation")]
+	{
+		let mut nested_headers = Vec::new();
+		without_nested_headers(header.clone(), &mut nested_headers);
+		if !nested_headers.is_empty()
+		{
+			return Ok(());
+		}
+	}
+	report(
+		options,
+		SINGLE_GROUP,
+		Error::new("Only one substitution group is given, so only one duplicate is made.")
+			.span(Span::call_site())
+			.hint(crate::pretty_errors::SINGLE_GROUP),
 	)
 }
 
+/// Warns about additional names of nested invocations (`nested_names`) that
+/// are also substitution identifiers of the invocation.
+#[cfg(feature = "nested_invocation")]
+fn check_nested_names(dup_def: &DuplicationDefinition)
+{
+	if let Some(names) = &dup_def.options.nested_names
+	{
+		for name in [&names.0, &names.1]
+		{
+			if dup_def
+				.duplications
+				.iter()
+				.chain(once(&dup_def.global_substitutions))
+				.any(|group| group.substitution_of(name).is_some())
+			{
+				crate::emit_warning(
+					Error::new(format!(
+						"Nested invocation name '{}' is also a substitution identifier, which \
+						 isn't substituted where it starts a nested invocation.",
+						name
+					))
+					.hint(NESTED_NAMES),
+				);
+			}
+		}
+	}
+}
+
+/// Returns the given tokens without the headers of any nested invocations,
+/// which are added to `headers`, each with whether it is a `duplicate!`
+/// invocation.
+#[cfg(feature = "nested_invocation")]
+fn without_nested_headers(
+	stream: TokenStream,
+	headers: &mut Vec<(TokenStream, bool)>,
+) -> TokenStream
+{
+	let mut result = Vec::new();
+	for token in stream
+	{
+		match token
+		{
+			TokenTree::Group(group) =>
+			{
+				let nested_name = match result.as_slice()
+				{
+					[.., TokenTree::Ident(ident), TokenTree::Punct(punct)]
+						if punct.as_char() == '!' =>
+					{
+						Some(ident.to_string())
+					},
+					_ => None,
+				}
+				.filter(|name| name == NESTED_DUPLICATE_NAME || name == NESTED_SUBSTITUTE_NAME);
+				let mut body = group.stream().into_iter().peekable();
+				let stream = match (nested_name, body.peek())
+				{
+					(Some(name), Some(TokenTree::Group(nested_header)))
+						if nested_header.delimiter() == Delimiter::Bracket =>
+					{
+						headers.push((nested_header.stream(), name == NESTED_DUPLICATE_NAME));
+						body.next();
+						without_nested_headers(body.collect(), headers)
+					},
+					_ => without_nested_headers(group.stream(), headers),
+				};
+				result.push(TokenTree::Group(new_group(
+					group.delimiter(),
+					stream,
+					group.span(),
+				)));
+			},
+			token => result.push(token),
+		}
+	}
+	result.into_iter().collect()
+}
+
+/// Returns the span of the first identifier with the given name in the given
+/// tokens, if any.
+#[cfg(feature = "nested_invocation")]
+fn find_identifier(stream: &TokenStream, name: &str) -> Option<Span>
+{
+	stream.clone().into_iter().find_map(|token| {
+		match token
+		{
+			TokenTree::Ident(ident) if ident.to_string() == name => Some(ident.span()),
+			TokenTree::Group(group) => find_identifier(&group.stream(), name),
+			_ => None,
+		}
+	})
+}
+
 /// Extracts a list of arguments from.
 /// The list is expected to be of comma-separated identifiers.
 pub(crate) fn extract_argument_list<'a, T: SubGroupIter<'a>>(
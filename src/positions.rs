@@ -0,0 +1,313 @@
+use crate::{
+	emit_warning,
+	error::Error,
+	new_group,
+	pretty_errors::POSITIONS,
+	token_iter::{is_punct, SubGroupIter, TokenIter},
+	Result,
+};
+use proc_macro::{TokenStream, TokenTree};
+use std::collections::HashMap;
+use syn::{
+	parse::{ParseStream, Parser},
+	visit_mut::{self, VisitMut},
+	Block, Expr, ExprMethodCall, ImplItemFn, Item, Member, Pat, Signature, TraitItemFn, Type,
+};
+
+/// The prefix of the identifiers temporarily replacing occurrences of
+/// restricted substitution identifiers outside their positions, followed by
+/// the index of the occurrence.
+const HIDDEN_PREFIX: &str = "__duplicate_hidden_";
+
+/// A position in the syntax of the item, to which a substitution identifier
+/// can be restricted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Position
+{
+	/// Anywhere syn parses a type.
+	Type,
+	/// The names of methods in method calls and in the definitions of
+	/// functions in `impl` and `trait` blocks.
+	Method,
+	/// Anywhere syn parses an expression, except in types, patterns, and
+	/// field names within it.
+	Expr,
+}
+
+/// The names of all positions.
+const POSITION_NAMES: &[(&str, Position)] = &[
+	("type", Position::Type),
+	("method", Position::Method),
+	("expr", Position::Expr),
+];
+
+/// The positions substitution identifiers are restricted to
+/// (`positions(...)`).
+#[derive(Debug, Default)]
+pub(crate) struct Positions
+{
+	/// The positions of each restricted substitution identifier.
+	allowed: HashMap<String, Vec<Position>>,
+}
+
+impl Positions
+{
+	/// Replaces the occurrences of restricted substitution identifiers in the
+	/// given item that aren't in their positions, so they aren't substituted.
+	///
+	/// Returns the item and the replaced occurrences, which `restore` puts
+	/// back. If the item can't be parsed, a warning is emitted and it is
+	/// returned as is, so all occurrences are substituted.
+	pub(crate) fn hide(&self, item: TokenStream) -> (TokenStream, Vec<TokenTree>)
+	{
+		if self.allowed.is_empty()
+		{
+			return (item, Vec::new());
+		}
+		let parser = |input: ParseStream| Block::parse_within(input);
+		match parser.parse2(item.clone().into())
+		{
+			Ok(mut stmts) =>
+			{
+				let mut hider = Hider {
+					positions: self,
+					position: None,
+					method_definition: false,
+					hidden: Vec::new(),
+				};
+				for stmt in stmts.iter_mut()
+				{
+					hider.visit_stmt_mut(stmt);
+				}
+				let hidden = hider
+					.hidden
+					.into_iter()
+					.map(|ident| {
+						TokenStream::from(proc_macro2::TokenStream::from(
+							proc_macro2::TokenTree::Ident(ident),
+						))
+						.into_iter()
+						.next()
+						.unwrap()
+					})
+					.collect();
+				(quote::quote!(#(#stmts)*).into(), hidden)
+			},
+			Err(err) =>
+			{
+				emit_warning(
+					Error::new(format!(
+						"Could not parse the item to restrict substitution identifiers to their \
+						 positions, so they are substituted in all positions: {}",
+						err
+					))
+					.span(err.span().unwrap())
+					.hint(POSITIONS),
+				);
+				(item, Vec::new())
+			},
+		}
+	}
+
+	/// Puts the given occurrences replaced by `hide` back into the given
+	/// output.
+	pub(crate) fn restore(output: TokenStream, hidden: &[TokenTree]) -> TokenStream
+	{
+		if hidden.is_empty()
+		{
+			return output;
+		}
+		output
+			.into_iter()
+			.map(|token| {
+				match token
+				{
+					TokenTree::Group(group) =>
+					{
+						TokenTree::Group(new_group(
+							group.delimiter(),
+							Self::restore(group.stream(), hidden),
+							group.span(),
+						))
+					},
+					TokenTree::Ident(ident) =>
+					{
+						match ident
+							.to_string()
+							.strip_prefix(HIDDEN_PREFIX)
+							.and_then(|index| index.parse::<usize>().ok())
+						{
+							Some(index) => hidden[index].clone(),
+							None => TokenTree::Ident(ident),
+						}
+					},
+					token => token,
+				}
+			})
+			.collect()
+	}
+}
+
+/// Validates the arguments of the `positions` option, which are substitution
+/// identifiers each followed by `@` and a position, separated by ','.
+///
+/// The same identifier may be given multiple times to allow it in multiple
+/// positions.
+pub(crate) fn validate_positions<'a, T: SubGroupIter<'a>>(
+	mut args: TokenIter<'a, T>,
+) -> Result<Positions>
+{
+	let mut result = Positions::default();
+	while args.has_next()?
+	{
+		let ident = args
+			.extract_identifier(Some("a substitution identifier"))
+			.map_err(|err| err.hint(POSITIONS))?;
+		args.expect_simple(|t| is_punct(t, '@'), Some("'@'"))
+			.map_err(|err| err.hint(POSITIONS))?;
+		let name = args
+			.extract_identifier(Some("'type', 'method', or 'expr'"))
+			.map_err(|err| err.hint(POSITIONS))?;
+		let position = POSITION_NAMES
+			.iter()
+			.find(|(position, _)| name.to_string() == *position)
+			.map(|(_, position)| *position)
+			.ok_or_else(|| {
+				Error::new("Unknown position.")
+					.span(name.span())
+					.hint(POSITIONS)
+			})?;
+		result
+			.allowed
+			.entry(ident.to_string())
+			.or_default()
+			.push(position);
+		if args.has_next()?
+		{
+			args.expect_comma()?;
+		}
+	}
+	Ok(result)
+}
+
+/// Replaces the occurrences of restricted substitution identifiers that aren't
+/// in their positions while visiting an item.
+struct Hider<'a>
+{
+	positions: &'a Positions,
+	/// The position of the syntax being visited, if any.
+	position: Option<Position>,
+	/// Whether the next signature visited is of a method definition.
+	method_definition: bool,
+	/// The replaced occurrences, indexed by the postfix of their replacements.
+	hidden: Vec<proc_macro2::Ident>,
+}
+
+impl Hider<'_>
+{
+	/// Visits syntax in the given position.
+	fn within(&mut self, position: Option<Position>, visit: impl FnOnce(&mut Self))
+	{
+		let outer = std::mem::replace(&mut self.position, position);
+		visit(self);
+		self.position = outer;
+	}
+
+	/// Replaces the given identifier if it is a restricted substitution
+	/// identifier and the given position isn't one of its positions.
+	fn hide_outside(&mut self, ident: &mut proc_macro2::Ident, position: Option<Position>)
+	{
+		if let Some(allowed) = self.positions.allowed.get(&ident.to_string())
+		{
+			if !position.map_or(false, |position| allowed.contains(&position))
+			{
+				let hidden = proc_macro2::Ident::new(
+					&format!("{}{}", HIDDEN_PREFIX, self.hidden.len()),
+					ident.span(),
+				);
+				self.hidden.push(std::mem::replace(ident, hidden));
+			}
+		}
+	}
+}
+
+impl VisitMut for Hider<'_>
+{
+	fn visit_ident_mut(&mut self, ident: &mut proc_macro2::Ident)
+	{
+		self.hide_outside(ident, self.position);
+	}
+
+	fn visit_type_mut(&mut self, node: &mut Type)
+	{
+		self.within(Some(Position::Type), |v| visit_mut::visit_type_mut(v, node));
+	}
+
+	fn visit_expr_mut(&mut self, node: &mut Expr)
+	{
+		self.within(Some(Position::Expr), |v| visit_mut::visit_expr_mut(v, node));
+	}
+
+	fn visit_pat_mut(&mut self, node: &mut Pat)
+	{
+		self.within(None, |v| visit_mut::visit_pat_mut(v, node));
+	}
+
+	fn visit_member_mut(&mut self, node: &mut Member)
+	{
+		self.within(None, |v| visit_mut::visit_member_mut(v, node));
+	}
+
+	fn visit_item_mut(&mut self, node: &mut Item)
+	{
+		self.within(None, |v| visit_mut::visit_item_mut(v, node));
+	}
+
+	fn visit_expr_method_call_mut(&mut self, node: &mut ExprMethodCall)
+	{
+		for attr in node.attrs.iter_mut()
+		{
+			self.visit_attribute_mut(attr);
+		}
+		self.visit_expr_mut(&mut node.receiver);
+		self.hide_outside(&mut node.method, Some(Position::Method));
+		if let Some(turbofish) = node.turbofish.as_mut()
+		{
+			self.visit_angle_bracketed_generic_arguments_mut(turbofish);
+		}
+		for arg in node.args.iter_mut()
+		{
+			self.visit_expr_mut(arg);
+		}
+	}
+
+	fn visit_impl_item_fn_mut(&mut self, node: &mut ImplItemFn)
+	{
+		self.method_definition = true;
+		visit_mut::visit_impl_item_fn_mut(self, node);
+	}
+
+	fn visit_trait_item_fn_mut(&mut self, node: &mut TraitItemFn)
+	{
+		self.method_definition = true;
+		visit_mut::visit_trait_item_fn_mut(self, node);
+	}
+
+	fn visit_signature_mut(&mut self, node: &mut Signature)
+	{
+		let position = std::mem::take(&mut self.method_definition).then_some(Position::Method);
+		self.within(None, |v| {
+			v.hide_outside(&mut node.ident, position);
+			v.visit_generics_mut(&mut node.generics);
+			for input in node.inputs.iter_mut()
+			{
+				v.visit_fn_arg_mut(input);
+			}
+			if let Some(variadic) = node.variadic.as_mut()
+			{
+				v.visit_variadic_mut(variadic);
+			}
+			v.visit_return_type_mut(&mut node.output);
+		});
+	}
+}
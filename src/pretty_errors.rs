@@ -1,7 +1,7 @@
 #![cfg_attr(not(feature = "pretty_errors"), allow(dead_code))]
 
-/// For when substitution parameters aren't enclosed in brackets
-pub(crate) const BRACKET_SUB_PARAM: &'static str = r#"Substitution parameters should be enclosed in '[]' each.
+/// For when substitution parameters aren't enclosed in brackets or braces
+pub(crate) const BRACKET_SUB_PARAM: &'static str = r#"Substitution parameters should be enclosed in '[]' or '{}' each.
 Example:
     sub_ident( [ parameter1 ] , [ paramter2 ] )
               ^^^          ^^^ ^^^         ^^^
@@ -28,8 +28,8 @@ Example:
 	^^^^^^^^^^^^^^^^^^^
 "#;
 
-/// For when short syntax substitutions aren't enclosed in brackets
-pub(crate) const SHORT_SYNTAX_MISSING_SUB_BRACKET: &'static str = r#"Each substitution should be enclosed in '[]'.
+/// For when short syntax substitutions aren't enclosed in brackets or braces
+pub(crate) const SHORT_SYNTAX_MISSING_SUB_BRACKET: &'static str = r#"Each substitution should be enclosed in '[]' or '{}'.
 Example:
     ident1 ident2;
     [ sub1 ] [ sub2 ] ;
@@ -46,6 +46,25 @@ Example:
    1^^^^^^ ^^^^^^2
 "#;
 
+/// For when the separator between short syntax substitution groups seems to
+/// be missing
+pub(crate) const SHORT_SYNTAX_MISSING_SEPARATOR: &str = r#"Each substitution group must end with ';' (or '=>' if the substitution identifiers do), except the last.
+Example:
+    ident1 ident2;
+    [sub1] [sub2];
+                 ^
+    [sub3] [sub4];
+"#;
+
+/// For when short syntax substitution groups are separated differently from
+/// the substitution identifiers
+pub(crate) const SHORT_SYNTAX_ARROWS: &str = r#"Either ';' or '=>' must separate the substitution identifiers and all substitution groups. With '=>', the last substitution group may end with ';'.
+Example:
+    ident1 ident2 =>
+    [sub1] [sub2] =>
+    [sub3] [sub4];
+"#;
+
 /// For when verbose syntax substitution group has too few or too many
 /// substitutions
 pub(crate) const VERBOSE_SYNTAX_SUBSTITUTION_IDENTIFIERS: &'static str = r#"All substitution groups must define the same substitution identifiers.
@@ -89,3 +108,412 @@ Example:
     name   [sub1];
     typ    [sub2];
 "#;
+
+/// For when global substitutions aren't separated by ';', possibly because
+/// they were meant as a verbose substitution group
+pub(crate) const GLOBAL_SUB_OR_VERBOSE_GROUP: &str = r#"Either end each global substitution with ';', or enclose the substitutions in '[]' to make them a verbose substitution group.
+Global substitutions:
+    name   [sub1];
+    typ    [sub2];
+Verbose substitution group:
+    [
+        name   [sub1]
+        typ    [sub2]
+    ]
+"#;
+
+/// For when an in-place duplication marker is used without substitution
+/// groups to duplicate with
+pub(crate) const MARKER_NO_GROUPS: &str = r#"In-place duplication needs substitution groups. Use 'duplicate_item' or 'duplicate!' instead.
+Example:
+    #[duplicate_item(
+        name;
+        [First];
+        [Second];
+    )]
+    enum Example {
+        #[duplicate::here]
+        name,
+    }
+"#;
+
+/// For when the arguments of `__concat__` don't produce a valid identifier
+pub(crate) const CONCAT_ARGUMENTS: &str = r#"'__concat__' concatenates identifiers and integers into one identifier, which must start with a letter or '_'.
+Example:
+    __concat__(name, _, __index__)
+"#;
+
+/// For when the arguments of `__concat_str__` don't produce a valid string
+pub(crate) const CONCAT_STR_ARGUMENTS: &str = r#"'__concat_str__' concatenates the contents of string literals and the text of other arguments into one string literal.
+Example:
+    __concat_str__("impl for ", ty, " backend")
+"#;
+
+/// For when the `repeat` option isn't given just a number of duplicates
+pub(crate) const REPEAT_COUNT: &str = r#"'repeat' takes the number of duplicates to make, which are identical except for '__index__'. Optionally followed by global substitutions.
+Example:
+    repeat(8);
+    name [Worker];
+"#;
+
+/// For when a substitution identifier alias is invalid
+pub(crate) const ALIAS: &str = r#"An alias must be a new name for an existing substitution identifier.
+Example:
+    alias t = int_type;
+    int_type;
+    [u8];
+"#;
+
+/// For when a case-preserving substitution identifier isn't substituted by a
+/// single identifier or the `module_disambiguation` feature is disabled
+pub(crate) const CASE_PRESERVING: &str = r#"A substitution identifier declared with '~' is also substituted where it is written in snake_case, PascalCase, or SCREAMING_SNAKE_CASE, by its substitution converted to the same case. The substitution must therefore be a single identifier that is still one in every case, and the substitution identifier can't have parameters. It requires the 'module_disambiguation' feature.
+Example:
+    [
+        ~backend  [sqlite]
+    ]
+    fn backend() -> Backend { ... }    // 'sqlite' and 'Sqlite'
+"#;
+
+/// For when the substitution groups excluded by `except` don't match
+pub(crate) const EXCEPT: &str = r#"'except' followed by a substitution for each substitution identifier excludes the substitution groups with the same substitutions, e.g. combinations generated by nested invocations. Each excluded group must be the same as at least one substitution group.
+Example:
+    src     dst;
+    [u8]    [u16];
+    [u16]   [u16];
+    except  [u16] [u16];
+"#;
+
+/// For when an accumulator declaration is invalid
+pub(crate) const ACCUMULATOR: &str = r#"An accumulator is declared as 'acc', a new name, '+=', and a fragment in brackets. In each duplicate, it is substituted by the fragment substituted with every substitution group up to and including the duplicate's.
+Example:
+    acc types += [ty,];
+    ty;
+    [A];  // types: A,
+    [B];  // types: A, B,
+"#;
+
+/// For when an optional substitution identifier is used in a duplicate whose
+/// substitution group doesn't define it
+pub(crate) const OPTIONAL_ABSENT: &str = r#"Optional substitution identifiers may only be used where all substitution groups reaching them define them. Either define it in every group or avoid using it in those duplicates.
+Example:
+    [
+        name            [first]
+        optional extra  [u8]
+    ]
+    [
+        name            [second]
+    ]
+"#;
+
+/// For when the column layout (`columns` option) is malformed
+pub(crate) const COLUMNS: &str = r#"With 'columns', each substitution identifier is followed by ':' and its substitution for every substitution group, ending with ';'. All identifiers must have the same number of substitutions.
+Example:
+    columns;
+    int_type:  [u8]  [u16];
+    max_value: [255] [65_535];
+"#;
+
+/// For when the `rows_from` option is misused
+pub(crate) const ROWS_FROM: &str = r#"'rows_from' takes the path of a file, relative to the crate's manifest directory, containing short syntax substitution groups. The substitution identifiers are still given in the invocation.
+Example:
+    rows_from("tables/int_types.dup");
+    int_type max_value;
+"#;
+
+/// For when an `assert_duplicate_expands` call is malformed
+pub(crate) const ASSERT_EXPANDS: &str = r#"'assert_duplicate_expands' takes the invocation within brackets, the code to duplicate within braces, and 'expect' followed by the expected expansion within braces.
+Example:
+    assert_duplicate_expands!{
+        [ name; [First]; [Second]; ]
+        { struct name; }
+        expect { struct First; struct Second; }
+    }
+"#;
+
+/// For when a `duplicate_file` or `substitute_file` call is malformed
+pub(crate) const FILE_MACRO: &str = r#"The path of the file, relative to the crate's manifest directory, must be followed by ',' and the invocation within brackets.
+Example:
+    duplicate_file!("templates/backend.rs", [
+        backend;
+        [Sqlite];
+        [Postgres];
+    ]);
+"#;
+
+/// For when a substitution from an environment variable is invalid
+pub(crate) const ENV_SUBSTITUTION: &str = r#"'env' substitutes the value of the named environment variable. An optional default is used if the variable isn't set.
+Example:
+    target_word env(TARGET_WORD, [u64]);
+"#;
+
+/// For when `keep` is given for a parameterized substitution identifier
+pub(crate) const KEEP: &str = r#"'keep' is given instead of a substitution to keep the substitution identifier as-is, which isn't possible for substitution identifiers with parameters.
+Example:
+    method     reference(ty);
+    [get]      [&ty];
+    keep       [&mut ty];
+"#;
+
+/// For when the `continues` option and `duplicate_item_rows` attributes don't
+/// line up
+pub(crate) const CONTINUES: &str = r#"With 'continues', the substitution groups of the short syntax are continued by 'duplicate_item_rows' attributes following the invocation.
+Example:
+    #[duplicate_item(
+        continues;
+        int_type max_value;
+    )]
+    #[duplicate_item_rows([u8] [255];)]
+    #[duplicate_item_rows([u16] [65_535];)]
+"#;
+
+/// For when `__for_each__` is misused
+pub(crate) const FOR_EACH: &str = r#"'__for_each__' takes a code fragment enclosed in '[]' or '{}', which is duplicated once for each substitution group. The duplicates are separated by ',' and end with ',' only if the fragment does.
+Example:
+    Example { __for_each__([name: 0,]) }
+"#;
+
+/// For when a duplicate invocation only has one substitution group and no
+/// global substitutions
+pub(crate) const SINGLE_GROUP: &str = r#"To only substitute, use 'substitute_item' or 'substitute!' with global substitutions instead.
+Example:
+    #[substitute_item(
+        name [Example];
+        ty   [u8];
+    )]
+"#;
+
+/// For when the template sections of a `duplicate!` body are malformed
+pub(crate) const TEMPLATE_SECTIONS: &str = r#"When using template sections, the code to duplicate consists only of sections. Each is 'template' followed by the section's name, optionally its own global substitutions within brackets, and its code within braces.
+Example:
+    template struct_def { pub struct name(ty); }
+    template tests [ value [1]; ] { ... }
+"#;
+
+/// For when `substitute_item` or `substitute!` is given substitution groups
+pub(crate) const SUBSTITUTE_GROUPS: &str = r#"Substitution groups are only accepted by 'duplicate_item' and 'duplicate!', which make a duplicate for each of them.
+Example:
+    #[duplicate_item(
+        name;
+        [First];
+        [Second];
+    )]
+"#;
+
+/// For when an identifier differs from a substitution identifier only by case
+pub(crate) const CASE_MISMATCH: &str = r#"Identifiers differing from a substitution identifier only by case are likely typos, but are left unchanged unless the 'case_mismatch' lint is denied (e.g. by 'strict').
+Example:
+    lints(deny(case_mismatch));
+    int_type;
+    [u8];
+    ...
+    Int_type  // Not substituted
+"#;
+
+/// For when a substitution or argument is empty
+pub(crate) const EMPTY_SUBSTITUTION: &str = r#"Empty substitutions are allowed by default, but are reported when the 'empty_substitution' lint is warned about or denied, as they are likely forgotten. If the substitution is meant to be empty, allow the lint instead.
+Example:
+    lints(warn(empty_substitution));
+    name    int_type;
+    [first] [u8];
+    [empty] [];  // Warned about
+"#;
+
+/// For when a nested invocation in a header uses a substitution identifier of
+/// the enclosing invocation
+#[cfg_attr(not(feature = "nested_invocation"), allow(dead_code))]
+pub(crate) const NESTED_SHADOWING: &str = r#"Nested invocations don't know they aren't the outer-most invocation, so they also substitute the enclosing invocation's identifiers in their bodies. Give the nested invocation its own substitution identifier.
+Example:
+    [
+        duplicate!{[int_type_nested; [u8]; [u16]] [int_type [int_type_nested]]}
+    ]
+"#;
+
+/// For when the `nested_names` option is misused
+pub(crate) const NESTED_NAMES: &str = r#"'nested_names' takes the names, separated by ',', that 'duplicate!' and 'substitute!' are also known by, e.g. when re-exported under other names. Invocations of them are expanded as nested invocations.
+Example:
+    nested_names(my_duplicate, my_substitute);
+"#;
+
+/// For when the `lints` option is malformed
+pub(crate) const LINTS: &str = r#"'lints' takes lint levels ('allow', 'warn', or 'deny'), each followed by the names of the lints to give that level, separated by ','. Warnings are only emitted by nightly compilers.
+Example:
+    lints(deny(case_mismatch), warn(...));
+"#;
+
+/// For when the module disambiguation postfixes of a substitution identifier
+/// aren't unique
+#[cfg_attr(not(feature = "module_disambiguation"), allow(dead_code))]
+pub(crate) const MODULE_POSTFIX_COLLISION: &str = r#"Each duplicate's substitution must be a different identifier (in snake case) to be postfixed on the module name.
+Otherwise, use 'module_name(auto)' or 'module_name(index)' to postfix the index of each substitution group.
+Example:
+    name      member_type;
+    [Reader]  [u8];
+    [Writer]  [u8];  // 'member_type' collides, so 'name' is used
+"#;
+
+/// For when `module_name(sanitize(..))` can't produce unique module names
+#[cfg_attr(not(feature = "module_disambiguation"), allow(dead_code))]
+pub(crate) const SANITIZE: &str = r#"With 'module_name(sanitize(ident))', the identifiers and literals of each substitution of 'ident' are joined in snake case to make the module name's postfix, which must start with a letter and be unique.
+Example:
+    module_name(sanitize(ty));
+    ty;
+    [Vec<u8>];       // module_vec_u8
+    [&'static str];  // module_static_str
+"#;
+
+/// For when the `module_names` option is malformed
+pub(crate) const MODULE_NAMES: &str = r#"'module_names' takes the module name of each duplicate, each enclosed in '[]' or '{}' and separated by ','. There must be one unique name for each substitution group.
+Example:
+    module_names([codec_u8], [codec_u16]);
+    int_type;
+    [u8];
+    [u16];
+"#;
+
+/// For when an invocation makes more duplicates or outputs more tokens than
+/// allowed
+pub(crate) const LIMITS: &str = r#"To catch accidental blowups, an invocation may by default make at most 10000 duplicates and output at most 1000000 tokens. 'max_duplicates(N)' changes the number of duplicates one invocation may make, while the 'DUPLICATE_MAX_DUPLICATES' and 'DUPLICATE_MAX_OUTPUT_TOKENS' environment variables change the defaults.
+Example:
+    max_duplicates(20000);
+"#;
+
+/// For when an invocation doesn't make the number of duplicates given by
+/// `expect`
+pub(crate) const EXPECT: &str = r#"'expect' takes the number of substitution groups the invocation must end up with, including any produced by nested invocations or continued rows. Update it if the change in the number of duplicates is intended.
+Example:
+    expect(3);
+    int_type;
+    [u8];
+    [u16];
+    [u32];
+"#;
+
+/// For when a substitution group doesn't define the identifiers given by
+/// `require`
+pub(crate) const REQUIRE: &str = r#"'require' takes the substitution identifiers, separated by ',', that every substitution group must define and no others, including groups produced by nested invocations.
+Example:
+    require(int_type, implementation);
+    [
+        int_type        [u8]
+        implementation  [u8::MAX]
+    ]
+"#;
+
+/// For when the sides of `zip` can't be combined
+pub(crate) const ZIP: &str = r#"'zip' combines the substitution groups of its two sides pairwise, so both must produce the same number of substitution groups and substitute different identifiers.
+Example:
+    zip {
+        ty; [u8]; [u16];
+    } {
+        max; [255]; [65_535];
+    }
+"#;
+
+/// For when a pattern substitution is malformed
+pub(crate) const PATTERN: &str = r#"A pattern substitution is 'pattern', the tokens to substitute in '[]', '=>', and the substitution. The tokens may not contain groups delimited by '()', '[]', or '{}'.
+Example:
+    pattern [Self::Assoc] => [<T as Trait>::Assoc];
+"#;
+
+/// For when the `recursive` option would substitute forever
+pub(crate) const RECURSIVE: &str = r#"With 'recursive', substitution identifiers in the output of a substitution are substituted again, so no substitution may end up using itself.
+Example:
+    recursive;
+    inner   [u8];
+    outer   [Vec<inner>];  // Vec<u8>
+"#;
+
+/// For when the `positions` option is malformed or can't restrict the
+/// substitution identifiers
+pub(crate) const POSITIONS: &str = r#"'positions' restricts substitution identifiers to positions in the item, each given as the identifier, '@', and 'type', 'method', or 'expr', separated by ','. It requires the 'positions' feature and that the item can be parsed.
+Example:
+    positions(ty@type, get@method);
+"#;
+
+/// For when a duplicate isn't valid Rust given the `validate` option
+pub(crate) const VALIDATE: &str = r#"With 'validate', the output of each duplicate is parsed as items before it is emitted, so errors can name the substitution group that caused them. Check that the substitutions give valid code where their identifiers are used.
+Example:
+    validate;
+    name    ty;
+    [Small] [u8];
+    [Large] [u64];
+"#;
+
+/// For when two duplicates define an item with the same name given the
+/// `unique_names` option
+pub(crate) const UNIQUE_NAMES: &str = r#"With 'unique_names', the items each duplicate defines outside of other items must have names that differ from the other duplicates', as they would otherwise be defined multiple times. Use a substitution identifier in the name or put the item in a module, which module disambiguation names differently in each duplicate.
+Example:
+    unique_names;
+    name    ty;
+    [Small] [u8];
+    [Large] [u64];
+    pub fn name() -> ty { ... }
+"#;
+
+/// For when the arguments of the `wrap_in` option aren't a module name,
+/// optionally preceded by a visibility
+pub(crate) const WRAP_IN: &str = r#"'wrap_in' takes the name of the module to put all duplicates in, optionally preceded by its visibility.
+Example:
+    wrap_in(pub(crate) generated);
+"#;
+
+/// For when the arguments of the `defer_as` option aren't a macro name,
+/// optionally preceded by `pub`, or the duplicates contain `$`
+pub(crate) const DEFER_AS: &str = r#"'defer_as' takes the name of the 'macro_rules!' macro emitting the duplicates where it is invoked, optionally preceded by 'pub' to export it from the crate. As the duplicates become the body of the macro, they can't contain '$'.
+Example:
+    defer_as(pub instantiate_backends);
+"#;
+
+/// For when `__item__` is used but the duplicated item's name can't be found
+pub(crate) const ITEM_NAME: &str = r#"'__item__' is the name of the duplicated item, which must be a function, struct, enum, union, trait, type alias, constant, static, module, or 'macro_rules!' declaration whose name is substituted by a single identifier.
+Example:
+    duplicate!{
+        [name; [First]; [Second]]
+        struct name();
+        impl Default for __item__ { ... }
+    }
+"#;
+
+/// For when `expand!` is given a macro it can't expand
+pub(crate) const EXPAND: &str = r#"'expand!' in an invocation is replaced by the expansion of the macro it is given, which must be 'concat!', 'stringify!', or 'include!' with a path relative to the crate's manifest directory.
+Example:
+    name;
+    expand!(include!("rows.in"))
+"#;
+
+/// For when `__first__` or `__last__` isn't given just a fragment
+pub(crate) const FIRST_LAST: &str = r#"'__first__' and '__last__' take a fragment in brackets, which is only substituted in the first or last duplicate, respectively, and removed from the others.
+Example:
+    __first__([ pub struct Header; ])
+    pub struct name;
+    __last__([ pub const COUNT: usize = __index__ + 1; ])
+"#;
+
+/// For when `__all__` is misused
+pub(crate) const ALL: &str = r#"'__all__' takes a substitution identifier and optionally a separator in brackets, and is substituted by the identifier's substitutions in all substitution groups, separated by the separator or ','.
+Example:
+    const NAMES: &[&str] = &[ __all__(name_str) ];
+    impl_for_all!( __all__(ty) );
+"#;
+
+/// For when `__group_sub__` is misused
+pub(crate) const GROUP_SUB: &str = r#"'__group_sub__' takes a substitution identifier and the index of a substitution group, and is substituted by the identifier's substitution in that group. An index with a sign ('+' or '-') is relative to the duplicate's substitution group and wraps around, while one without is absolute.
+Example:
+    impl From<Wrapper<int_type>> for Wrapper<__group_sub__(int_type, +1)>
+"#;
+
+/// For when the `maybe_async` option is misused
+pub(crate) const MAYBE_ASYNC: &str = r#"'maybe_async' makes an async duplicate of the item as written and a sync duplicate without 'async', '.await', and 'impl Future<Output = T>'. Optionally, it takes a configuration predicate that enables the async duplicate, while the sync duplicate is enabled otherwise. Substitution groups may be given for the two duplicates, e.g. to name them differently.
+Example:
+    maybe_async(feature = "async");
+"#;
+
+/// For when the `auto_cfg` option is malformed or its substitution can't be
+/// used as the value of a configuration option
+pub(crate) const AUTO_CFG: &str = r#"'auto_cfg' takes a configuration option, '=', and a substitution identifier, whose substitution in each duplicate must be a single identifier or literal. Each duplicate is then only compiled if the option has that value.
+Example:
+    auto_cfg(feature = backend);
+    backend;
+    [tokio];      // #[cfg(feature = "tokio")]
+    [async_std];  // #[cfg(feature = "async_std")]
+"#;
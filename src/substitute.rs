@@ -1,13 +1,21 @@
 #[cfg(feature = "module_disambiguation")]
-use crate::module_disambiguation::try_substitute_mod;
+use crate::module_disambiguation::{disambiguated_name, postfix_of, try_substitute_mod};
+#[cfg(feature = "validate")]
+use crate::validate::validate_duplicate;
 use crate::{
-	disambiguate_module, error::Error, new_group, token_iter::SubGroupIter, Result,
-	SubstitutionGroup, Token, TokenIter,
+	disambiguate_module,
+	error::Error,
+	limits::{check_output_tokens, count_tokens, max_output_tokens},
+	lint::{check_empty_substitution, report, LintLevel, LintLevels, CASE_MISMATCH},
+	new_group,
+	token_iter::{is_dollar, is_ident, is_punct, SubGroupIter},
+	InvocationOptions, Postfix, Result, SubstitutionGroup, Token, TokenIter,
 };
-use proc_macro::{Delimiter, Ident, Span, TokenStream, TokenTree};
+use proc_macro::{Delimiter, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use std::collections::{HashMap, HashSet};
 
 /// The types of sub-substitutions composing a single substitution.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SubType
 {
 	/// A simple substitution with the TokenStream
@@ -16,7 +24,9 @@ pub enum SubType
 	Argument(usize),
 	/// Substitution with a group with the specified delimiter and the contents
 	/// being what is produced by the nested substitution.
-	Group(Delimiter, Substitution),
+	///
+	/// The span is that of the group in the substitution's definition.
+	Group(Delimiter, Substitution, Span),
 }
 
 /// A substitution for an identifier.
@@ -30,7 +40,7 @@ pub enum SubType
 /// number of arguments must match the number given to `new`,) which will yield
 /// the final TokenStream that should be substituted for the identifier ( +
 /// arguments).
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Substitution
 {
 	/// The number of arguments to the substitution
@@ -38,6 +48,9 @@ pub struct Substitution
 	/// The substitution. The list is ordered, with the result of an application
 	/// being the concatenation of each sub-substitution.
 	sub: Vec<SubType>,
+	/// Whether the substitution keeps its identifier as-is (`keep`), in which
+	/// case it takes the span of each occurrence it substitutes.
+	keeps_identifier: bool,
 }
 
 impl Substitution
@@ -48,6 +61,16 @@ impl Substitution
 		Self {
 			arg_count: 0,
 			sub: vec![SubType::Token(substitution)],
+			keeps_identifier: false,
+		}
+	}
+
+	/// Create a new substitution of the given identifier by itself (`keep`).
+	pub fn new_keep(ident: Ident) -> Self
+	{
+		Self {
+			keeps_identifier: true,
+			..Self::new_simple(TokenTree::Ident(ident).into())
 		}
 	}
 
@@ -82,13 +105,27 @@ impl Substitution
 					}
 					substitutions.push(SubType::Argument(find_argument(&ident).unwrap()));
 				},
-				Token::Group(del, iter, _) =>
+				Token::Simple(t) if is_dollar(&t) =>
+				{
+					// Metavariables (identifiers following '$') are never arguments
+					let saved = saved_tokens.get_or_insert_with(TokenStream::new);
+					saved.extend(Some(t));
+					if let Some(Token::Simple(TokenTree::Ident(_))) = stream.peek()?
+					{
+						saved.extend(Some(TokenTree::from(stream.next_fallible()?.unwrap())));
+					}
+				},
+				Token::Group(del, iter, span) =>
 				{
 					if let Some(sub_stream) = saved_tokens.take()
 					{
 						substitutions.push(SubType::Token(sub_stream));
 					}
-					substitutions.push(SubType::Group(del, Substitution::new(arguments, iter)?));
+					substitutions.push(SubType::Group(
+						del,
+						Substitution::new(arguments, iter)?,
+						span,
+					));
 				},
 				token =>
 				{
@@ -105,10 +142,24 @@ impl Substitution
 		let substitution = Self {
 			arg_count: arguments.len(),
 			sub: substitutions,
+			keeps_identifier: false,
 		};
 		Ok(substitution)
 	}
 
+	/// Whether the substitution uses the argument with the given index.
+	pub fn uses_argument(&self, index: usize) -> bool
+	{
+		self.sub.iter().any(|sub| {
+			match sub
+			{
+				SubType::Token(_) => false,
+				SubType::Argument(idx) => *idx == index,
+				SubType::Group(_, subst, _) => subst.uses_argument(index),
+			}
+		})
+	}
+
 	/// Apply the substitution, assuming it takes no arguments.
 	pub fn apply_simple(&self, err_span: Span) -> Result<TokenStream>
 	{
@@ -129,14 +180,25 @@ impl Substitution
 				result.extend(
 					match sub
 					{
+						SubType::Token(stream) if self.keeps_identifier =>
+						{
+							stream
+								.clone()
+								.into_iter()
+								.map(|mut token| {
+									token.set_span(err_span);
+									token
+								})
+								.collect()
+						},
 						SubType::Token(stream) => stream.clone(),
 						SubType::Argument(idx) => arguments[*idx].clone(),
-						SubType::Group(delimiter, subst) =>
+						SubType::Group(delimiter, subst, span) =>
 						{
 							TokenStream::from(TokenTree::Group(new_group(
 								delimiter.clone(),
 								subst.apply(arguments, err_span)?,
-								Span::call_site(),
+								*span,
 							)))
 						},
 					}
@@ -179,166 +241,2531 @@ impl Substitution
 		None
 	}
 
+	/// Whether the substitution keeps its identifier as-is (`keep`).
+	pub fn keeps_identifier(&self) -> bool
+	{
+		self.keeps_identifier
+	}
+
 	pub fn argument_count(&self) -> usize
 	{
 		self.arg_count
 	}
+
+	/// The text of the substitution's tokens separated by spaces, with the
+	/// uses of arguments given by their index (e.g. `$0`).
+	pub(crate) fn normalized_text(&self) -> String
+	{
+		self.sub
+			.iter()
+			.map(|sub| {
+				match sub
+				{
+					SubType::Token(stream) => normalized_text(stream.clone()),
+					SubType::Argument(idx) => format!("${}", idx),
+					SubType::Group(delimiter, subst, _) =>
+					{
+						let (open, close) = delimiter_text(*delimiter);
+						format!("{} {} {}", open, subst.normalized_text(), close)
+					},
+				}
+			})
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+}
+
+/// The text of the given tokens separated by spaces, which doesn't depend on
+/// how they are spaced or printed by the compiler.
+fn normalized_text(stream: TokenStream) -> String
+{
+	stream
+		.into_iter()
+		.map(|token| {
+			match token
+			{
+				TokenTree::Group(group) =>
+				{
+					let (open, close) = delimiter_text(group.delimiter());
+					format!("{} {} {}", open, normalized_text(group.stream()), close)
+				},
+				TokenTree::Punct(punct) => punct.as_char().to_string(),
+				token => token.to_string(),
+			}
+		})
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+/// Whether the given tokens are the same, ignoring spans, spacing, and
+/// None-delimited groups.
+pub(crate) fn same_tokens(left: &TokenStream, right: &TokenStream) -> bool
+{
+	normalized_text(strip_none_groups(left.clone()))
+		== normalized_text(strip_none_groups(right.clone()))
+}
+
+/// The opening and closing text of the given delimiter.
+fn delimiter_text(delimiter: Delimiter) -> (&'static str, &'static str)
+{
+	match delimiter
+	{
+		Delimiter::Parenthesis => ("(", ")"),
+		Delimiter::Bracket => ("[", "]"),
+		Delimiter::Brace => ("{", "}"),
+		Delimiter::None => ("", ""),
+	}
+}
+
+/// Returns a short identifier derived from the text of the substitutions of
+/// the given substitution group (`__hash__`).
+///
+/// Groups with the same substitutions always get the same identifier, no matter
+/// the order of their substitution identifiers, the compiler, or the build.
+fn hash_of(substitutions: &SubstitutionGroup) -> String
+{
+	let mut idents: Vec<_> = substitutions.identifiers().collect();
+	idents.sort();
+	let text = idents
+		.into_iter()
+		.map(|ident| {
+			format!(
+				"{} [ {} ]",
+				ident,
+				substitutions
+					.substitution_of(ident)
+					.unwrap()
+					.normalized_text()
+			)
+		})
+		.collect::<Vec<_>>()
+		.join(" ");
+	// 64-bit FNV-1a, which unlike the standard library's hasher is specified not
+	// to change
+	let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+	});
+	format!("h{:08x}", (hash ^ (hash >> 32)) as u32)
+}
+
+/// A sequence of tokens that is substituted wherever it occurs, like a
+/// substitution identifier (`pattern [tokens] => [substitution]`).
+///
+/// A pattern matches the same identifiers, literals, and punctuation in the
+/// same order, where all but the last punctuation must also be joined to the
+/// next token in the same way. Patterns can't contain delimited groups, so a
+/// match never crosses the delimiters of a group.
+#[derive(Debug, Clone)]
+pub(crate) struct Pattern
+{
+	/// The tokens to match.
+	tokens: Vec<TokenTree>,
+	/// The span of the pattern's brackets.
+	span: Span,
+}
+
+impl Pattern
+{
+	/// Creates a pattern matching the given tokens, which are enclosed in
+	/// brackets with the given span.
+	pub(crate) fn new(stream: TokenStream, span: Span) -> Result<Self>
+	{
+		let tokens: Vec<_> = stream.into_iter().collect();
+		if tokens.is_empty()
+		{
+			return Err(Error::new("Expected tokens to match.")
+				.span(span)
+				.hint(crate::pretty_errors::PATTERN));
+		}
+		if let Some(group) = tokens.iter().find(|t| matches!(t, TokenTree::Group(_)))
+		{
+			return Err(Error::new("Patterns can't contain delimited groups.")
+				.span(group.span())
+				.hint(crate::pretty_errors::PATTERN));
+		}
+		Ok(Self { tokens, span })
+	}
+
+	/// The name the pattern's substitution is given in its substitution group.
+	///
+	/// As it isn't an identifier, it doesn't conflict with any substitution
+	/// identifier.
+	pub(crate) fn key(&self) -> String
+	{
+		format!("pattern [{}]", TokenStream::from_iter(self.tokens.clone()))
+	}
+
+	pub(crate) fn span(&self) -> Span
+	{
+		self.span
+	}
+
+	/// Whether the given token matches the token of the pattern at the given
+	/// index.
+	fn token_matches(&self, index: usize, token: &TokenTree) -> bool
+	{
+		match (&self.tokens[index], token)
+		{
+			(TokenTree::Punct(expected), TokenTree::Punct(found)) =>
+			{
+				expected.as_char() == found.as_char()
+					&& (index + 1 == self.tokens.len() || expected.spacing() == found.spacing())
+			},
+			(TokenTree::Ident(expected), TokenTree::Ident(found)) =>
+			{
+				expected.to_string() == found.to_string()
+			},
+			(TokenTree::Literal(expected), TokenTree::Literal(found)) =>
+			{
+				expected.to_string() == found.to_string()
+			},
+			_ => false,
+		}
+	}
+
+	/// Whether the given tokens could be the start of a match that needs more
+	/// tokens.
+	fn is_continued_by(&self, tokens: &[TokenTree]) -> bool
+	{
+		tokens.len() < self.tokens.len()
+			&& tokens
+				.iter()
+				.enumerate()
+				.all(|(index, token)| self.token_matches(index, token))
+	}
+
+	/// Whether the given tokens start with a match of the pattern.
+	fn is_matched_by(&self, tokens: &[TokenTree]) -> bool
+	{
+		tokens.len() >= self.tokens.len()
+			&& self
+				.tokens
+				.iter()
+				.enumerate()
+				.all(|(index, _)| self.token_matches(index, &tokens[index]))
+	}
+}
+
+/// The built-in identifier substituted by the index of the duplicate's
+/// substitution group.
+const INDEX_NAME: &str = "__index__";
+
+/// The built-in identifier substituted by a short identifier derived from the
+/// substitutions of the duplicate's substitution group.
+const HASH_NAME: &str = "__hash__";
+
+/// The built-in identifier substituted by the name of the duplicated item.
+const ITEM_NAME: &str = "__item__";
+
+/// The built-in identifier concatenating its arguments into one identifier.
+const CONCAT_NAME: &str = "__concat__";
+
+/// The built-in identifier concatenating its arguments into one string literal.
+const CONCAT_STR_NAME: &str = "__concat_str__";
+
+/// The built-in identifier duplicating its argument in place for each
+/// substitution group.
+const FOR_EACH_NAME: &str = "__for_each__";
+
+/// The built-in identifier expanding its argument only in the first
+/// duplicate.
+const FIRST_NAME: &str = "__first__";
+
+/// The built-in identifier expanding its argument only in the last duplicate.
+const LAST_NAME: &str = "__last__";
+
+/// The built-in identifier substituted by the substitutions of an identifier in
+/// every substitution group.
+const ALL_NAME: &str = "__all__";
+
+/// The built-in identifier substituted by the substitution of an identifier in
+/// another substitution group.
+const GROUP_SUB_NAME: &str = "__group_sub__";
+
+/// How many substitutions may be substituted within each other's output
+/// (given the `recursive` option).
+const MAX_RECURSION_DEPTH: usize = 64;
+
+/// The context in which the tokens of a duplicate are substituted.
+#[derive(Clone, Copy)]
+struct SubstitutionContext<'b>
+{
+	/// The options of the invocation.
+	options: &'b InvocationOptions,
+	/// The global substitutions of the invocation.
+	global_subs: &'b SubstitutionGroup,
+	/// The substitution group of the duplicate.
+	substitutions: &'b SubstitutionGroup,
+	/// The index of the duplicate's substitution group.
+	index: usize,
+	/// The name of the item in the duplicate (`__item__`), if it is used and
+	/// the item has one.
+	item_name: Option<&'b Ident>,
+	/// Whether the tokens are inside an attribute (`#[...]`), meaning string
+	/// literals should also be substituted in (given the `attr_strings`
+	/// option).
+	in_attribute: bool,
+	/// The substitution identifiers of the invocation keyed by their lowercase
+	/// (given the `strict` option, otherwise empty).
+	lowercase_identifiers: &'b HashMap<String, String>,
+	/// The innermost substitution whose output is being substituted again
+	/// (given the `recursive` option), if any.
+	resubstituting: Option<&'b Resubstitution<'b>>,
+	/// The substitution identifiers declared by the inner invocations the
+	/// tokens are in, which aren't substituted (given the `protect_inner`
+	/// option).
+	shadowed: Option<&'b HashSet<String>>,
+}
+
+impl SubstitutionContext<'_>
+{
+	/// Whether the given identifier is declared by an inner invocation, so it
+	/// isn't substituted (see `shadowed`).
+	fn is_shadowed(&self, ident: &Ident) -> bool
+	{
+		self.shadowed
+			.map_or(false, |shadowed| shadowed.contains(&ident.to_string()))
+	}
+}
+
+/// A substitution whose output is being substituted again, given the
+/// `recursive` option.
+struct Resubstitution<'b>
+{
+	/// The substitution identifier whose substitution it is.
+	ident: &'b str,
+	/// The number of substitutions being substituted again, including this one.
+	depth: usize,
+	/// The substitution whose output this one is in, if any.
+	outer: Option<&'b Resubstitution<'b>>,
 }
 
 /// Duplicates the given token stream, substituting any identifiers found.
 pub(crate) fn duplicate_and_substitute<'a>(
 	item: TokenStream,
+	options: &InvocationOptions,
 	global_subs: &'a SubstitutionGroup,
 	mut sub_groups: impl Iterator<Item = &'a SubstitutionGroup> + Clone,
 ) -> Result<TokenStream>
 {
+	if options.interleave_by_item
+	{
+		if let Some(items) = split_items(&item).filter(|items| items.len() > 1)
+		{
+			// Duplicate each item on its own, so all its duplicates come before the next
+			// item's
+			let mut result = TokenStream::new();
+			for item in items
+			{
+				result.extend(duplicate_and_substitute(
+					item,
+					options,
+					global_subs,
+					sub_groups.clone(),
+				)?);
+			}
+			return Ok(result);
+		}
+	}
+
 	let mut result = TokenStream::new();
-	#[allow(unused_variables)]
-	let mod_and_postfix_sub = disambiguate_module(&item, sub_groups.clone())?;
+	// With in-place duplication, the item is output only once and so never needs
+	// disambiguation
+	let in_place = contains_marker(&item);
+	if let (true, Some((_, span))) = (in_place, &options.maybe_async)
+	{
+		return Err(
+			Error::new("'maybe_async' can't be used with in-place duplication.")
+				.span(*span)
+				.hint(crate::pretty_errors::MAYBE_ASYNC),
+		);
+	}
+	if let (true, Some((_, ident))) = (in_place, &options.auto_cfg)
+	{
+		return Err(
+			Error::new("'auto_cfg' can't be used with in-place duplication.")
+				.span(ident.span())
+				.hint(crate::pretty_errors::AUTO_CFG),
+		);
+	}
+	if let (true, Some((name, _))) = (in_place, options.accumulators.first())
+	{
+		return Err(
+			Error::new("Accumulators can't be used with in-place duplication.")
+				.span(name.span())
+				.hint(crate::pretty_errors::ACCUMULATOR),
+		);
+	}
+	let mod_and_postfix_sub = if in_place
+	{
+		None
+	}
+	else
+	{
+		disambiguate_module(&item, sub_groups.clone(), &options.module_name)?
+	};
+	#[cfg(all(feature = "module_disambiguation", feature = "pretty_errors"))]
+	if options.explain_module_names
+	{
+		if let Some((mod_name, postfix)) = &mod_and_postfix_sub
+		{
+			crate::module_disambiguation::explain_module_names(
+				mod_name,
+				postfix,
+				&options.module_name,
+				sub_groups.clone(),
+			);
+		}
+	}
+
+	let mut lowercase_identifiers = HashMap::new();
+	if LintLevels::level(options, CASE_MISMATCH) != LintLevel::Allow
+	{
+		for ident in global_subs
+			.identifiers()
+			.chain(sub_groups.clone().flat_map(|group| group.identifiers()))
+		{
+			lowercase_identifiers.insert(ident.to_lowercase(), ident.clone());
+		}
+	}
 
+	let uses_item_name = contains_ident(&item, ITEM_NAME)
+		|| global_subs
+			.substitutions
+			.values()
+			.chain(
+				sub_groups
+					.clone()
+					.flat_map(|group| group.substitutions.values()),
+			)
+			.any(|substitution| substitution.normalized_text().contains(ITEM_NAME));
+
+	// The first substitution group whose duplicate defines each top-level name
+	// (given the `unique_names` option)
+	let mut defined_names: HashMap<String, usize> = HashMap::new();
+	let max_tokens = max_output_tokens()?;
+	let mut output_tokens = 0;
 	let sub_groups_clone = sub_groups.clone();
-	let mut duplicate_and_substitute_one = |substitutions: &SubstitutionGroup| -> Result<()> {
-		let mut item_iter = TokenIter::new(item.clone(), global_subs, sub_groups_clone.clone());
+	let empty_sub = SubstitutionGroup::empty();
+	// The running value of each accumulator, extended by every duplicate
+	let mut accumulated = vec![TokenStream::new(); options.accumulators.len()];
+	let mut duplicate_and_substitute_one =
+		|substitutions: &SubstitutionGroup, index: usize| -> Result<()> {
+			let with_accumulated;
+			let substitutions = if options.accumulators.is_empty()
+			{
+				substitutions
+			}
+			else
+			{
+				with_accumulated = accumulate(
+					substitutions,
+					index,
+					&mut accumulated,
+					options,
+					global_subs,
+					&lowercase_identifiers,
+				)?;
+				&with_accumulated
+			};
+			let ctx = SubstitutionContext {
+				options,
+				global_subs,
+				substitutions,
+				index,
+				item_name: None,
+				in_attribute: false,
+				lowercase_identifiers: &lowercase_identifiers,
+				resubstituting: None,
+				shadowed: None,
+			};
+			let item_name = if uses_item_name
+			{
+				let iter = TokenIter::new(item.clone(), global_subs, sub_groups_clone.clone())
+					.with_nested_names(options);
+				item_name(iter, ctx, &mod_and_postfix_sub)?
+			}
+			else
+			{
+				None
+			};
+			let ctx = SubstitutionContext {
+				item_name: item_name.as_ref(),
+				..ctx
+			};
+			let mut item_iter = TokenIter::new(item.clone(), global_subs, sub_groups_clone.clone())
+				.with_nested_names(options);
+			let mut duplicate = TokenStream::from_iter(substitutions.docs.iter().cloned());
 
-		#[cfg(feature = "module_disambiguation")]
-		let mut substituted_mod = false;
-		loop
-		{
 			#[cfg(feature = "module_disambiguation")]
+			let mut substituted_mod = false;
+			loop
 			{
-				if !substituted_mod
+				#[cfg(feature = "module_disambiguation")]
 				{
-					let stream =
-						try_substitute_mod(&mod_and_postfix_sub, substitutions, &mut item_iter);
-					substituted_mod = !stream.is_empty();
-					result.extend(stream);
+					if !substituted_mod
+					{
+						let stream = try_substitute_mod(
+							&mod_and_postfix_sub,
+							substitutions,
+							index,
+							&mut item_iter,
+						);
+						substituted_mod = !stream.is_empty();
+						duplicate.extend(stream);
+					}
 				}
-			}
 
-			if let Some(stream) = substitute_next_token(&mut item_iter, global_subs, substitutions)?
+				if let Some(stream) = substitute_next_token(&mut item_iter, ctx)?
+				{
+					output_tokens += count_tokens(stream.clone());
+					duplicate.extend(stream);
+				}
+				else
+				{
+					break;
+				}
+			}
+			if let Some((predicate, _)) = &options.maybe_async
 			{
-				result.extend(stream);
+				// The second duplicate is the sync one
+				let sync = index == 1;
+				if sync
+				{
+					duplicate = strip_async(duplicate);
+				}
+				if let Some(predicate) = predicate
+				{
+					duplicate = cfg_gate(duplicate, predicate, sync);
+				}
 			}
-			else
+			if let Some((key, ident)) = &options.auto_cfg
 			{
-				break;
+				let predicate = auto_cfg_predicate(key, ident, substitutions, global_subs)?;
+				duplicate = cfg_gate(duplicate, &(predicate, ident.span()), false);
 			}
-		}
-		Ok(())
-	};
+			#[cfg(feature = "validate")]
+			if options.validate
+			{
+				// Without a substitution group, only the global substitutions were used
+				let (substitutions, index) = if std::ptr::eq(substitutions, empty_sub)
+				{
+					(global_subs, None)
+				}
+				else
+				{
+					(substitutions, Some(index))
+				};
+				validate_duplicate(&strip_none_groups(duplicate.clone()), substitutions, index)?;
+			}
+			if options.unique_names
+			{
+				for name in top_level_names(strip_none_groups(duplicate.clone()))
+				{
+					if let Some(first) = defined_names.insert(name.to_string(), index)
+					{
+						return Err(Error::new(format!(
+							"The duplicates of substitution groups {} and {} both define '{}'.",
+							first + 1,
+							index + 1,
+							name
+						))
+						.span(name.span())
+						.hint(crate::pretty_errors::UNIQUE_NAMES));
+					}
+				}
+			}
+			result.extend(duplicate);
+			// Checked after each duplicate, to stop blowups early
+			check_output_tokens(output_tokens, max_tokens)
+		};
 
-	// We always want at least 1 duplicate.
-	// If no groups are given, we just want to run the global substitutions
-	let empty_sub = SubstitutionGroup::new();
-	duplicate_and_substitute_one(sub_groups.next().unwrap_or(&empty_sub))?;
+	if in_place
+	{
+		// Only the marked fragments are duplicated, which happens during
+		// substitution. The rest of the item only gets the global substitutions.
+		duplicate_and_substitute_one(empty_sub, 0)?;
+	}
+	else if let Some(substitutions) = sub_groups.next()
+	{
+		duplicate_and_substitute_one(substitutions, 0)?;
 
-	for substitutions in sub_groups
+		for (index, substitutions) in sub_groups.enumerate()
+		{
+			duplicate_and_substitute_one(substitutions, index + 1)?;
+		}
+	}
+	else if !options.allow_empty
 	{
-		duplicate_and_substitute_one(&substitutions)?;
+		// Unless explicitly allowed, we always want at least 1 duplicate.
+		// If no groups are given, we just want to run the global substitutions
+		duplicate_and_substitute_one(empty_sub, 0)?;
 	}
 
-	Ok(result)
+	Ok(strip_none_groups(result))
 }
 
-/// Recursively checks the given token for any use of the given substitution
-/// identifiers and substitutes them, returning the resulting token stream.
-fn substitute_next_token<'a, T: SubGroupIter<'a>>(
-	tree: &mut TokenIter<'a, T>,
-	global_subs: &SubstitutionGroup,
-	substitutions: &SubstitutionGroup,
-) -> Result<Option<TokenStream>>
+/// The names of the items defined by the given tokens outside of other items,
+/// found by the keyword before them (e.g. `fn` or `struct`), without parsing
+/// the items.
+///
+/// Items with a `cfg` attribute are skipped, as they might not be compiled.
+/// Keywords in lifetimes (e.g. `'static`) or between `<` and `>` (e.g. `const`
+/// generics) don't start items.
+fn top_level_names(stream: TokenStream) -> Vec<Ident>
 {
-	let mut result = None;
-	match tree.next_fallible()?
+	let tokens: Vec<_> = stream.into_iter().collect();
+	let mut names = Vec::new();
+	let mut cfg_gated = false;
+	// Shifts and comparisons in expressions may unbalance the brackets, so items
+	// ending resets the depth
+	let mut angle_depth = 0usize;
+	for (i, t) in tokens.iter().enumerate()
 	{
-		Some(Token::Simple(TokenTree::Ident(ident))) =>
+		let after_joint = |c: char| {
+			i > 0
+				&& matches!(&tokens[i - 1], TokenTree::Punct(p)
+					if p.as_char() == c && p.spacing() == Spacing::Joint)
+		};
+		match t
 		{
-			match (
-				substitutions.substitution_of(&ident.to_string()),
-				global_subs.substitution_of(&ident.to_string()),
-			)
+			TokenTree::Punct(p) if p.as_char() == '<' => angle_depth += 1,
+			// Not the '>' of '->' or '=>'
+			TokenTree::Punct(p) if p.as_char() == '>' && !after_joint('-') && !after_joint('=') =>
 			{
-				(Some(subst), None) | (None, Some(subst)) =>
+				angle_depth = angle_depth.saturating_sub(1)
+			},
+			TokenTree::Ident(_) if angle_depth > 0 || after_joint('\'') => (),
+			TokenTree::Group(g) if g.delimiter() == Delimiter::Bracket =>
+			{
+				let after_hash = i > 0 && is_punct(&tokens[i - 1], '#');
+				let cfg = g
+					.stream()
+					.into_iter()
+					.next()
+					.map_or(false, |t| is_ident(&t, Some("cfg")));
+				cfg_gated |= after_hash && cfg;
+			},
+			// Ends an item without a name, e.g. an 'impl' block
+			TokenTree::Group(g) if g.delimiter() == Delimiter::Brace =>
+			{
+				cfg_gated = false;
+				angle_depth = 0;
+			},
+			TokenTree::Punct(p) if p.as_char() == ';' =>
+			{
+				cfg_gated = false;
+				angle_depth = 0;
+			},
+			TokenTree::Ident(keyword) =>
+			{
+				let name = match (keyword.to_string().as_str(), tokens.get(i + 1))
 				{
-					let stream = if subst.arg_count > 0
+					("static", Some(m)) if is_ident(m, Some("mut")) => tokens.get(i + 2),
+					(
+						"fn" | "struct" | "enum" | "union" | "trait" | "type" | "const" | "static"
+						| "mod",
+						name,
+					) => name,
+					_ => continue,
+				};
+				match name
+				{
+					Some(TokenTree::Ident(name))
+						if !["fn", "unsafe", "async", "extern", "_"]
+							.contains(&name.to_string().as_str()) =>
 					{
-						let (mut group_iter, span) =
-							tree.next_group(Some(Delimiter::Parenthesis))?;
-						let mut args = Vec::new();
-						loop
+						if !cfg_gated
 						{
-							match group_iter.next_group(Some(Delimiter::Bracket))
-							{
-								Ok((group, _)) =>
-								{
-									args.push(duplicate_and_substitute(
-										group.to_token_stream(),
-										global_subs,
-										Some(substitutions).into_iter(),
-									)?);
-									if group_iter.has_next()?
-									{
-										group_iter.expect_comma()?;
-									}
-								},
-								Err(err) =>
-								{
-									if group_iter.has_next()?
-									{
-										return Err(
-											err.hint(crate::pretty_errors::BRACKET_SUB_PARAM)
-										);
-									}
-									else
-									{
-										break;
-									}
-								},
-							}
+							names.push(name.clone());
 						}
-						subst.apply(&args, span)?
-					}
-					else
-					{
-						subst.apply_simple(ident.span())?
-					};
-					result
-						.get_or_insert_with(|| TokenStream::new())
-						.extend(stream.into_iter());
-				},
-				(None, None) =>
+						cfg_gated = false;
+					},
+					_ => (),
+				}
+			},
+			_ => (),
+		}
+	}
+	names
+}
+
+/// Extends the running value of each accumulator (`acc name += [..];`) by its
+/// fragment substituted with the given substitution group, returning a copy of
+/// the group that also substitutes each accumulator by its new value.
+fn accumulate(
+	substitutions: &SubstitutionGroup,
+	index: usize,
+	accumulated: &mut [TokenStream],
+	options: &InvocationOptions,
+	global_subs: &SubstitutionGroup,
+	lowercase_identifiers: &HashMap<String, String>,
+) -> Result<SubstitutionGroup>
+{
+	let ctx = SubstitutionContext {
+		options,
+		global_subs,
+		substitutions,
+		index,
+		item_name: None,
+		in_attribute: false,
+		lowercase_identifiers,
+		resubstituting: None,
+		shadowed: None,
+	};
+	let mut group = substitutions.clone();
+	for ((name, fragment), value) in options.accumulators.iter().zip(accumulated.iter_mut())
+	{
+		let mut iter = TokenIter::new(
+			fragment.clone(),
+			global_subs,
+			Some(substitutions).into_iter(),
+		)
+		.with_nested_names(options);
+		while let Some(stream) = substitute_next_token(&mut iter, ctx)?
+		{
+			value.extend(stream);
+		}
+		group.add_substitution(name.clone(), Substitution::new_simple(value.clone()))?;
+	}
+	Ok(group)
+}
+
+/// Makes the sync version of the given duplicate (`maybe_async`) by removing
+/// `async` (and the `move` of `async move {..}` blocks), `.await`, and
+/// `impl Future<Output = T>`, leaving `T`.
+fn strip_async(stream: TokenStream) -> TokenStream
+{
+	let tokens: Vec<_> = stream.into_iter().collect();
+	let mut result = TokenStream::new();
+	let mut i = 0;
+	while i < tokens.len()
+	{
+		let next = tokens.get(i + 1);
+		match &tokens[i]
+		{
+			t if is_ident(t, Some("async")) =>
+			{
+				let block = matches!(tokens.get(i + 2),
+					Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace);
+				if next.map_or(false, |t| is_ident(t, Some("move"))) && block
 				{
-					result
-						.get_or_insert_with(|| TokenStream::new())
-						.extend(TokenStream::from(TokenTree::Ident(ident)).into_iter());
-				},
-				_ =>
+					i += 1;
+				}
+			},
+			t if is_punct(t, '.') && next.map_or(false, |t| is_ident(t, Some("await"))) =>
+			{
+				i += 1;
+			},
+			t if is_ident(t, Some("impl")) =>
+			{
+				match future_output(&tokens[i + 1..])
 				{
-					return Err(
-						Error::new("Multiple substitutions for identifier").span(ident.span())
-					)
-				},
-			}
+					Some((output, length)) =>
+					{
+						result.extend(strip_async(output));
+						i += length;
+					},
+					None => result.extend(Some(t.clone())),
+				}
+			},
+			TokenTree::Group(group) =>
+			{
+				result.extend(Some(TokenTree::Group(new_group(
+					group.delimiter(),
+					strip_async(group.stream()),
+					group.span(),
+				))))
+			},
+			t => result.extend(Some(t.clone())),
+		}
+		i += 1;
+	}
+	result
+}
+
+/// If the given tokens start with `Future<Output = T>` (with any path to
+/// `Future`), optionally followed by bounds (e.g. `+ Send + 'a`), returns `T`
+/// and the number of tokens up to and including the last bound.
+fn future_output(tokens: &[TokenTree]) -> Option<(TokenStream, usize)>
+{
+	// The path to 'Future'
+	let mut i = tokens
+		.iter()
+		.position(|t| !(is_char(t, ':') || matches!(t, TokenTree::Ident(_))))?;
+	if i == 0 || !is_ident(&tokens[i - 1], Some("Future")) || !is_char(&tokens[i], '<')
+	{
+		return None;
+	}
+	if !(is_ident(tokens.get(i + 1)?, Some("Output")) && is_char(tokens.get(i + 2)?, '='))
+	{
+		return None;
+	}
+	let start = i + 3;
+	i = start + generics_length(&tokens[start..])?;
+	let output = tokens[start..i].iter().cloned().collect();
+	i += 1;
+
+	// Bounds
+	while tokens.get(i).map_or(false, |t| is_char(t, '+'))
+	{
+		i += 1;
+		if tokens.get(i).map_or(false, |t| is_char(t, '\''))
+		{
+			i += 2;
+			continue;
+		}
+		while tokens.get(i).map_or(false, |t| {
+			is_char(t, ':') || matches!(t, TokenTree::Ident(_))
+		})
+		{
+			i += 1;
+		}
+		if tokens.get(i).map_or(false, |t| is_char(t, '<'))
+		{
+			i += 1;
+			i += generics_length(&tokens[i..])? + 1;
+		}
+	}
+	Some((output, i))
+}
+
+/// Whether the token is the given punctuation, regardless of its spacing.
+fn is_char(t: &TokenTree, c: char) -> bool
+{
+	matches!(t, TokenTree::Punct(p) if p.as_char() == c)
+}
+
+/// Returns the number of the given tokens before the `>` closing generic
+/// arguments whose `<` precedes them.
+fn generics_length(tokens: &[TokenTree]) -> Option<usize>
+{
+	let mut depth = 0;
+	for (i, t) in tokens.iter().enumerate()
+	{
+		match t
+		{
+			TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+			// Not the '>' of '->'
+			TokenTree::Punct(p)
+				if p.as_char() == '>'
+					&& !(i > 0
+						&& matches!(&tokens[i-1], TokenTree::Punct(arrow)
+							if arrow.as_char() == '-' && arrow.spacing() == Spacing::Joint)) =>
+			{
+				if depth == 0
+				{
+					return Some(i);
+				}
+				depth -= 1;
+			},
+			_ => (),
+		}
+	}
+	None
+}
+
+/// Enables the given duplicate only if the given configuration predicate holds
+/// or, if `negate`, doesn't hold (`maybe_async`), by giving each of its items
+/// (or the whole duplicate if it can't be split into items) a `cfg` attribute.
+fn cfg_gate(
+	stream: TokenStream,
+	(predicate, span): &(TokenStream, Span),
+	negate: bool,
+) -> TokenStream
+{
+	let predicate = if negate
+	{
+		let mut not = TokenStream::from(TokenTree::Ident(Ident::new("not", *span)));
+		not.extend(Some(TokenTree::Group(new_group(
+			Delimiter::Parenthesis,
+			predicate.clone(),
+			*span,
+		))));
+		not
+	}
+	else
+	{
+		predicate.clone()
+	};
+	let mut attr = TokenStream::from(TokenTree::Ident(Ident::new("cfg", *span)));
+	attr.extend(Some(TokenTree::Group(new_group(
+		Delimiter::Parenthesis,
+		predicate,
+		*span,
+	))));
+	let mut hash = Punct::new('#', Spacing::Alone);
+	hash.set_span(*span);
+	let mut attr_tokens = TokenStream::from(TokenTree::Punct(hash));
+	attr_tokens.extend(Some(TokenTree::Group(new_group(
+		Delimiter::Bracket,
+		attr,
+		*span,
+	))));
+
+	let items = split_items(&stream)
+		.filter(|items| !items.is_empty())
+		.unwrap_or_else(|| vec![stream]);
+	let mut result = TokenStream::new();
+	for item in items
+	{
+		result.extend(attr_tokens.clone());
+		result.extend(item);
+	}
+	result
+}
+
+/// Returns the configuration predicate gating the duplicate using the given
+/// substitution group (`auto_cfg`), i.e. the given key, `=`, and the
+/// substitution of the given identifier as a string literal.
+///
+/// Returns an error if the substitution isn't a single identifier or literal.
+fn auto_cfg_predicate(
+	key: &Ident,
+	ident: &Ident,
+	substitutions: &SubstitutionGroup,
+	global_subs: &SubstitutionGroup,
+) -> Result<TokenStream>
+{
+	let name = ident.to_string();
+	let substitution = substitutions
+		.substitution_of(&name)
+		.or_else(|| global_subs.substitution_of(&name))
+		.filter(|substitution| substitution.argument_count() == 0)
+		.ok_or_else(|| {
+			Error::new(format!(
+				"'auto_cfg' requires '{}' to be substituted without arguments.",
+				name
+			))
+			.span(ident.span())
+			.hint(crate::pretty_errors::AUTO_CFG)
+		})?;
+	let stream = strip_none_groups(substitution.apply_simple(ident.span())?);
+	let mut tokens = stream.clone().into_iter();
+	let value = match (tokens.next(), tokens.next())
+	{
+		(Some(TokenTree::Ident(value)), None) => value.to_string(),
+		(Some(TokenTree::Literal(value)), None) =>
+		{
+			crate::token_iter::string_value(&value).unwrap_or_else(|| value.to_string())
 		},
-		Some(Token::Group(del, mut group_iter, span)) =>
+		(first, _) =>
 		{
-			let mut substituted = TokenStream::new();
-			while let Some(stream) =
-				substitute_next_token(&mut group_iter, global_subs, substitutions)?
+			return Err(Error::new(format!(
+				"'auto_cfg' requires the substitution of '{}' to be a single identifier or \
+				 literal, found '{}'.",
+				name, stream
+			))
+			.span(first.map_or(ident.span(), |t| t.span()))
+			.hint(crate::pretty_errors::AUTO_CFG))
+		},
+	};
+	let mut value = Literal::string(&value);
+	value.set_span(ident.span());
+	let mut eq = Punct::new('=', Spacing::Alone);
+	eq.set_span(ident.span());
+	Ok(TokenStream::from_iter([
+		TokenTree::Ident(key.clone()),
+		TokenTree::Punct(eq),
+		TokenTree::Literal(value),
+	]))
+}
+
+/// Finds the name of the given item in the duplicate using the given context
+/// (`__item__`), i.e. the identifier following the keyword of the item's kind,
+/// once substituted or disambiguated like the rest of the duplicate.
+///
+/// Returns None if the item isn't a function, struct, enum, union, trait,
+/// type alias, constant, static, module, or `macro_rules!` declaration, or if
+/// its name isn't substituted by a single identifier.
+fn item_name<'a, T: SubGroupIter<'a>>(
+	mut iter: TokenIter<'a, T>,
+	ctx: SubstitutionContext,
+	#[allow(unused_variables)] mod_and_postfix_sub: &Option<(Ident, Postfix)>,
+) -> Result<Option<Ident>>
+{
+	iter.skip_attributes()?;
+	// Skip the visibility and qualifiers until the keyword of the item's kind
+	let kind = loop
+	{
+		let ident = match iter.next_fallible()?
+		{
+			Some(Token::Simple(TokenTree::Ident(ident))) => ident.to_string(),
+			_ => return Ok(None),
+		};
+		match ident.as_str()
+		{
+			"pub" =>
 			{
-				substituted.extend(stream)
-			}
-			result.get_or_insert_with(|| TokenStream::new()).extend(
-				TokenStream::from(TokenTree::Group(new_group(del, substituted, span))).into_iter(),
-			);
+				if let Some(Token::Group(Delimiter::Parenthesis, _, _)) = iter.peek()?
+				{
+					iter.next_fallible()?;
+				}
+			},
+			"extern" =>
+			{
+				if let Some(Token::Simple(TokenTree::Literal(_))) = iter.peek()?
+				{
+					iter.next_fallible()?;
+				}
+			},
+			// Unless it qualifies a function
+			"const"
+				if !matches!(iter.peek()?, Some(Token::Simple(t))
+					if ["fn", "async", "unsafe", "extern"].iter().any(|q| is_ident(t, Some(q)))) =>
+			{
+				break ident
+			},
+			"fn" | "struct" | "enum" | "union" | "trait" | "type" | "static" | "mod"
+			| "macro_rules" => break ident,
+			_ => (),
+		}
+	};
+	match kind.as_str()
+	{
+		"static" =>
+		{
+			let _ = iter.extract_simple(|t| is_ident(t, Some("mut")), |t| t, None);
 		},
-		Some(token) =>
+		"macro_rules" if iter.expect_simple(|t| is_punct(t, '!'), None).is_err() =>
 		{
-			result
-				.get_or_insert_with(|| TokenStream::new())
-				.extend(Some(TokenTree::from(token)).into_iter())
+			return Ok(None)
 		},
 		_ => (),
 	}
+
+	#[cfg(feature = "module_disambiguation")]
+	if let Some((mod_name, postfix)) = mod_and_postfix_sub
+	{
+		if kind == "mod" || kind == "macro_rules"
+		{
+			return Ok(Some(Ident::new(
+				&disambiguated_name(mod_name, postfix, ctx.substitutions, ctx.index),
+				mod_name.span(),
+			)));
+		}
+	}
+
+	let name = substitute_next_token(&mut iter, ctx)?.map(strip_none_groups);
+	let mut name = name.into_iter().flatten();
+	match (name.next(), name.next())
+	{
+		(Some(TokenTree::Ident(ident)), None) => Ok(Some(ident)),
+		_ => Ok(None),
+	}
+}
+
+/// Whether the given stream contains the given identifier, including within
+/// groups.
+fn contains_ident(stream: &TokenStream, name: &str) -> bool
+{
+	stream.clone().into_iter().any(|t| {
+		match &t
+		{
+			TokenTree::Group(g) => contains_ident(&g.stream(), name),
+			t => is_ident(t, Some(name)),
+		}
+	})
+}
+
+/// Splits the given stream into its top-level items.
+///
+/// This is a shallow scan: An item ends with a top-level `;` or with a
+/// top-level `{...}` group not followed by punctuation (other than `;`, which
+/// is included in the item, and the `#` of a following attribute).
+/// Returns None if the stream doesn't end at an item boundary.
+fn split_items(stream: &TokenStream) -> Option<Vec<TokenStream>>
+{
+	let mut items = Vec::new();
+	let mut current = TokenStream::new();
+	let mut iter = stream.clone().into_iter().peekable();
+	while let Some(t) = iter.next()
+	{
+		let ends_item = match &t
+		{
+			TokenTree::Punct(p) => p.as_char() == ';',
+			TokenTree::Group(g) if g.delimiter() == Delimiter::Brace =>
+			{
+				match iter.peek()
+				{
+					Some(TokenTree::Punct(p)) => p.as_char() == '#',
+					_ => true,
+				}
+			},
+			_ => false,
+		};
+		current.extend(Some(t));
+		if ends_item
+		{
+			items.push(std::mem::replace(&mut current, TokenStream::new()));
+		}
+	}
+	if current.is_empty()
+	{
+		Some(items)
+	}
+	else
+	{
+		None
+	}
+}
+
+/// Replaces any None-delimited group in the given stream with its contents,
+/// recursively.
+///
+/// Nested invocations are spliced back into the stream as None-delimited groups
+/// and such groups may also arrive from declarative macros. Since the compiler
+/// and downstream macros don't accept them in all positions (e.g. patterns),
+/// the final output must not contain any.
+fn strip_none_groups(stream: TokenStream) -> TokenStream
+{
+	let mut result = TokenStream::new();
+	for tree in stream
+	{
+		match tree
+		{
+			TokenTree::Group(group) if group.delimiter() == Delimiter::None =>
+			{
+				result.extend(strip_none_groups(group.stream()))
+			},
+			TokenTree::Group(group) =>
+			{
+				result.extend(Some(TokenTree::Group(new_group(
+					group.delimiter(),
+					strip_none_groups(group.stream()),
+					group.span(),
+				))))
+			},
+			tree => result.extend(Some(tree)),
+		}
+	}
+	result
+}
+
+/// Substitutes the given output of the substitution with the given name (i.e.
+/// its substitution identifier or pattern) again, so any substitution
+/// identifiers in it are also substituted (given the `recursive` option).
+///
+/// The span is used for errors.
+fn resubstitute(
+	stream: TokenStream,
+	name: String,
+	span: Span,
+	ctx: SubstitutionContext,
+) -> Result<TokenStream>
+{
+	let mut outer = ctx.resubstituting;
+	while let Some(resub) = outer
+	{
+		if resub.ident == name
+		{
+			return Err(Error::new(format!(
+				"'{}' is used in the output of its own substitution.",
+				name
+			))
+			.span(span)
+			.hint(crate::pretty_errors::RECURSIVE));
+		}
+		outer = resub.outer;
+	}
+	let depth = ctx.resubstituting.map_or(1, |resub| resub.depth + 1);
+	if depth > MAX_RECURSION_DEPTH
+	{
+		return Err(Error::new(format!(
+			"Substitutions are substituted within each other more than {} levels deep.",
+			MAX_RECURSION_DEPTH
+		))
+		.span(span)
+		.hint(crate::pretty_errors::RECURSIVE));
+	}
+	let resub = Resubstitution {
+		ident: &name,
+		depth,
+		outer: ctx.resubstituting,
+	};
+	let ctx = SubstitutionContext {
+		resubstituting: Some(&resub),
+		..ctx
+	};
+	let mut iter = TokenIter::new(stream, ctx.global_subs, Some(ctx.substitutions).into_iter())
+		.with_nested_names(ctx.options);
+	let mut result = TokenStream::new();
+	while let Some(stream) = substitute_next_token(&mut iter, ctx)?
+	{
+		result.extend(stream);
+	}
 	Ok(result)
 }
+
+/// If the next tokens match any pattern of the substitution group or global
+/// substitutions, consumes the longest match and returns the substitution of
+/// its pattern.
+fn substitute_pattern<'a, T: SubGroupIter<'a>>(
+	tree: &mut TokenIter<'a, T>,
+	ctx: SubstitutionContext,
+) -> Result<Option<TokenStream>>
+{
+	let patterns: Vec<_> = ctx
+		.substitutions
+		.patterns()
+		.chain(ctx.global_subs.patterns())
+		.collect();
+	let mut tokens = Vec::new();
+	while patterns
+		.iter()
+		.any(|pattern| pattern.is_continued_by(&tokens))
+	{
+		match tree.next_fallible()?
+		{
+			Some(Token::Simple(token)) => tokens.push(token),
+			Some(token) =>
+			{
+				tree.push_front(token);
+				break;
+			},
+			None => break,
+		}
+	}
+	let matched = patterns
+		.into_iter()
+		.filter(|pattern| pattern.is_matched_by(&tokens))
+		.max_by_key(|pattern| pattern.tokens.len());
+	let matched_len = matched.map_or(0, |pattern| pattern.tokens.len());
+	for token in tokens.drain(matched_len..).rev()
+	{
+		tree.push_front(Token::Simple(token));
+	}
+
+	if let Some(pattern) = matched
+	{
+		let key = pattern.key();
+		let span = tokens[0].span();
+		let stream = match (
+			ctx.substitutions.substitution_of(&key),
+			ctx.global_subs.substitution_of(&key),
+		)
+		{
+			(Some(subst), None) | (None, Some(subst)) => subst.apply_simple(span)?,
+			_ => return Err(Error::new("Multiple substitutions for pattern").span(span)),
+		};
+		if ctx.options.recursive
+		{
+			resubstitute(stream, key, span, ctx).map(Some)
+		}
+		else
+		{
+			Ok(Some(stream))
+		}
+	}
+	else
+	{
+		Ok(None)
+	}
+}
+
+/// Recursively checks the given token for any use of the given substitution
+/// identifiers and substitutes them, returning the resulting token stream.
+fn substitute_next_token<'a, T: SubGroupIter<'a>>(
+	tree: &mut TokenIter<'a, T>,
+	ctx: SubstitutionContext,
+) -> Result<Option<TokenStream>>
+{
+	if let Some(stream) = substitute_pattern(tree, ctx)?
+	{
+		return Ok(Some(stream));
+	}
+	let mut result = None;
+	match tree.next_fallible()?
+	{
+		Some(Token::Simple(TokenTree::Ident(ident)))
+			if ctx.options.protect_inner && next_is_inner_macro(&ident, tree) =>
+		{
+			let result = result.get_or_insert_with(TokenStream::new);
+			result.extend(Some(TokenTree::Ident(ident)));
+			result.extend(Some(TokenTree::from(tree.next_fallible()?.unwrap())));
+			if let Some(Token::Group(del, mut body, span)) = tree.next_fallible()?
+			{
+				let (header, header_span) = body.next_group(Some(Delimiter::Bracket))?;
+				let header = header.to_token_stream();
+				let shadowed = inner_identifiers(header.clone(), true, ctx);
+				let mut substituted = TokenStream::from(TokenTree::Group(new_group(
+					Delimiter::Bracket,
+					header,
+					header_span,
+				)));
+				let ctx = SubstitutionContext {
+					shadowed: Some(&shadowed),
+					..ctx
+				};
+				while let Some(stream) = substitute_next_token(&mut body, ctx)?
+				{
+					substituted.extend(stream);
+				}
+				result.extend(Some(TokenTree::Group(new_group(del, substituted, span))));
+			}
+		},
+		Some(Token::Simple(TokenTree::Ident(ident))) =>
+		{
+			let shadowed = ctx.is_shadowed(&ident);
+			match (
+				ctx.substitutions
+					.substitution_of(&ident.to_string())
+					.filter(|_| !shadowed),
+				ctx.global_subs
+					.substitution_of(&ident.to_string())
+					.filter(|_| !shadowed),
+			)
+			{
+				(Some(subst), None) | (None, Some(subst)) =>
+				{
+					let stream = if subst.arg_count > 0
+					{
+						let (mut group_iter, span) =
+							tree.next_group(Some(Delimiter::Parenthesis))?;
+						let mut args = Vec::new();
+						loop
+						{
+							match group_iter.next_substitution()
+							{
+								Ok((group, arg_span)) =>
+								{
+									let group = group.to_token_stream();
+									check_empty_substitution(
+										ctx.options,
+										&group,
+										arg_span,
+										"argument",
+									)?;
+									// Arguments are substituted with only the current group,
+									// including any nested invocations in them
+									let mut arg_iter = TokenIter::new(
+										group,
+										ctx.global_subs,
+										Some(ctx.substitutions).into_iter(),
+									)
+									.with_nested_names(ctx.options);
+									// Arguments the substitution doesn't use are dropped without
+									// substituting them, so they may e.g. use optional
+									// substitution identifiers this group doesn't define
+									let mut arg = TokenStream::new();
+									if subst.uses_argument(args.len())
+									{
+										while let Some(stream) =
+											substitute_next_token(&mut arg_iter, ctx)?
+										{
+											arg.extend(stream);
+										}
+									}
+									args.push(arg);
+									if group_iter.has_next()?
+									{
+										group_iter.expect_comma()?;
+									}
+								},
+								Err(err) =>
+								{
+									if group_iter.has_next()?
+									{
+										return Err(
+											err.hint(crate::pretty_errors::BRACKET_SUB_PARAM)
+										);
+									}
+									else
+									{
+										break;
+									}
+								},
+							}
+						}
+						subst.apply(&args, span)?
+					}
+					else
+					{
+						subst.apply_simple(ident.span())?
+					};
+					// Substituting a kept identifier again would never end
+					let stream = if ctx.options.recursive && !subst.keeps_identifier()
+					{
+						resubstitute(stream, ident.to_string(), ident.span(), ctx)?
+					}
+					else
+					{
+						stream
+					};
+					result
+						.get_or_insert_with(|| TokenStream::new())
+						.extend(stream.into_iter());
+				},
+				(None, None) if ctx.substitutions.is_absent(&ident.to_string()) =>
+				{
+					return Err(Error::new(format!(
+						"Optional substitution identifier '{}' used in a duplicate whose \
+						 substitution group doesn't define it.",
+						ident
+					))
+					.span(ident.span())
+					.hint(crate::pretty_errors::OPTIONAL_ABSENT));
+				},
+				(None, None) if ident.to_string() == HASH_NAME =>
+				{
+					result
+						.get_or_insert_with(TokenStream::new)
+						.extend(Some(TokenTree::Ident(Ident::new(
+							&hash_of(ctx.substitutions),
+							ident.span(),
+						))));
+				},
+				(None, None) if ident.to_string() == ITEM_NAME =>
+				{
+					let name = ctx.item_name.ok_or_else(|| {
+						Error::new("Couldn't find the name of the duplicated item for '__item__'.")
+							.span(ident.span())
+							.hint(crate::pretty_errors::ITEM_NAME)
+					})?;
+					// Cloned, as raw identifiers (e.g. 'r#match') can't be created from text
+					let mut name = name.clone();
+					name.set_span(ident.span());
+					result
+						.get_or_insert_with(TokenStream::new)
+						.extend(Some(TokenTree::Ident(name)));
+				},
+				(None, None) if ident.to_string() == INDEX_NAME =>
+				{
+					let mut index = Literal::usize_unsuffixed(ctx.index);
+					index.set_span(ident.span());
+					result
+						.get_or_insert_with(TokenStream::new)
+						.extend(Some(TokenTree::Literal(index)));
+				},
+				(None, None) if ident.to_string() == "pub" =>
+				{
+					// A visibility placeholder, 'pub(in vis)', is replaced entirely
+					let result = result.get_or_insert_with(TokenStream::new);
+					if let Some(subst) = visibility_placeholder(tree, ctx)?
+					{
+						let (_, span) = tree.next_group(Some(Delimiter::Parenthesis))?;
+						result.extend(subst.apply_simple(span)?);
+					}
+					else
+					{
+						result.extend(Some(TokenTree::Ident(ident)));
+					}
+				},
+				(None, None)
+					if ident.to_string() == CONCAT_NAME
+						&& matches!(
+							tree.peek()?,
+							Some(Token::Group(Delimiter::Parenthesis, _, _))
+						) =>
+				{
+					let (group_iter, _) = tree.next_group(Some(Delimiter::Parenthesis))?;
+					result
+						.get_or_insert_with(TokenStream::new)
+						.extend(Some(TokenTree::Ident(concat_identifier(
+							group_iter,
+							ident.span(),
+							ctx,
+						)?)));
+				},
+				(None, None)
+					if ident.to_string() == CONCAT_STR_NAME
+						&& matches!(
+							tree.peek()?,
+							Some(Token::Group(Delimiter::Parenthesis, _, _))
+						) =>
+				{
+					let (group_iter, _) = tree.next_group(Some(Delimiter::Parenthesis))?;
+					result
+						.get_or_insert_with(TokenStream::new)
+						.extend(Some(TokenTree::Literal(concat_string(
+							group_iter,
+							ident.span(),
+							ctx,
+						)?)));
+				},
+				(None, None)
+					if ident.to_string() == FOR_EACH_NAME
+						&& matches!(
+							tree.peek()?,
+							Some(Token::Group(Delimiter::Parenthesis, _, _))
+						) =>
+				{
+					let (group_iter, _) = tree.next_group(Some(Delimiter::Parenthesis))?;
+					let duplicates = for_each(group_iter, tree, ident.span(), ctx)?;
+					result
+						.get_or_insert_with(TokenStream::new)
+						.extend(duplicates);
+				},
+				(None, None)
+					if (ident.to_string() == FIRST_NAME || ident.to_string() == LAST_NAME)
+						&& matches!(
+							tree.peek()?,
+							Some(Token::Group(Delimiter::Parenthesis, _, _))
+						) =>
+				{
+					let (group_iter, _) = tree.next_group(Some(Delimiter::Parenthesis))?;
+					let last = ident.to_string() == LAST_NAME;
+					let stream = first_or_last(group_iter, tree, last, ctx)?;
+					result.get_or_insert_with(TokenStream::new).extend(stream);
+				},
+				(None, None)
+					if ident.to_string() == ALL_NAME
+						&& matches!(
+							tree.peek()?,
+							Some(Token::Group(Delimiter::Parenthesis, _, _))
+						) =>
+				{
+					let (group_iter, _) = tree.next_group(Some(Delimiter::Parenthesis))?;
+					let stream = all_substitutions(group_iter, tree)?;
+					result.get_or_insert_with(TokenStream::new).extend(stream);
+				},
+				(None, None)
+					if ident.to_string() == GROUP_SUB_NAME
+						&& matches!(
+							tree.peek()?,
+							Some(Token::Group(Delimiter::Parenthesis, _, _))
+						) =>
+				{
+					let (group_iter, _) = tree.next_group(Some(Delimiter::Parenthesis))?;
+					let stream = group_substitution(group_iter, tree, ctx)?;
+					result.get_or_insert_with(TokenStream::new).extend(stream);
+				},
+				(None, None) =>
+				{
+					if let Some(expected) = ctx
+						.lowercase_identifiers
+						.get(&ident.to_string().to_lowercase())
+					{
+						report(
+							ctx.options,
+							CASE_MISMATCH,
+							Error::new(format!(
+								"Identifier '{}' differs from the substitution identifier '{}' \
+								 only by case.",
+								ident, expected
+							))
+							.span(ident.span())
+							.hint(format!(
+								"Did you mean '{}'?\n{}",
+								expected,
+								crate::pretty_errors::CASE_MISMATCH
+							)),
+						)?;
+					}
+					result
+						.get_or_insert_with(|| TokenStream::new())
+						.extend(TokenStream::from(TokenTree::Ident(ident)).into_iter());
+				},
+				_ =>
+				{
+					return Err(
+						Error::new("Multiple substitutions for identifier").span(ident.span())
+					)
+				},
+			}
+		},
+		Some(Token::Simple(TokenTree::Punct(tick))) if tick.as_char() == '\'' =>
+		{
+			let result = result.get_or_insert_with(TokenStream::new);
+			match tree.peek()?
+			{
+				Some(Token::Simple(TokenTree::Ident(ident))) =>
+				{
+					let ident = ident.clone();
+					match ctx
+						.substitutions
+						.substitution_of(&ident.to_string())
+						.or_else(|| ctx.global_subs.substitution_of(&ident.to_string()))
+						.filter(|_| !ctx.is_shadowed(&ident))
+					{
+						Some(subst) if subst.arg_count == 0 =>
+						{
+							// Lifetime substitution
+							tree.next_fallible()?;
+							let stream = subst.apply_simple(ident.span())?;
+							match stream.clone().into_iter().next()
+							{
+								// The substitution is a whole lifetime or no lifetime
+								Some(TokenTree::Punct(p)) if p.as_char() == '\'' => (),
+								None => (),
+								_ => result.extend(Some(TokenTree::Punct(tick))),
+							}
+							result.extend(stream);
+						},
+						_ => result.extend(Some(TokenTree::Punct(tick))),
+					}
+				},
+				_ => result.extend(Some(TokenTree::Punct(tick))),
+			}
+		},
+		Some(Token::Simple(t)) if is_dollar(&t) =>
+		{
+			// Metavariables (identifiers following '$') are never substituted
+			let result = result.get_or_insert_with(TokenStream::new);
+			result.extend(Some(t));
+			if let Some(Token::Simple(TokenTree::Ident(_))) = tree.peek()?
+			{
+				result.extend(Some(TokenTree::from(tree.next_fallible()?.unwrap())));
+			}
+		},
+		Some(Token::Simple(t)) if is_punct(&t, '#') && next_is_marker(tree) =>
+		{
+			tree.next_group(Some(Delimiter::Bracket))?;
+			result
+				.get_or_insert_with(TokenStream::new)
+				.extend(duplicate_in_place(tree, ctx, t.span())?);
+		},
+		Some(Token::Simple(t))
+			if ctx.options.protect_inner && is_punct(&t, '#') && next_is_inner_attribute(tree) =>
+		{
+			// An inner invocation, whose header is output as is and whose
+			// substitution identifiers aren't substituted in the item it applies to
+			let (attr, span) = tree.next_group(Some(Delimiter::Bracket))?;
+			let attr: Vec<_> = attr.to_token_stream().into_iter().collect();
+			let (name, args) = match &attr[..]
+			{
+				[.., TokenTree::Ident(name), TokenTree::Group(args)] => (name, args.stream()),
+				_ => unreachable!(),
+			};
+			let shadowed = inner_identifiers(args, name.to_string() == "duplicate_item", ctx);
+			let result = result.get_or_insert_with(TokenStream::new);
+			result.extend(Some(t));
+			result.extend(Some(TokenTree::Group(new_group(
+				Delimiter::Bracket,
+				attr.into_iter().collect(),
+				span,
+			))));
+
+			// The item ends with its first ';' or '{}' group
+			let ctx = SubstitutionContext {
+				shadowed: Some(&shadowed),
+				..ctx
+			};
+			while let Some(next) = tree.peek()?
+			{
+				let end = match next
+				{
+					Token::Group(Delimiter::Brace, _, _) => true,
+					Token::Simple(t) => is_punct(t, ';'),
+					_ => false,
+				};
+				result.extend(substitute_next_token(tree, ctx)?);
+				if end
+				{
+					break;
+				}
+			}
+		},
+		Some(Token::Simple(t)) if ctx.options.attr_strings && is_punct(&t, '#') =>
+		{
+			// Start of an attribute, whose string literals should be substituted in
+			let result = result.get_or_insert_with(TokenStream::new);
+			result.extend(Some(t));
+			if let Some(Token::Simple(t)) = tree.peek()?
+			{
+				if is_punct(t, '!')
+				{
+					result.extend(Some(TokenTree::from(tree.next_fallible()?.unwrap())));
+				}
+			}
+			if let Some(Token::Group(Delimiter::Bracket, _, _)) = tree.peek()?
+			{
+				let (mut group_iter, span) = tree.next_group(Some(Delimiter::Bracket))?;
+				let mut substituted = TokenStream::new();
+				let ctx = SubstitutionContext {
+					in_attribute: true,
+					..ctx
+				};
+				while let Some(stream) = substitute_next_token(&mut group_iter, ctx)?
+				{
+					substituted.extend(stream)
+				}
+				result.extend(Some(TokenTree::Group(new_group(
+					Delimiter::Bracket,
+					substituted,
+					span,
+				))));
+			}
+		},
+		Some(Token::Simple(TokenTree::Literal(lit))) if ctx.in_attribute =>
+		{
+			result
+				.get_or_insert_with(TokenStream::new)
+				.extend(Some(TokenTree::Literal(substitute_in_string(
+					lit,
+					ctx.global_subs,
+					ctx.substitutions,
+				)?)));
+		},
+		Some(Token::Group(del, mut group_iter, span)) =>
+		{
+			let mut substituted = TokenStream::new();
+			while let Some(stream) = substitute_next_token(&mut group_iter, ctx)?
+			{
+				substituted.extend(stream)
+			}
+			result.get_or_insert_with(|| TokenStream::new()).extend(
+				TokenStream::from(TokenTree::Group(new_group(del, substituted, span))).into_iter(),
+			);
+		},
+		Some(token) =>
+		{
+			result
+				.get_or_insert_with(|| TokenStream::new())
+				.extend(Some(TokenTree::from(token)).into_iter())
+		},
+		_ => (),
+	}
+	Ok(result)
+}
+
+/// If the next token is the group of a visibility placeholder, `pub(in vis)`
+/// where `vis` is a substitution identifier (taking no arguments), returns the
+/// substitution (assuming `pub` has already been consumed).
+fn visibility_placeholder<'a, 'b, T: SubGroupIter<'a>>(
+	tree: &mut TokenIter<'a, T>,
+	ctx: SubstitutionContext<'b>,
+) -> Result<Option<&'b Substitution>>
+{
+	if let Some(Token::Group(Delimiter::Parenthesis, group, _)) = tree.peek()?
+	{
+		let tokens: Vec<_> = group.clone().to_token_stream().into_iter().collect();
+		if let [keyword, TokenTree::Ident(vis)] = &tokens[..]
+		{
+			if is_ident(keyword, Some("in"))
+			{
+				let subst = ctx
+					.substitutions
+					.substitution_of(&vis.to_string())
+					.or_else(|| ctx.global_subs.substitution_of(&vis.to_string()));
+				return Ok(subst.filter(|subst| subst.arg_count == 0));
+			}
+		}
+	}
+	Ok(None)
+}
+
+/// Substitutes the arguments of a `__concat__` and concatenates them into one
+/// identifier.
+///
+/// The arguments are separated by ',' and must each produce only identifiers
+/// and integer literals.
+fn concat_identifier<'a, T: SubGroupIter<'a>>(
+	mut args: TokenIter<'a, T>,
+	span: Span,
+	ctx: SubstitutionContext,
+) -> Result<Ident>
+{
+	let mut substituted = TokenStream::new();
+	while let Some(stream) = substitute_next_token(&mut args, ctx)?
+	{
+		substituted.extend(stream);
+	}
+
+	let mut name = String::new();
+	for t in strip_none_groups(substituted)
+	{
+		match t
+		{
+			TokenTree::Punct(p) if p.as_char() == ',' => (),
+			TokenTree::Ident(ident) => name += &ident.to_string(),
+			TokenTree::Literal(lit) if lit.to_string().chars().all(|c| c.is_ascii_digit()) =>
+			{
+				name += &lit.to_string()
+			},
+			t =>
+			{
+				return Err(Error::new(format!(
+					"Expected only identifiers and integers to concatenate, found '{}'.",
+					t
+				))
+				.span(t.span())
+				.hint(crate::pretty_errors::CONCAT_ARGUMENTS))
+			},
+		}
+	}
+	if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit())
+	{
+		return Err(Error::new(format!(
+			"Concatenation produced '{}', which is not a valid identifier.",
+			name
+		))
+		.span(span)
+		.hint(crate::pretty_errors::CONCAT_ARGUMENTS));
+	}
+	Ok(Ident::new(&name, span))
+}
+
+/// Substitutes the arguments of a `__concat_str__` and concatenates them into
+/// one string literal.
+///
+/// The arguments are separated by ','. An argument producing just a string
+/// literal contributes its contents, while any other argument contributes its
+/// tokens as a string (like `stringify!`), escaped.
+fn concat_string<'a, T: SubGroupIter<'a>>(
+	mut args: TokenIter<'a, T>,
+	span: Span,
+	ctx: SubstitutionContext,
+) -> Result<Literal>
+{
+	let mut text = String::from("\"");
+	while args.has_next()?
+	{
+		let mut argument = TokenStream::new();
+		while match args.peek()?
+		{
+			None => false,
+			Some(Token::Simple(t)) => !is_punct(t, ','),
+			_ => true,
+		}
+		{
+			argument.extend(substitute_next_token(&mut args, ctx)?);
+		}
+		let tokens: Vec<_> = strip_none_groups(argument).into_iter().collect();
+		match &tokens[..]
+		{
+			[TokenTree::Literal(lit)]
+				if lit.to_string().len() >= 2
+					&& lit.to_string().starts_with('"')
+					&& lit.to_string().ends_with('"') =>
+			{
+				// A (non-raw and unsuffixed) string literal, whose escapes are kept
+				let lit = lit.to_string();
+				text += &lit[1..lit.len() - 1];
+			},
+			_ =>
+			{
+				for c in tokens
+					.into_iter()
+					.collect::<TokenStream>()
+					.to_string()
+					.chars()
+				{
+					if c == '"' || c == '\\'
+					{
+						text.push('\\');
+					}
+					text.push(c);
+				}
+			},
+		}
+		if args.has_next()?
+		{
+			args.expect_comma()?;
+		}
+	}
+	text.push('"');
+
+	let mut lit: Literal = text.parse().map_err(|_| {
+		Error::new("Concatenation produced an invalid string.")
+			.span(span)
+			.hint(crate::pretty_errors::CONCAT_STR_ARGUMENTS)
+	})?;
+	lit.set_span(span);
+	Ok(lit)
+}
+
+/// Duplicates the argument of a `__for_each__` once for each substitution
+/// group, substituting each duplicate with its group.
+///
+/// The duplicates are separated by ',', and the last is followed by ',' only
+/// if the argument ends with one.
+fn for_each<'a, T: SubGroupIter<'a>>(
+	mut args: TokenIter<'a, T>,
+	tree: &TokenIter<'a, T>,
+	span: Span,
+	ctx: SubstitutionContext,
+) -> Result<TokenStream>
+{
+	let (fragment, _) = args
+		.next_substitution()
+		.map_err(|err| err.hint(crate::pretty_errors::FOR_EACH))?;
+	if let Some(token) = args.next_fallible()?
+	{
+		return Err(Error::new("Unexpected token.")
+			.span(token.span())
+			.found(token.describe())
+			.hint(crate::pretty_errors::FOR_EACH));
+	}
+	let mut fragment: Vec<_> = fragment.to_token_stream().into_iter().collect();
+	let trailing_comma = fragment.last().map_or(false, |t| is_punct(t, ','));
+	if trailing_comma
+	{
+		fragment.pop();
+	}
+
+	let mut groups = tree.sub_groups().peekable();
+	if groups.peek().is_none() && !ctx.options.allow_empty
+	{
+		return Err(Error::new(format!(
+			"'{}' used without any substitution groups.",
+			FOR_EACH_NAME
+		))
+		.span(span)
+		.hint(crate::pretty_errors::FOR_EACH));
+	}
+
+	let mut result = TokenStream::new();
+	let mut index = 0;
+	while let Some(group) = groups.next()
+	{
+		let mut fragment_iter = TokenIter::new_like(fragment.iter().cloned().collect(), tree);
+		let ctx = SubstitutionContext {
+			substitutions: group,
+			index,
+			..ctx
+		};
+		while let Some(stream) = substitute_next_token(&mut fragment_iter, ctx)?
+		{
+			result.extend(stream);
+		}
+		if trailing_comma || groups.peek().is_some()
+		{
+			result.extend(Some(TokenTree::Punct(Punct::new(',', Spacing::Alone))));
+		}
+		index += 1;
+	}
+	Ok(result)
+}
+
+/// Substitutes the argument of `__first__(..)` or, if `last`, `__last__(..)`
+/// if the duplicate is the first or last one, respectively, otherwise returning
+/// nothing.
+///
+/// Without substitution groups, the only duplicate is both the first and the
+/// last.
+fn first_or_last<'a, T: SubGroupIter<'a>>(
+	mut args: TokenIter<'a, T>,
+	tree: &TokenIter<'a, T>,
+	last: bool,
+	ctx: SubstitutionContext,
+) -> Result<TokenStream>
+{
+	let (fragment, _) = args
+		.next_substitution()
+		.map_err(|err| err.hint(crate::pretty_errors::FIRST_LAST))?;
+	if let Some(token) = args.next_fallible()?
+	{
+		return Err(Error::new("Unexpected token.")
+			.span(token.span())
+			.found(token.describe())
+			.hint(crate::pretty_errors::FIRST_LAST));
+	}
+	let expand = if last
+	{
+		ctx.index + 1 >= tree.sub_groups().count()
+	}
+	else
+	{
+		ctx.index == 0
+	};
+	let mut result = TokenStream::new();
+	if expand
+	{
+		let mut fragment_iter = TokenIter::new_like(fragment.to_token_stream(), tree);
+		while let Some(stream) = substitute_next_token(&mut fragment_iter, ctx)?
+		{
+			result.extend(stream);
+		}
+	}
+	Ok(result)
+}
+
+/// Substitutes the arguments of `__all__(ident)` or `__all__(ident, [sep])`
+/// by the substitutions of `ident` in every substitution group, separated by
+/// `sep`, or ',' if not given.
+///
+/// Substitution groups that don't define an optional `ident` are skipped.
+fn all_substitutions<'a, T: SubGroupIter<'a>>(
+	mut args: TokenIter<'a, T>,
+	tree: &TokenIter<'a, T>,
+) -> Result<TokenStream>
+{
+	let ident = args
+		.extract_identifier(Some("a substitution identifier"))
+		.map_err(|err| err.hint(crate::pretty_errors::ALL))?;
+	let separator = if args.peek()?.is_some()
+	{
+		args.expect_simple(|t| is_punct(t, ','), Some("','"))
+			.map_err(|err| err.hint(crate::pretty_errors::ALL))?;
+		let (separator, _) = args
+			.next_substitution()
+			.map_err(|err| err.hint(crate::pretty_errors::ALL))?;
+		separator.to_token_stream()
+	}
+	else
+	{
+		TokenTree::Punct(Punct::new(',', Spacing::Alone)).into()
+	};
+	if let Some(token) = args.next_fallible()?
+	{
+		return Err(Error::new("Unexpected token.")
+			.span(token.span())
+			.found(token.describe())
+			.hint(crate::pretty_errors::ALL));
+	}
+
+	let name = ident.to_string();
+	if tree
+		.sub_groups()
+		.any(|group| group.substitution_of(&name).is_none() && !group.is_absent(&name))
+	{
+		return Err(Error::new(format!(
+			"'{}' isn't a substitution identifier of all substitution groups.",
+			ident
+		))
+		.span(ident.span())
+		.hint(crate::pretty_errors::ALL));
+	}
+
+	let mut result = TokenStream::new();
+	let mut substitutions = tree
+		.sub_groups()
+		.filter_map(|group| group.substitution_of(&name))
+		.peekable();
+	while let Some(substitution) = substitutions.next()
+	{
+		if substitution.argument_count() > 0
+		{
+			return Err(Error::new(format!(
+				"'{}' takes a substitution identifier without parameters.",
+				ALL_NAME
+			))
+			.span(ident.span())
+			.hint(crate::pretty_errors::ALL));
+		}
+		result.extend(substitution.apply_simple(ident.span())?);
+		if substitutions.peek().is_some()
+		{
+			result.extend(separator.clone());
+		}
+	}
+	Ok(result)
+}
+
+/// Substitutes the arguments of `__group_sub__(ident, index)` by the
+/// substitution of `ident` in the substitution group with the given index.
+///
+/// An index with a sign (e.g. `+1`) is relative to the duplicate's substitution
+/// group and wraps around at either end, so `+1` in the last duplicate gives
+/// the first group. An index without a sign is absolute and must be less than
+/// the number of substitution groups.
+fn group_substitution<'a, T: SubGroupIter<'a>>(
+	mut args: TokenIter<'a, T>,
+	tree: &TokenIter<'a, T>,
+	ctx: SubstitutionContext,
+) -> Result<TokenStream>
+{
+	let hint = |err: Error| err.hint(crate::pretty_errors::GROUP_SUB);
+	let ident = args
+		.extract_identifier(Some("a substitution identifier"))
+		.map_err(hint)?;
+	args.expect_simple(|t| is_punct(t, ','), Some("','"))
+		.map_err(hint)?;
+	let sign = args
+		.extract_simple(|t| is_punct(t, '+') || is_punct(t, '-'), |t| t, None)
+		.ok()
+		.map(|t| is_punct(&t, '-'));
+	let (index, index_span) = args
+		.extract_simple(
+			|t| matches!(t, TokenTree::Literal(_)),
+			|t| (t.to_string().parse::<usize>().ok(), t.span()),
+			Some("an integer index"),
+		)
+		.map_err(hint)?;
+	let index = index.ok_or_else(|| {
+		hint(Error::new("Expected an integer index without suffix.").span(index_span))
+	})?;
+	if let Some(token) = args.next_fallible()?
+	{
+		return Err(hint(
+			Error::new("Unexpected token.")
+				.span(token.span())
+				.found(token.describe()),
+		));
+	}
+
+	let groups: Vec<_> = tree.sub_groups().collect();
+	let target = match sign
+	{
+		_ if groups.is_empty() => return Ok(TokenStream::new()),
+		Some(negative) =>
+		{
+			let offset = index % groups.len();
+			if negative
+			{
+				(ctx.index + groups.len() - offset) % groups.len()
+			}
+			else
+			{
+				(ctx.index + offset) % groups.len()
+			}
+		},
+		None if index < groups.len() => index,
+		None =>
+		{
+			return Err(hint(
+				Error::new(format!(
+					"Substitution group index {} is out of range, as there are {} substitution \
+					 groups.",
+					index,
+					groups.len()
+				))
+				.span(index_span),
+			))
+		},
+	};
+
+	let name = ident.to_string();
+	let substitution = groups[target].substitution_of(&name).ok_or_else(|| {
+		hint(
+			Error::new(format!(
+				"'{}' isn't defined by substitution group {}.",
+				ident, target
+			))
+			.span(ident.span()),
+		)
+	})?;
+	if substitution.argument_count() > 0
+	{
+		return Err(hint(
+			Error::new(format!(
+				"'{}' takes a substitution identifier without parameters.",
+				GROUP_SUB_NAME
+			))
+			.span(ident.span()),
+		));
+	}
+	substitution.apply_simple(ident.span())
+}
+
+/// Whether the given stream is exactly the in-place duplication marker's
+/// attribute path: `duplicate::here`.
+fn is_marker(stream: TokenStream) -> bool
+{
+	let tokens: Vec<_> = stream.into_iter().collect();
+	match &tokens[..]
+	{
+		[duplicate, TokenTree::Punct(colon1), colon2, here] =>
+		{
+			is_ident(duplicate, Some("duplicate"))
+				&& colon1.as_char() == ':'
+				&& colon1.spacing() == Spacing::Joint
+				&& is_punct(colon2, ':')
+				&& is_ident(here, Some("here"))
+		},
+		_ => false,
+	}
+}
+
+/// Whether the next token of the iterator is the bracket group of an in-place
+/// duplication marker (assuming the preceding token was a '#').
+fn next_is_marker<'a, T: SubGroupIter<'a>>(tree: &mut TokenIter<'a, T>) -> bool
+{
+	match tree.peek()
+	{
+		Ok(Some(Token::Group(Delimiter::Bracket, iter, _))) =>
+		{
+			is_marker(iter.clone().to_token_stream())
+		},
+		_ => false,
+	}
+}
+
+/// Whether the next token is an attribute invoking `duplicate_item` or
+/// `substitute_item` (assuming '#' has already been consumed).
+fn next_is_inner_attribute<'a, T: SubGroupIter<'a>>(tree: &mut TokenIter<'a, T>) -> bool
+{
+	match tree.peek()
+	{
+		Ok(Some(Token::Group(Delimiter::Bracket, iter, _))) =>
+		{
+			let attr: Vec<_> = iter.clone().to_token_stream().into_iter().collect();
+			let path_len = attr.len().saturating_sub(2);
+			matches!(
+				&attr[path_len..],
+				[TokenTree::Ident(name), TokenTree::Group(args)]
+					if (name.to_string() == "duplicate_item" || name.to_string() == "substitute_item")
+						&& args.delimiter() == Delimiter::Parenthesis
+			) && attr[..path_len]
+				.iter()
+				.all(|t| is_ident(t, Some("duplicate")) || is_punct(t, ':'))
+		},
+		_ => false,
+	}
+}
+
+/// Whether the given identifier and the next tokens are a `duplicate!` or
+/// `substitute!` invocation that is passed through (without the
+/// `nested_invocation` feature), i.e. followed by '!' and a group starting
+/// with its header in '[]'.
+fn next_is_inner_macro<'a, T: SubGroupIter<'a>>(ident: &Ident, tree: &TokenIter<'a, T>) -> bool
+{
+	if !is_ident(&TokenTree::Ident(ident.clone()), Some("duplicate"))
+		&& !is_ident(&TokenTree::Ident(ident.clone()), Some("substitute"))
+	{
+		return false;
+	}
+	let mut tree = tree.clone();
+	if !matches!(tree.next_fallible(), Ok(Some(Token::Simple(t))) if is_punct(&t, '!'))
+	{
+		return false;
+	}
+	match tree.next_fallible()
+	{
+		Ok(Some(Token::Group(_, mut body, _))) =>
+		{
+			matches!(
+				body.peek(),
+				Ok(Some(Token::Group(Delimiter::Bracket, _, _)))
+			)
+		},
+		_ => false,
+	}
+}
+
+/// Returns the substitution identifiers declared by the given header of an
+/// inner invocation, together with those already shadowed in the given
+/// context.
+///
+/// If the header can't be parsed, only the already shadowed identifiers are
+/// returned, as the inner invocation reports the error itself.
+fn inner_identifiers(
+	header: TokenStream,
+	duplicating: bool,
+	ctx: SubstitutionContext,
+) -> HashSet<String>
+{
+	let mut result = ctx.shadowed.cloned().unwrap_or_default();
+	let dup_def = if duplicating
+	{
+		crate::parse::parse_duplicate_invocation(header, &mut TokenStream::new())
+	}
+	else
+	{
+		crate::parse::parse_global_substitutions_only(header)
+	};
+	if let Ok(dup_def) = dup_def
+	{
+		result.extend(
+			dup_def
+				.global_substitutions
+				.identifiers()
+				.chain(
+					dup_def
+						.duplications
+						.iter()
+						.flat_map(|group| group.identifiers()),
+				)
+				.cloned(),
+		);
+	}
+	result
+}
+
+/// Whether the given stream contains an in-place duplication marker
+/// (`#[duplicate::here]`) or `__for_each__(..)` anywhere.
+///
+/// The bodies of nested invocations are not searched, as any marker there
+/// belongs to the nested invocation.
+fn contains_marker(stream: &TokenStream) -> bool
+{
+	let mut previous: [Option<TokenTree>; 2] = [None, None];
+	for t in stream.clone()
+	{
+		if let TokenTree::Group(g) = &t
+		{
+			let after_hash = previous[1].as_ref().map_or(false, |p| is_punct(p, '#'));
+			let for_each = previous[1]
+				.as_ref()
+				.map_or(false, |p| is_ident(p, Some(FOR_EACH_NAME)));
+			let nested_body = match &previous
+			{
+				[Some(name), Some(bang)] =>
+				{
+					(is_ident(name, Some("duplicate")) || is_ident(name, Some("substitute")))
+						&& is_punct(bang, '!')
+				},
+				_ => false,
+			};
+			if (after_hash && g.delimiter() == Delimiter::Bracket && is_marker(g.stream()))
+				|| (for_each && g.delimiter() == Delimiter::Parenthesis)
+				|| (!nested_body && contains_marker(&g.stream()))
+			{
+				return true;
+			}
+		}
+		previous = [previous[1].take(), Some(t)];
+	}
+	false
+}
+
+/// Duplicates the next fragment of the iterator in place, once for each
+/// substitution group, assuming the marker preceding it has been consumed.
+///
+/// If the fragment is a named field (e.g. in a struct) whose name isn't a
+/// substitution identifier, the name is disambiguated like a module's.
+/// Returns an error if the duplicated fields don't have unique names.
+fn duplicate_in_place<'a, T: SubGroupIter<'a>>(
+	tree: &mut TokenIter<'a, T>,
+	ctx: SubstitutionContext,
+	marker_span: Span,
+) -> Result<TokenStream>
+{
+	let (fragment, separated) = next_fragment(tree)?;
+	let mut groups = tree.sub_groups().peekable();
+	if groups.peek().is_none()
+	{
+		if ctx.options.allow_empty
+		{
+			// The fragment is removed
+			return Ok(TokenStream::new());
+		}
+		return Err(Error::new(
+			"In-place duplication marker used without any substitution groups.",
+		)
+		.span(marker_span)
+		.hint(crate::pretty_errors::MARKER_NO_GROUPS));
+	}
+
+	let fragment: Vec<_> = fragment.into_iter().collect();
+	let field = field_name_index(&fragment).filter(|idx| {
+		groups
+			.peek()
+			.unwrap()
+			.substitution_of(&fragment[*idx].to_string())
+			.is_none()
+	});
+	#[cfg(feature = "module_disambiguation")]
+	let postfix_sub = field
+		.map(|idx| {
+			crate::module_disambiguation::find_simple(
+				tree.sub_groups(),
+				fragment[idx].span(),
+				&ctx.options.module_name,
+			)
+		})
+		.transpose()?;
+	#[cfg(not(feature = "module_disambiguation"))]
+	if let Some(idx) = field
+	{
+		return Err(Error::new(format!(
+			"Duplicating the field '{}' without giving each duplicate a unique name.\nHint: Use a \
+			 substitution identifier as the name or enable the 'duplicate' crate's \
+			 'module_disambiguation' feature to automatically generate unique field names.",
+			fragment[idx]
+		))
+		.span(fragment[idx].span()));
+	}
+
+	let mut result = TokenStream::new();
+	let mut field_names = HashSet::new();
+	let mut index = 0;
+	while let Some(group) = groups.next()
+	{
+		#[cfg_attr(not(feature = "module_disambiguation"), allow(unused_mut))]
+		let mut fragment = fragment.clone();
+		#[cfg(feature = "module_disambiguation")]
+		if let (Some(idx), Some(postfix_sub)) = (field, &postfix_sub)
+		{
+			let name = fragment[idx].to_string() + "_" + &postfix_of(group, postfix_sub, index);
+			fragment[idx] = TokenTree::Ident(Ident::new(&name, fragment[idx].span()));
+		}
+
+		let mut fragment_iter = TokenIter::new_like(fragment.into_iter().collect(), tree);
+		let mut duplicate = Vec::new();
+		let ctx = SubstitutionContext {
+			substitutions: group,
+			index,
+			..ctx
+		};
+		while let Some(stream) = substitute_next_token(&mut fragment_iter, ctx)?
+		{
+			duplicate.extend(stream);
+		}
+		if let Some(idx) = field_name_index(&duplicate)
+		{
+			if !field_names.insert(duplicate[idx].to_string())
+			{
+				return Err(Error::new(format!(
+					"In-place duplication produced the field '{}' multiple times.",
+					duplicate[idx]
+				))
+				.span(duplicate[idx].span()));
+			}
+		}
+
+		result.extend(duplicate);
+		if separated || groups.peek().is_some()
+		{
+			result.extend(Some(TokenTree::Punct(Punct::new(',', Spacing::Alone))));
+		}
+		index += 1;
+	}
+	Ok(result)
+}
+
+/// If the given tokens are a named field (optionally preceded by attributes and
+/// a visibility) returns the index of the field's name.
+fn field_name_index(tokens: &[TokenTree]) -> Option<usize>
+{
+	let mut idx = 0;
+	while let [hash, TokenTree::Group(g), ..] = &tokens[idx..]
+	{
+		if !is_punct(hash, '#') || g.delimiter() != Delimiter::Bracket
+		{
+			break;
+		}
+		idx += 2;
+	}
+	if tokens.get(idx).map_or(false, |t| is_ident(t, Some("pub")))
+	{
+		idx += 1;
+		if let Some(TokenTree::Group(g)) = tokens.get(idx)
+		{
+			if g.delimiter() == Delimiter::Parenthesis
+			{
+				idx += 1;
+			}
+		}
+	}
+	match &tokens[idx..]
+	{
+		[name, colon, ..] if is_ident(name, None) && is_punct(colon, ':') => Some(idx),
+		_ => None,
+	}
+}
+
+/// Takes the tokens up to the next ',' (or the end of the iterator), returning
+/// them and whether the ',' was found. The ',' is consumed but not returned.
+///
+/// Commas between angle brackets (e.g. in generic arguments) don't end the
+/// fragment. Angle brackets are only counted where generics are possible, so
+/// not in expressions following `=` (e.g. `1 << shift` in a discriminant),
+/// `if`, or `=>`, other than after `::`. A block following `=>` (a match arm's
+/// body) ends the fragment even without a ','.
+fn next_fragment<'a, T: SubGroupIter<'a>>(
+	tree: &mut TokenIter<'a, T>,
+) -> Result<(TokenStream, bool)>
+{
+	let mut fragment = TokenStream::new();
+	let mut angle_depth = 0usize;
+	let mut in_expression = false;
+	let mut after_arrow = false;
+	let mut previous: Option<(char, Spacing)> = None;
+	for token in tree
+	{
+		let token = token?;
+		let mut arrow = false;
+		match &token
+		{
+			Token::Simple(TokenTree::Punct(p)) =>
+			{
+				let joined = matches!(previous, Some((_, Spacing::Joint)));
+				match p.as_char()
+				{
+					',' if angle_depth == 0 => return Ok((fragment, true)),
+					'<' if !in_expression || matches!(previous, Some((':', _))) => angle_depth += 1,
+					// The '>' of a match arm's '=>', which starts its body
+					'>' if joined && angle_depth == 0 && matches!(previous, Some(('=', _))) =>
+					{
+						in_expression = true;
+						arrow = true;
+					},
+					// The '>' of any other arrow ('->' or '=>')
+					'>' if joined && matches!(previous, Some(('-' | '=', _))) => (),
+					'>' => angle_depth = angle_depth.saturating_sub(1),
+					// Not part of e.g. '==' or '<='
+					'=' if angle_depth == 0 && !joined && p.spacing() == Spacing::Alone =>
+					{
+						in_expression = true
+					},
+					_ => (),
+				}
+				previous = Some((p.as_char(), p.spacing()));
+			},
+			// A match arm's guard
+			Token::Simple(t) if angle_depth == 0 && is_ident(t, Some("if")) =>
+			{
+				in_expression = true;
+				previous = None;
+			},
+			Token::Group(Delimiter::Brace, ..) if after_arrow =>
+			{
+				fragment.extend(Some(TokenTree::from(token)));
+				return Ok((fragment, false));
+			},
+			_ => previous = None,
+		}
+		after_arrow = arrow;
+		fragment.extend(Some(TokenTree::from(token)));
+	}
+	Ok((fragment, false))
+}
+
+/// If the given literal is a string literal, substitutes any identifiers in it.
+///
+/// Only whole words are substituted and only by substitutions taking no
+/// arguments. The substitution's tokens are rendered as a string and escaped.
+/// Any other literal is returned unchanged.
+fn substitute_in_string(
+	lit: Literal,
+	global_subs: &SubstitutionGroup,
+	substitutions: &SubstitutionGroup,
+) -> Result<Literal>
+{
+	let text = lit.to_string();
+	if text.len() < 2 || !text.starts_with('"') || !text.ends_with('"')
+	{
+		// Not a (non-raw and unsuffixed) string literal
+		return Ok(lit);
+	}
+
+	let mut result = String::from("\"");
+	let mut chars = text[1..text.len() - 1].chars().peekable();
+	while let Some(c) = chars.next()
+	{
+		if c == '\\'
+		{
+			// Escape sequences are kept as-is
+			result.push(c);
+			result.extend(chars.next());
+		}
+		else if c.is_alphanumeric() || c == '_'
+		{
+			let mut word = String::from(c);
+			while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_')
+			{
+				word.push(c);
+			}
+			match substitutions
+				.substitution_of(&word)
+				.or_else(|| global_subs.substitution_of(&word))
+			{
+				Some(subst) if subst.arg_count == 0 =>
+				{
+					for c in subst.apply_simple(lit.span())?.to_string().chars()
+					{
+						if c == '"' || c == '\\'
+						{
+							result.push('\\');
+						}
+						result.push(c);
+					}
+				},
+				_ => result += &word,
+			}
+		}
+		else
+		{
+			result.push(c);
+		}
+	}
+	result.push('"');
+
+	let mut substituted: Literal = result
+		.parse()
+		.map_err(|_| Error::new("Substitution produced an invalid string.").span(lit.span()))?;
+	substituted.set_span(lit.span());
+	Ok(substituted)
+}
@@ -1,13 +1,29 @@
-use crate::{
-	duplicate_and_substitute, error::Error, invoke_nested, new_group, Result, SubstitutionGroup,
+#[cfg(feature = "nested_invocation")]
+use crate::{duplicate_and_substitute, invoke_nested, InvocationOptions};
+use crate::{error::Error, new_group, Result, SubstitutionGroup};
+use proc_macro::{
+	token_stream::IntoIter, Delimiter, Ident, Literal, Spacing, Span, TokenStream, TokenTree,
 };
-use proc_macro::{token_stream::IntoIter, Delimiter, Ident, Spacing, Span, TokenStream, TokenTree};
+#[cfg(feature = "nested_invocation")]
+use std::rc::Rc;
 use std::{
 	collections::VecDeque,
 	fmt::{Debug, Formatter},
-	iter::{once, FromIterator},
+	iter::{FromIterator, FusedIterator},
 };
 
+/// The string identifying a nested `duplicate!` invocation
+#[cfg(feature = "nested_invocation")]
+pub(crate) const NESTED_DUPLICATE_NAME: &str = "duplicate";
+/// The string identifying a nested `substitute!` invocation
+#[cfg(feature = "nested_invocation")]
+pub(crate) const NESTED_SUBSTITUTE_NAME: &str = "substitute";
+
+/// Additional names recognized as nested `duplicate!` and `substitute!`
+/// invocations, respectively (`nested_names`).
+#[cfg(feature = "nested_invocation")]
+pub(crate) type NestedNames = Option<Rc<(String, String)>>;
+
 /// Trait alias
 pub(crate) trait SubGroupIter<'a>: Iterator<Item = &'a SubstitutionGroup> + Clone {}
 impl<'a, T: Iterator<Item = &'a SubstitutionGroup> + Clone> SubGroupIter<'a> for T {}
@@ -33,6 +49,20 @@ impl<'a, T: SubGroupIter<'a>> Token<'a, T>
 			Token::Group(_, _, span) => span.clone(),
 		}
 	}
+
+	/// Returns a short rendering of the token for error messages, where groups
+	/// are shown without their body.
+	pub(crate) fn describe(&self) -> String
+	{
+		match self
+		{
+			Token::Simple(t) => t.to_string(),
+			Token::Group(Delimiter::Bracket, _, _) => "[...]".into(),
+			Token::Group(Delimiter::Brace, _, _) => "{...}".into(),
+			Token::Group(Delimiter::Parenthesis, _, _) => "(...)".into(),
+			Token::Group(Delimiter::None, _, _) => "...".into(),
+		}
+	}
 }
 
 impl<'a, T: SubGroupIter<'a>> From<Token<'a, T>> for TokenTree
@@ -51,7 +81,7 @@ impl<'a, T: SubGroupIter<'a>> From<Token<'a, T>> for TokenTree
 }
 
 /// Whether the token tree is a punctuation
-fn is_punct(t: &TokenTree, c: char) -> bool
+pub fn is_punct(t: &TokenTree, c: char) -> bool
 {
 	if let TokenTree::Punct(p) = t
 	{
@@ -69,6 +99,23 @@ pub fn is_semicolon(t: &TokenTree) -> bool
 	is_punct(t, ';')
 }
 
+/// Whether the token tree is a '$' punctuation
+///
+/// An identifier following a '$' is a metavariable of a declarative macro.
+pub fn is_dollar(t: &TokenTree) -> bool
+{
+	is_punct(t, '$')
+}
+
+/// Whether the given tokens start with the `!` following the name of a macro
+/// invocation, i.e. one that isn't part of e.g. `!=`
+#[cfg(feature = "nested_invocation")]
+fn starts_invocation(mut tokens: impl Iterator<Item = TokenTree>) -> bool
+{
+	matches!(tokens.next(), Some(TokenTree::Punct(p))
+		if p.as_char() == '!' && p.spacing() == Spacing::Alone)
+}
+
 /// Whether the token tree is an identifier, and if so, whether it is equal to
 /// the given string (if given)
 pub fn is_ident(t: &TokenTree, comp: Option<&str>) -> bool
@@ -96,6 +143,70 @@ pub fn get_ident(t: TokenTree) -> Option<Ident>
 	}
 }
 
+/// The value of the given string literal, with its escapes resolved, if it is
+/// a string or raw string literal without a suffix.
+pub fn string_value(lit: &Literal) -> Option<String>
+{
+	let text = lit.to_string();
+	if let Some(raw) = text.strip_prefix('r')
+	{
+		let hashes = raw.len() - raw.trim_start_matches('#').len();
+		return raw[hashes..]
+			.strip_prefix('"')?
+			.strip_suffix(&"#".repeat(hashes))?
+			.strip_suffix('"')
+			.map(String::from);
+	}
+	let mut chars = text
+		.strip_prefix('"')?
+		.strip_suffix('"')?
+		.chars()
+		.peekable();
+	let mut result = String::new();
+	while let Some(c) = chars.next()
+	{
+		if c != '\\'
+		{
+			result.push(c);
+			continue;
+		}
+		match chars.next()?
+		{
+			'n' => result.push('\n'),
+			'r' => result.push('\r'),
+			't' => result.push('\t'),
+			'0' => result.push('\0'),
+			c @ ('\\' | '\'' | '"') => result.push(c),
+			'x' =>
+			{
+				let code: String = chars.by_ref().take(2).collect();
+				result.push(char::from(u8::from_str_radix(&code, 16).ok()?));
+			},
+			'u' =>
+			{
+				if chars.next()? != '{'
+				{
+					return None;
+				}
+				let code: String = chars.by_ref().take_while(|c| *c != '}').collect();
+				result.push(char::from_u32(
+					u32::from_str_radix(&code.replace('_', ""), 16).ok()?,
+				)?);
+			},
+			// A line continuation skips the line break and the whitespace after it
+			'\n' | '\r' =>
+			{
+				while chars.peek().map_or(false, |c| c.is_whitespace())
+				{
+					chars.next();
+				}
+			},
+			_ => return None,
+		}
+	}
+	Some(result)
+}
+
 /// Used to iterate through tokens from a TokenStream.
 ///
 /// Will automatically expand any nested `duplicate` calls, ensuring only final
@@ -111,6 +222,9 @@ pub fn get_ident(t: TokenTree) -> Option<Ident>
 /// Most methods return a Result because the processing happens lazily, meaning
 /// a processing error (e.g. if nested invocations fail) can happen at any time.
 /// If a method returns an error, no tokens are consumed.
+///
+/// As an `Iterator`, it produces the same tokens as `next_fallible`, but stops
+/// after the first error.
 #[derive(Clone)]
 pub(crate) struct TokenIter<'a, T: SubGroupIter<'a>>
 {
@@ -131,8 +245,26 @@ pub(crate) struct TokenIter<'a, T: SubGroupIter<'a>>
 	/// substitution groups.
 	sub_groups: T,
 
-	/// The span of the last token to be produced.
-	last_span: Span,
+	/// The span of the last token to be produced, if any.
+	///
+	/// Otherwise, errors at the end of the tokens use the call site, which is
+	/// only asked for then, as it has to be requested from the compiler.
+	last_span: Option<Span>,
+
+	/// Whether the last raw token to be processed was a '$'.
+	///
+	/// If so, an identifier following it is a metavariable and cannot start a
+	/// nested invocation.
+	#[cfg(feature = "nested_invocation")]
+	after_dollar: bool,
+
+	/// Additional names of nested invocations to expand.
+	#[cfg(feature = "nested_invocation")]
+	nested_names: NestedNames,
+
+	/// Whether an error has been produced as an `Iterator`, after which no more
+	/// tokens are produced as one.
+	failed: bool,
 }
 impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 {
@@ -144,10 +276,9 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 	{
 		if let Some(t) = self.raw_tokens.next()
 		{
-			/// The string identifying a nested `duplicate!` invocation
-			const NESTED_DUPLICATE_NAME: &'static str = "duplicate";
-			/// The string identifying a nested `substitute!` invocation
-			const NESTED_SUBSTITUTE_NAME: &'static str = "substitute";
+			#[cfg(feature = "nested_invocation")]
+			let after_dollar = std::mem::replace(&mut self.after_dollar, is_dollar(&t));
+
 			match t
 			{
 				TokenTree::Group(g) =>
@@ -158,54 +289,10 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 						g.span(),
 					))
 				},
-				TokenTree::Ident(id)
-					if id.to_string() == NESTED_DUPLICATE_NAME
-						|| id.to_string() == NESTED_SUBSTITUTE_NAME =>
+				#[cfg(feature = "nested_invocation")]
+				TokenTree::Ident(id) if !after_dollar && self.nested_kind(&id).is_some() =>
 				{
-					if let Some(TokenTree::Punct(p)) = self.raw_tokens.next()
-					{
-						if is_punct(&TokenTree::Punct(p.clone()), '!')
-						{
-							// Nested Invocation. First perform any needed duplication/substitutions
-							// from outer invocation, then invoke it.
-							let nested_body = if !self.global_subs.substitutions.is_empty()
-								|| self.sub_groups.clone().count() > 1
-							{
-								duplicate_and_substitute(
-									TokenStream::from_iter(self.raw_tokens.next().into_iter()),
-									self.global_subs,
-									self.sub_groups.clone(),
-								)?
-							}
-							else
-							{
-								TokenStream::from_iter(self.raw_tokens.next().into_iter())
-							};
-							let stream = invoke_nested(
-								&mut TokenIter::new_like(nested_body, self),
-								id.to_string() == NESTED_DUPLICATE_NAME,
-							)?;
-							self.unconsumed.push_back(Token::Group(
-								Delimiter::None,
-								TokenIter::new_like(stream, self),
-								p.span(),
-							));
-						}
-						else
-						{
-							// Not nested invocation
-							self.unconsumed
-								.push_back(Token::Simple(TokenTree::Ident(id)));
-							self.unconsumed
-								.push_back(Token::Simple(TokenTree::Punct(p)));
-						}
-					}
-					else
-					{
-						// Not nested invocation
-						self.unconsumed
-							.push_back(Token::Simple(TokenTree::Ident(id)));
-					}
+					self.fetch_nested(id)?
 				},
 				_ => self.unconsumed.push_back(Token::Simple(t)),
 			}
@@ -217,6 +304,126 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 		}
 	}
 
+	/// Returns whether the given identifier names a nested `duplicate!` (true)
+	/// or `substitute!` (false) invocation, or none if it names neither.
+	#[cfg(feature = "nested_invocation")]
+	fn nested_kind(&self, id: &Ident) -> Option<bool>
+	{
+		let name = id.to_string();
+		let (duplicate_name, substitute_name) = self
+			.nested_names
+			.as_deref()
+			.map_or(("", ""), |(dup, sub)| (dup.as_str(), sub.as_str()));
+		if name == NESTED_DUPLICATE_NAME || name == duplicate_name
+		{
+			Some(true)
+		}
+		else if name == NESTED_SUBSTITUTE_NAME || name == substitute_name
+		{
+			Some(false)
+		}
+		else
+		{
+			None
+		}
+	}
+
+	/// Sets the additional names of nested invocations to expand.
+	///
+	/// Tokens that have been fetched but not produced are fetched again, so
+	/// the names apply to all tokens yet to be produced.
+	#[cfg(feature = "nested_invocation")]
+	pub(crate) fn set_nested_names(&mut self, names: NestedNames)
+	{
+		self.nested_names = names;
+		self.refetch();
+	}
+
+	/// Sets the global substitutions nested invocations are substituted with
+	/// before they are expanded.
+	///
+	/// Like with `set_nested_names`, tokens that have been fetched but not
+	/// produced are fetched again.
+	#[cfg(feature = "nested_invocation")]
+	pub(crate) fn set_global_subs(&mut self, global_subs: &'a SubstitutionGroup)
+	{
+		self.global_subs = global_subs;
+		self.refetch();
+	}
+
+	/// Returns the tokens that have been fetched but not produced to the raw
+	/// tokens, so they are fetched again.
+	#[cfg(feature = "nested_invocation")]
+	fn refetch(&mut self)
+	{
+		let unconsumed = std::mem::take(&mut self.unconsumed);
+		self.raw_tokens =
+			TokenStream::from_iter(unconsumed.into_iter().map(TokenTree::from).chain(
+				std::mem::replace(&mut self.raw_tokens, TokenStream::new().into_iter()),
+			))
+			.into_iter();
+	}
+
+	/// Whether the next token starts a nested invocation that hasn't been
+	/// expanded yet.
+	#[cfg(feature = "nested_invocation")]
+	pub(crate) fn next_is_nested(&self) -> bool
+	{
+		let mut raw = self.raw_tokens.clone();
+		self.unconsumed.is_empty()
+			&& !self.after_dollar
+			&& matches!(raw.next(), Some(TokenTree::Ident(id)) if self.nested_kind(&id).is_some())
+			&& starts_invocation(raw)
+	}
+
+	/// Puts the given identifier in the unconsumed queue, or, if it and the
+	/// following raw tokens are a nested invocation, the expansion of the
+	/// invocation.
+	#[cfg(feature = "nested_invocation")]
+	fn fetch_nested(&mut self, id: Ident) -> Result<()>
+	{
+		if starts_invocation(self.raw_tokens.clone())
+		{
+			let bang = self.raw_tokens.next().unwrap();
+			// Nested Invocation. First perform any needed duplication/substitutions
+			// from outer invocation, then invoke it.
+			let nested_body = if !self.global_subs.substitutions.is_empty()
+				|| self.sub_groups.clone().count() > 1
+			{
+				duplicate_and_substitute(
+					TokenStream::from_iter(self.raw_tokens.next().into_iter()),
+					&InvocationOptions::default(),
+					self.global_subs,
+					self.sub_groups.clone(),
+				)
+				.map_err(Error::fatal)?
+			}
+			else
+			{
+				TokenStream::from_iter(self.raw_tokens.next().into_iter())
+			};
+			// Errors are fatal, since the nested invocation is there no matter how
+			// the tokens around it are parsed
+			let stream = invoke_nested(
+				&mut TokenIter::new_like(nested_body, self),
+				self.nested_kind(&id) == Some(true),
+			)
+			.map_err(Error::fatal)?;
+			self.unconsumed.push_back(Token::Group(
+				Delimiter::None,
+				TokenIter::new_like(stream, self),
+				bang.span(),
+			));
+		}
+		else
+		{
+			// Not nested invocation, so the following tokens are fetched as usual
+			self.unconsumed
+				.push_back(Token::Simple(TokenTree::Ident(id)));
+		}
+		Ok(())
+	}
+
 	/// Attempts to get the next unconsumed token.
 	///
 	/// If the next token is a None-delimited group, attempts to get its next
@@ -248,7 +455,13 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 	pub fn next_fallible(&mut self) -> Result<Option<Token<'a, T>>>
 	{
 		self.fetch()?;
-		self.next_unconsumed()
+		let token = self.next_unconsumed()?;
+		if let Some(token) = &token
+		{
+			// Errors at the end of the tokens point at the last token
+			self.last_span = Some(token.span());
+		}
+		Ok(token)
 	}
 
 	/// Extracts a value from the next token.
@@ -272,7 +485,9 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 			let mut err = Error::new(error);
 			if let Some(expected_string) = expected
 			{
-				err = err.hint("Expected ".to_string() + expected_string + ".");
+				err = err
+					.hint("Expected ".to_string() + expected_string + ".")
+					.expected(expected_string);
 			}
 			err
 		};
@@ -280,13 +495,18 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 		{
 			Some(Token::Simple(t)) if p(&t) =>
 			{
-				self.last_span = t.span();
-				Ok(f(self.next_fallible().unwrap().unwrap().into()))
+				self.last_span = Some(t.span());
+				Ok(f(self.next_fallible()?.unwrap().into()))
 			},
-			Some(Token::Simple(t)) => Err(create_error("Unexpected token.").span(t.span())),
-			Some(Token::Group(_, _, span)) =>
+			Some(Token::Simple(t)) =>
 			{
-				Err(create_error("Unexpected delimiter.").span(span.clone()))
+				Err(create_error("Unexpected token.").span(t.span()).found(t))
+			},
+			Some(token @ Token::Group(_, _, _)) =>
+			{
+				Err(create_error("Unexpected delimiter.")
+					.span(token.span())
+					.found(token.describe()))
 			},
 			None => Err(create_error("Unexpected end of code.")),
 		}
@@ -334,6 +554,112 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 		self.expect_simple(is_semicolon, Some("';'"))
 	}
 
+	/// Consumes the next two tokens if they are `=>`.
+	///
+	/// Returns whether they were consumed.
+	pub fn extract_arrow(&mut self) -> Result<bool>
+	{
+		match self.peek()?
+		{
+			Some(Token::Simple(TokenTree::Punct(p)))
+				if p.as_char() == '=' && p.spacing() == Spacing::Joint =>
+			{},
+			_ => return Ok(false),
+		}
+		let equals = self.next_fallible()?.unwrap();
+		match self.peek()?
+		{
+			Some(Token::Simple(TokenTree::Punct(p))) if p.as_char() == '>' =>
+			{
+				self.next_fallible()?;
+				Ok(true)
+			},
+			_ =>
+			{
+				self.push_front(equals);
+				Ok(false)
+			},
+		}
+	}
+
+	/// Skips any outer attributes (`#[...]`), including doc comments.
+	///
+	/// Consumes nothing if the next tokens aren't an attribute.
+	pub fn skip_attributes(&mut self) -> Result<()>
+	{
+		while let Some(Token::Simple(t)) = self.peek()?
+		{
+			if !is_punct(t, '#')
+			{
+				break;
+			}
+			let hash = self.next_fallible()?.unwrap();
+			if let Some(Token::Group(Delimiter::Bracket, _, _)) = self.peek()?
+			{
+				self.next_fallible()?;
+			}
+			else
+			{
+				self.push_front(hash);
+				break;
+			}
+		}
+		Ok(())
+	}
+
+	/// Skips a visibility (e.g. `pub` or `pub(crate)`), if the next tokens are
+	/// one.
+	pub fn skip_visibility(&mut self) -> Result<()>
+	{
+		if self
+			.extract_simple(|t| is_ident(t, Some("pub")), |t| t, None)
+			.is_ok()
+		{
+			if let Some(Token::Group(Delimiter::Parenthesis, _, _)) = self.peek()?
+			{
+				self.next_fallible()?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Extracts any doc attributes (`#[doc ...]`), including doc comments, and
+	/// returns their tokens.
+	///
+	/// Consumes nothing if the next tokens aren't a doc attribute.
+	pub fn extract_doc_attributes(&mut self) -> Result<Vec<TokenStream>>
+	{
+		let mut result = Vec::new();
+		while let Some(Token::Simple(t)) = self.peek()?
+		{
+			if !is_punct(t, '#')
+			{
+				break;
+			}
+			let hash = self.next_fallible()?.unwrap();
+			let is_doc = match self.peek()?
+			{
+				Some(Token::Group(Delimiter::Bracket, body, _)) =>
+				{
+					matches!(body.clone().next_fallible()?,
+						Some(Token::Simple(t)) if is_ident(&t, Some("doc")))
+				},
+				_ => false,
+			};
+			if !is_doc
+			{
+				self.push_front(hash);
+				break;
+			}
+			let attribute = self.next_fallible()?.unwrap();
+			result.push(TokenStream::from_iter([
+				TokenTree::from(hash),
+				TokenTree::from(attribute),
+			]));
+		}
+		Ok(result)
+	}
+
 	/// Gets the body and span of the next group.
 	///
 	/// Returns an error if:
@@ -362,18 +688,20 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 
 		match self.peek()?
 		{
-			Some(Token::Group(del, _, span)) if *del != Delimiter::None =>
+			Some(token @ Token::Group(del, _, span)) if *del != Delimiter::None =>
 			{
 				if let Some(exp_del) = expected
 				{
 					if exp_del != *del
 					{
-						return Err(Error::new(error()).span(span.clone()));
+						return Err(Error::new(error())
+							.span(span.clone())
+							.found(token.describe()));
 					}
 				}
 				if let Token::Group(_, iter, span) = self.next_fallible()?.unwrap()
 				{
-					self.last_span = span;
+					self.last_span = Some(span);
 					Ok((iter, span))
 				}
 				else
@@ -381,21 +709,53 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 					unreachable!()
 				}
 			},
-			Some(token) => Err(Error::new(error()).span(token.span())),
-			_ => Err(Error::new(error()).span(self.last_span)),
+			Some(token) =>
+			{
+				Err(Error::new(error())
+					.span(token.span())
+					.found(token.describe()))
+			},
+			_ =>
+			{
+				let err = Error::new(error());
+				// Without a last token, the caller may know where the tokens are from
+				Err(match self.last_span
+				{
+					Some(span) => err.span(span),
+					None => err,
+				})
+			},
 		}
 	}
 
-	/// Converts to a TokenStream immediately processing the whole iterator,
-	/// panicking if an error is encountered.
-	pub fn process_all(mut self) -> TokenStream
+	/// Gets the body and span of the next substitution, which is enclosed in
+	/// either '[]' or '{}'.
+	///
+	/// Returns the same errors as `next_group(Some(Delimiter::Bracket))`.
+	pub fn next_substitution(&mut self) -> Result<(Self, Span)>
 	{
-		let mut result = TokenStream::new();
-		while let Some(t) = self.next_fallible().unwrap()
+		if let Some(Token::Group(Delimiter::Brace, _, _)) = self.peek()?
 		{
-			result.extend(once(TokenTree::from(t)));
+			return self.next_group(Some(Delimiter::Brace));
 		}
-		result
+		self.next_group(Some(Delimiter::Bracket))
+	}
+
+	/// Whether the next token is a substitution enclosed in either '[]' or
+	/// '{}'.
+	pub fn next_is_substitution(&mut self) -> Result<bool>
+	{
+		Ok(matches!(
+			self.peek()?,
+			Some(Token::Group(Delimiter::Bracket, _, _))
+				| Some(Token::Group(Delimiter::Brace, _, _))
+		))
+	}
+
+	/// Converts to a TokenStream immediately processing the whole iterator.
+	pub fn process_all(self) -> Result<TokenStream>
+	{
+		self.map(|t| t.map(TokenTree::from)).collect()
 	}
 
 	/// Convert to TokenStream __without any processing__.
@@ -416,7 +776,6 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 	}
 
 	/// Whether there is a next token and it is a ';'
-	#[cfg_attr(not(feature = "pretty_errors"), allow(dead_code))]
 	pub fn has_next_semicolon(&mut self) -> Result<bool>
 	{
 		self.peek().map_or_else(
@@ -488,20 +847,62 @@ impl<'a, T: SubGroupIter<'a>> TokenIter<'a, T>
 		Self {
 			raw_tokens: stream.into_iter(),
 			unconsumed: VecDeque::new(),
-			last_span: Span::call_site(),
+			last_span: None,
+			#[cfg(feature = "nested_invocation")]
+			after_dollar: false,
+			#[cfg(feature = "nested_invocation")]
+			nested_names: None,
+			failed: false,
 			global_subs,
 			sub_groups,
 		}
 	}
 
+	/// Uses the additional names of nested invocations given by the options.
+	#[allow(unused_variables, unused_mut)]
+	pub(crate) fn with_nested_names(mut self, options: &crate::InvocationOptions) -> Self
+	{
+		#[cfg(feature = "nested_invocation")]
+		self.set_nested_names(options.nested_names.clone());
+		self
+	}
+
+	/// Returns the substitution groups used to duplicate nested invocations.
+	pub fn sub_groups(&self) -> T
+	{
+		self.sub_groups.clone()
+	}
+
 	/// Construct new token iterator from the given stream.
 	///
 	/// Substitution/duplication of nested invocations is taken from 'like'
 	pub fn new_like(stream: TokenStream, like: &Self) -> Self
 	{
-		Self::new(stream, like.global_subs, like.sub_groups.clone())
+		#[allow(unused_mut)]
+		let mut iter = Self::new(stream, like.global_subs, like.sub_groups.clone());
+		#[cfg(feature = "nested_invocation")]
+		{
+			iter.nested_names = like.nested_names.clone();
+		}
+		iter
+	}
+}
+impl<'a, T: SubGroupIter<'a>> Iterator for TokenIter<'a, T>
+{
+	type Item = Result<Token<'a, T>>;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		if self.failed
+		{
+			return None;
+		}
+		let next = self.next_fallible().transpose();
+		self.failed = matches!(next, Some(Err(_)));
+		next
 	}
 }
+impl<'a, T: SubGroupIter<'a>> FusedIterator for TokenIter<'a, T> {}
 impl<'a, T: SubGroupIter<'a> + Debug> Debug for TokenIter<'a, T>
 {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
@@ -0,0 +1,64 @@
+use crate::{limits::count_tokens, Result};
+use proc_macro::TokenStream;
+use std::{cell::Cell, time::Instant};
+
+thread_local! {
+	/// The number of nested invocations expanded so far by this thread.
+	static NESTED_INVOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Records that a nested invocation is being expanded.
+#[cfg(feature = "nested_invocation")]
+pub(crate) fn nested_invocation()
+{
+	NESTED_INVOCATIONS.with(|count| count.set(count.get() + 1));
+}
+
+/// Measures an invocation from when it is started until it is reported.
+pub(crate) struct Trace
+{
+	/// The name of the macro invoked (e.g. "duplicate").
+	name: &'static str,
+	/// When the invocation started.
+	start: Instant,
+	/// The number of tokens given to the invocation.
+	input_tokens: usize,
+	/// The number of nested invocations expanded before the invocation started.
+	nested_before: usize,
+}
+
+impl Trace
+{
+	/// Starts measuring an invocation of the given macro with the given
+	/// invocation and body.
+	pub(crate) fn start(name: &'static str, attr: &TokenStream, item: &TokenStream) -> Self
+	{
+		Self {
+			name,
+			start: Instant::now(),
+			input_tokens: count_tokens(attr.clone()) + count_tokens(item.clone()),
+			nested_before: NESTED_INVOCATIONS.with(Cell::get),
+		}
+	}
+
+	/// Reports the measurements of the invocation to stderr, given its number
+	/// of substitution groups and its result.
+	///
+	/// Failed invocations aren't reported, as their error already is.
+	pub(crate) fn report(self, groups: usize, result: &Result<TokenStream>)
+	{
+		if let Ok(output) = result
+		{
+			eprintln!(
+				"duplicate trace: '{}' invocation with {} substitution groups, {} input tokens, \
+				 {} output tokens, and {} nested invocations took {:?}",
+				self.name,
+				groups,
+				self.input_tokens,
+				count_tokens(output.clone()),
+				NESTED_INVOCATIONS.with(Cell::get) - self.nested_before,
+				self.start.elapsed()
+			);
+		}
+	}
+}
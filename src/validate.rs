@@ -0,0 +1,67 @@
+use crate::{error::Error, pretty_errors::VALIDATE, Result, SubstitutionGroup};
+use proc_macro::{Span, TokenStream};
+use syn::{
+	parse::{ParseStream, Parser},
+	Item,
+};
+
+/// Checks that the given output of a duplicate parses as items (given the
+/// `validate` option).
+///
+/// The duplicate is described in the error by the index of its substitution
+/// group, if any, and the substitutions it used. The error is given at the
+/// invocation, since the output's own spans point into it at best.
+pub(crate) fn validate_duplicate(
+	output: &TokenStream,
+	substitutions: &SubstitutionGroup,
+	index: Option<usize>,
+) -> Result<()>
+{
+	let parser = |input: ParseStream| {
+		while !input.is_empty()
+		{
+			input.parse::<Item>()?;
+		}
+		Ok(())
+	};
+	parser.parse2(output.clone().into()).map_err(|err| {
+		let duplicate = match index
+		{
+			Some(index) => format!("The duplicate of substitution group {}", index + 1),
+			None => "The output".to_string(),
+		};
+		Error::new(format!("{} isn't valid Rust: {}", duplicate, err))
+			.span(Span::call_site())
+			.hint(format!(
+				"It was substituted with: {}\n{}",
+				substitutions_text(substitutions),
+				VALIDATE
+			))
+	})
+}
+
+/// The substitutions of the given group, ordered by their substitution
+/// identifiers, e.g. `name = [Small], ty = [u8]`.
+fn substitutions_text(substitutions: &SubstitutionGroup) -> String
+{
+	let mut idents: Vec<_> = substitutions.identifiers().collect();
+	if idents.is_empty()
+	{
+		return "no substitutions".to_string();
+	}
+	idents.sort();
+	idents
+		.into_iter()
+		.map(|ident| {
+			format!(
+				"{} = [{}]",
+				ident,
+				substitutions
+					.substitution_of(ident)
+					.unwrap()
+					.normalized_text()
+			)
+		})
+		.collect::<Vec<_>>()
+		.join(", ")
+}
@@ -40,3 +40,4 @@ macro_rules! default_features {
 
 default_features!(pretty_errors "pretty_errors");
 default_features!(module_disambiguation "module_disambiguation");
+default_features!(nested_invocation "nested_invocation");
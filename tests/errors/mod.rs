@@ -1,11 +1,17 @@
 use crate::utils::ExpansionTester;
 #[cfg(feature = "pretty_errors")]
+use crate::utils::{expected_section, for_enabled_features};
+#[cfg(feature = "pretty_errors")]
 use std::path::{Path, PathBuf};
 
-/// Tests all expected basic error messages in 'basic' on their respective
-/// source files in 'source'
+/// Tests all expected basic error messages in 'basic' and the 'message'
+/// sections of the combined expectation files in 'expected' on their
+/// respective source files in 'source'
 ///
-/// Expects every source file to have an expected basic.
+/// Expects every source file to have an expected basic for the enabled
+/// features. As the message differs when `pretty_errors` is disabled, a
+/// combined file can give it for both configurations (see
+/// `expected_section`).
 #[test]
 fn basic_expansion_errors()
 {
@@ -19,6 +25,17 @@ fn basic_expansion_errors()
 			ExpansionTester::copy_with_prefix_postfix("inline_basic_", ".expanded.rs"),
 		],
 	);
+	tester.add_source_dir(
+		"expected",
+		vec![
+			ExpansionTester::copy_section_with_prefix_postfix("message", "basic_", ".expanded.rs"),
+			ExpansionTester::copy_section_with_prefix_postfix(
+				"message",
+				"inline_basic_",
+				".expanded.rs",
+			),
+		],
+	);
 	tester.add_source_dir(
 		"source",
 		vec![ExpansionTester::duplicate_for_inline_with_prefix("basic_")],
@@ -29,16 +46,29 @@ fn basic_expansion_errors()
 /// Copies the source file with the same name as the current file
 /// into the testing directory in both attribute and inline version (see
 /// duplicate_for_inline)
+///
+/// If the current file is only for the attribute or inline variant (i.e. has
+/// the `attr` or `inline` extension) or for some features (e.g. has the
+/// `module_disambiguation` extension) but the source file isn't, the source
+/// file without the extensions is used. Files for other features are skipped.
 #[cfg(feature = "pretty_errors")]
 pub fn get_source(prefix: &str) -> Box<dyn '_ + Fn(&Path, &dyn AsRef<Path>)>
 {
 	Box::new(move |file, destination| {
-		let mut source_file_name = std::ffi::OsString::from(file.file_name().unwrap());
-		source_file_name.push(".rs");
-
+		let file_name = file.file_name().unwrap().to_str().unwrap();
+		if !for_enabled_features(file_name)
+		{
+			return;
+		}
 		let mut source_file_path = PathBuf::from(file.parent().unwrap().parent().unwrap());
 		source_file_path.push("source");
-		source_file_path.push(source_file_name);
+		source_file_path.push(file_name.to_owned() + ".rs");
+
+		if !source_file_path.exists()
+		{
+			let without_extensions = file_name.split('.').next().unwrap();
+			source_file_path.set_file_name(without_extensions.to_owned() + ".rs");
+		}
 
 		assert!(
 			source_file_path.exists(),
@@ -50,10 +80,29 @@ pub fn get_source(prefix: &str) -> Box<dyn '_ + Fn(&Path, &dyn AsRef<Path>)>
 	})
 }
 
-/// Tests the expected hints in 'hint' against their respective source files in
+/// Like `get_source`, except the source file is only copied if the current
+/// file, a combined expectation file (see `expected_section`), has the given
+/// section.
+#[cfg(feature = "pretty_errors")]
+pub fn get_source_for_section<'a>(
+	prefix: &'a str,
+	section: &'a str,
+) -> Box<dyn 'a + Fn(&Path, &dyn AsRef<Path>)>
+{
+	let get_source = get_source(prefix);
+	Box::new(move |file, destination| {
+		if expected_section(&std::fs::read_to_string(file).unwrap(), section).is_some()
+		{
+			get_source(file, destination);
+		}
+	})
+}
+
+/// Tests the expected hints in 'hint' and the 'hint' sections of the combined
+/// expectation files in 'expected' against their respective source files in
 /// 'source'.
 ///
-/// Only tests source files that have a hint file.
+/// Only tests source files that have a hint file or section.
 #[cfg(feature = "pretty_errors")]
 #[rustversion::since(1.43)]
 #[test]
@@ -69,14 +118,27 @@ fn hint_expansion_errors()
 			get_source("hint_"),
 		],
 	);
+	tester.add_source_dir(
+		"expected",
+		vec![
+			ExpansionTester::copy_section_with_prefix_postfix("hint", "hint_", ".expanded.rs"),
+			ExpansionTester::copy_section_with_prefix_postfix(
+				"hint",
+				"inline_hint_",
+				".expanded.rs",
+			),
+			get_source_for_section("hint_", "hint"),
+		],
+	);
 
 	tester.execute_tests();
 }
 
-/// Tests the expected code highlights in 'highlight' against their respective
-/// source files in 'source'.
+/// Tests the expected code highlights in 'highlight' and the 'highlight'
+/// sections of the combined expectation files in 'expected' against their
+/// respective source files in 'source'.
 ///
-/// Only tests source files that have a highlight file.
+/// Only tests source files that have a highlight file or section.
 #[cfg(feature = "pretty_errors")]
 #[test]
 fn highlight_expansion_errors()
@@ -91,6 +153,22 @@ fn highlight_expansion_errors()
 			get_source("highlight_"),
 		],
 	);
+	tester.add_source_dir(
+		"expected",
+		vec![
+			ExpansionTester::copy_section_with_prefix_postfix(
+				"highlight",
+				"highlight_",
+				".expanded.rs",
+			),
+			ExpansionTester::copy_section_with_prefix_postfix(
+				"highlight",
+				"inline_highlight_",
+				".expanded.rs",
+			),
+			get_source_for_section("highlight_", "highlight"),
+		],
+	);
 
 	tester.execute_tests();
 }
@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that an accumulator cannot be an existing substitution identifier
+#[duplicate_item(
+	acc ty += [name,];
+	name   ty;
+	[Some] [u8];
+)]//duplicate_end
+pub struct name(ty);
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that an alias cannot be an existing substitution identifier
+#[duplicate_item(
+	alias ty = name;
+	name   ty;
+	[Some] [u8];
+)]//duplicate_end
+pub struct name(ty);
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that an alias must be of a substitution identifier
+#[duplicate_item(
+	alias ty = typ;
+	name   ty2;
+	[Some] [u8];
+)]//duplicate_end
+pub struct name(ty2);
+//item_end
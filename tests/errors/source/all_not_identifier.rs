@@ -0,0 +1,10 @@
+use duplicate::*;
+// Test that '__all__' must be given a substitution identifier of the
+// substitution groups
+#[duplicate_item(
+	name;
+	[First];
+	[Second];
+)]//duplicate_end
+pub const name: &[&str] = &[__all__(names)];
+//item_end
@@ -0,0 +1,8 @@
+use duplicate::*;
+// Tests that 'allow_empty' is only accepted when duplicating
+#[substitute_item(
+	allow_empty;
+	ty [u32];
+)]//duplicate_end
+pub struct Example(ty);
+//item_end
@@ -0,0 +1,12 @@
+use duplicate::*;
+// Tests that with 'expect exactly', an expansion only differing from the
+// expected code in the spacing of punctuation fails
+assert_duplicate_expands!{
+	[
+		name;
+		[First];
+		[Second];
+	]
+	{ pub type name = std::string::String; }
+	expect exactly { pub type First = std::string::String; pub type Second = std: :string::String; }
+}
@@ -0,0 +1,12 @@
+use duplicate::*;
+// Tests that an expansion differing from the expected code fails, pointing to
+// the first difference
+assert_duplicate_expands!{
+	[
+		name;
+		[First];
+		[Second];
+	]
+	{ pub struct name; }
+	expect { pub struct First; pub struct Third; }
+}
@@ -0,0 +1,10 @@
+use duplicate::*;
+// Tests that 'auto_cfg' requires a single identifier or literal
+#[duplicate_item(
+	auto_cfg(feature = backend);
+	backend			name;
+	[tokio]			[Tokio];
+	[async std]		[AsyncStd];
+)]//duplicate_end
+pub struct name;
+//item_end
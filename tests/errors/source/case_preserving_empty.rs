@@ -0,0 +1,8 @@
+use duplicate::*;
+// Tests that case-preserving substitutions must be identifiers in every case
+#[duplicate_item(
+	[ ~backend [sqlite] ]
+	[ ~backend [__] ]
+)]//duplicate_end
+pub struct Backend;
+//item_end
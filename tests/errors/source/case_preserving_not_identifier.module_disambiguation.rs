@@ -0,0 +1,8 @@
+use duplicate::*;
+#[duplicate_item(
+	[
+		~name	[Vec<u8>]
+	]
+)]//duplicate_end
+pub struct name();
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that all columns must have the same number of substitutions
+#[duplicate_item(
+	columns;
+	int_type:  [u8]  [u16] [u32];
+	max_value: [255] [65_535];
+)]//duplicate_end
+pub struct Example(int_type, max_value);
+//item_end
@@ -0,0 +1,11 @@
+use duplicate::*;
+// Tests that concatenation only accepts identifiers and integers
+#[duplicate_item(
+	name;
+	[Alpha];
+	[Beta];
+)]//duplicate_end
+pub fn name() {
+	let __concat__(name, "text") = 0;
+}
+//item_end
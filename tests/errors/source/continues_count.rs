@@ -0,0 +1,12 @@
+use duplicate::*;
+// Tests that the substitution groups of 'duplicate_item_rows' attributes must
+// match the substitution identifiers of the invocation
+#[duplicate_item(
+	continues;
+	name		member;
+	[SomeName1]	[u8];
+)]//duplicate_end
+#[duplicate_item_rows([SomeName2] [u16];)]
+#[duplicate_item_rows([SomeName3];)]
+pub struct name(member);
+//item_end
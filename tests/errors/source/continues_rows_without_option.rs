@@ -0,0 +1,10 @@
+use duplicate::*;
+// Tests that 'duplicate_item_rows' attributes require the 'continues' option, as
+// without it, they aren't consumed by the invocation
+#[duplicate_item(
+	name;
+	[SomeName1];
+)]//duplicate_end
+#[duplicate_item_rows([SomeName2];)]
+pub struct name;
+//item_end
@@ -0,0 +1,11 @@
+use duplicate::*;
+// Tests that the 'continues' option can only be used with the short syntax
+#[duplicate_item(
+	continues;
+	[
+		name [SomeName1]
+	]
+)]//duplicate_end
+#[duplicate_item_rows([SomeName2];)]
+pub struct name;
+//item_end
@@ -0,0 +1,10 @@
+use duplicate::*;
+// Tests that the 'continues' option must be followed by 'duplicate_item_rows'
+// attributes
+#[duplicate_item(
+	continues;
+	name;
+	[SomeName];
+)]//duplicate_end
+pub struct name;
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+#[duplicate_item(
+	defer_as(instantiate);
+	name;
+	[first];
+	[second];
+)]//duplicate_end
+macro_rules! name { ($x:expr) => { $x }; }
+//item_end
@@ -0,0 +1,6 @@
+use duplicate::*;
+// Tests that duplicating a missing file fails with its path
+duplicate_file!("missing.rs", [
+	name;
+	[SomeName];
+]);
@@ -0,0 +1,9 @@
+use duplicate::*;
+#[duplicate_item(
+	lints(deny(empty_substitution));
+	name     vis;
+	[first]  [pub];
+	[second] [];
+)]//duplicate_end
+pub(in vis) fn name() {}
+//item_end
@@ -0,0 +1,7 @@
+use duplicate::*;
+// Tests that environment variables without defaults must be set
+#[substitute_item(
+	int_type env(DUPLICATE_TEST_UNSET);
+)]//duplicate_end
+pub struct Example(int_type);
+//item_end
@@ -0,0 +1,10 @@
+use duplicate::*;
+// Tests that an excluded substitution group must match a substitution group
+#[duplicate_item(
+	name    ty;
+	[Small] [u8];
+	[Large] [u64];
+	except  [Large] [u32];
+)]//duplicate_end
+pub struct name(ty);
+//item_end
@@ -0,0 +1,8 @@
+use duplicate::*;
+// Tests that 'expand!' only expands the macros it supports
+#[duplicate_item(
+	name;
+	expand!(format!("[{}];", "Some"))
+)]//duplicate_end
+pub struct name;
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+#[duplicate_item(
+	expect(3);
+	name;
+	[First];
+	[Second];
+)]//duplicate_end
+pub struct name();
+//item_end
@@ -0,0 +1,12 @@
+use duplicate::*;
+// Tests that the argument of '__for_each__' must be enclosed in brackets
+#[duplicate_item(
+	ty;
+	[u8];
+	[u16];
+)]//duplicate_end
+pub fn example() -> [u64; 2]
+{
+	[__for_each__(0 as ty)]
+}
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+// Test that an absolute substitution group index must be in range
+#[duplicate_item(
+	name     value;
+	[First]  [1];
+	[Second] [2];
+)]//duplicate_end
+pub const name: u8 = __group_sub__(value, 2);
+//item_end
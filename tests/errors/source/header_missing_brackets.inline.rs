@@ -0,0 +1,7 @@
+use duplicate::*;
+// Tests that the invocation of 'duplicate!' must be enclosed in brackets
+duplicate!{
+	name;
+	[SomeName];
+	pub struct name();
+}
@@ -0,0 +1,7 @@
+use duplicate::*;
+// Tests that the invocation of 'substitute!' must be enclosed in brackets, not
+// parentheses
+substitute!{
+	(name [SomeName];)
+	pub struct name();
+}
@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that only known interleavings are accepted
+#[duplicate_item(
+	interleave(by_row);
+	name;
+	[First];
+)]//duplicate_end
+pub struct name;
+//item_end
@@ -0,0 +1,11 @@
+use duplicate::*;
+// Tests that '__item__' requires an item whose name can be found
+#[duplicate_item(
+	ty;
+	[u8];
+	[u16];
+)]//duplicate_end
+impl Trait for ty {
+	const NAME: &'static str = stringify!(__item__);
+}
+//item_end
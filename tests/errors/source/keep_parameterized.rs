@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that 'keep' can't keep a parameterized substitution identifier
+#[duplicate_item(
+	name        reference(ty);
+	[First]     [&ty];
+	[Second]    keep;
+)]//duplicate_end
+pub fn name(value: reference([u8])) {}
+//item_end
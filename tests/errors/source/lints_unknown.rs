@@ -0,0 +1,11 @@
+use duplicate::*;
+#[duplicate_item(
+	lints(deny(unused_identifier));
+	int_type max_value;
+	[u8]     [255];
+	[u16]    [65_535];
+)]//duplicate_end
+pub fn max() -> Int_type {
+	max_value
+}
+//item_end
\ No newline at end of file
@@ -0,0 +1,13 @@
+use duplicate::*;
+// Tests that in-place duplicated fields must get unique names
+#[duplicate_item(
+	name        ty;
+	[field]     [u8];
+	[field]     [u16];
+)]//duplicate_end
+pub struct Example
+{
+	#[duplicate::here]
+	name: ty,
+}
+//item_end
@@ -0,0 +1,11 @@
+use duplicate::*;
+// Tests that in-place duplication markers need substitution groups
+#[substitute_item(
+	ty [u32];
+)]//duplicate_end
+pub enum Example
+{
+	#[duplicate::here]
+	Variant(ty),
+}
+//item_end
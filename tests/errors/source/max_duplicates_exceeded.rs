@@ -0,0 +1,10 @@
+use duplicate::*;
+#[duplicate_item(
+	max_duplicates(2);
+	name;
+	[First];
+	[Second];
+	[Third];
+)]//duplicate_end
+pub struct name();
+//item_end
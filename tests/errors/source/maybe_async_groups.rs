@@ -0,0 +1,11 @@
+use duplicate::*;
+// Tests that 'maybe_async' takes either no substitution groups or two
+#[duplicate_item(
+	maybe_async;
+	name;
+	[first];
+	[second];
+	[third];
+)]//duplicate_end
+async fn name() {}
+//item_end
@@ -0,0 +1,13 @@
+use duplicate::*;
+#[duplicate_item(
+	module_names([first], [second]);
+	name;
+	[First];
+	[Second];
+	[Third];
+)]//duplicate_end
+mod module
+{
+	pub struct name();
+}
+//item_end
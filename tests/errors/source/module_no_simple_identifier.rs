@@ -0,0 +1,10 @@
+use duplicate::*;
+#[duplicate_item(
+	member_type;
+	[Option<u8>];
+	[&'static str];
+)]//duplicate_end
+mod module {
+	pub struct Wrapper(pub member_type);
+}
+//item_end
\ No newline at end of file
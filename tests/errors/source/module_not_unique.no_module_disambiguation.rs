@@ -0,0 +1,11 @@
+use duplicate::*;
+// Tests that modules can't be duplicated without 'module_disambiguation'
+#[duplicate_item(
+	int_type;
+	[Signed];
+)]//duplicate_end
+mod numbers
+{
+	pub struct int_type(i32);
+}
+//item_end
@@ -0,0 +1,11 @@
+use duplicate::*;
+#[duplicate_item(
+	name		member_type;
+	[Reader]	[u8];
+	[Reader]	[u16];
+	[Writer]	[u8];
+)]//duplicate_end
+mod module {
+	pub struct name(pub member_type);
+}
+//item_end
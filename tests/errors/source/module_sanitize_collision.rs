@@ -0,0 +1,11 @@
+use duplicate::*;
+#[duplicate_item(
+	module_name(sanitize(member_type));
+	name		member_type;
+	[Value]		[u8];
+	[Reference]	[&u8];
+)]//duplicate_end
+mod module {
+	pub struct name(pub member_type);
+}
+//item_end
\ No newline at end of file
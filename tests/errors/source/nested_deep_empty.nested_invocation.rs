@@ -0,0 +1,12 @@
+use duplicate::*;
+// Test that an empty nested invocation two levels deep is pointed at instead
+// of the outer-most invocation
+#[duplicate_item(
+	name ty;
+	duplicate!{
+		[ n; [A]; [B] ]
+		[n] duplicate!{};
+	}
+)]//duplicate_end
+pub struct name(ty);
+//item_end
@@ -0,0 +1,15 @@
+use duplicate::*;
+// Test that an error in a nested invocation two levels deep points at the
+// offending token instead of the outer-most invocation
+#[duplicate_item(
+	name ty;
+	duplicate!{
+		[ n; [A]; [B] ]
+		duplicate!{
+			[ t(x); [u8]; [u16] ]
+			[n] [t];
+		}
+	}
+)]//duplicate_end
+pub struct name(ty);
+//item_end
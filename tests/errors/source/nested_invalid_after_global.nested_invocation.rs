@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that an invalid nested invocation following a global substitution is
+// reported
+#[substitute_item(
+	name [Example];
+	duplicate! ty
+)]//duplicate_end
+pub struct name();
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that an invalid nested invocation in a global substitution is reported
+#[duplicate_item(
+	name [duplicate!{[x]}];
+	ty;
+	[u8];
+)]//duplicate_end
+pub struct Example(ty);
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that an invalid nested invocation among the substitution identifiers is
+// reported
+#[duplicate_item(
+	name substitute! ty;
+	[Example] [u8];
+)]//duplicate_end
+pub struct name(ty);
+//item_end
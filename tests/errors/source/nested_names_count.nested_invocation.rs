@@ -0,0 +1,10 @@
+use duplicate::*;
+// Tests that 'nested_names' requires both names
+#[duplicate_item(
+	nested_names(my_dup);
+	name;
+	[First];
+	[Second];
+)]//duplicate_end
+pub struct name();
+//item_end
@@ -0,0 +1,8 @@
+use duplicate::*;
+#[duplicate_item(
+	strict;
+	int_type;
+	duplicate!{[int_type; [u8]; [u16]] [int_type];}
+)]//duplicate_end
+impl Trait for int_type {}
+//item_end
@@ -0,0 +1,14 @@
+use duplicate::*;
+// Tests that optional substitution identifiers can't be used by duplicates
+// whose substitution group doesn't define them
+#[duplicate_item(
+	[
+		name            [First]
+		optional extra  [u8]
+	]
+	[
+		name            [Second]
+	]
+)]//duplicate_end
+pub struct name(extra);
+//item_end
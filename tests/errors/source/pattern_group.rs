@@ -0,0 +1,6 @@
+use duplicate::*;
+#[substitute_item(
+	pattern [Option<(u8, u16)>] => [Pair];
+)]//duplicate_end
+pub type Alias = Pair;
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+#[duplicate_item(
+	positions(ty@field);
+	ty;
+	[u8];
+	[u16];
+)]//duplicate_end
+pub type Alias = ty;
+//item_end
@@ -0,0 +1,8 @@
+use duplicate::*;
+#[substitute_item(
+	recursive;
+	first [second];
+	second [Wrapper<first>];
+)]//duplicate_end
+pub type Alias = first;
+//item_end
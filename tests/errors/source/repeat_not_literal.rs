@@ -0,0 +1,7 @@
+use duplicate::*;
+// Tests that 'repeat' only accepts an integer literal
+#[duplicate_item(
+	repeat(count);
+)]//duplicate_end
+pub struct Example;
+//item_end
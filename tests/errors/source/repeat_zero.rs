@@ -0,0 +1,7 @@
+use duplicate::*;
+// Tests that 'repeat' must make at least one duplicate
+#[duplicate_item(
+	repeat(0);
+)]//duplicate_end
+pub struct Example;
+//item_end
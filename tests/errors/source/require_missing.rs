@@ -0,0 +1,11 @@
+use duplicate::*;
+#[duplicate_item(
+	require(int_type, max_value);
+	int_type;
+	[u8];
+	[u16];
+)]//duplicate_end
+pub fn max() -> int_type {
+	max_value
+}
+//item_end
@@ -0,0 +1,15 @@
+use duplicate::*;
+#[duplicate_item(
+	require(int_type, implementation);
+	duplicate!{
+		[ty; [u8]; [u16]]
+		[
+			int_type		[ty]
+			implemenation	[ty::MAX]
+		]
+	}
+)]//duplicate_end
+pub fn max() -> int_type {
+	implementation
+}
+//item_end
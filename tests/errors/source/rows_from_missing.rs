@@ -0,0 +1,8 @@
+use duplicate::*;
+// Tests that reading substitution groups from a missing file fails
+#[duplicate_item(
+	rows_from("missing.dup");
+	name;
+)]//duplicate_end
+pub struct name;
+//item_end
@@ -0,0 +1,8 @@
+use duplicate::*;
+#[duplicate_item(
+	name	ty		name;
+	[First]	[u8]	[Second];
+	[Third]	[u16]	[Fourth];
+)]//duplicate_end
+pub struct name(ty);
+//item_end
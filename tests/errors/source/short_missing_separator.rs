@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that a missing ';' between substitution groups is detected
+#[duplicate_item(
+	name	ty;
+	[Name1]	[u8]
+	[Name2]	[u16];
+)]//duplicate_end
+pub struct name(ty);
+//item_end
@@ -0,0 +1,11 @@
+use duplicate::*;
+// Tests that a missing '=>' between substitution groups is detected
+#[duplicate_item(
+	name	ty =>
+	[Name1]	[u8] =>
+	[Name2]	[u16]
+	[Name3]	[u32]
+	[Name4]	[u64]
+)]//duplicate_end
+pub struct name(ty);
+//item_end
@@ -0,0 +1,10 @@
+use duplicate::*;
+// Tests that substitution groups can't be separated by ';' when the
+// substitution identifiers end with '=>'
+#[duplicate_item(
+	name =>
+	[SomeName1];
+	[SomeName2];
+)]//duplicate_end
+pub struct name();
+//item_end
@@ -1,5 +1,5 @@
 use duplicate::*;
-// Tests that if the globals are followed by short syntax, the hint refers to 'duplicate'
+// Tests that if the globals are followed by short syntax, the error refers to 'duplicate'
 #[substitute_item(
 	name 	[sub1];
 	ty 		[u32];
@@ -0,0 +1,10 @@
+use duplicate::*;
+// Tests that if 'substitute_item' is given substitution groups, the error
+// explains that they are for 'duplicate_item'
+#[substitute_item(
+	name;
+	[First];
+	[Second];
+)]//duplicate_end
+pub struct name;
+//item_end
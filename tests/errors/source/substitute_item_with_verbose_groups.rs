@@ -0,0 +1,9 @@
+use duplicate::*;
+// Tests that if 'substitute_item' is given only verbose syntax substitution
+// groups, the error explains that they are for 'duplicate_item'
+#[substitute_item(
+	[ name [First] ]
+	[ name [Second] ]
+)]//duplicate_end
+pub struct name;
+//item_end
@@ -0,0 +1,13 @@
+use duplicate::*;
+// Tests that section-local global substitutions can't substitute the
+// identifiers of the invocation
+duplicate!{
+	[
+		name;
+		[First];
+		[Second];
+	]
+	template struct_def [ name [Third]; ] {
+		pub struct name();
+	}
+}
@@ -0,0 +1,12 @@
+use duplicate::*;
+#[duplicate_item(
+	unique_names;
+	ty;
+	[u8];
+	[u64];
+)]//duplicate_end
+pub fn check(value: ty) -> bool
+{
+	value > 0
+}
+//item_end
@@ -0,0 +1,9 @@
+use duplicate::*;
+#[duplicate_item(
+	validate;
+	name	ty;
+	[Small]	[u8];
+	[Large]	[u64 u8];
+)]//duplicate_end
+pub type name = ty;
+//item_end
\ No newline at end of file
@@ -0,0 +1,7 @@
+use duplicate::*;
+#[duplicate_item(
+	name [Foo] member [u8]
+	[name [Bar] member [u16]]
+)]//duplicate_end
+pub struct name(member);
+//item_end
\ No newline at end of file
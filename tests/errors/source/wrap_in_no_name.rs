@@ -0,0 +1,9 @@
+use duplicate::*;
+#[duplicate_item(
+	wrap_in(pub(crate));
+	name;
+	[First];
+	[Second];
+)]//duplicate_end
+pub struct name();
+//item_end
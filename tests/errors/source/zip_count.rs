@@ -0,0 +1,10 @@
+use duplicate::*;
+#[duplicate_item(
+	zip {
+		name; [First]; [Second];
+	} {
+		ty; [u8];
+	}
+)]//duplicate_end
+pub struct name(ty);
+//item_end
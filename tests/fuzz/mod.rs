@@ -0,0 +1,322 @@
+//! Tests that the macros never panic or loop forever on malformed invocations.
+//!
+//! Invocations are generated pseudo-randomly from a grammar of identifiers
+//! (including the keywords, options, and built-in identifiers the macros look
+//! for), punctuation, literals, and groups, partly arranged like options and
+//! substitution groups, and then expanded by compiling them. With
+//! 'pretty_errors', errors are reported without panicking, so any panic is a
+//! bug.
+//!
+//! The 'DUPLICATE_FUZZ_SEED' and 'DUPLICATE_FUZZ_CASES' environment variables
+//! can be used to generate other or more invocations than the default.
+use crate::utils::{
+	FEATURE_MODULE_DISAMBIGUATION, FEATURE_NESTED_INVOCATION, FEATURE_PRETTY_ERRORS,
+};
+use std::{
+	io::Read,
+	process::Command,
+	time::{Duration, Instant},
+};
+
+/// Identifiers to generate, favoring those with meaning to the macros.
+const IDENTS: &[&str] = &[
+	"name",
+	"ty",
+	"a",
+	"mod",
+	"macro_rules",
+	"pub",
+	"crate",
+	"in",
+	"fn",
+	"struct",
+	"async",
+	"await",
+	"_",
+	"__",
+	// Keywords of invocations
+	"env",
+	"alias",
+	"optional",
+	"zip",
+	"except",
+	"keep",
+	"pattern",
+	"acc",
+	"template",
+	"expand",
+	"include",
+	"concat",
+	"stringify",
+	// Invocation options and their arguments
+	"attr_strings",
+	"allow_empty",
+	"repeat",
+	"interleave",
+	"by_item",
+	"columns",
+	"rows_from",
+	"continues",
+	"strict",
+	"lints",
+	"allow",
+	"warn",
+	"deny",
+	"case_mismatch",
+	"empty_substitution",
+	"nested_shadowing",
+	"module_name",
+	"index",
+	"auto",
+	"sanitize",
+	"module_names",
+	"explain_module_names",
+	"max_duplicates",
+	"expect",
+	"require",
+	"recursive",
+	"protect_inner",
+	"positions",
+	"validate",
+	"unique_names",
+	"wrap_in",
+	"defer_as",
+	"nested_names",
+	"maybe_async",
+	"auto_cfg",
+	// Built-in identifiers
+	"__index__",
+	"__concat__",
+	"__for_each__",
+	"__hash__",
+	"__concat_str__",
+	"__item__",
+	"__first__",
+	"__last__",
+	"__all__",
+	"__group_sub__",
+	// Nested invocations and in-place duplication
+	"duplicate",
+	"substitute",
+	"dup",
+	"sub",
+	"here",
+];
+
+/// Punctuation to generate.
+const PUNCTS: &[&str] = &[
+	";", ",", ":", "::", "=", "!", "#", "'a", "$", "->", "<", ">", "~", "=>", "@", "+=", "+", "-",
+	"==",
+];
+
+/// Literals to generate.
+const LITERALS: &[&str] = &["0", "1", "3", "-1", "\"name\"", "r\"name\"", "'c'"];
+
+/// How long expanding all invocations may take before assuming the macros loop
+/// forever.
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A simple pseudo-random generator, so the generated invocations are
+/// reproducible without extra dependencies.
+pub struct Rng(pub u64);
+
+impl Rng
+{
+	pub fn next(&mut self, bound: usize) -> usize
+	{
+		// Linear congruential generator from Knuth's MMIX
+		self.0 = self
+			.0
+			.wrapping_mul(6364136223846793005)
+			.wrapping_add(1442695040888963407);
+		((self.0 >> 33) % bound as u64) as usize
+	}
+
+	pub fn pick<'a>(&mut self, options: &[&'a str]) -> &'a str
+	{
+		options[self.next(options.len())]
+	}
+}
+
+/// Generates a random sequence of tokens with at most the given nesting depth.
+fn tokens(rng: &mut Rng, depth: usize) -> String
+{
+	let mut result = String::new();
+	for _ in 0..rng.next(8)
+	{
+		match rng.next(10)
+		{
+			0..=3 => result += rng.pick(IDENTS),
+			4..=5 => result += rng.pick(PUNCTS),
+			6 => result += rng.pick(LITERALS),
+			// Substitution identifiers followed by their substitution are common to all
+			// syntaxes
+			7 if depth > 0 =>
+			{
+				result += rng.pick(IDENTS);
+				result += " [";
+				result += &tokens(rng, depth - 1);
+				result += "]";
+			},
+			_ if depth > 0 =>
+			{
+				let (open, close) = [("[", "]"), ("(", ")"), ("{", "}")][rng.next(3)];
+				result += open;
+				result += &tokens(rng, depth - 1);
+				result += close;
+			},
+			_ => result += rng.pick(IDENTS),
+		}
+		result += " ";
+	}
+	result
+}
+
+/// Generates a random substitution identifier with a random marker (e.g. `~`)
+/// in front.
+fn identifier(rng: &mut Rng) -> String
+{
+	format!(
+		"{} {}",
+		rng.pick(&["", "", "~", "optional", "alias"]),
+		rng.pick(IDENTS)
+	)
+}
+
+/// Generates random invocation syntax, which is either random tokens or
+/// options followed by substitution groups in the short or verbose syntax
+/// with random substitutions, so the parser gets past the start of the
+/// invocation.
+fn invocation_syntax(rng: &mut Rng) -> String
+{
+	let mut result = String::new();
+	for _ in 0..rng.next(3)
+	{
+		result += &format!("{} {};", rng.pick(IDENTS), tokens(rng, 1));
+	}
+	let groups = 1 + rng.next(3);
+	match rng.next(3)
+	{
+		0 => result += &tokens(rng, 3),
+		1 =>
+		{
+			let idents = 1 + rng.next(2);
+			for _ in 0..idents
+			{
+				result += &identifier(rng);
+				result += " ";
+			}
+			for _ in 0..groups
+			{
+				result += ";";
+				for _ in 0..idents
+				{
+					result += &format!("[{}]", tokens(rng, 2));
+				}
+			}
+		},
+		_ =>
+		{
+			for _ in 0..groups
+			{
+				result += "[";
+				for _ in 0..1 + rng.next(2)
+				{
+					result += &format!("{} [{}]", identifier(rng), tokens(rng, 2));
+				}
+				result += "]";
+			}
+		},
+	}
+	result
+}
+
+/// Generates a random invocation of either 'duplicate!' or 'substitute!'.
+fn invocation(rng: &mut Rng) -> String
+{
+	format!(
+		"{}!{{[{}] {}}}",
+		rng.pick(&["duplicate", "substitute"]),
+		invocation_syntax(rng),
+		tokens(rng, 3)
+	)
+}
+
+#[test]
+fn fuzz_invocations()
+{
+	if !FEATURE_PRETTY_ERRORS
+	{
+		// Errors are reported by panicking
+		return;
+	}
+	let seed = std::env::var("DUPLICATE_FUZZ_SEED")
+		.map_or(0, |seed| seed.parse().expect("Invalid DUPLICATE_FUZZ_SEED"));
+	let cases = std::env::var("DUPLICATE_FUZZ_CASES").map_or(200, |cases| {
+		cases.parse().expect("Invalid DUPLICATE_FUZZ_CASES")
+	});
+
+	let mut rng = Rng(seed);
+	let mut source = String::from("#![allow(warnings)]\nuse duplicate::*;\n");
+	for i in 0..cases
+	{
+		// Each invocation in its own module, so items of one don't clash with
+		// another's
+		source += &format!(
+			"mod case_{} {{ use super::*; {} }}\n",
+			i,
+			invocation(&mut rng)
+		);
+	}
+	source += "fn main() {}\n";
+	std::fs::create_dir_all("tests/fuzz/src").unwrap();
+	std::fs::write("tests/fuzz/src/main.rs", &source).unwrap();
+
+	let mut features = vec!["pretty_errors"];
+	if FEATURE_MODULE_DISAMBIGUATION
+	{
+		features.push("module_disambiguation");
+	}
+	if FEATURE_NESTED_INVOCATION
+	{
+		features.push("nested_invocation");
+	}
+	let mut child = Command::new("cargo")
+		.arg("check")
+		.arg("--quiet")
+		.arg("--features")
+		.arg(features.join(","))
+		.current_dir("tests/fuzz")
+		.stdout(std::process::Stdio::null())
+		.stderr(std::process::Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	// Read the errors concurrently, so a full pipe doesn't stall the compiler
+	let mut child_stderr = child.stderr.take().unwrap();
+	let reader = std::thread::spawn(move || {
+		let mut stderr = String::new();
+		child_stderr.read_to_string(&mut stderr).unwrap();
+		stderr
+	});
+
+	let start = Instant::now();
+	while child.try_wait().unwrap().is_none()
+	{
+		if start.elapsed() > TIMEOUT
+		{
+			child.kill().unwrap();
+			panic!("Expansion timed out (seed {}, {} cases)", seed, cases);
+		}
+		std::thread::sleep(Duration::from_millis(100));
+	}
+	let stderr = reader.join().unwrap();
+
+	assert!(
+		!stderr.contains("panicked"),
+		"Macro panicked (seed {}, {} cases, see 'tests/fuzz/src/main.rs'):\n{}",
+		seed,
+		cases,
+		stderr
+	);
+}
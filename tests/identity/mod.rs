@@ -0,0 +1,142 @@
+//! Tests that duplicating with identity substitutions reproduces the item.
+//!
+//! Items are generated pseudo-randomly (see `crate::fuzz`) from identifiers,
+//! punctuation, literals, and nested groups of every delimiter. Each is
+//! duplicated twice with substitutions mapping identifiers to themselves, or
+//! with identifiers that don't occur in it, and `assert_duplicate_expands`
+//! (with `expect exactly`) checks that both duplicates are token-identical to
+//! the item, so rebuilt groups must keep their delimiters, contents, and
+//! punctuation spacing, and no invisible groups may be added.
+//!
+//! The 'DUPLICATE_FUZZ_SEED' and 'DUPLICATE_FUZZ_CASES' environment variables
+//! can be used to generate other or more items than the default.
+use crate::{
+	fuzz::Rng,
+	utils::{FEATURE_MODULE_DISAMBIGUATION, FEATURE_NESTED_INVOCATION, FEATURE_PRETTY_ERRORS},
+};
+use std::process::Command;
+
+/// Identifiers to generate.
+///
+/// Excludes those with meaning to the macros (e.g. `mod` or `duplicate`), as
+/// they don't expand to themselves.
+const IDENTS: &[&str] = &["a", "b", "foo", "Bar", "x1", "fn", "let", "self", "where"];
+
+/// The identifiers that may be substitution identifiers.
+const SUBSTITUTION_IDENTS: &[&str] = &["a", "b", "foo", "Bar", "x1"];
+
+/// Punctuation to generate.
+const PUNCTS: &[&str] = &[
+	";", ",", ":", "::", "=", "==", "!", "#", "'a", "->", "<", ">", "+", "&", ".", "..", "@", "?",
+];
+
+/// Literals to generate.
+const LITERALS: &[&str] = &["0", "1u8", "2.5", "-3", "\"s\"", "'c'", "b\"x\""];
+
+/// Generates a random sequence of tokens with at most the given nesting depth.
+fn tokens(rng: &mut Rng, depth: usize) -> String
+{
+	let mut result = String::new();
+	for _ in 0..rng.next(8)
+	{
+		match rng.next(10)
+		{
+			0..=3 => result += rng.pick(IDENTS),
+			4..=5 => result += rng.pick(PUNCTS),
+			6 => result += rng.pick(LITERALS),
+			_ if depth > 0 =>
+			{
+				let (open, close) = [("[", "]"), ("(", ")"), ("{", "}")][rng.next(3)];
+				result += open;
+				result += &tokens(rng, depth - 1);
+				result += close;
+			},
+			_ => result += rng.pick(IDENTS),
+		}
+		result += " ";
+	}
+	result
+}
+
+/// Generates an invocation with two substitution groups that each substitute
+/// the chosen identifiers with themselves, in either syntax.
+///
+/// With no identifiers chosen, one that isn't generated is used instead.
+fn identity_invocation(rng: &mut Rng) -> String
+{
+	let mut idents: Vec<_> = SUBSTITUTION_IDENTS
+		.iter()
+		.filter(|_| rng.next(3) == 0)
+		.copied()
+		.collect();
+	if idents.is_empty()
+	{
+		idents.push("unused");
+	}
+	if rng.next(2) == 0
+	{
+		let group: String = idents.iter().map(|i| format!("[{}] ", i)).collect();
+		format!("{}; {}; {}", idents.join(" "), group, group)
+	}
+	else
+	{
+		let group: String = idents.iter().map(|i| format!("{} [{}] ", i, i)).collect();
+		format!("[{}] [{}]", group, group)
+	}
+}
+
+#[test]
+fn identity_substitutions()
+{
+	let seed = std::env::var("DUPLICATE_FUZZ_SEED")
+		.map_or(0, |seed| seed.parse().expect("Invalid DUPLICATE_FUZZ_SEED"));
+	let cases = std::env::var("DUPLICATE_FUZZ_CASES").map_or(200, |cases| {
+		cases.parse().expect("Invalid DUPLICATE_FUZZ_CASES")
+	});
+
+	let mut rng = Rng(seed);
+	let mut source = String::from("use duplicate::*;\n");
+	for _ in 0..cases
+	{
+		let invocation = identity_invocation(&mut rng);
+		let item = tokens(&mut rng, 3);
+		source += &format!(
+			"assert_duplicate_expands!{{ [{}] {{ {} }} expect exactly {{ {} {} }} }}\n",
+			invocation, item, item, item
+		);
+	}
+	source += "fn main() {}\n";
+	std::fs::create_dir_all("tests/identity/src").unwrap();
+	std::fs::write("tests/identity/src/main.rs", &source).unwrap();
+
+	let mut features = Vec::new();
+	if FEATURE_PRETTY_ERRORS
+	{
+		features.push("pretty_errors");
+	}
+	if FEATURE_MODULE_DISAMBIGUATION
+	{
+		features.push("module_disambiguation");
+	}
+	if FEATURE_NESTED_INVOCATION
+	{
+		features.push("nested_invocation");
+	}
+	let output = Command::new("cargo")
+		.arg("check")
+		.arg("--quiet")
+		.arg("--features")
+		.arg(features.join(","))
+		.current_dir("tests/identity")
+		.output()
+		.unwrap();
+
+	assert!(
+		output.status.success(),
+		"Identity substitutions changed an item (seed {}, {} cases, see \
+		 'tests/identity/src/main.rs'):\n{}",
+		seed,
+		cases,
+		String::from_utf8_lossy(&output.stderr)
+	);
+}
@@ -0,0 +1,13 @@
+use duplicate::*;
+pub struct SomeType;
+pub struct Values {
+    pub value_u8: u8,
+    pub value_u16: u16,
+    pub value_some_type: SomeType,
+    #[doc = "Documented"]
+    limit_u8: u8,
+    #[doc = "Documented"]
+    limit_u16: u16,
+    #[doc = "Documented"]
+    limit_some_type: SomeType,
+}
@@ -0,0 +1,9 @@
+use duplicate::*;
+mod module_some_name1 {
+    pub struct SomeName1();
+    pub const NAME: &str = "module_some_name1";
+}
+mod module_some_name2 {
+    pub struct SomeName2();
+    pub const NAME: &str = "module_some_name2";
+}
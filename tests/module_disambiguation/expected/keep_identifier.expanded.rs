@@ -0,0 +1,7 @@
+use duplicate::*;
+mod module_some_name1 {
+    pub struct SomeName1();
+}
+mod module_name {
+    pub struct name();
+}
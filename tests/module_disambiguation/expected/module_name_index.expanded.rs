@@ -0,0 +1,22 @@
+use duplicate::*;
+mod module_0 {
+    pub struct Bytes(pub Option<u8>);
+}
+mod module_1 {
+    pub struct Words(pub Option<u16>);
+}
+mod fallback_0 {
+    pub struct Wrapper(pub Option<u8>);
+}
+mod fallback_1 {
+    pub struct Wrapper(pub &'static str);
+}
+mod fallback_2 {
+    pub struct Wrapper(pub [u32; 4]);
+}
+mod auto_bytes {
+    pub struct Bytes(pub Option<u8>);
+}
+mod auto_words {
+    pub struct Words(pub Option<u16>);
+}
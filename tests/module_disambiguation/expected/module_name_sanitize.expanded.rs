@@ -0,0 +1,13 @@
+use duplicate::*;
+mod module_vec_u8 {
+    pub struct Bytes(pub Vec<u8>);
+}
+mod module_static_str {
+    pub struct Text(pub &'static str);
+}
+mod module_std_string_string {
+    pub struct Owned(pub std::string::String);
+}
+mod module_u32_4 {
+    pub struct Array(pub [u32; 4]);
+}
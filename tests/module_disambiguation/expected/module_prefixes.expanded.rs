@@ -0,0 +1,31 @@
+use duplicate::*;
+pub mod module_some_name11 {
+    pub struct SomeName11();
+}
+pub mod module_some_name12 {
+    pub struct SomeName12();
+}
+pub(crate) mod module_some_name21 {
+    pub struct SomeName21();
+}
+pub(crate) mod module_some_name22 {
+    pub struct SomeName22();
+}
+#[allow(unused_imports)]
+#[allow(dead_code)]
+mod module_some_name31 {
+    pub struct SomeName31();
+}
+#[allow(unused_imports)]
+#[allow(dead_code)]
+mod module_some_name32 {
+    pub struct SomeName32();
+}
+#[doc = " A module."]
+pub mod module_some_name41 {
+    pub struct SomeName41();
+}
+#[doc = " A module."]
+pub mod module_some_name42 {
+    pub struct SomeName42();
+}
@@ -0,0 +1,37 @@
+use duplicate::*;
+mod module_some_name11 {
+    pub struct SomeName11(u8);
+}
+mod module_some_name12 {
+    pub struct SomeName12(u8);
+}
+mod module_some_name13 {
+    pub struct SomeName13(u64);
+}
+mod module_u8 {
+    pub struct SomeName(u8);
+}
+mod module_u32 {
+    pub struct SomeName(u32);
+}
+mod module_u64 {
+    pub struct SomeName(u64);
+}
+mod module_i8 {
+    pub struct HttpName31(i8);
+}
+mod module_i32 {
+    pub struct HTTPName31(i32);
+}
+mod module_i64 {
+    pub struct SomeName33(i64);
+}
+mod module_u16 {
+    pub struct SomeName(u16);
+}
+mod module_i16 {
+    pub struct SomeName(i16);
+}
+mod module_bool {
+    pub struct SomeName(bool);
+}
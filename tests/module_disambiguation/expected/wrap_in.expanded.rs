@@ -0,0 +1,10 @@
+use duplicate::*;
+pub mod generated {
+    use super::*;
+    pub mod module_some_name1 {
+        pub struct SomeName1();
+    }
+    pub mod module_some_name2 {
+        pub struct SomeName2();
+    }
+}
@@ -0,0 +1,5 @@
+use duplicate::*;
+pub struct SomeName1(u8);
+pub struct SomeName2(u16);
+pub struct SomeName3(u32);
+pub struct SomeName4(u64);
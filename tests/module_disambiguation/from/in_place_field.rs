@@ -0,0 +1,18 @@
+pub struct SomeType;
+
+// Test that duplicated fields whose name isn't substituted are disambiguated
+#[duplicate_item(
+	int_type    max;
+	[u8]        [255];
+	[u16]       [65_535];
+	[SomeType]  [0];
+)]//duplicate_end
+pub struct Values
+{
+	#[duplicate::here]
+	pub value: int_type,
+	#[duplicate::here]
+	#[doc = "Documented"]
+	limit: int_type
+}
+//item_end
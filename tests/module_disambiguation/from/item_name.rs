@@ -0,0 +1,11 @@
+// Tests that '__item__' is the disambiguated name of the module
+#[duplicate_item(
+	name;
+	[SomeName1];
+	[SomeName2];
+)]//duplicate_end
+mod module {
+	pub struct name();
+	pub const NAME: &str = stringify!(__item__);
+}
+//item_end
@@ -0,0 +1,10 @@
+// Tests that a kept identifier ('keep') is used to disambiguate the module
+#[duplicate_item(
+	name;
+	[SomeName1];
+	keep;
+)]//duplicate_end
+mod module {
+	pub struct name();
+}
+//item_end
@@ -0,0 +1,38 @@
+// Tests that 'module_name(index)' postfixes the index of each substitution
+// group, even when a substitution identifier could be used.
+#[duplicate_item(
+	module_name(index);
+	name		member_type;
+	[Bytes]		[Option<u8>];
+	[Words]		[Option<u16>];
+)]//duplicate_end
+mod module {
+	pub struct name(pub member_type);
+}
+//item_end
+
+// Tests that 'module_name(auto)' falls back to the index when no substitution
+// identifier has only identifiers as substitutions.
+#[duplicate_item(
+	module_name(auto);
+	member_type;
+	[Option<u8>];
+	[&'static str];
+	[[u32; 4]];
+)]//duplicate_end
+mod fallback {
+	pub struct Wrapper(pub member_type);
+}
+//item_end
+
+// Tests that 'module_name(auto)' uses a substitution identifier when possible.
+#[duplicate_item(
+	module_name(auto);
+	name		member_type;
+	[Bytes]		[Option<u8>];
+	[Words]		[Option<u16>];
+)]//duplicate_end
+mod auto {
+	pub struct name(pub member_type);
+}
+//item_end
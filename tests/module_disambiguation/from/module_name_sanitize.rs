@@ -0,0 +1,15 @@
+// Tests that 'module_name(sanitize(..))' postfixes the sanitized substitutions
+// of the given substitution identifier, including generics, references, and
+// paths.
+#[duplicate_item(
+	module_name(sanitize(member_type));
+	name		member_type;
+	[Bytes]		[Vec<u8>];
+	[Text]		[&'static str];
+	[Owned]		[std::string::String];
+	[Array]		[[u32; 4]];
+)]//duplicate_end
+mod module {
+	pub struct name(pub member_type);
+}
+//item_end
@@ -0,0 +1,46 @@
+// Tests a public module is disambiguated
+#[duplicate_item(
+	name;
+	[SomeName11];
+	[SomeName12];
+)]//duplicate_end
+pub mod module {
+	pub struct name();
+}
+//item_end
+
+// Tests a module with a restricted visibility is disambiguated
+#[duplicate_item(
+	name;
+	[SomeName21];
+	[SomeName22];
+)]//duplicate_end
+pub(crate) mod module {
+	pub struct name();
+}
+//item_end
+
+// Tests a module with attributes is disambiguated
+#[duplicate_item(
+	name;
+	[SomeName31];
+	[SomeName32];
+)]//duplicate_end
+#[allow(unused_imports)]
+#[allow(dead_code)]
+mod module {
+	pub struct name();
+}
+//item_end
+
+// Tests a documented public module is disambiguated
+#[duplicate_item(
+	name;
+	[SomeName41];
+	[SomeName42];
+)]//duplicate_end
+/// A module.
+pub mod module {
+	pub struct name();
+}
+//item_end
@@ -0,0 +1,50 @@
+// The following tests all ensure that if the first substitution identifier
+// that could be used to postfix the module gives the same postfix more than
+// once, then the next is chosen.
+// Test 1
+#[duplicate_item(
+	member_type	name;
+	[u8]		[SomeName11];
+	[u8]		[SomeName12];
+	[u64]		[SomeName13]
+)]//duplicate_end
+mod module {
+	pub struct name(member_type);
+}
+//item_end
+
+// Test 1, reversed
+#[duplicate_item(
+	name			member_type;
+	[SomeName21]	[u8];
+	[SomeName21]	[u32];
+	[SomeName23]	[u64]
+)]//duplicate_end
+mod module {
+	pub struct SomeName(member_type);
+}
+//item_end
+
+// Test 2, collision only in snake case
+#[duplicate_item(
+	name			member_type;
+	[HttpName31]	[i8];
+	[HTTPName31]	[i32];
+	[SomeName33]	[i64]
+)]//duplicate_end
+mod module {
+	pub struct name(member_type);
+}
+//item_end
+
+// Test 3, 3 identifiers of which the first 2 collide
+#[duplicate_item(
+	last_identifier	name			member_type;
+	[OtherIdent]	[SomeName41]	[u16];
+	[AnotherIdent]	[SomeName41]	[i16];
+	[OtherIdent]	[SomeName43]	[bool]
+)]//duplicate_end
+mod module {
+	pub struct SomeName(member_type);
+}
+//item_end
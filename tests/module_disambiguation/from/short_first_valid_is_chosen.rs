@@ -2,7 +2,6 @@
 // can be used to postfix the module, then the first is chosen.
 // We have many test here to ensure that if the choice of identifier to use
 // is pseudo-random, most likely at least one of them will fail.
-use duplicate::*;
 // Test 1
 #[duplicate_item(
 	name			member_type;
@@ -0,0 +1,30 @@
+// Tests macro names are postfixed from substitution identifier,
+// and that substitution happens inside matchers and transcribers.
+#[duplicate_item(
+	backend		ty;
+	[Backend1]	[u8];
+	[Backend2]	[u16];
+)]//duplicate_end
+macro_rules! make_struct {
+	($name:ident, backend) => {
+		pub struct $name(ty);
+	};
+}
+//item_end
+make_struct_backend1!(SomeName1, Backend1);
+make_struct_backend2!(SomeName2, Backend2);
+
+// Tests macros using parentheses
+#[duplicate_item(
+	backend		ty;
+	[Backend3]	[u32];
+	[Backend4]	[u64];
+)]//duplicate_end
+macro_rules! make_struct (
+	($name:ident) => {
+		pub struct $name(ty);
+	};
+);
+//item_end
+make_struct_backend3!(SomeName3);
+make_struct_backend4!(SomeName4);
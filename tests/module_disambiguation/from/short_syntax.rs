@@ -1,4 +1,3 @@
-use duplicate::*;
 // Tests module names are postfixed from substitution identifier
 #[duplicate_item(
 	name;
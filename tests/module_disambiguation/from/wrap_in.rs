@@ -0,0 +1,12 @@
+// Tests that the modules of the duplicates are disambiguated inside the
+// module wrapping them, which isn't
+#[duplicate_item(
+	wrap_in(pub generated);
+	name;
+	[SomeName1];
+	[SomeName2];
+)]//duplicate_end
+pub mod module {
+	pub struct name();
+}
+//item_end
@@ -0,0 +1,60 @@
+//! Used to test that invocations wrapped in `cfg_attr` work both when the
+//! condition holds and when it doesn't.
+//! When wrapped, the item may carry attributes that followed the `cfg_attr`,
+//! which must not hinder the expansion.
+#![allow(non_camel_case_types, dead_code)]
+
+#[cfg_attr(feature = "gen", duplicate::duplicate_item(
+	name;
+	[SomeName1];
+	[SomeName2];
+))]
+/// Documentation between the invocation and the item
+#[derive(Debug)]
+pub struct name();
+
+#[cfg_attr(feature = "gen", duplicate::substitute_item(
+	ty [u64];
+))]
+/// Documentation between the invocation and the item
+pub type Alias = ty;
+
+/// Used when the invocations are disabled
+type ty = u32;
+
+#[cfg(feature = "module_disambiguation")]
+#[cfg_attr(feature = "gen", duplicate::duplicate_item(
+	ty;
+	[u8];
+	[u16];
+))]
+/// Documentation between the invocation and the module
+#[allow(unused_imports)]
+mod module
+{
+	use super::*;
+	pub type ModAlias = ty;
+}
+
+fn main()
+{
+	#[cfg(feature = "gen")]
+	{
+		let _ = (SomeName1(), SomeName2());
+		let _: Alias = 0u64;
+	}
+	#[cfg(not(feature = "gen"))]
+	{
+		let _ = name();
+		let _: Alias = 0u32;
+	}
+	#[cfg(all(feature = "gen", feature = "module_disambiguation"))]
+	{
+		let _: module_u8::ModAlias = 0u8;
+		let _: module_u16::ModAlias = 0u16;
+	}
+	#[cfg(all(not(feature = "gen"), feature = "module_disambiguation"))]
+	{
+		let _: module::ModAlias = 0u32;
+	}
+}
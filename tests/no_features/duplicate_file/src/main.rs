@@ -0,0 +1,31 @@
+use duplicate::{duplicate_file, substitute_file};
+
+trait Backend
+{
+	fn name(&self) -> &'static str;
+}
+
+// The template is duplicated for each substitution group
+duplicate_file!("templates/backend.rs", [
+	backend    label;
+	[Sqlite]   ["sqlite"];
+	[Postgres] ["postgres"];
+]);
+
+mod memory
+{
+	use super::*;
+
+	// The template only has the global substitutions applied
+	substitute_file!("templates/backend.rs", [
+		backend [Memory];
+		label ["memory"];
+	]);
+}
+
+fn main()
+{
+	assert_eq!(Sqlite.name(), "sqlite");
+	assert_eq!(Postgres.name(), "postgres");
+	assert_eq!(memory::Memory.name(), "memory");
+}
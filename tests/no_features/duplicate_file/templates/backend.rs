@@ -0,0 +1,9 @@
+pub struct backend;
+
+impl Backend for backend
+{
+	fn name(&self) -> &'static str
+	{
+		label
+	}
+}
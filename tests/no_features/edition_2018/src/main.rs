@@ -0,0 +1,11 @@
+//! Used to test that duplicate doesn't force its edition on the expanded code.
+#![allow(non_fmt_panics, unused_variables, unreachable_code)]
+
+#[path = "../../editions/entry_points.rs"]
+mod entry_points;
+
+fn main()
+{
+	// Before edition 2021, a single argument isn't a format string
+	entry_points::check_all("{a}");
+}
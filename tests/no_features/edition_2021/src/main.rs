@@ -1,13 +1,26 @@
-/// Used to test that duplicate doesn't force its edition on the expanded code.
+//! Used to test that duplicate doesn't force its edition on the expanded code.
+#![allow(unused_variables, unreachable_code)]
+
+#[path = "../../editions/entry_points.rs"]
+mod entry_points;
+
 /// The following code acceptable in edition 2021 but not the earlier.
 /// So if duplicate uses an earlier edition, it shouldn't result in this code being
 /// rejected.
 #[deny(non_fmt_panics)]
-fn main(){
+fn format_args()
+{
 	let a = 42;
 	// This allowed in edition 2021 but not <=2018
 	// Above deny ensured build fails if this code is treated as edition <=2018
 	duplicate::duplicate! { [foo; [];]
 		panic!("{a}");
     }
-}
\ No newline at end of file
+}
+
+fn main()
+{
+	let _ = format_args;
+	// Since edition 2021, a single argument is a format string
+	entry_points::check_all("42");
+}
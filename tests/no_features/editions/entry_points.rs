@@ -0,0 +1,188 @@
+//! Checks that the code expanded by each entry point keeps the edition of the
+//! invoking crate.
+//!
+//! Each check panics with `panic!("{a}")`, whose message is "{a}" before
+//! edition 2021 and "42" since. Therefore, if any of the tokens of the panic
+//! are given the edition of `duplicate`, the message differs from that of the
+//! invoking crate's edition.
+use duplicate::{duplicate, duplicate_item, substitute, substitute_item};
+
+#[duplicate_item(
+	name;
+	[duplicate_item_1];
+	[duplicate_item_2];
+)]
+fn name()
+{
+	let a = 42;
+	panic!("{a}");
+}
+
+#[substitute_item(
+	value [42];
+)]
+fn substitute_item()
+{
+	let a = value;
+	panic!("{a}");
+}
+
+duplicate! {
+	[
+		name;
+		[duplicate_1];
+		[duplicate_2];
+	]
+	fn name()
+	{
+		let a = 42;
+		panic!("{a}");
+	}
+}
+
+substitute! {
+	[
+		value [42];
+	]
+	fn substitute_macro()
+	{
+		let a = value;
+		panic!("{a}");
+	}
+}
+
+// The panic is given in the substitution
+#[duplicate_item(
+	name              fail;
+	[substitution_1]  [panic!("{a}")];
+	[substitution_2]  [{ panic!("{a}") }];
+)]
+fn name()
+{
+	let a = 42;
+	fail;
+}
+
+// The panic is built from parameterized substitutions and their arguments,
+// whose output is substituted again
+#[duplicate_item(
+	recursive;
+	name          fail(message)       mac;
+	[arguments_1] [panic!(message)]   [panic];
+	[arguments_2] [mac!(message)]     [panic];
+)]
+fn name()
+{
+	let a = 42;
+	fail(["{a}"]);
+}
+
+// The name of the panic macro is produced by a built-in
+duplicate! {
+	[
+		name;
+		[built_in_1];
+		[built_in_2];
+	]
+	fn name()
+	{
+		let a = 42;
+		__concat__(pan, ic)!("{a}");
+	}
+}
+
+// The panic is produced with the verbose syntax
+#[duplicate_item(
+	[
+		name [verbose_1]
+		message ["{a}"]
+	]
+	[
+		name [verbose_2]
+		message ["{a}"]
+	]
+)]
+fn name()
+{
+	let a = 42;
+	panic!(message);
+}
+
+#[cfg(feature = "nested_invocation")]
+#[duplicate_item(
+	name;
+	duplicate!{[number; [1]; [2]] [__concat__(nested_, number)];}
+)]
+fn name()
+{
+	let a = 42;
+	duplicate! {[message; ["{a}"]] panic!(message); }
+}
+
+// The module is duplicated and given unique names
+#[cfg(feature = "module_disambiguation")]
+#[duplicate_item(
+	ty;
+	[u8];
+	[u16];
+)]
+mod module
+{
+	pub fn check()
+	{
+		let a: ty = 42;
+		panic!("{a}");
+	}
+}
+
+/// Returns the message of the panic of the given function.
+fn panic_message(f: fn()) -> String
+{
+	let payload = std::panic::catch_unwind(f).unwrap_err();
+	payload
+		.downcast_ref::<&str>()
+		.map(|message| message.to_string())
+		.or_else(|| payload.downcast_ref::<String>().cloned())
+		.unwrap()
+}
+
+/// Asserts that every check panics with the given message.
+pub fn check_all(expected: &str)
+{
+	// The expected panics aren't reported
+	std::panic::set_hook(Box::new(|_| {}));
+	#[allow(unused_mut)]
+	let mut checks: Vec<(&str, fn())> = vec![
+		("duplicate_item_1", duplicate_item_1),
+		("duplicate_item_2", duplicate_item_2),
+		("substitute_item", substitute_item),
+		("duplicate_1", duplicate_1),
+		("duplicate_2", duplicate_2),
+		("substitute_macro", substitute_macro),
+		("substitution_1", substitution_1),
+		("substitution_2", substitution_2),
+		("arguments_1", arguments_1),
+		("arguments_2", arguments_2),
+		("built_in_1", built_in_1),
+		("built_in_2", built_in_2),
+		("verbose_1", verbose_1),
+		("verbose_2", verbose_2),
+	];
+	#[cfg(feature = "nested_invocation")]
+	checks.extend([("nested_1", nested_1 as fn()), ("nested_2", nested_2)]);
+	#[cfg(feature = "module_disambiguation")]
+	checks.extend([
+		("module_u8", module_u8::check as fn()),
+		("module_u16", module_u16::check),
+	]);
+	let messages: Vec<_> = checks
+		.into_iter()
+		.map(|(name, check)| (name, panic_message(check)))
+		.collect();
+	// Restore the default hook, so a failed assertion is reported
+	let _ = std::panic::take_hook();
+	for (name, message) in messages
+	{
+		assert_eq!(message, expected, "in '{}'", name);
+	}
+}
@@ -0,0 +1,49 @@
+use duplicate::{duplicate, duplicate_item, substitute};
+
+trait IsMax
+{
+	fn is_max(&self) -> bool;
+}
+
+// Substitution groups included from a file follow those in the invocation
+#[duplicate_item(
+	int_type max_value;
+	[u8] [255];
+	expand!(include!("tables/int_types.rows"))
+)]
+impl IsMax for int_type
+{
+	fn is_max(&self) -> bool
+	{
+		*self == max_value
+	}
+}
+
+// The substitution identifiers can also be included
+duplicate! {
+	[expand!(include!("tables/names.rows"))]
+	pub struct name;
+	impl name
+	{
+		const NAME: &'static str = stringify!(name);
+	}
+}
+
+// Literals are concatenated and tokens stringified in substitutions
+substitute! {
+	[
+		greeting [expand!(concat!("Hello, ", stringify!(world), '!', 1u8, true))];
+	]
+	const GREETING: &str = greeting;
+}
+
+fn main()
+{
+	assert!(255u8.is_max());
+	assert!(65_535u16.is_max());
+	assert!(4_294_967_295u32.is_max());
+	assert!(!42u32.is_max());
+	assert_eq!(First::NAME, "First");
+	assert_eq!(Second::NAME, "Second");
+	assert_eq!(GREETING, "Hello, world!1true");
+}
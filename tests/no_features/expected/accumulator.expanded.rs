@@ -0,0 +1,26 @@
+use duplicate::*;
+impl<A> Arity for (A,) {
+    const ARITY: usize = 1;
+}
+impl<A, B> Arity for (A, B) {
+    const ARITY: usize = 2;
+}
+impl<A, B, C> Arity for (A, B, C) {
+    const ARITY: usize = 3;
+}
+impl Total for (u8,) {
+    fn total(&self) -> u64 {
+        [self.0 as u64].iter().sum()
+    }
+}
+impl Total for (u8, u16) {
+    fn total(&self) -> u64 {
+        [self.0 as u64, self.1 as u64].iter().sum()
+    }
+}
+pub trait Arity {
+    const ARITY: usize;
+}
+pub trait Total {
+    fn total(&self) -> u64;
+}
@@ -0,0 +1,23 @@
+use duplicate::*;
+impl Converter<u8> for u8 {
+    fn convert(value: u8) -> u8 {
+        value
+    }
+}
+impl Converter<u16> for u16 {
+    fn convert(value: u16) -> u16 {
+        value
+    }
+}
+pub fn first(value: Option<u32>) -> Option<u32> {
+    value
+}
+pub fn second(value: Vec<u32>) -> Vec<u32> {
+    value
+}
+pub fn identity(value: i64) -> i64 {
+    value
+}
+pub trait Converter<T> {
+    fn convert(value: T) -> Self;
+}
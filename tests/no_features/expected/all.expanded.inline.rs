@@ -0,0 +1,28 @@
+use duplicate::*;
+pub const ALL: &[&str] = &["u8", "u16", "u32"];
+impl Zero for u8 {
+    const ZERO: Self = 0;
+}
+impl Zero for u16 {
+    const ZERO: Self = 0;
+}
+impl Zero for u32 {
+    const ZERO: Self = 0;
+}
+impl Named for u8 {
+    const NAME: &'static str = "u8";
+}
+impl Named for u16 {
+    const NAME: &'static str = "u16";
+}
+impl Named for u32 {
+    const NAME: &'static str = "u32";
+}
+pub const SUM: u32 = 1 + 2 + 3;
+pub type Suffixes = (u8, u32);
+pub trait Named {
+    const NAME: &'static str;
+}
+pub trait Zero {
+    const ZERO: Self;
+}
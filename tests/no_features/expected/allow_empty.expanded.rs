@@ -0,0 +1,6 @@
+use duplicate::*;
+pub enum Kept {
+    First,
+    Last,
+}
+pub struct Present(u8);
@@ -0,0 +1,14 @@
+use duplicate::*;
+#[doc(alias = "alpha")]
+#[deprecated(note = "use \"alpha\" or backend_old instead")]
+pub struct Alpha(&'static str);
+#[doc(alias = "beta")]
+#[deprecated(note = "use \"beta\" or backend_old instead")]
+pub struct Beta(&'static str);
+#[doc(alias = "name_alias")]
+#[doc(alias = "Gamma")]
+pub struct name_struct(&'static str);
+#[doc(alias = "name")]
+pub struct Delta(&'static str);
+#[doc(alias = "epsilon")]
+pub const EPSILON: &str = "backend";
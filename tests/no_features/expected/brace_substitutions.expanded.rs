@@ -0,0 +1,18 @@
+use duplicate::*;
+pub struct Bytes([u8; 4]);
+pub struct Words([u16; 2]);
+pub fn first() -> usize {
+    [0u64].len() + [1].len()
+}
+pub fn second() -> usize {
+    [0u64; 2].len() + [1; 2].len()
+}
+pub fn fn_name_1() -> u8 {
+    {
+        let x = 0;
+        x
+    }
+}
+pub fn fn_name_2() -> u8 {
+    [1][0]
+}
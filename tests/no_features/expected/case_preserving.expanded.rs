@@ -0,0 +1,18 @@
+use duplicate::*;
+pub struct Integer;
+pub mod sqlite {
+    pub struct Sqlite(pub super::Integer);
+    pub const SQLITE: &str = "backend";
+    pub const PAGE_SIZE: usize = 4096;
+    pub fn page_size(integer: super::Integer) -> Sqlite {
+        Sqlite(integer)
+    }
+}
+pub mod postgres_sql {
+    pub struct PostgresSql(pub super::Integer);
+    pub const POSTGRES_SQL: &str = "backend";
+    pub const BLOCK: usize = 4096;
+    pub fn block(integer: super::Integer) -> PostgresSql {
+        PostgresSql(integer)
+    }
+}
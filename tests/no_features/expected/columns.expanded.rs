@@ -0,0 +1,25 @@
+use duplicate::*;
+impl IsMax for u8 {
+    fn is_max(&self) -> bool {
+        *self == 255
+    }
+}
+impl IsMax for u16 {
+    fn is_max(&self) -> bool {
+        *self == 65_535
+    }
+}
+impl IsMax for u32 {
+    fn is_max(&self) -> bool {
+        *self == 4_294_967_295
+    }
+}
+pub fn first(value: Vec<u64>) -> Vec<u64> {
+    value
+}
+pub fn second(value: Option<u64>) -> Option<u64> {
+    value
+}
+pub trait IsMax {
+    fn is_max(&self) -> bool;
+}
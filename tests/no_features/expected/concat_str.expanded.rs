@@ -0,0 +1,7 @@
+use duplicate::*;
+pub fn first() -> &'static str {
+    "duplicate 0: say(\"hi\") from \"first\""
+}
+pub fn second() -> &'static str {
+    "duplicate 1: \"quoted\" from \"second\""
+}
@@ -0,0 +1,11 @@
+use duplicate::*;
+pub struct SomeName1(u8);
+pub struct SomeName2(u16);
+pub struct SomeName3(u32);
+pub struct SomeName4(u64);
+#[allow(dead_code)]
+#[doc = " Documentation"]
+pub struct SomeName5(u8);
+#[allow(dead_code)]
+#[doc = " Documentation"]
+pub struct SomeName6(u16);
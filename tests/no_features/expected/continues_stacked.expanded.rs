@@ -0,0 +1,10 @@
+use duplicate::*;
+pub trait Marker {}
+impl Marker for (u8, i8) {}
+impl Marker for (u8, i16) {}
+impl Marker for (u16, i8) {}
+impl Marker for (u16, i16) {}
+impl Marker for (u32, i32) {}
+impl Marker for (u32, i64) {}
+impl Marker for (u64, i32) {}
+impl Marker for (u64, i64) {}
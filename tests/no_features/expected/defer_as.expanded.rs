@@ -0,0 +1,16 @@
+use duplicate::*;
+mod core {
+    use super::*;
+}
+mod backend {
+    pub struct Small(pub u8);
+    pub struct Large(pub u64);
+}
+#[macro_export]
+mod wrapped {
+    pub mod generated {
+        use super::*;
+        pub const ONE: u8 = 1;
+        pub const TWO: u8 = 2;
+    }
+}
@@ -0,0 +1,26 @@
+use duplicate::*;
+pub trait Backend {
+    fn name(&self) -> &'static str;
+}
+pub struct Sqlite;
+impl Backend for Sqlite {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+}
+pub struct Postgres;
+impl Backend for Postgres {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+}
+const _: &[u8] =
+    b"pub struct backend;\n\nimpl Backend for backend\n{\n\tfn name(&self) -> &\'static str\n\t{\n\t\tlabel\n\t}\n}\n";
+pub struct Memory;
+impl Backend for Memory {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+}
+const _: &[u8] =
+    b"pub struct backend;\n\nimpl Backend for backend\n{\n\tfn name(&self) -> &\'static str\n\t{\n\t\tlabel\n\t}\n}\n";
@@ -0,0 +1,3 @@
+use duplicate::*;
+pub struct Small(u8);
+pub struct Large(Vec<u8>);
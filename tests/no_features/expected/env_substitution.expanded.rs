@@ -0,0 +1,13 @@
+use duplicate::*;
+pub fn first(value: u32) -> u8 {
+    value as u8
+}
+pub fn second(value: u32) -> u32 {
+    value
+}
+pub fn third(value: i64) -> i64 {
+    value
+}
+pub fn fourth(value: u32) -> u32 {
+    value
+}
@@ -0,0 +1,5 @@
+use duplicate::*;
+pub struct Small(pub u8);
+pub struct Large(pub u32);
+pub type First = Vec<u8>;
+pub type Third = Vec<u16>;
@@ -0,0 +1,32 @@
+use duplicate::*;
+impl From<Wrapper<u8>> for Wrapper<u16> {
+    fn from(value: Wrapper<u8>) -> Self {
+        Wrapper(value.0 as u16)
+    }
+}
+impl From<Wrapper<u8>> for Wrapper<u32> {
+    fn from(value: Wrapper<u8>) -> Self {
+        Wrapper(value.0 as u32)
+    }
+}
+impl From<Wrapper<u16>> for Wrapper<u8> {
+    fn from(value: Wrapper<u16>) -> Self {
+        Wrapper(value.0 as u8)
+    }
+}
+impl From<Wrapper<u16>> for Wrapper<u32> {
+    fn from(value: Wrapper<u16>) -> Self {
+        Wrapper(value.0 as u32)
+    }
+}
+impl From<Wrapper<u32>> for Wrapper<u8> {
+    fn from(value: Wrapper<u32>) -> Self {
+        Wrapper(value.0 as u8)
+    }
+}
+impl From<Wrapper<u32>> for Wrapper<u16> {
+    fn from(value: Wrapper<u32>) -> Self {
+        Wrapper(value.0 as u16)
+    }
+}
+pub struct Wrapper<T>(pub T);
@@ -0,0 +1,4 @@
+use duplicate::*;
+pub struct First();
+pub struct Second();
+pub struct Third();
@@ -0,0 +1,5 @@
+use duplicate::*;
+pub struct First();
+pub struct Second();
+pub struct Third();
+pub struct Fourth();
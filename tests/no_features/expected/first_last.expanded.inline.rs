@@ -0,0 +1,20 @@
+use duplicate::*;
+#[doc = " The sizes of the integer types."]
+pub struct Sizes;
+pub const SMALL: usize = std::mem::size_of::<u8>();
+pub const MEDIUM: usize = std::mem::size_of::<u16>();
+pub const LARGE: usize = std::mem::size_of::<u32>();
+#[doc = " The number of sizes."]
+pub const COUNT: usize = 2 + 1;
+pub struct Only;
+pub struct First;
+pub struct Last;
+pub fn first() -> u8 {
+    1 + 10
+}
+pub fn second() -> u8 {
+    2
+}
+pub fn third() -> u8 {
+    3 + 20
+}
@@ -0,0 +1,10 @@
+use duplicate::*;
+pub fn sum(a: u8, b: u16, c: u32) -> u32 {
+    a as u32 + b as u32 + c
+}
+pub fn call_sum() -> u32 {
+    sum(0 as u8, 1 as u16, 2 as u32)
+}
+pub fn indices() -> [u64; 2] {
+    [0 as u8 as u64, 1 as u16 as u64]
+}
@@ -0,0 +1,13 @@
+use duplicate::*;
+pub struct Example {
+    pub small: u8,
+    pub medium: u16,
+    pub large: u32,
+}
+pub fn example() -> Example {
+    Example {
+        small: 0 as u8,
+        medium: 0 as u16,
+        large: 0 as u32,
+    }
+}
@@ -0,0 +1,12 @@
+use duplicate::*;
+#[doc = " Implementation tuned for small integers."]
+pub struct Small();
+pub struct Medium();
+#[doc = " Implementation tuned for large integers."]
+#[doc = " Uses more memory."]
+pub struct Large();
+#[doc = " Implementation tuned for small integers."]
+#[doc = " An integer."]
+pub struct SmallInt();
+#[doc = " An integer."]
+pub struct LargeInt();
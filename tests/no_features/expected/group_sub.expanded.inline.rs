@@ -0,0 +1,26 @@
+use duplicate::*;
+impl From<Wrapper<u8>> for Wrapper<u16> {
+    fn from(value: Wrapper<u8>) -> Self {
+        Wrapper(value.0 as _)
+    }
+}
+impl From<Wrapper<u16>> for Wrapper<u32> {
+    fn from(value: Wrapper<u16>) -> Self {
+        Wrapper(value.0 as _)
+    }
+}
+impl From<Wrapper<u32>> for Wrapper<u8> {
+    fn from(value: Wrapper<u32>) -> Self {
+        Wrapper(value.0 as _)
+    }
+}
+pub fn first() -> (u8, u8) {
+    (3, 1)
+}
+pub fn second() -> (u8, u8) {
+    (1, 1)
+}
+pub fn third() -> (u8, u8) {
+    (2, 1)
+}
+pub struct Wrapper<T>(pub T);
@@ -0,0 +1,10 @@
+use duplicate::*;
+pub fn first_hdb578c7a() -> u8 {
+    Default::default()
+}
+pub fn second_h4af3dd74() -> Vec<u16> {
+    Default::default()
+}
+pub mod second_h4af3dd74 {
+    pub type Type = Vec<u16>;
+}
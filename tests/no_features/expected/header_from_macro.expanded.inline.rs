@@ -0,0 +1,7 @@
+use duplicate::*;
+pub struct SomeName1();
+pub struct SomeName2();
+pub struct SomeName3();
+pub struct SomeName4();
+pub struct SomeName5();
+pub struct SomeName6();
@@ -0,0 +1,17 @@
+use duplicate::*;
+impl From<Wrapper<u8>> for u8 {
+    fn from(value: Wrapper<u8>) -> Self {
+        value.0
+    }
+}
+impl From<Wrapper<u16>> for u16 {
+    fn from(value: Wrapper<u16>) -> Self {
+        value.0
+    }
+}
+impl From<Wrapper<u32>> for u32 {
+    fn from(value: Wrapper<u32>) -> Self {
+        value.0
+    }
+}
+pub struct Wrapper<T>(pub T);
@@ -0,0 +1,34 @@
+use duplicate::*;
+use std::collections::HashMap;
+pub enum Value {
+    Empty,
+    Small(u8),
+    Large(u64),
+    Sized(usize),
+    Other(bool),
+}
+pub fn to_u64(value: Value) -> u64 {
+    match value {
+        Value::Empty => 0,
+        Value::Other(_) => 1,
+        Value::Small(v) => v as u64,
+        Value::Large(v) => v as u64,
+        Value::Sized(v) => v as u64,
+    }
+}
+pub enum Generic {
+    Map(HashMap<u8, u16>),
+    Result(Result<u32, ()>),
+}
+pub enum Flag {
+    Bit0 = 1 << 0,
+    Bit1 = 1 << 1,
+    Other = 64,
+}
+pub fn describe(value: Value) -> u64 {
+    match value {
+        Value::Small(v) if (v as u64) < 10 => v as u64,
+        Value::Large(v) if (v as u64) < 10 => v as u64,
+        _ => 0,
+    }
+}
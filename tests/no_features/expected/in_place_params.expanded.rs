@@ -0,0 +1,15 @@
+use duplicate::*;
+pub fn sum(first: u64, arg0: u8, arg1: u16, arg2: u32) -> u64 {
+    first + [arg0 as u64, arg1 as u64, arg2 as u64].iter().sum::<u64>()
+}
+pub fn call_sum() -> u64 {
+    sum(0, 1 as u8, 1 as u16, 1 as u32)
+}
+pub fn Alpha() -> [u8; 0] {
+    let value_0 = [0; 0];
+    value_0
+}
+pub fn Beta() -> [u16; 1] {
+    let value_1 = [0; 1];
+    value_1
+}
@@ -0,0 +1,20 @@
+use duplicate::*;
+mod registers {
+    pub struct Registers {
+        pub control: u8,
+        pub channel_a: u8,
+        pub channel_b: u16,
+        pub channel_c: u32,
+    }
+    impl Registers {
+        pub fn new(control: u8, channel_a: u8, channel_b: u16, channel_c: u32) -> Self {
+            Self {
+                control,
+                channel_a,
+                channel_b,
+                channel_c,
+            }
+        }
+    }
+}
+pub struct Tuple(bool, Option<u8>, Vec<u16>);
@@ -0,0 +1,40 @@
+use duplicate::*;
+pub mod by_group {
+    use duplicate::*;
+    pub struct First;
+    impl First {
+        pub const NAME: &'static str = "First";
+    }
+    pub const _: () = ();
+    pub struct Second;
+    impl Second {
+        pub const NAME: &'static str = "Second";
+    }
+    pub const _: () = ();
+}
+pub mod by_group_explicit {
+    use duplicate::*;
+    pub struct First;
+    impl First {
+        pub const NAME: &'static str = "First";
+    }
+    pub const _: () = ();
+    pub struct Second;
+    impl Second {
+        pub const NAME: &'static str = "Second";
+    }
+    pub const _: () = ();
+}
+pub mod by_item {
+    use duplicate::*;
+    pub struct First;
+    pub struct Second;
+    impl First {
+        pub const NAME: &'static str = "First";
+    }
+    impl Second {
+        pub const NAME: &'static str = "Second";
+    }
+    pub const _: () = ();
+    pub const _: () = ();
+}
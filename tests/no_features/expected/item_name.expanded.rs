@@ -0,0 +1,20 @@
+use duplicate::*;
+pub fn first() -> &'static str {
+    let _: fn() -> &'static str = first;
+    "first"
+}
+pub fn second() -> &'static str {
+    let _: fn() -> &'static str = second;
+    "Second: second"
+}
+pub struct Small(u8, Option<Box<Small>>);
+pub struct Large(u16, Option<Box<Large>>);
+pub fn r#match(_x: u8) -> &'static str {
+    "r#match"
+}
+pub fn r#loop(_x: u16) -> &'static str {
+    "r#loop"
+}
+pub fn r#type(_x: u32) -> &'static str {
+    "r#type"
+}
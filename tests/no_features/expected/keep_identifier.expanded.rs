@@ -0,0 +1,25 @@
+use duplicate::*;
+pub fn get(value: &u8) -> &u8 {
+    value
+}
+pub fn get_mut(value: &mut u8) -> &mut u8 {
+    value
+}
+pub fn method(value: &u8) -> &u8 {
+    value
+}
+pub struct Renamed(pub u8);
+pub struct Name(pub u16);
+pub type First = u8;
+pub type Second = Inner;
+pub struct Inner;
+pub struct Third(pub u32);
+pub struct name(pub u64);
+pub fn Unsigned() -> bool {
+    let value: Option<u8> = Some(1u8);
+    value.is_some()
+}
+pub fn Inferred() -> bool {
+    let value: Option<_> = Some(1u8);
+    value.is_some()
+}
@@ -0,0 +1,13 @@
+use duplicate::*;
+pub fn named<'a>(x: &'a u8) -> &'a u8 {
+    x
+}
+pub fn stat<'a>(x: &'static u8) -> &'static u8 {
+    x
+}
+pub fn elided<'a>(x: &u8) -> &u8 {
+    x
+}
+pub fn ident<'a>(x: &'a u8) -> &'a u8 {
+    x
+}
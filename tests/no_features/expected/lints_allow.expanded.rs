@@ -0,0 +1,7 @@
+use duplicate::*;
+pub fn first(Int_type: u8) -> u8 {
+    Int_type
+}
+pub fn second(Int_type: u16) -> u16 {
+    Int_type
+}
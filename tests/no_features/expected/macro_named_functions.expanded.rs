@@ -0,0 +1,25 @@
+use duplicate::*;
+impl First {
+    fn duplicate() -> u8 {
+        1
+    }
+    fn substitute(self) -> Self {
+        self
+    }
+    fn is_duplicate(duplicate: u8) -> bool {
+        duplicate != Self::duplicate()
+    }
+}
+impl Second {
+    fn duplicate() -> u8 {
+        1
+    }
+    fn substitute(self) -> Self {
+        self
+    }
+    fn is_duplicate(duplicate: u8) -> bool {
+        duplicate != Self::duplicate()
+    }
+}
+pub struct First;
+pub struct Second;
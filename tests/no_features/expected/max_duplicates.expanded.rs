@@ -0,0 +1,6 @@
+use duplicate::*;
+pub struct First();
+pub struct Second();
+pub struct Third();
+pub struct Worker0();
+pub struct Worker1();
@@ -0,0 +1,17 @@
+use duplicate::*;
+async fn load_async(id: u32) -> Vec<u8> {
+    Vec::from([id as u8])
+}
+fn load_sync(id: u32) -> Vec<u8> {
+    Vec::from([id as u8])
+}
+pub async fn fetch_async(id: u32) -> impl std::future::Future<Output = Vec<u8>> + Send + 'static {
+    let data = load_async(id).await;
+    async move { data }
+}
+pub fn fetch_sync(id: u32) -> Vec<u8> {
+    let data = load_sync(id);
+    {
+        data
+    }
+}
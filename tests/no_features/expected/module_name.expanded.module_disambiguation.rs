@@ -0,0 +1,4 @@
+use duplicate::*;
+mod numbers_signed {
+    pub struct Signed(i32);
+}
@@ -0,0 +1,10 @@
+use duplicate::*;
+mod codec_u8 {
+    pub const MAX: u8 = 255;
+}
+mod codec_u16 {
+    pub const MAX: u16 = 65_535;
+}
+mod Codec32 {
+    pub const MAX: u32 = 4_294_967_295;
+}
@@ -0,0 +1,16 @@
+use duplicate::*;
+pub struct SomeName(u8);
+pub enum SomeEnum {
+    Variant1,
+    Variant2,
+}
+fn is_second(value: SomeEnum) -> bool {
+    match value {
+        SomeEnum::Variant2 => true,
+        _ => false,
+    }
+}
+fn unwrap_name(value: SomeName) -> u8 {
+    let SomeName(inner) = value;
+    inner
+}
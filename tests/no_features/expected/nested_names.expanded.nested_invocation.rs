@@ -0,0 +1,13 @@
+use duplicate::*;
+pub struct First(u8);
+pub struct Second(u16);
+impl First {
+    pub fn get(&self) -> u32 {
+        0
+    }
+}
+impl Second {
+    pub fn get(&self) -> u32 {
+        0
+    }
+}
@@ -0,0 +1,3 @@
+use duplicate::duplicate_item;
+pub const FIRST: &str = "u8 is passed through";
+pub const SECOND: &str = "u16 is passed through";
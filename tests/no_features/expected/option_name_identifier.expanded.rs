@@ -0,0 +1,6 @@
+use duplicate::*;
+pub trait Marker {}
+impl Marker for u8 {}
+impl Marker for u16 {}
+impl Marker for u32 {}
+impl Marker for u64 {}
@@ -0,0 +1,22 @@
+use duplicate::*;
+pub struct Checked(pub u32);
+pub struct Unchecked(pub u32);
+impl Checked {
+    pub fn new(value: u32) -> Self {
+        if !(value <= 100) {
+            ::core::panicking::panic("assertion failed: value <= 100")
+        };
+        Self(value)
+    }
+}
+impl Unchecked {
+    pub fn new(value: u32) -> Self {
+        Self(value)
+    }
+}
+pub fn first(value: u8) -> u8 {
+    value
+}
+pub fn second(value: u16) -> u16 {
+    value
+}
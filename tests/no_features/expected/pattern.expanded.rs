@@ -0,0 +1,16 @@
+use duplicate::*;
+pub trait Trait {
+    type Assoc;
+    fn get(&self) -> Self::Assoc;
+}
+pub fn shift_left<T: Trait<Assoc = u32>>(value: T) -> <T as Trait>::Assoc {
+    value.get() << 2
+}
+pub fn shift_right<T: Trait<Assoc = u32>>(value: T) -> u32 {
+    value.get() >> 2
+}
+pub const SUMS: [u32; 3] = {
+    let y = 1;
+    [2, (y) - 1, 1 + 2]
+};
+pub type Pattern = u16;
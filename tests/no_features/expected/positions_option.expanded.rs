@@ -0,0 +1,30 @@
+use duplicate::*;
+pub struct Wrapper<T> {
+    pub value: T,
+}
+impl Wrapper<u8> {
+    pub fn get_small(&self) -> u8 {
+        let value: u8 = self.value;
+        value
+    }
+    pub fn double(&self) -> u8 {
+        self.get_small() * 2
+    }
+}
+impl Wrapper<u64> {
+    pub fn get_large(&self) -> u64 {
+        let value: u64 = self.value;
+        value
+    }
+    pub fn double(&self) -> u64 {
+        self.get_large() * 2
+    }
+}
+pub fn first_small(values: &[u8]) -> u8 {
+    let u8 = values[0];
+    u8
+}
+pub fn first_large(values: &[u64]) -> u64 {
+    let u64 = values[0];
+    u64
+}
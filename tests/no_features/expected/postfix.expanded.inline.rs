@@ -0,0 +1,11 @@
+use duplicate::*;
+pub struct Short1(u8);
+pub struct Short2(u16);
+pub struct Verbose1();
+pub struct Verbose2();
+pub struct Global(u32);
+pub fn first(array: [u8; 2]) -> u8 {
+    match array {
+        whole @ [_, _] => whole[0],
+    }
+}
@@ -0,0 +1,3 @@
+use duplicate::*;
+pub struct Outer1(u8);
+pub struct Outer2(u8);
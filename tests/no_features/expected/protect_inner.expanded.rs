@@ -0,0 +1,25 @@
+use duplicate::*;
+pub struct Small(u8);
+pub struct Large(u64);
+impl Small {
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+    pub fn as_i32(&self) -> i32 {
+        self.0 as i32
+    }
+    pub fn as_f64(&self) -> f64 {
+        self.0 as f64
+    }
+}
+impl Large {
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+    pub fn as_i32(&self) -> i32 {
+        self.0 as i32
+    }
+    pub fn as_f64(&self) -> f64 {
+        self.0 as f64
+    }
+}
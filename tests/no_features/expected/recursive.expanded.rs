@@ -0,0 +1,6 @@
+use duplicate::*;
+pub type Bytes = Vec<[u8; 2]>;
+pub type Words = Vec<[u16; 4]>;
+pub type Boxed = Box<u32>;
+pub struct element();
+pub type Literal = Vec<element>;
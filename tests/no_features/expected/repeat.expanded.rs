@@ -0,0 +1,21 @@
+use duplicate::*;
+pub struct Worker0(pub usize);
+pub struct Worker1(pub usize);
+pub struct Worker2(pub usize);
+pub struct Worker3(pub usize);
+pub struct Worker4(pub usize);
+pub struct Worker5(pub usize);
+pub struct Worker6(pub usize);
+pub struct Worker7(pub usize);
+pub fn workers() -> [usize; 8] {
+    [
+        Worker0(0).0,
+        Worker1(1).0,
+        Worker2(2).0,
+        Worker3(3).0,
+        Worker4(4).0,
+        Worker5(5).0,
+        Worker6(6).0,
+        Worker7(7).0,
+    ]
+}
@@ -0,0 +1,10 @@
+use duplicate::*;
+pub trait Max {
+    const MAX: Self;
+}
+impl Max for u8 {
+    const MAX: Self = 255;
+}
+impl Max for u16 {
+    const MAX: Self = 65_535;
+}
@@ -0,0 +1,23 @@
+use duplicate::*;
+pub struct Small(u8);
+impl Small {
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+}
+pub struct Large(u64);
+impl Large {
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+impl Small {
+    pub fn new() -> Self {
+        Small(42)
+    }
+}
+impl Large {
+    pub fn new() -> Self {
+        Large(42)
+    }
+}
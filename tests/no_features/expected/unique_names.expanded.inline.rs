@@ -0,0 +1,35 @@
+use duplicate::*;
+pub mod small {
+    pub fn check(value: u8) -> bool {
+        value > 0
+    }
+}
+pub struct Small(u8);
+impl Small {
+    pub const ZERO: u8 = 0;
+    pub fn check(&self) -> bool {
+        self.0 > Self::ZERO
+    }
+}
+const _: u8 = 0;
+pub fn small_text() -> &'static str {
+    "Small"
+}
+pub struct SmallArray<const N: usize>([u8; N]);
+pub mod large {
+    pub fn check(value: u64) -> bool {
+        value > 0
+    }
+}
+pub struct Large(u64);
+impl Large {
+    pub const ZERO: u64 = 0;
+    pub fn check(&self) -> bool {
+        self.0 > Self::ZERO
+    }
+}
+const _: u64 = 0;
+pub fn large_text() -> &'static str {
+    "Large"
+}
+pub struct LargeArray<const N: usize>([u64; N]);
@@ -0,0 +1,4 @@
+use duplicate::*;
+pub struct Small(u8);
+pub struct Large(u64);
+pub type Alias = u32;
@@ -0,0 +1,21 @@
+use duplicate::*;
+struct Private {
+    field: u8,
+}
+pub struct Public {
+    pub field: u8,
+}
+pub(crate) struct Crate {
+    pub(crate) field: u8,
+}
+pub(crate) struct InPath {
+    pub(crate) field: u8,
+}
+fn private() {}
+pub fn public() {}
+pub struct Fields {
+    a: u8,
+    pub b: u8,
+    pub(crate) c: u8,
+}
+pub(crate) struct Untouched;
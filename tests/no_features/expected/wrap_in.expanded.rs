@@ -0,0 +1,12 @@
+use duplicate::*;
+struct Wrapper<T>(T);
+mod generated {
+    use super::*;
+    pub type Small = Wrapper<u8>;
+    pub type Large = Wrapper<u64>;
+}
+pub(crate) mod constants {
+    use super::*;
+    pub const ONE: u8 = 1;
+    pub const TWO: u8 = 2;
+}
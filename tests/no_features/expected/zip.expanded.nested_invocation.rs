@@ -0,0 +1,12 @@
+use duplicate::*;
+pub const fn max_u8() -> u8 {
+    255
+}
+pub const fn max_u16() -> u16 {
+    65_535
+}
+pub const fn max_u32() -> u32 {
+    4_294_967_295
+}
+pub struct SmallFirst(pub u8);
+pub struct SmallSecond(pub u16);
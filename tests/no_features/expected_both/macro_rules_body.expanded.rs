@@ -0,0 +1,7 @@
+use duplicate::*;
+pub struct Wrapper<T>(T);
+pub struct SomeName1(u8);
+pub struct SomeName2(u16);
+pub struct SomeName3(crate::Wrapper<u8>);
+pub struct SomeName4(crate::Wrapper<u8>);
+pub struct SomeName5(crate::Wrapper<u16>);
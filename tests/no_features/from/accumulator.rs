@@ -0,0 +1,42 @@
+
+// Test an accumulator giving the tuples of arity 1 to 3
+#[duplicate_item(
+	acc types += [ty,];
+	ty  count;
+	[A] [1];
+	[B] [2];
+	[C] [3];
+)]//duplicate_end
+impl<types> Arity for (types)
+{
+	const ARITY: usize = count;
+}
+//item_end
+
+// Test multiple accumulators with built-ins, global substitutions, and verbose
+// syntax
+#[duplicate_item(
+	acc fields += [int_type,];
+	acc widened += [self.__index__ as wide,];
+	wide [u64];
+	[ int_type [u8] ]
+	[ int_type [u16] ]
+)]//duplicate_end
+impl Total for (fields)
+{
+	fn total(&self) -> wide
+	{
+		[widened].iter().sum()
+	}
+}
+//item_end
+
+pub trait Arity
+{
+	const ARITY: usize;
+}
+
+pub trait Total
+{
+	fn total(&self) -> u64;
+}
@@ -0,0 +1,53 @@
+
+// Test that both an alias and its substitution identifier are substituted
+#[duplicate_item(
+	alias t = int_type;
+	int_type;
+	[u8];
+	[u16];
+)]//duplicate_end
+impl Converter<int_type> for t
+{
+	fn convert(value: int_type) -> t
+	{
+		value
+	}
+}
+//item_end
+
+// Test aliases of parameterized and global substitution identifiers with
+// verbose syntax
+#[duplicate_item(
+	alias w = wrapped;
+	alias n = number;
+	number [u32];
+	[
+		wrapped(inner) [Option<inner>]
+		name           [first]
+	]
+	[
+		wrapped(inner) [Vec<inner>]
+		name           [second]
+	]
+)]//duplicate_end
+pub fn name(value: wrapped([n])) -> w([number])
+{
+	value
+}
+//item_end
+
+// Test aliases when only substituting
+#[substitute_item(
+	alias ty = long_type_name;
+	long_type_name [i64];
+)]//duplicate_end
+pub fn identity(value: long_type_name) -> ty
+{
+	value
+}
+//item_end
+
+pub trait Converter<T>
+{
+	fn convert(value: T) -> Self;
+}
@@ -0,0 +1,54 @@
+
+macro_rules! impl_zero {
+	($($ty:ty),*) => {
+		$(
+			impl Zero for $ty
+			{
+				const ZERO: Self = 0;
+			}
+		)*
+	};
+}
+
+// Test that '__all__' lists the substitutions of all substitution groups, also
+// in a macro invoked once with '__first__'
+duplicate!{
+	[
+		int_type name;
+		[u8]     ["u8"];
+		[u16]    ["u16"];
+		[u32]    ["u32"];
+	]
+	__first__([
+		pub const ALL: &[&str] = &[__all__(name)];
+		impl_zero!(__all__(int_type));
+	])
+	impl Named for int_type
+	{
+		const NAME: &'static str = name;
+	}
+}
+
+// Test a separator and an optional substitution identifier that not all
+// substitution groups define
+duplicate!{
+	[
+		[ value [1] optional suffix [u8] ]
+		[ value [2] ]
+		[ value [3] optional suffix [u32] ]
+	]
+	__last__([
+		pub const SUM: u32 = __all__(value, [+]);
+		pub type Suffixes = (__all__(suffix),);
+	])
+}
+
+pub trait Named
+{
+	const NAME: &'static str;
+}
+
+pub trait Zero
+{
+	const ZERO: Self;
+}
@@ -0,0 +1,40 @@
+
+// Test that a short syntax header without substitution groups removes the item
+#[duplicate_item(
+	allow_empty;
+	name;
+)]//duplicate_end
+pub struct name(u8);
+//item_end
+
+// Test that global substitutions without substitution groups remove the item
+#[duplicate_item(
+	allow_empty;
+	ty [u16];
+)]//duplicate_end
+pub struct Removed(ty);
+//item_end
+
+// Test that in-place duplication without substitution groups removes the
+// marked fragment only
+#[duplicate_item(
+	allow_empty;
+	name;
+)]//duplicate_end
+pub enum Kept
+{
+	First,
+	#[duplicate::here]
+	name(u64),
+	Last,
+}
+//item_end
+
+// Test that items are still duplicated when substitution groups are given
+#[duplicate_item(
+	allow_empty;
+	name;
+	[Present];
+)]//duplicate_end
+pub struct name(u8);
+//item_end
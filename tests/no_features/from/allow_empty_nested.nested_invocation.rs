@@ -0,0 +1,15 @@
+
+// Test that a nested invocation producing no substitution groups removes the
+// item
+#[duplicate_item(
+	allow_empty;
+	duplicate!{
+		[
+			allow_empty;
+			name;
+		]
+		[ name [name] ]
+	}
+)]//duplicate_end
+pub struct Nested(u32);
+//item_end
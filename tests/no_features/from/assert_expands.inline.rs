@@ -0,0 +1,50 @@
+
+// Test that an invocation expanding to the expected code expands to nothing
+assert_duplicate_expands!{
+	[
+		name    ty;
+		[Small] [u8];
+		[Large] [u64];
+	]
+	{
+		pub struct name(ty);
+	}
+	expect {
+		pub struct Small(u8);
+		pub struct Large(u64);
+	}
+}
+
+// Test that spacing is ignored and verbose syntax and parameterized
+// substitution are supported
+assert_duplicate_expands!{
+	[
+		[
+			name [get_u8]
+			wrap(inner) [Vec<inner>]
+		]
+		[
+			name [get_u16]
+			wrap(inner) [Option<inner>]
+		]
+	]
+	{
+		fn name() -> wrap([u8]) { unimplemented!() }
+	}
+	expect {
+		fn get_u8()->Vec<u8>{unimplemented!()}
+		fn get_u16()->Option<u8>{unimplemented!()}
+	}
+}
+
+// Test that a generated invocation can be checked
+macro_rules! generate_newtypes {
+	($($name:ident($ty:ty))*) => {
+		assert_duplicate_expands!{
+			[ name ty; $([$name] [$ty];)* ]
+			{ pub struct name(pub ty); }
+			expect { $(pub struct $name(pub $ty);)* }
+		}
+	}
+}
+generate_newtypes!(Meters(f64) Seconds(u32));
@@ -0,0 +1,40 @@
+
+// Test that identifiers in attribute strings are substituted when requested
+#[duplicate_item(
+	attr_strings;
+	name    backend;
+	[Alpha] [alpha];
+	[Beta]  [beta];
+)]//duplicate_end
+#[cfg_attr(not(feature = "backend"), doc(alias = "backend"))]
+#[deprecated(note = "use \"backend\" or backend_old instead")]
+pub struct name(&'static str);
+//item_end
+
+#[substitute_item(
+	attr_strings;
+	name [Gamma];
+)]//duplicate_end
+#[doc(alias = "name_alias")]
+#[doc(alias = "name")]
+pub struct name_struct(&'static str);
+//item_end
+
+// Test that without the option, attribute strings are left alone
+#[duplicate_item(
+	name;
+	[Delta];
+)]//duplicate_end
+#[doc(alias = "name")]
+pub struct name(&'static str);
+//item_end
+
+// Test that strings outside attributes are left alone
+#[duplicate_item(
+	attr_strings;
+	name       backend;
+	[EPSILON]  [epsilon];
+)]//duplicate_end
+#[doc(alias = "backend")]
+pub const name: &str = "backend";
+//item_end
@@ -0,0 +1,53 @@
+
+// Test that each duplicate is gated on the feature given by its substitution,
+// whether an identifier or a literal, whose escapes are resolved
+assert_duplicate_expands!{
+	[
+		auto_cfg(feature = backend);
+		backend		name;
+		[tokio]		[TokioRuntime];
+		["async-std"]	[AsyncStdRuntime];
+		[smol]		[SmolRuntime];
+		[r"glommio"]	[GlommioRuntime];
+		["mon\x6Fio"]	[MonoioRuntime];
+	]
+	{
+		pub struct name;
+	}
+	expect {
+		#[cfg(feature = "tokio")]
+		pub struct TokioRuntime;
+		#[cfg(feature = "async-std")]
+		pub struct AsyncStdRuntime;
+		#[cfg(feature = "smol")]
+		pub struct SmolRuntime;
+		#[cfg(feature = "glommio")]
+		pub struct GlommioRuntime;
+		#[cfg(feature = "monoio")]
+		pub struct MonoioRuntime;
+	}
+}
+
+// Test that every item of a duplicate is gated
+assert_duplicate_expands!{
+	[
+		auto_cfg(target_os = os);
+		os;
+		[linux];
+		[windows];
+	]
+	{
+		struct Marker;
+		impl Marker {}
+	}
+	expect {
+		#[cfg(target_os = "linux")]
+		struct Marker;
+		#[cfg(target_os = "linux")]
+		impl Marker {}
+		#[cfg(target_os = "windows")]
+		struct Marker;
+		#[cfg(target_os = "windows")]
+		impl Marker {}
+	}
+}
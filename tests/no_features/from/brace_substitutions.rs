@@ -0,0 +1,40 @@
+// Tests that substitutions may be enclosed in '{}' instead of '[]', mixing
+// both in the same invocation.
+#[duplicate_item(
+	name		array_type;
+	[Bytes]		{ [u8; 4] };
+	{Words}		[ [u16; 2] ];
+)]//duplicate_end
+pub struct name(array_type);
+//item_end
+
+// Test verbose syntax, global substitutions, and parameterized substitution
+// arguments
+#[duplicate_item(
+	inner { 0u64 };
+	[
+		name			{ first }
+		wrapped(value)	[ [value] ]
+	]
+	[
+		name			[ second ]
+		wrapped(value)	{ [value; 2] }
+	]
+)]//duplicate_end
+pub fn name() -> usize
+{
+	wrapped({ inner }).len() + wrapped([1]).len()
+}
+//item_end
+
+// Test columns and substitutions containing braces or brackets
+#[duplicate_item(
+	columns;
+	fn_name: { fn_name_1 }            [ fn_name_2 ];
+	body:    { { let x = 0; x } }     [ [1][0] ];
+)]//duplicate_end
+pub fn fn_name() -> u8
+{
+	body
+}
+//item_end
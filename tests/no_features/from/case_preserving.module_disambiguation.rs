@@ -0,0 +1,27 @@
+pub struct Integer;
+
+// Test that case-preserving substitution identifiers are substituted in
+// snake_case, PascalCase, and SCREAMING_SNAKE_CASE, by the substitution in the
+// same case, also as global substitutions and when declared in another case
+#[duplicate_item(
+	~KIND [integer];
+	[
+		~backend		[sqlite]
+		~StorageUnit	[page_size]
+	]
+	[
+		~backend		[PostgresSql]
+		~StorageUnit	[block]
+	]
+)]//duplicate_end
+pub mod backend
+{
+	pub struct Backend(pub super::Kind);
+	pub const BACKEND: &str = "backend";
+	pub const STORAGE_UNIT: usize = 4096;
+	pub fn storage_unit(kind: super::Kind) -> Backend
+	{
+		Backend(kind)
+	}
+}
+//item_end
@@ -0,0 +1,34 @@
+
+// Test that each line can define an identifier and all its substitutions
+#[duplicate_item(
+	columns;
+	int_type:  [u8]  [u16]    [u32];
+	max_value: [255] [65_535] [4_294_967_295];
+)]//duplicate_end
+impl IsMax for int_type
+{
+	fn is_max(&self) -> bool
+	{
+		*self == max_value
+	}
+}
+//item_end
+
+// Test columns of parameterized substitution identifiers and with global
+// substitutions
+#[duplicate_item(
+	columns;
+	inner [u64];
+	name:           [first]      [second];
+	wrapped(value): [Vec<value>] [Option<value>];
+)]//duplicate_end
+pub fn name(value: wrapped([inner])) -> wrapped([inner])
+{
+	value
+}
+//item_end
+
+pub trait IsMax
+{
+	fn is_max(&self) -> bool;
+}
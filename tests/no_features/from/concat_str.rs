@@ -0,0 +1,13 @@
+
+// Test that '__concat_str__' concatenates the contents of string literals and
+// the text of substitutions, escaping any quotes or backslashes in the text
+#[duplicate_item(
+	name		message;
+	[first]		[say("hi")];
+	[second]	["\"quoted\""];
+)]//duplicate_end
+pub fn name() -> &'static str
+{
+	__concat_str__("duplicate ", __index__, ": ", message, " from \"", name, "\"")
+}
+//item_end
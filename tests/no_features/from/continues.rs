@@ -0,0 +1,28 @@
+// Tests that substitution groups can be continued by 'duplicate_item_rows'
+// attributes following the invocation.
+#[duplicate_item(
+	continues;
+	name		member;
+	[SomeName1]	[u8];
+)]//duplicate_end
+#[duplicate_item_rows([SomeName2] [u16];)]
+#[duplicate_item_rows(
+	[SomeName3]	[u32];
+	[SomeName4]	[u64]
+)]
+pub struct name(member);
+//item_end
+
+// Test that other attributes after the continuing ones are kept, that the
+// invocation doesn't need to give any substitution groups, and the attributes
+// can use the crate path
+#[duplicate_item(
+	continues;
+	name		member =>
+)]//duplicate_end
+#[duplicate::duplicate_item_rows([SomeName5] [u8] =>)]
+#[duplicate_item_rows([SomeName6] [u16])]
+#[allow(dead_code)]
+/// Documentation
+pub struct name(member);
+//item_end
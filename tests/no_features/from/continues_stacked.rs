@@ -0,0 +1,33 @@
+pub trait Marker {}
+// Tests that an invocation doesn't consume the 'duplicate_item_rows'
+// attributes continuing another invocation stacked on the item
+#[duplicate_item(
+	outer;
+	[u8];
+	[u16];
+)]//duplicate_end
+#[duplicate::duplicate_item(
+	continues;
+	inner;
+	[i8];
+)]
+#[duplicate_item_rows([i16];)]
+impl Marker for (outer, inner) {}
+//item_end
+
+// Tests that an invocation only consumes the 'duplicate_item_rows' attributes
+// before another invocation stacked on the item, which may also continue
+#[duplicate_item(
+	continues;
+	outer;
+	[u32];
+)]//duplicate_end
+#[duplicate_item_rows([u64];)]
+#[duplicate::duplicate_item(
+	continues;
+	inner;
+	[i32];
+)]
+#[duplicate_item_rows([i64];)]
+impl Marker for (outer, inner) {}
+//item_end
@@ -0,0 +1,35 @@
+// Tests that with 'defer_as', the duplicates are emitted where the generated
+// macro is invoked instead of where it is generated
+#[macro_use]
+mod core
+{
+	use super::*;
+	#[duplicate_item(
+		defer_as(instantiate);
+		name	ty;
+		[Small]	[u8];
+		[Large]	[u64];
+	)]//duplicate_end
+	pub struct name(pub ty);
+	//item_end
+}
+mod backend
+{
+	instantiate!();
+}
+
+// Tests that with 'pub', the macro is exported, and that with 'wrap_in' it
+// expands to the wrapping module
+#[duplicate_item(
+	defer_as(pub instantiate_wrapped);
+	wrap_in(pub generated);
+	name	value;
+	[ONE]	[1];
+	[TWO]	[2];
+)]//duplicate_end
+pub const name: u8 = value;
+//item_end
+mod wrapped
+{
+	instantiate_wrapped!();
+}
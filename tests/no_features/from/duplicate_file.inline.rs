@@ -0,0 +1,21 @@
+
+// The path is relative to the crate macrotest generates in
+// 'target/tests/duplicate/', so the template of the 'duplicate_file' crate is
+// reused
+pub trait Backend
+{
+	fn name(&self) -> &'static str;
+}
+
+// Test that the template is duplicated for each substitution group
+duplicate_file!("../../../../tests/no_features/duplicate_file/templates/backend.rs", [
+	backend    label;
+	[Sqlite]   ["sqlite"];
+	[Postgres] ["postgres"];
+]);
+
+// Test that the template only has the global substitutions applied
+substitute_file!("../../../../tests/no_features/duplicate_file/templates/backend.rs", [
+	backend [Memory];
+	label ["memory"];
+]);
@@ -0,0 +1,9 @@
+// Tests that a parameterized substitution identifier named 'env' in a short
+// syntax header isn't taken to be a substitution from the environment
+#[duplicate_item(
+	name	env(ty);
+	[Small]	[ty];
+	[Large]	[Vec<ty>];
+)]//duplicate_end
+pub struct name(env([u8]));
+//item_end
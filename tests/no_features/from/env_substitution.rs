@@ -0,0 +1,39 @@
+
+// Test that global substitutions can take the value of an environment variable
+// or its default
+#[duplicate_item(
+	int_type env(DUPLICATE_TEST_INT_TYPE);
+	unset_type env(DUPLICATE_TEST_UNSET, [u8]);
+	name;
+	[first];
+)]//duplicate_end
+pub fn name(value: int_type) -> unset_type
+{
+	value as unset_type
+}
+//item_end
+
+// Test environment variables in short syntax substitution groups
+#[duplicate_item(
+	name     int_type;
+	[second] env(DUPLICATE_TEST_INT_TYPE);
+	[third]  env(DUPLICATE_TEST_UNSET, [i64]);
+)]//duplicate_end
+pub fn name(value: int_type) -> int_type
+{
+	value
+}
+//item_end
+
+// Test environment variables in verbose syntax substitution groups
+#[duplicate_item(
+	[
+		name      [fourth]
+		int_type  env(DUPLICATE_TEST_INT_TYPE, [u16])
+	]
+)]//duplicate_end
+pub fn name(value: int_type) -> int_type
+{
+	value
+}
+//item_end
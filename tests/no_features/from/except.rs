@@ -0,0 +1,35 @@
+
+// Test that excluded substitution groups aren't duplicated
+#[duplicate_item(
+	name        ty;
+	[Small]     [u8];
+	[Medium]    [u16];
+	[Large]     [u32];
+	except      [Medium] [u16];
+)]//duplicate_end
+pub struct name(pub ty);
+//item_end
+
+// Test that an excluded substitution group removes all its copies and is
+// compared ignoring spacing
+#[duplicate_item(
+	name        ty;
+	[First]     [Vec<u8>];
+	except      [ Second ] [Vec < u8 >];
+	[Second]    [Vec<u8>];
+	[Second]    [Vec<u8>];
+	[Third]     [Vec<u16>];
+)]//duplicate_end
+pub type name = ty;
+//item_end
+
+// Test that excluding all substitution groups removes the item with
+// 'allow_empty'
+#[duplicate_item(
+	allow_empty;
+	name;
+	[Removed];
+	except [Removed];
+)]//duplicate_end
+pub struct name;
+//item_end
@@ -0,0 +1,23 @@
+
+// Test excluding the identity conversions from the combinations of three types
+// generated by nested invocations
+#[duplicate_item(
+	src dst;
+	duplicate!{
+		[ s; [u8]; [u16]; [u32] ]
+		duplicate!{ [ d; [u8]; [u16]; [u32] ] [s] [d]; }
+	}
+	except [u8] [u8];
+	except [u16] [u16];
+	except [u32] [u32];
+)]//duplicate_end
+impl From<Wrapper<src>> for Wrapper<dst>
+{
+	fn from(value: Wrapper<src>) -> Self
+	{
+		Wrapper(value.0 as dst)
+	}
+}
+//item_end
+
+pub struct Wrapper<T>(pub T);
@@ -0,0 +1,11 @@
+
+// Test that an invocation making the expected number of duplicates expands
+#[duplicate_item(
+	expect(3);
+	name;
+	[First];
+	[Second];
+	[Third];
+)]//duplicate_end
+pub struct name();
+//item_end
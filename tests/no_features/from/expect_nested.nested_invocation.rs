@@ -0,0 +1,11 @@
+
+// Test that the expected number of duplicates includes the substitution groups
+// produced by nested invocations
+#[duplicate_item(
+	expect(4);
+	name;
+	[First];
+	duplicate!{[nested; [Second]; [Third]; [Fourth]] [nested];}
+)]//duplicate_end
+pub struct name();
+//item_end
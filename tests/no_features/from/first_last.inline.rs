@@ -0,0 +1,45 @@
+
+// Test that '__first__' and '__last__' are only expanded in the first and last
+// duplicates, with their contents substituted
+duplicate!{
+	[
+		name     ty;
+		[SMALL]  [u8];
+		[MEDIUM] [u16];
+		[LARGE]  [u32];
+	]
+	__first__([
+		/// The sizes of the integer types.
+		pub struct Sizes;
+	])
+	pub const name: usize = std::mem::size_of::<ty>();
+	__last__([
+		/// The number of sizes.
+		pub const COUNT: usize = __index__ + 1;
+	])
+}
+
+// Test that a single duplicate is both the first and the last
+duplicate!{
+	[
+		name;
+		[Only];
+	]
+	pub struct name;
+	__first__([pub struct First;])
+	__last__([pub struct Last;])
+}
+
+// Test that they can be used in expressions
+duplicate!{
+	[
+		name     value;
+		[first]  [1];
+		[second] [2];
+		[third]  [3];
+	]
+	pub fn name() -> u8
+	{
+		value __first__([+ 10]) __last__([+ 20])
+	}
+}
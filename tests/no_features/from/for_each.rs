@@ -0,0 +1,31 @@
+
+pub fn sum(a: u8, b: u16, c: u32) -> u32
+{
+	a as u32 + b as u32 + c
+}
+
+// Test a function call with one argument per substitution group, without a
+// trailing ',', and the index of each group
+#[duplicate_item(
+	ty;
+	[u8];
+	[u16];
+	[u32];
+)]//duplicate_end
+pub fn call_sum() -> u32
+{
+	sum(__for_each__({ __index__ as ty }))
+}
+//item_end
+
+// Test array elements, ending with a ',' like the fragment
+#[duplicate_item(
+	ty;
+	[u8];
+	[u16];
+)]//duplicate_end
+pub fn indices() -> [u64; 2]
+{
+	[__for_each__([__index__ as ty as u64,])]
+}
+//item_end
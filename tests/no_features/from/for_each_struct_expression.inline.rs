@@ -0,0 +1,23 @@
+
+// Only tested with 'duplicate!', as struct expressions don't parse with
+// '__for_each__' as a field, which attribute macros require.
+pub struct Example
+{
+	pub small: u8,
+	pub medium: u16,
+	pub large: u32,
+}
+
+// Test that '__for_each__' builds a struct expression with one field per
+// substitution group, ending with a ',' like the fragment
+#[duplicate_item(
+	member	ty;
+	[small]	[u8];
+	[medium] [u16];
+	[large]	[u32];
+)]//duplicate_end
+pub fn example() -> Example
+{
+	Example { __for_each__([member: 0 as ty,]) }
+}
+//item_end
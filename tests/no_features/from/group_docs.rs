@@ -0,0 +1,29 @@
+
+// Test that doc comments before a short syntax substitution group are only
+// given to its duplicate
+#[duplicate_item(
+	name;
+	/// Implementation tuned for small integers.
+	[Small];
+	[Medium];
+	/// Implementation tuned for large integers.
+	/// Uses more memory.
+	[Large];
+)]//duplicate_end
+pub struct name();
+//item_end
+
+// Test that doc comments before a verbose syntax substitution group are only
+// given to its duplicate, before the item's own documentation
+#[duplicate_item(
+	/// Implementation tuned for small integers.
+	[
+		name [SmallInt]
+	]
+	[
+		name [LargeInt]
+	]
+)]//duplicate_end
+/// An integer.
+pub struct name();
+//item_end
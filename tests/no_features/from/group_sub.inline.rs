@@ -0,0 +1,34 @@
+
+// Test a chain of conversions from each type to the next one, where the last
+// wraps around to the first
+duplicate!{
+	[
+		int_type;
+		[u8];
+		[u16];
+		[u32];
+	]
+	impl From<Wrapper<int_type>> for Wrapper<__group_sub__(int_type, +1)>
+	{
+		fn from(value: Wrapper<int_type>) -> Self
+		{
+			Wrapper(value.0 as _)
+		}
+	}
+}
+
+// Test a negative relative index and an absolute index
+duplicate!{
+	[
+		name     value;
+		[first]  [1];
+		[second] [2];
+		[third]  [3];
+	]
+	pub fn name() -> (u8, u8)
+	{
+		(__group_sub__(value, -1), __group_sub__(value, 0))
+	}
+}
+
+pub struct Wrapper<T>(pub T);
@@ -0,0 +1,38 @@
+
+macro_rules! item {
+	($($t:tt)*) => { $($t)* };
+}
+
+// Test that '__hash__' is substituted by an identifier derived from the
+// substitutions of each substitution group
+#[duplicate_item(
+	[
+		name	[first]
+		ty		[u8]
+	]
+	[
+		name	[second]
+		ty		[Vec<u16>]
+	]
+)]//duplicate_end
+item! {
+	pub fn __concat__(name, _, __hash__)() -> ty {
+		Default::default()
+	}
+}
+//item_end
+
+// Test that the same substitutions give the same identifier, regardless of the
+// order and spacing of the substitutions
+#[duplicate_item(
+	[
+		ty		[Vec< u16 >]
+		name	[second]
+	]
+)]//duplicate_end
+item! {
+	pub mod __concat__(second, _, __hash__) {
+		pub type Type = ty;
+	}
+}
+//item_end
@@ -0,0 +1,40 @@
+// These tests ensure that 'duplicate!' and 'substitute!' work if their whole
+// argument, including the invocation, was forwarded through macro_rules
+// fragments.
+//
+// Fragments like 'expr' and 'ty' wrap the invocation in a None-delimited
+// group, while 'tt' fragments forward it unchanged.
+
+macro_rules! test_header_from_macro_expr_variable{
+	{ $header:expr, $($body:tt)* } => {
+		duplicate!{ $header $($body)* }
+	}
+}
+test_header_from_macro_expr_variable!([name; [SomeName1]], pub struct name(););
+
+macro_rules! test_header_from_macro_type_variable{
+	{ $header:ty, $($body:tt)* } => {
+		duplicate!{ $header $($body)* }
+	}
+}
+test_header_from_macro_type_variable!([name; [SomeName2]], pub struct name(););
+
+macro_rules! test_header_from_macro_forwarded_variable{
+	{ $header:expr, $($body:tt)* } => {
+		test_header_from_macro_token_tree_variable!{ $header $($body)* }
+	}
+}
+macro_rules! test_header_from_macro_token_tree_variable{
+	{ $header:tt $($body:tt)* } => {
+		duplicate!{ $header $($body)* }
+	}
+}
+test_header_from_macro_forwarded_variable!([name; [SomeName3]], pub struct name(););
+test_header_from_macro_token_tree_variable!([name; [SomeName4]; [SomeName5]] pub struct name(););
+
+macro_rules! test_substitute_from_macro_token_tree_variables{
+	{ $($all:tt)* } => {
+		substitute!{ $($all)* }
+	}
+}
+test_substitute_from_macro_token_tree_variables!([name [SomeName6];] pub struct name(););
@@ -0,0 +1,21 @@
+
+// Test that global substitutions are substituted in nested invocations in the
+// rows
+#[duplicate_item(
+	int_types [[u8]; [u16]; [u32]];
+	name  ty;
+	duplicate!{
+		[ t; int_types ]
+		[Wrapper] [t];
+	}
+)]//duplicate_end
+impl From<name<ty>> for ty
+{
+	fn from(value: name<ty>) -> Self
+	{
+		value.0
+	}
+}
+//item_end
+
+pub struct Wrapper<T>(pub T);
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+// Test that a marked enum variant is duplicated in place
+#[duplicate_item(
+	name    ty;
+	[Small] [u8];
+	[Large] [u64];
+	[Sized] [usize];
+)]//duplicate_end
+pub enum Value
+{
+	Empty,
+	#[duplicate::here]
+	name(ty),
+	Other(bool)
+}
+//item_end
+
+// Test that marked match arms are duplicated in place, and that a variant at
+// the end without a comma is duplicated with separating commas
+#[duplicate_item(
+	name    ty;
+	[Small] [u8];
+	[Large] [u64];
+	[Sized] [usize];
+)]//duplicate_end
+pub fn to_u64(value: Value) -> u64
+{
+	match value
+	{
+		Value::Empty => 0,
+		Value::Other(_) => 1,
+		#[duplicate::here]
+		Value::name(v) => v as u64
+	}
+}
+//item_end
+
+// Test global substitutions apply to the whole item
+#[duplicate_item(
+	enum_name [Generic];
+	name       ty;
+	[Map]      [HashMap<u8, u16>];
+	[Result]   [Result<u32, ()>];
+)]//duplicate_end
+pub enum enum_name
+{
+	#[duplicate::here]
+	name(ty),
+}
+//item_end
+
+// Test that a marked variant's discriminant may shift, without the '<<'
+// opening generics that would take the following variants into the fragment
+#[duplicate_item(
+	name    shift;
+	[Bit0]  [0];
+	[Bit1]  [1];
+)]//duplicate_end
+pub enum Flag
+{
+	#[duplicate::here]
+	name = 1 << shift,
+	Other = 64,
+}
+//item_end
+
+// Test that a marked match arm with a block body and no comma ends at the
+// block
+#[duplicate_item(
+	name    ty;
+	[Small] [u8];
+	[Large] [u64];
+)]//duplicate_end
+pub fn describe(value: Value) -> u64
+{
+	match value
+	{
+		#[duplicate::here]
+		Value::name(v) if (v as u64) < 10 =>
+		{
+			v as u64
+		}
+		_ => 0,
+	}
+}
+//item_end
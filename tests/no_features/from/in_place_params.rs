@@ -0,0 +1,43 @@
+
+// Test that marked function parameters are duplicated in place, with unique
+// names from concatenation with the index, and that the body can use them all
+#[duplicate_item(
+	ty;
+	[u8];
+	[u16];
+	[u32];
+)]//duplicate_end
+pub fn sum(
+	first: u64,
+	#[duplicate::here]
+	__concat__(arg, __index__): ty,
+) -> u64
+{
+	first + [#[duplicate::here] __concat__(arg, __index__) as u64].iter().sum::<u64>()
+}
+//item_end
+
+#[duplicate_item(
+	ty;
+	[u8];
+	[u16];
+	[u32];
+)]//duplicate_end
+pub fn call_sum() -> u64
+{
+	sum(0, #[duplicate::here] 1 as ty)
+}
+//item_end
+
+// Test the index and concatenation outside in-place duplication
+#[duplicate_item(
+	name    inner;
+	[Alpha] [u8];
+	[Beta]  [u16];
+)]//duplicate_end
+pub fn name() -> [inner; __index__]
+{
+	let __concat__(value_, __index__) = [0; __index__];
+	__concat__(value_, __index__)
+}
+//item_end
@@ -0,0 +1,45 @@
+
+// Test that marked struct fields are duplicated in place, including a last
+// field without a trailing comma, in both a struct and its constructor
+#[duplicate_item(
+	name        ty;
+	[channel_a] [u8];
+	[channel_b] [u16];
+	[channel_c] [u32];
+)]//duplicate_end
+mod registers
+{
+	pub struct Registers
+	{
+		pub control: u8,
+		#[duplicate::here]
+		pub name: ty
+	}
+
+	impl Registers
+	{
+		pub fn new(control: u8, #[duplicate::here] name: ty) -> Self
+		{
+			Self {
+				control,
+				#[duplicate::here]
+				name,
+			}
+		}
+	}
+}
+//item_end
+
+// Test that fields can be duplicated in tuple structs and using parameterized
+// substitutions
+#[duplicate_item(
+	ty      wrap(inner);
+	[u8]    [Option<inner>];
+	[u16]   [Vec<inner>];
+)]//duplicate_end
+pub struct Tuple(
+	bool,
+	#[duplicate::here]
+	wrap([ty]),
+);
+//item_end
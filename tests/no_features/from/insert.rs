@@ -1,4 +1,3 @@
-use duplicate::*;
 #[substitute_item(
 	name [SomeName]
 )]//duplicate_end
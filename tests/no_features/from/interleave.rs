@@ -0,0 +1,60 @@
+
+// Test that by default all items are output for each duplicate in turn
+pub mod by_group
+{
+	use duplicate::*;
+	duplicate! {
+		[
+			name;
+			[First];
+			[Second];
+		]
+		pub struct name;
+		impl name
+		{
+			pub const NAME: &'static str = stringify!(name);
+		}
+		pub const _: () = ();
+	}
+}
+
+// Test that 'interleave(by_group)' is the default
+pub mod by_group_explicit
+{
+	use duplicate::*;
+	duplicate! {
+		[
+			interleave(by_group);
+			name;
+			[First];
+			[Second];
+		]
+		pub struct name;
+		impl name
+		{
+			pub const NAME: &'static str = stringify!(name);
+		}
+		pub const _: () = ();
+	}
+}
+
+// Test that 'interleave(by_item)' outputs all duplicates of an item before the
+// next item
+pub mod by_item
+{
+	use duplicate::*;
+	duplicate! {
+		[
+			interleave(by_item);
+			name;
+			[First];
+			[Second];
+		]
+		pub struct name;
+		impl name
+		{
+			pub const NAME: &'static str = stringify!(name);
+		}
+		pub const _: () = ();
+	}
+}
@@ -0,0 +1,46 @@
+// Tests that '__item__' is the name of the item in each duplicate, both in the
+// item and in the arguments of substitutions
+#[duplicate_item(
+	name	label(item);
+	[first]	[stringify!(item)];
+	[second][concat!("Second: ", stringify!(item))];
+)]//duplicate_end
+pub fn name() -> &'static str
+{
+	let _: fn() -> &'static str = __item__;
+	label([__item__])
+}
+//item_end
+
+// Tests that '__item__' is the substituted name of the item
+#[duplicate_item(
+	name	ty;
+	[Small]	[u8];
+	[Large]	[u16];
+)]//duplicate_end
+pub struct name(ty, Option<Box<__item__>>);
+//item_end
+
+// Tests that '__item__' can be a raw identifier
+#[duplicate_item(
+	name		ty;
+	[r#match]	[u8];
+	[r#loop]	[u16];
+)]//duplicate_end
+pub fn name(_x: ty) -> &'static str
+{
+	stringify!(__item__)
+}
+//item_end
+
+// Tests that '__item__' can be an unsubstituted raw identifier
+#[duplicate_item(
+	expect(1);
+	ty;
+	[u32];
+)]//duplicate_end
+pub fn r#type(_x: ty) -> &'static str
+{
+	stringify!(__item__)
+}
+//item_end
@@ -0,0 +1,61 @@
+
+// Test that 'keep' keeps the substitution identifier as-is in short syntax
+#[duplicate_item(
+	method     reference(ty);
+	[get]      [&ty];
+	[get_mut]  [&mut ty];
+	keep       [&ty];
+)]//duplicate_end
+pub fn method(value: reference([u8])) -> reference([u8])
+{
+	value
+}
+//item_end
+
+// Test that 'keep' keeps the substitution identifier as-is in verbose syntax
+#[duplicate_item(
+	[
+		Name  [Renamed]
+		ty    [u8]
+	]
+	[
+		Name  keep
+		ty    [u16]
+	]
+)]//duplicate_end
+pub struct Name(pub ty);
+//item_end
+
+// Test that a kept identifier isn't substituted again with 'recursive'
+#[duplicate_item(
+	recursive;
+	name      Inner;
+	[First]   [u8];
+	[Second]  keep;
+)]//duplicate_end
+pub type name = Inner;
+//item_end
+
+pub struct Inner;
+
+// Test that 'keep' keeps the substitution identifier as-is in columns
+#[duplicate_item(
+	columns;
+	name:  [Third] keep;
+	ty:    [u32]   [u64];
+)]//duplicate_end
+pub struct name(pub ty);
+//item_end
+
+// Test that a substitution of '_' is just '_'
+#[duplicate_item(
+	name         ty;
+	[Unsigned]   [u8];
+	[Inferred]   [_];
+)]//duplicate_end
+pub fn name() -> bool
+{
+	let value: Option<ty> = Some(1u8);
+	value.is_some()
+}
+//item_end
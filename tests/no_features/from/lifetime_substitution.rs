@@ -0,0 +1,15 @@
+
+// Test that lifetimes can be substituted by whole lifetimes, no lifetime,
+// or an identifier (prefixed by the lifetime's tick)
+#[duplicate_item(
+	name        lt;
+	[named]     ['a];
+	[stat]      ['static];
+	[elided]    [];
+	[ident]     [a];
+)]//duplicate_end
+pub fn name<'a>(x: &'lt u8) -> &'lt u8
+{
+	x
+}
+//item_end
@@ -0,0 +1,14 @@
+// Tests that allowing the case_mismatch lint leaves identifiers differing from a
+// substitution identifier only by case unchanged, even with strict.
+#[duplicate_item(
+	strict;
+	lints(allow(case_mismatch));
+	name		int_type;
+	[first]		[u8];
+	[second]	[u16];
+)]//duplicate_end
+pub fn name(Int_type: int_type) -> int_type
+{
+	Int_type
+}
+//item_end
@@ -0,0 +1,29 @@
+
+// Test that identifiers named like the nested invocations are kept, along
+// with the tokens following them, when they aren't invocations
+#[duplicate_item(
+	name;
+	[First];
+	[Second];
+)]//duplicate_end
+impl name
+{
+	fn duplicate() -> u8
+	{
+		1
+	}
+
+	fn substitute(self) -> Self
+	{
+		self
+	}
+
+	fn is_duplicate(duplicate: u8) -> bool
+	{
+		duplicate != Self::duplicate()
+	}
+}
+//item_end
+
+pub struct First;
+pub struct Second;
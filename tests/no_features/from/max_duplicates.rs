@@ -0,0 +1,25 @@
+
+macro_rules! item {
+	($($t:tt)*) => { $($t)* };
+}
+
+// Test that an invocation may make exactly as many duplicates as its maximum
+#[duplicate_item(
+	max_duplicates(3);
+	name;
+	[First];
+	[Second];
+	[Third];
+)]//duplicate_end
+pub struct name();
+//item_end
+
+// Test that the maximum also applies to 'repeat'
+#[duplicate_item(
+	max_duplicates(2);
+	repeat(2);
+)]//duplicate_end
+item! {
+	pub struct __concat__(Worker, __index__)();
+}
+//item_end
@@ -0,0 +1,22 @@
+async fn load_async(id: u32) -> Vec<u8>
+{
+	Vec::from([id as u8])
+}
+fn load_sync(id: u32) -> Vec<u8>
+{
+	Vec::from([id as u8])
+}
+// Tests that the second duplicate is made sync by removing 'async', '.await',
+// and 'impl Future<Output = T>'
+#[duplicate_item(
+	maybe_async;
+	name	load;
+	[fetch_async]	[load_async];
+	[fetch_sync]	[load_sync];
+)]//duplicate_end
+pub async fn name(id: u32) -> impl std::future::Future<Output = Vec<u8>> + Send + 'static
+{
+	let data = load(id).await;
+	async move { data }
+}
+//item_end
@@ -0,0 +1,10 @@
+// Tests that modules are disambiguated when 'module_disambiguation' is enabled
+#[duplicate_item(
+	int_type;
+	[Signed];
+)]//duplicate_end
+mod numbers
+{
+	pub struct int_type(i32);
+}
+//item_end
@@ -0,0 +1,13 @@
+// Test that 'module_names' names each duplicated module exactly
+#[duplicate_item(
+	module_names([codec_u8], {codec_u16}, [Codec32]);
+	int_type	max_value;
+	[u8]		[255];
+	[u16]		[65_535];
+	[u32]		[4_294_967_295];
+)]//duplicate_end
+mod codec
+{
+	pub const MAX: int_type = max_value;
+}
+//item_end
@@ -1,4 +1,4 @@
-use duplicate::*;
+//expected_unchecked: implements foreign traits with made-up generics
 struct Example{one: u8, two: u8}
 // Tests nesting in substitutions
 #[substitute_item(
@@ -0,0 +1,27 @@
+pub struct SomeName(u8);
+pub enum SomeEnum
+{
+	Variant1,
+	Variant2,
+}
+
+// Test that the output of nested invocations can be used in pattern position
+#[substitute_item(
+	variant [Variant2];
+)]//duplicate_end
+fn is_second(value: SomeEnum) -> bool {
+	match value {
+		substitute!{[path [SomeEnum::variant]] path} => true,
+		_ => false,
+	}
+}
+//item_end
+
+#[substitute_item(
+	name [SomeName];
+)]//duplicate_end
+fn unwrap_name(value: name) -> u8 {
+	let duplicate!{[ty; [name]] ty(inner)} = value;
+	inner
+}
+//item_end
@@ -0,0 +1,38 @@
+
+// A facade re-exporting the macros under other names
+macro_rules! my_dup {
+	($($tokens:tt)*) => { duplicate!{ $($tokens)* } }
+}
+macro_rules! my_sub {
+	($($tokens:tt)*) => { substitute!{ $($tokens)* } }
+}
+
+// Test that invocations with the given names are expanded as nested
+// invocations in the header
+#[duplicate_item(
+	nested_names(my_dup, my_sub);
+	name ty;
+	my_dup!{[n t; [First] [u8]; [Second] [u16]] [n] [t];}
+)]//duplicate_end
+pub struct name(ty);
+//item_end
+
+// Test that invocations with the given names are expanded as nested
+// invocations in the body
+#[duplicate_item(
+	nested_names(my_dup, my_sub);
+	name;
+	[First];
+	[Second];
+)]//duplicate_end
+impl name
+{
+	my_sub!{
+		[inner [u32];]
+		pub fn get(&self) -> inner
+		{
+			0
+		}
+	}
+}
+//item_end
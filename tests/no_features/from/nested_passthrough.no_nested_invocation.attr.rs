@@ -0,0 +1,20 @@
+//no_prelude
+use duplicate::duplicate_item;
+
+// A local macro named like the crate's inline macro, which needs the
+// 'nested_invocation' feature to be disabled to be used in the body
+macro_rules! duplicate {
+	($name:ident [$($t:tt)*]) => {
+		pub const $name: &str = stringify!($($t)*);
+	};
+}
+
+// Test that 'duplicate!' in the body isn't mistaken for a nested invocation,
+// but is passed through with only substitutions applied
+#[duplicate_item(
+	name		ty;
+	[FIRST]		[u8];
+	[SECOND]	[u16];
+)]//duplicate_end
+duplicate! { name [ty is passed through] }
+//item_end
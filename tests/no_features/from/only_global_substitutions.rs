@@ -1,4 +1,3 @@
-use duplicate::*;
 #[substitute_item(
 	ty 		[u16];
 )]//duplicate_end
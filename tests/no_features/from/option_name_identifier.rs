@@ -0,0 +1,21 @@
+pub trait Marker {}
+// Tests that an option name followed by substitution groups is the
+// substitution identifier of the short syntax instead of an option
+#[duplicate_item(
+	columns;
+	[u8];
+	[u16];
+)]//duplicate_end
+impl Marker for columns {}
+//item_end
+
+// Tests that an option followed by a short syntax header using an option name
+// is still an option
+#[duplicate_item(
+	strict;
+	validate;
+	[u32];
+	[u64];
+)]//duplicate_end
+impl Marker for validate {}
+//item_end
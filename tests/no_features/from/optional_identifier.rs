@@ -0,0 +1,56 @@
+//expected_unchecked: 'assert!' expands to the compiler's unstable internals
+
+// Test that optional substitution identifiers can be used where only the
+// groups defining them reach
+#[duplicate_item(
+	[
+		name                [Checked]
+		when_checked(code)  [code]
+		optional limit      [100]
+	]
+	[
+		name                [Unchecked]
+		when_checked(code)  []
+	]
+)]//duplicate_end
+pub struct name(pub u32);
+//item_end
+
+#[duplicate_item(
+	[
+		name                [Checked]
+		when_checked(code)  [code]
+		optional limit      [100]
+	]
+	[
+		name                [Unchecked]
+		when_checked(code)  []
+	]
+)]//duplicate_end
+impl name
+{
+	pub fn new(value: u32) -> Self
+	{
+		when_checked([assert!(value <= limit)]);
+		Self(value)
+	}
+}
+//item_end
+
+// Test that an optional identifier needn't be the same across groups and that
+// 'optional' can itself be a substitution identifier
+#[duplicate_item(
+	[
+		optional  [first]
+		optional extra [u8]
+	]
+	[
+		optional  [second]
+		optional extra [u16]
+	]
+)]//duplicate_end
+pub fn optional(value: extra) -> extra
+{
+	value
+}
+//item_end
@@ -0,0 +1,47 @@
+
+pub trait Trait
+{
+	type Assoc;
+	fn get(&self) -> Self::Assoc;
+}
+
+// Test that patterns are substituted differently in each duplicate, both a
+// qualified path and an operator made of two tokens
+#[duplicate_item(
+	[
+		name                      [shift_left]
+		pattern [Self::Assoc]  => [<T as Trait>::Assoc]
+		pattern [<<]           => [<<]
+	]
+	[
+		name                      [shift_right]
+		pattern [Self::Assoc]  => [u32]
+		pattern [<<]           => [>>]
+	]
+)]//duplicate_end
+pub fn name<T: Trait<Assoc = u32>>(value: T) -> Self::Assoc
+{
+	value.get() << 2
+}
+//item_end
+
+// Test that the longest match is substituted, that matches don't cross group
+// delimiters, and that the tokens of a match aren't otherwise substituted
+#[substitute_item(
+	x [y];
+	pattern [+ 1] => [- 1];
+	pattern [x +] => [1 +];
+	pattern [x + 1] => [2];
+)]//duplicate_end
+pub const SUMS: [u32; 3] = {
+	let y = 1;
+	[x + 1, (x) + 1, x + 2]
+};
+//item_end
+
+// Test that 'pattern' without '=>' is a substitution identifier
+#[substitute_item(
+	pattern [u16];
+)]//duplicate_end
+pub type Pattern = pattern;
+//item_end
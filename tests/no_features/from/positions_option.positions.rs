@@ -0,0 +1,43 @@
+
+pub struct Wrapper<T>
+{
+	pub value: T,
+}
+
+// Test that restricted substitution identifiers are only substituted in their
+// positions, 'value' in types and 'get' in method calls and definitions
+#[duplicate_item(
+	positions(value@type, get@method);
+	value  get;
+	[u8]   [get_small];
+	[u64]  [get_large];
+)]//duplicate_end
+impl Wrapper<value>
+{
+	pub fn get(&self) -> value
+	{
+		let value: value = self.value;
+		value
+	}
+
+	pub fn double(&self) -> value
+	{
+		self.get() * 2
+	}
+}
+//item_end
+
+// Test that if the item can't be parsed, restricted substitution identifiers
+// are substituted in all positions
+#[duplicate_item(
+	positions(value@type);
+	name           value  reference(ty);
+	[first_small]  [u8]   [&ty];
+	[first_large]  [u64]  [&ty];
+)]//duplicate_end
+pub fn name(values: reference([[value]])) -> value
+{
+	let value = values[0];
+	value
+}
+//item_end
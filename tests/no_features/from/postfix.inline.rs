@@ -0,0 +1,35 @@
+
+// Test that the invocation can follow the code it applies to, preceded by '@'
+duplicate!{
+	pub struct name(ty);
+	@ [
+		name	ty;
+		[Short1] [u8];
+		[Short2] [u16];
+	]
+}
+
+// Test postfix verbose syntax
+duplicate!{
+	pub struct name();
+	@ [
+		[ name [Verbose1] ]
+		[ name [Verbose2] ]
+	]
+}
+
+// Test postfix global substitutions
+substitute!{
+	pub struct Global(ty);
+	@ [ ty [u32]; ]
+}
+
+// Test that a body ending with '@ [...]' is kept whole when the invocation
+// comes first
+pub fn first(array: [u8; 2]) -> u8
+{
+	match array
+	{
+		substitute!{[bound [whole];] bound @ [_, _]} => whole[0],
+	}
+}
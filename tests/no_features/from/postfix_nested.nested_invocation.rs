@@ -0,0 +1,13 @@
+
+// Test that nested invocations can be postfix and be nested in postfix
+// invocations
+duplicate!{
+	substitute!{
+		pub struct name(ty);
+		@ [ ty [u8]; ]
+	}
+	@ [
+		name;
+		duplicate!{ [ group; [Outer1]; [Outer2] ] [group]; }
+	]
+}
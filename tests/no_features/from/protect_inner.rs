@@ -0,0 +1,30 @@
+
+pub struct Small(u8);
+pub struct Large(u64);
+
+// Test that with 'protect_inner', the header of an inner invocation isn't
+// substituted, and neither are its substitution identifiers in the item it
+// applies to, so both invocations can use 'ty' independently
+#[duplicate_item(
+	protect_inner;
+	name	ty;
+	[Small]	[u8];
+	[Large]	[u64];
+)]//duplicate_end
+impl name
+{
+	pub fn get(&self) -> ty
+	{
+		self.0
+	}
+
+	#[duplicate_item(method	ty;
+		[as_i32]	[i32];
+		[as_f64]	[f64];
+	)]
+	pub fn method(&self) -> ty
+	{
+		self.0 as ty
+	}
+}
+//item_end
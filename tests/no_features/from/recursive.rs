@@ -0,0 +1,34 @@
+
+// Test that substitution identifiers in substitutions are substituted with
+// 'recursive', both from the same substitution group and from global
+// substitutions
+#[duplicate_item(
+	recursive;
+	list [Vec<element>];
+	name     element       inner;
+	[Bytes]  [[inner; 2]]  [u8];
+	[Words]  [[inner; 4]]  [u16];
+)]//duplicate_end
+pub type name = list;
+//item_end
+
+// Test that arguments and the output of parameterized substitutions are
+// substituted again
+#[substitute_item(
+	recursive;
+	inner [u32];
+	wrap(ty) [Box<ty>];
+	boxed [wrap([inner])];
+)]//duplicate_end
+pub type Boxed = boxed;
+//item_end
+
+pub struct element();
+
+// Test that without the option, substitutions are output as is
+#[substitute_item(
+	list [Vec<element>];
+	element [u8];
+)]//duplicate_end
+pub type Literal = list;
+//item_end
@@ -0,0 +1,29 @@
+
+macro_rules! item {
+	($($t:tt)*) => { $($t)* };
+}
+
+// Test that the item is repeated the given number of times, using '__index__'
+// and '__concat__' to give each a unique name
+#[duplicate_item(
+	repeat(8);
+)]//duplicate_end
+item! {
+	pub struct __concat__(Worker, __index__)(pub usize);
+}
+//item_end
+
+// Test that repeating composes with global substitutions and in-place
+// duplication
+#[duplicate_item(
+	repeat(8);
+	ty [usize];
+)]//duplicate_end
+pub fn workers() -> [ty; 8]
+{
+	[
+		#[duplicate::here]
+		__concat__(Worker, __index__)(__index__).0,
+	]
+}
+//item_end
@@ -0,0 +1,22 @@
+
+pub trait Max {
+	const MAX: Self;
+}
+
+// Test that an invocation whose substitution groups define exactly the required
+// substitution identifiers expands
+#[duplicate_item(
+	require(int_type, max_value);
+	[
+		int_type	[u8]
+		max_value	[255]
+	]
+	[
+		max_value	[65_535]
+		int_type	[u16]
+	]
+)]//duplicate_end
+impl Max for int_type {
+	const MAX: Self = max_value;
+}
+//item_end
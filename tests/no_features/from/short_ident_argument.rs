@@ -1,4 +1,4 @@
-use duplicate::*;
+//expected_unchecked: functions have empty bodies
 // Test single-token argument
 #[duplicate_item(
 	fn_name 		refs(type);
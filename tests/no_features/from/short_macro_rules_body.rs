@@ -0,0 +1,36 @@
+// These tests ensure that declarative macro definitions can be duplicated.
+//
+// Identifiers following '$' are metavariables and must not be substituted,
+// even if they are equal to a substitution identifier.
+//expected_unchecked: refers to its own items through 'crate::'
+pub struct Wrapper<T>(T);
+
+#[duplicate_item(
+	macro_name	ty;
+	[make_u8]	[u8];
+	[make_u16]	[u16];
+)]//duplicate_end
+macro_rules! macro_name {
+	($ty:ident) => {
+		pub struct $ty(ty);
+	};
+}
+//item_end
+make_u8!(SomeName1);
+make_u16!(SomeName2);
+
+#[duplicate_item(
+	macro_name			ty;
+	[make_wrapped_u8]	[u8];
+	[make_wrapped_u16]	[u16];
+)]//duplicate_end
+macro_rules! macro_name {
+	($($duplicate:ident),*) => {
+		$(
+			pub struct $duplicate($crate::Wrapper<ty>);
+		)*
+	};
+}
+//item_end
+make_wrapped_u8!(SomeName3, SomeName4);
+make_wrapped_u16!(SomeName5);
@@ -1,4 +1,3 @@
-use duplicate::*;
 #[duplicate_item(
 	name;
 	duplicate!{[ some_name; [SomeName1]; [SomeName2] ]
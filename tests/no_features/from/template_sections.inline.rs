@@ -0,0 +1,30 @@
+
+// Test that all template sections are duplicated with the same substitution
+// groups, one section after the other, where section-local global
+// substitutions only apply to their section
+duplicate!{
+	[
+		name    ty;
+		[Small] [u8];
+		[Large] [u64];
+	]
+	template struct_def {
+		pub struct name(ty);
+		impl name
+		{
+			pub fn value(&self) -> ty
+			{
+				self.0
+			}
+		}
+	}
+	template constructor [ value [42]; ] {
+		impl name
+		{
+			pub fn new() -> Self
+			{
+				name(value)
+			}
+		}
+	}
+}
@@ -0,0 +1,36 @@
+// Test that with 'unique_names', items with the same name are accepted in
+// modules and impl blocks named differently in each duplicate, as are those
+// with a 'cfg' attribute and unnamed constants. Keywords in lifetimes and
+// generics don't define items either
+duplicate!{
+	[
+		unique_names;
+		module	name	ty		text			array;
+		[small]	[Small]	[u8]	[small_text]	[SmallArray];
+		[large]	[Large]	[u64]	[large_text]	[LargeArray];
+	]
+	pub mod module
+	{
+		pub fn check(value: ty) -> bool
+		{
+			value > 0
+		}
+	}
+	pub struct name(ty);
+	impl name
+	{
+		pub const ZERO: ty = 0;
+		pub fn check(&self) -> bool
+		{
+			self.0 > Self::ZERO
+		}
+	}
+	#[cfg(any())]
+	pub const DISABLED: ty = 0;
+	const _: ty = 0;
+	pub fn text() -> &'static str
+	{
+		stringify!(name)
+	}
+	pub struct array<const N: usize>([ty; N]);
+}
@@ -0,0 +1,18 @@
+
+// Test that valid duplicates are output unchanged by 'validate'
+#[duplicate_item(
+	validate;
+	name	ty;
+	[Small]	[u8];
+	[Large]	[u64];
+)]//duplicate_end
+pub struct name(ty);
+//item_end
+
+// Test that the output of 'substitute_item' is also validated
+#[substitute_item(
+	validate;
+	ty [u32];
+)]//duplicate_end
+pub type Alias = ty;
+//item_end
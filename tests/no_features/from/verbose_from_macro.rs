@@ -4,7 +4,7 @@
 // Each test consists of a macro_rules declaration which uses
 // some specific macro variable type to no_features to the duplicate invocation.
 // Then the created macro is invoked.
-use duplicate::*;
+//expected_unchecked: binds variables without types
 
 macro_rules! test_ident_from_macro_variable{
 	{ $name:ident } => {
@@ -1,4 +1,3 @@
-use duplicate::*;
 #[duplicate_item(
 	duplicate!{[ some_name; [SomeName1]; [SomeName2] ]
 		[ name	[some_name] ]
@@ -1,4 +1,3 @@
-use duplicate::*;
 #[duplicate_item(
 	[
 		name	[SomeName1]
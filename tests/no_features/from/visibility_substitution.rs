@@ -0,0 +1,44 @@
+
+// Test that the visibility placeholder is replaced by empty and
+// non-empty visibilities for structs, functions, and fields
+#[duplicate_item(
+	name        vis;
+	[Private]   [];
+	[Public]    [pub];
+	[Crate]     [pub(crate)];
+	[InPath]    [pub(in crate)];
+)]//duplicate_end
+pub(in vis) struct name
+{
+	pub(in vis) field: u8,
+}
+//item_end
+
+#[duplicate_item(
+	name        vis;
+	[private]   [];
+	[public]    [pub];
+)]//duplicate_end
+pub(in vis) fn name() {}
+//item_end
+
+#[duplicate_item(
+	name    vis;
+	[a]     [];
+	[b]     [pub];
+	[c]     [pub(crate)];
+)]//duplicate_end
+pub struct Fields
+{
+	#[duplicate::here]
+	pub(in vis) name: u8,
+}
+//item_end
+
+// Test that other visibilities are untouched
+#[duplicate_item(
+	name;
+	[Untouched];
+)]//duplicate_end
+pub(in crate) struct name;
+//item_end
@@ -0,0 +1,21 @@
+struct Wrapper<T>(T);
+// Tests that with 'wrap_in', all duplicates are put in a private module that
+// imports everything from the invocation's module
+#[duplicate_item(
+	wrap_in(generated);
+	name	ty;
+	[Small]	[u8];
+	[Large]	[u64];
+)]//duplicate_end
+pub type name = Wrapper<ty>;
+//item_end
+
+// Tests that the module is given the visibility before its name
+#[duplicate_item(
+	wrap_in(pub(crate) constants);
+	name	value;
+	[ONE]	[1];
+	[TWO]	[2];
+)]//duplicate_end
+pub const name: u8 = value;
+//item_end
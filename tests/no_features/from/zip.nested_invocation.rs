@@ -0,0 +1,40 @@
+
+macro_rules! item {
+	($($t:tt)*) => { $($t)* };
+}
+
+// Test that 'zip' combines the substitution groups produced by nested
+// invocations pairwise
+#[duplicate_item(
+	zip {
+		duplicate!{[ ty; [u8]; [u16]; [u32] ]
+			[ name [ty] ]
+		}
+	} {
+		duplicate!{[ max; [255]; [65_535]; [4_294_967_295] ]
+			[ max_value [max] ]
+		}
+	}
+)]//duplicate_end
+item! {
+	pub const fn __concat__(max_, name)() -> name {
+		max_value
+	}
+}
+//item_end
+
+// Test that the sides may use different syntaxes and that global
+// substitutions come before 'zip'
+#[duplicate_item(
+	prefix [Small];
+	zip {
+		name; [First]; [Second];
+	} {
+		[ ty [u8] ]
+		[ ty [u16] ]
+	}
+)]//duplicate_end
+item! {
+	pub struct __concat__(prefix, name)(pub ty);
+}
+//item_end
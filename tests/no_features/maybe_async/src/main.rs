@@ -0,0 +1,96 @@
+use duplicate::{duplicate, duplicate_item};
+use std::{
+	future::Future,
+	pin::pin,
+	sync::Arc,
+	task::{Context, Poll, Wake},
+};
+
+// Tests that both duplicates are made, the second without 'async' and '.await'
+#[duplicate_item(
+	maybe_async;
+	add;
+	[add_async];
+	[add_sync];
+)]
+async fn add(x: u32, y: u32) -> u32
+{
+	x + y
+}
+
+#[duplicate_item(
+	maybe_async;
+	double          add;
+	[double_async]  [add_async];
+	[double_sync]   [add_sync];
+)]
+async fn double(x: u32) -> u32
+{
+	let sum = add(x, x).await;
+	let block = async move { sum };
+	block.await
+}
+
+// Tests that 'impl Future' is replaced by its output, including any bounds,
+// and that the duplicates are gated by the predicate
+#[duplicate_item(maybe_async(feature = "async"))]
+fn triple(x: u32) -> impl std::future::Future<Output = u32> + Send + 'static
+{
+	let product = async move { x * 3 };
+	product
+}
+
+// Tests that every item is gated
+duplicate! {
+	[
+		maybe_async(feature = "async");
+		double;
+		[double_async];
+		[double_sync];
+	]
+	async fn quadruple(x: u32) -> u32
+	{
+		double(double(x).await).await
+	}
+	async fn quadruple_plus(x: u32, y: u32) -> u32
+	{
+		quadruple(x).await + y
+	}
+}
+
+/// Polls the given future to completion.
+fn block_on<T>(future: impl Future<Output = T>) -> T
+{
+	struct Noop;
+	impl Wake for Noop
+	{
+		fn wake(self: Arc<Self>) {}
+	}
+	let waker = Arc::new(Noop).into();
+	let mut context = Context::from_waker(&waker);
+	let mut future = pin!(future);
+	loop
+	{
+		if let Poll::Ready(result) = future.as_mut().poll(&mut context)
+		{
+			return result;
+		}
+	}
+}
+
+fn main()
+{
+	assert_eq!(block_on(double_async(2)), 4);
+	assert_eq!(double_sync(2), 4);
+
+	#[cfg(feature = "async")]
+	{
+		assert_eq!(block_on(triple(2)), 6);
+		assert_eq!(block_on(quadruple_plus(2, 1)), 9);
+	}
+	#[cfg(not(feature = "async"))]
+	{
+		assert_eq!(triple(2), 6);
+		assert_eq!(quadruple_plus(2, 1), 9);
+	}
+}
@@ -1,25 +1,36 @@
 #[test]
 fn test_expansions()
 {
-	crate::utils::ExpansionTester::run_default_test_setup("tests/no_features", "testing");
+	let mut test =
+		crate::utils::ExpansionTester::default_test_setup("tests/no_features", "testing", false);
+	// Used by the 'env_substitution' test
+	test.set_env("DUPLICATE_TEST_INT_TYPE", "u32");
+	test.execute_tests();
 }
 
-/// Test that using the crate from a edition 2021 crate works, even if
-/// the use expands to code that only works with edition 2021.
+/// Test that the code expanded by each entry point keeps the edition of the
+/// invoking crate, both for edition 2018 and 2021 crates, instead of getting
+/// the edition of this crate.
+///
+/// The edition 2021 crate also checks that code only working with edition 2021
+/// is accepted.
 #[rustversion::since(1.56)]
 #[test]
-fn test_edition_2021()
+fn test_editions()
 {
-	let output = std::process::Command::new("cargo")
-		.arg("build")
-		.current_dir("tests/no_features/edition_2021")
-		.output()
-		.unwrap();
-	assert!(
-		output.status.success(),
-		"Failed to build edition 2021: {:?}",
-		output
-	);
+	let mut features = Vec::new();
+	if crate::utils::FEATURE_MODULE_DISAMBIGUATION
+	{
+		features.push("module_disambiguation");
+	}
+	if crate::utils::FEATURE_NESTED_INVOCATION
+	{
+		features.push("nested_invocation");
+	}
+	for edition in ["edition_2018", "edition_2021"]
+	{
+		crate::utils::run_fixture_crate(&format!("tests/no_features/{}", edition), &features);
+	}
 }
 
 /// Tests that nowhere in the source code do we call `Group::new` as that has
@@ -44,3 +55,120 @@ fn ensure_no_group_new()
 		);
 	}
 }
+
+/// Test that invocations wrapped in `cfg_attr` compile both when the condition
+/// holds and when it doesn't.
+#[test]
+fn test_cfg_attr()
+{
+	for gen in [false, true]
+	{
+		let mut features = Vec::new();
+		if gen
+		{
+			features.push("gen");
+		}
+		if crate::utils::FEATURE_MODULE_DISAMBIGUATION
+		{
+			features.push("module_disambiguation");
+		}
+		let output = std::process::Command::new("cargo")
+			.arg("build")
+			.arg("--features")
+			.arg(features.join(","))
+			.current_dir("tests/no_features/cfg_attr")
+			.output()
+			.unwrap();
+		assert!(
+			output.status.success(),
+			"Failed to build cfg_attr (features: {:?}): {:?}",
+			features,
+			output
+		);
+	}
+}
+
+/// Test that substitution groups can be read from a file relative to the
+/// invoking crate's manifest directory.
+#[test]
+fn test_rows_from()
+{
+	crate::utils::run_fixture_crate("tests/no_features/rows_from", &[]);
+}
+
+/// Test that `expand!` in invocations expands `include!` of files relative to
+/// the invoking crate's manifest directory, `concat!`, and `stringify!`.
+#[test]
+fn test_expand()
+{
+	crate::utils::run_fixture_crate("tests/no_features/expand", &[]);
+}
+
+/// Test that the contents of a file relative to the invoking crate's manifest
+/// directory can be duplicated and substituted.
+#[test]
+fn test_duplicate_file()
+{
+	crate::utils::run_fixture_crate("tests/no_features/duplicate_file", &[]);
+}
+
+/// Test that 'maybe_async' makes working async and sync duplicates, both with
+/// and without the feature gating them.
+#[test]
+fn test_maybe_async()
+{
+	for features in [&[][..], &["async"]]
+	{
+		crate::utils::run_fixture_crate("tests/no_features/maybe_async", features);
+	}
+}
+
+/// Test that the expanded code keeps the spans of the code it originates from.
+///
+/// The 'spans' crate deliberately fails to compile, so we check that the
+/// reported errors point to the expected locations.
+///
+/// Spans can only be inspected inside a procedural macro, and this crate
+/// doesn't depend on 'proc_macro2' for unit tests to inspect them with, so
+/// the locations the compiler reports are used as the span assertions.
+#[test]
+fn test_spans()
+{
+	let mut features = Vec::new();
+	if crate::utils::FEATURE_MODULE_DISAMBIGUATION
+	{
+		features.push("module_disambiguation");
+	}
+	let output = std::process::Command::new("cargo")
+		.arg("build")
+		.arg("--features")
+		.arg(features.join(","))
+		.current_dir("tests/no_features/spans")
+		.output()
+		.unwrap();
+	let stderr = String::from_utf8_lossy(&output.stderr);
+
+	assert!(!output.status.success(), "'spans' compiled: {:?}", output);
+	let mut expected = vec![
+		// Body token
+		"src/main.rs:14:2",
+		// Group in parameterized substitution
+		"src/main.rs:20:15",
+		// Substitution
+		"src/main.rs:29:15",
+	];
+	if crate::utils::FEATURE_MODULE_DISAMBIGUATION
+	{
+		// Disambiguated module name (reported by 'name_span')
+		expected.push("error: Module 'module_first'\n  --> src/main.rs:42:5");
+	}
+	for location in expected
+	{
+		assert!(
+			stderr.contains(location),
+			"Expected error at '{}': {}",
+			location,
+			stderr
+		);
+	}
+}
@@ -0,0 +1,3 @@
+// Prepended to every source file of these tests (see
+// 'ExpansionTester' in 'tests/utils.rs').
+use duplicate::*;
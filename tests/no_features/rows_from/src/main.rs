@@ -0,0 +1,48 @@
+use duplicate::{duplicate, duplicate_item};
+
+trait IsMax
+{
+	fn is_max(&self) -> bool;
+}
+
+// Substitution groups from the file follow those in the invocation, and escapes
+// in the path are resolved
+#[duplicate_item(
+	rows_from("tables\x2Fint_types.dup");
+	int_type max_value;
+	[u8] [255];
+)]
+impl IsMax for int_type
+{
+	fn is_max(&self) -> bool
+	{
+		*self == max_value
+	}
+}
+
+trait Bits
+{
+	const BITS: u32;
+}
+
+// All substitution groups can come from the file, whose path may be a raw
+// string literal
+duplicate! {
+	[
+		rows_from(r#"tables/int_types.dup"#);
+		int_type max_value;
+	]
+	impl Bits for int_type
+	{
+		const BITS: u32 = (max_value as int_type).count_ones();
+	}
+}
+
+fn main()
+{
+	assert!(255u8.is_max());
+	assert!(65_535u16.is_max());
+	assert!(!42u32.is_max());
+	assert_eq!(<u16 as Bits>::BITS, 16);
+	assert_eq!(<u32 as Bits>::BITS, 32);
+}
@@ -0,0 +1,37 @@
+//! Used by the 'spans' crate to test the spans of item names, which the
+//! compiler doesn't report errors at by itself.
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Replaces the module it is put on with an error at the module's name,
+/// reporting the name.
+#[proc_macro_attribute]
+pub fn error_at_name(_: TokenStream, item: TokenStream) -> TokenStream
+{
+	let name = item
+		.into_iter()
+		.skip_while(|t| !matches!(t, TokenTree::Ident(id) if id.to_string() == "mod"))
+		.nth(1)
+		.expect("Expected a module");
+	compile_error(&format!("Module '{}'", name), name.span())
+}
+
+/// Produces `compile_error!("message");` with all tokens having the given span.
+fn compile_error(message: &str, span: Span) -> TokenStream
+{
+	let mut message = Literal::string(message);
+	message.set_span(span);
+	let mut body = Group::new(Delimiter::Parenthesis, TokenTree::from(message).into());
+	body.set_span(span);
+	let mut bang = Punct::new('!', Spacing::Alone);
+	bang.set_span(span);
+	let mut semicolon = Punct::new(';', Spacing::Alone);
+	semicolon.set_span(span);
+	[
+		TokenTree::from(Ident::new("compile_error", span)),
+		bang.into(),
+		body.into(),
+		semicolon.into(),
+	]
+	.into_iter()
+	.collect()
+}
@@ -0,0 +1,44 @@
+//! Used to test that expanded code keeps the spans of the code it originates
+//! from.
+//!
+//! This crate must fail to compile. Each error is deliberate and is expected to
+//! be reported at a specific location (see 'test_spans').
+use duplicate::duplicate_item;
+
+// Body tokens keep their spans
+#[duplicate_item(
+	fn_name;
+	[fn_name_1];
+)]
+fn fn_name() -> u8 {
+	"body"
+}
+
+// Groups in parameterized substitutions get the span of the definition
+#[duplicate_item(
+	fn_name		wrap(value);
+	[fn_name_2]	[(value, "group")];
+)]
+fn fn_name() -> u8 {
+	wrap([1])
+}
+
+// Substitutions keep the spans of their definition
+#[duplicate_item(
+	fn_name		value;
+	[fn_name_3]	["substitution"];
+)]
+fn fn_name() -> u8 {
+	value
+}
+
+// Disambiguated module names keep the span of the original name
+#[cfg(feature = "module_disambiguation")]
+#[duplicate_item(
+	name;
+	[First];
+)]
+#[name_span::error_at_name]
+mod module {}
+
+fn main() {}
@@ -1,7 +1,10 @@
 #[cfg(feature = "default")]
 mod default_features;
 mod errors;
+mod fuzz;
+mod identity;
 #[cfg(feature = "module_disambiguation")]
 mod module_disambiguation;
 mod no_features;
+mod ui;
 mod utils;
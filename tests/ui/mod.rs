@@ -0,0 +1,344 @@
+//! UI tests comparing the full compiler output of errors against snapshots.
+//!
+//! The basic, hint, and highlight error tests only check parts of the output.
+//! These tests additionally check the exact location (line and column) and code
+//! frame the user sees, for a representative set of the error sources in
+//! 'tests/errors/source' (in both attribute and inline versions).
+//!
+//! Notes and warnings, which don't fail compilation, are likewise checked for
+//! the sources in 'tests/ui/notes' and 'tests/ui/warnings'.
+//!
+//! The errors are checked on all compilers, with separate snapshots for
+//! nightly, so they are also checked along with the notes and warnings, which
+//! only nightly compilers give.
+//!
+//! These tests don't use 'trybuild', since it can't be fetched in all the
+//! environments the tests run in; the normalization below instead removes
+//! the parts of the output that differ between environments.
+//!
+//! Set the 'DUPLICATE_UI_BLESS' environment variable to overwrite the
+//! snapshots with the current output.
+use crate::utils::{
+	ExpansionTester, FEATURE_MODULE_DISAMBIGUATION, FEATURE_NESTED_INVOCATION, FEATURE_POSITIONS,
+	FEATURE_PRETTY_ERRORS, FEATURE_VALIDATE,
+};
+use std::{
+	path::Path,
+	process::{Command, Output},
+	sync::Mutex,
+};
+
+/// Held while using 'tests/ui/src/bin', since tests run concurrently.
+static BIN_DIR: Mutex<()> = Mutex::new(());
+
+/// The error sources in 'tests/errors/source' to test.
+const CASES: &[&str] = &[
+	"global_sub_semicolon",
+	"marker_without_groups",
+	"parameters_not_encapsulated",
+	"short_missing_substitution",
+	"verbose_semicolon",
+];
+
+/// The sources in 'tests/ui/notes' to test the notes of.
+#[rustversion::nightly]
+const NOTE_CASES: &[&str] = &[];
+
+/// The sources in 'tests/ui/notes' to test the notes of that are only given
+/// with module disambiguation.
+#[rustversion::nightly]
+const MODULE_NOTE_CASES: &[&str] = &["explain_module_names"];
+
+/// The sources in 'tests/ui/warnings' to test the warnings of.
+#[rustversion::nightly]
+const WARNING_CASES: &[&str] = &[
+	"empty_substitution_warn",
+	"lints_warn",
+	"single_group",
+	"single_group_intended",
+];
+
+/// The sources in 'tests/ui/warnings' to test the warnings of that are only
+/// given with nested invocations.
+#[rustversion::nightly]
+const NESTED_WARNING_CASES: &[&str] = &["nested_shadowing"];
+
+/// Removes everything from the output of cargo but the diagnostics of the given
+/// level (e.g. "error"), making paths relative to the crate and removing any
+/// terminal colors.
+fn normalize(stderr: &str, level: &str) -> String
+{
+	// Colors in messages may be escaped by rustc as '␛'
+	let stderr = regex::Regex::new("(\x1b|␛)\\[[0-9;]*m")
+		.unwrap()
+		.replace_all(stderr, "");
+	let mut result = String::new();
+	// Cargo's and rustc's output is separated into blocks by empty lines
+	for block in stderr.split("\n\n")
+	{
+		let block = block
+			.lines()
+			.filter(|line| !line.trim_start().starts_with("Compiling"))
+			.filter(|line| !line.trim_start().starts_with("Checking"))
+			.filter(|line| !line.trim_start().starts_with("Blocking"))
+			.collect::<Vec<_>>()
+			.join("\n");
+		if block.starts_with(level) && !block.starts_with("error: could not compile")
+		{
+			result += &block.replace("src/bin/", "$DIR/");
+			result += "\n\n";
+		}
+	}
+	result
+}
+
+/// Prefixes the directories of the error snapshots.
+///
+/// On nightly, errors are emitted directly instead of through
+/// 'compile_error!', which formats them differently, so they have their own
+/// snapshots.
+#[rustversion::nightly]
+const ERROR_SNAPSHOTS_PREFIX: &str = "nightly_";
+#[rustversion::not(nightly)]
+const ERROR_SNAPSHOTS_PREFIX: &str = "";
+
+#[test]
+fn ui_errors()
+{
+	check_snapshots(
+		CASES,
+		Path::new("tests/errors/source"),
+		&format!(
+			"{}{}",
+			ERROR_SNAPSHOTS_PREFIX,
+			if FEATURE_PRETTY_ERRORS
+			{
+				"pretty_errors"
+			}
+			else
+			{
+				"no_pretty_errors"
+			}
+		),
+		"error",
+	);
+}
+
+/// Notes are only emitted by nightly compilers and with 'pretty_errors'.
+#[rustversion::nightly]
+#[test]
+fn ui_notes()
+{
+	if FEATURE_PRETTY_ERRORS
+	{
+		check_snapshots(NOTE_CASES, Path::new("tests/ui/notes"), "notes", "note");
+		if FEATURE_MODULE_DISAMBIGUATION
+		{
+			check_snapshots(
+				MODULE_NOTE_CASES,
+				Path::new("tests/ui/notes"),
+				"notes",
+				"note",
+			);
+		}
+	}
+}
+
+/// Warnings are only emitted by nightly compilers and with 'pretty_errors'.
+#[rustversion::nightly]
+#[test]
+fn ui_warnings()
+{
+	if FEATURE_PRETTY_ERRORS
+	{
+		check_snapshots(
+			WARNING_CASES,
+			Path::new("tests/ui/warnings"),
+			"warnings",
+			"warning",
+		);
+		if FEATURE_NESTED_INVOCATION
+		{
+			check_snapshots(
+				NESTED_WARNING_CASES,
+				Path::new("tests/ui/warnings"),
+				"warnings",
+				"warning",
+			);
+		}
+	}
+}
+
+/// Compiles each of the given cases in 'source_dir' (in both attribute and
+/// inline versions) and compares the diagnostics of the given level against
+/// the snapshots in the given directory.
+///
+/// Cases must fail to compile if, and only if, the level is "error".
+fn check_snapshots(cases: &[&str], source_dir: &Path, snapshot_dir: &str, level: &str)
+{
+	let _lock = BIN_DIR.lock().unwrap_or_else(|err| err.into_inner());
+	let bin_dir = Path::new("tests/ui/src/bin");
+	let _ = std::fs::remove_dir_all(bin_dir);
+	std::fs::create_dir_all(bin_dir).unwrap();
+
+	let snapshot_dir = Path::new("tests/ui").join(snapshot_dir);
+	let bless = std::env::var_os("DUPLICATE_UI_BLESS").is_some();
+
+	let mut failures = Vec::new();
+	for case in cases
+	{
+		let source = source_dir.join(format!("{}.rs", case));
+		ExpansionTester::duplicate_for_inline()(&source, &bin_dir);
+
+		for name in [case.to_string(), format!("inline_{}", case)]
+		{
+			let output = check_bin(&name, &[]);
+			if level == "error"
+			{
+				assert!(!output.status.success(), "'{}' compiled", name);
+			}
+			else
+			{
+				assert!(output.status.success(), "'{}' failed to compile", name);
+			}
+			let actual = normalize(&String::from_utf8_lossy(&output.stderr), level);
+
+			let snapshot = snapshot_dir.join(format!("{}.stderr", name));
+			if bless
+			{
+				std::fs::create_dir_all(&snapshot_dir).unwrap();
+				std::fs::write(&snapshot, &actual).unwrap();
+			}
+			else
+			{
+				let expected = std::fs::read_to_string(&snapshot).unwrap_or_default();
+				if expected != actual
+				{
+					failures.push(format!(
+						"{:?}:\n--- Expected:\n{}--- Actual:\n{}",
+						snapshot, expected, actual
+					));
+				}
+			}
+		}
+	}
+	assert!(
+		failures.is_empty(),
+		"UI test output differs from snapshots (set 'DUPLICATE_UI_BLESS' to update):\n{}",
+		failures.join("\n")
+	);
+}
+
+/// Checks the binary with the given name in 'tests/ui/src/bin' with the enabled
+/// features and the given extra features of 'duplicate', returning the output
+/// of cargo.
+///
+/// Adds a main function to the binary first.
+fn check_bin(name: &str, extra_features: &[&str]) -> Output
+{
+	let bin = Path::new("tests/ui/src/bin").join(format!("{}.rs", name));
+	let mut content = std::fs::read_to_string(&bin).unwrap();
+	content += "fn main() {}\n";
+	std::fs::write(&bin, content).unwrap();
+
+	let mut features = extra_features.to_vec();
+	if FEATURE_PRETTY_ERRORS
+	{
+		features.push("pretty_errors");
+	}
+	if FEATURE_MODULE_DISAMBIGUATION
+	{
+		features.push("module_disambiguation");
+	}
+	if FEATURE_NESTED_INVOCATION
+	{
+		features.push("nested_invocation");
+	}
+	if FEATURE_POSITIONS
+	{
+		features.push("positions");
+	}
+	if FEATURE_VALIDATE
+	{
+		features.push("validate");
+	}
+
+	Command::new("cargo")
+		.arg("check")
+		.arg("--quiet")
+		.arg("--bin")
+		.arg(name)
+		.arg("--no-default-features")
+		.arg("--features")
+		.arg(features.join(","))
+		.current_dir("tests/ui")
+		.output()
+		.unwrap()
+}
+
+/// With the 'trace' feature, every invocation prints its metrics to stderr.
+///
+/// Since the time taken varies, only the counts are checked.
+#[test]
+fn ui_trace()
+{
+	let _lock = BIN_DIR.lock().unwrap_or_else(|err| err.into_inner());
+	let bin_dir = Path::new("tests/ui/src/bin");
+	let _ = std::fs::remove_dir_all(bin_dir);
+	std::fs::create_dir_all(bin_dir).unwrap();
+	std::fs::copy(
+		"tests/ui/trace/invocations.rs",
+		bin_dir.join("invocations.rs"),
+	)
+	.unwrap();
+
+	let output = check_bin("invocations", &["trace"]);
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(
+		output.status.success(),
+		"'invocations' failed to compile:\n{}",
+		stderr
+	);
+
+	let trace = regex::Regex::new(
+		"duplicate trace: '(duplicate|substitute)' invocation with ([0-9]+) substitution groups, \
+		 ([0-9]+) input tokens, ([0-9]+) output tokens, and ([0-9]+) nested invocations took",
+	)
+	.unwrap();
+	let traces: Vec<_> = trace
+		.captures_iter(&stderr)
+		.map(|captures| {
+			let count = |i: usize| captures[i].parse::<usize>().unwrap();
+			(
+				captures[1].to_string(),
+				count(2),
+				count(3),
+				count(4),
+				count(5),
+			)
+		})
+		.collect();
+	assert_eq!(traces.len(), 3, "Expected 3 traces:\n{}", stderr);
+	for (name, groups, input, output, nested) in traces
+	{
+		match (name.as_str(), groups)
+		{
+			// 'IsMax' implemented 3 times
+			("duplicate", 3) =>
+			{
+				assert_eq!(nested, 0);
+				assert!(output > input, "{} output for {} input", output, input);
+			},
+			// The nested invocation, making 2 structs
+			("duplicate", 2) => assert_eq!(nested, 0),
+			// The outer invocation, whose nested invocation is otherwise expanded by the
+			// compiler
+			("substitute", 0) =>
+			{
+				assert_eq!(nested, FEATURE_NESTED_INVOCATION as usize);
+				assert!(output < input, "{} output for {} input", output, input);
+			},
+			_ => panic!("Unexpected trace of '{}' with {} groups", name, groups),
+		}
+	}
+}
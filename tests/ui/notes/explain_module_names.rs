@@ -0,0 +1,14 @@
+use duplicate::*;
+// Tests that a note explains which substitution identifier module
+// disambiguation uses and the resulting module names
+#[duplicate_item(
+	explain_module_names;
+	int_type	name;
+	[u8]		[First];
+	[u16]		[Second];
+)]//duplicate_end
+mod module
+{
+	pub type name = int_type;
+}
+//item_end
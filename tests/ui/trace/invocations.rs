@@ -0,0 +1,31 @@
+// 'duplicate' is only used without the 'nested_invocation' feature
+#[allow(unused_imports)]
+use duplicate::{duplicate, duplicate_item, substitute};
+
+pub trait IsMax
+{
+	fn is_max(&self) -> bool;
+}
+
+#[duplicate_item(
+	int_type;
+	[u8];
+	[u16];
+	[u32];
+)]
+impl IsMax for int_type
+{
+	fn is_max(&self) -> bool
+	{
+		*self == int_type::MAX
+	}
+}
+
+substitute! {
+	[ member_type [u64]; ]
+	pub type Word = member_type;
+	duplicate! {
+		[ name; [First]; [Second]; ]
+		pub struct name(pub member_type);
+	}
+}
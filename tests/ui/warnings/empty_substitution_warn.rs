@@ -0,0 +1,15 @@
+use duplicate::*;
+// Tests that warning about the empty_substitution lint reports an empty
+// substitution in a substitution group and an empty argument.
+#[duplicate_item(
+	lints(warn(empty_substitution));
+	name		vis		list(rest);
+	[first]		[pub]	[vec![1, rest]];
+	[second]	[]		[vec![2, rest]];
+)]//duplicate_end
+#[allow(dead_code)]
+pub(in vis) fn name() -> (Vec<u8>, Vec<u8>)
+{
+	(list([3]), list([]))
+}
+//item_end
@@ -0,0 +1,16 @@
+use duplicate::*;
+// Tests that warning about the case_mismatch lint leaves identifiers differing
+// from a substitution identifier only by case unchanged, even with strict.
+#[duplicate_item(
+	strict;
+	lints(warn(case_mismatch));
+	name		int_type;
+	[first]		[u8];
+	[second]	[u16];
+)]//duplicate_end
+#[allow(non_snake_case)]
+pub fn name(Int_type: int_type) -> int_type
+{
+	Int_type
+}
+//item_end
@@ -0,0 +1,18 @@
+use duplicate::*;
+// Tests that a nested invocation in the header is warned about if one of its
+// substitution identifiers is also one of the enclosing invocation, but not if
+// it uses its own.
+pub trait Shadowed {}
+pub trait NotShadowed {}
+#[duplicate_item(
+	int_type;
+	duplicate!{[int_type; [u8]; [u16]] [int_type];}
+)]//duplicate_end
+impl Shadowed for int_type {}
+//item_end
+#[duplicate_item(
+	int_type;
+	duplicate!{[int_type_nested; [u8]; [u16]] [int_type_nested];}
+)]//duplicate_end
+impl NotShadowed for int_type {}
+//item_end
@@ -0,0 +1,11 @@
+use duplicate::*;
+// Tests that a warning suggests 'substitute_item' for only one substitution group
+#[duplicate_item(
+	name		ty;
+	[example]	[u8];
+)]//duplicate_end
+pub fn name() -> ty
+{
+	0
+}
+//item_end
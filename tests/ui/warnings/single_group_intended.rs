@@ -0,0 +1,23 @@
+use duplicate::*;
+// Tests that single substitution groups aren't warned about when they are
+// expected or the 'single_group' lint is allowed.
+#[duplicate_item(
+	expect(1);
+	name		ty;
+	[expected]	[u8];
+)]//duplicate_end
+pub fn name() -> ty
+{
+	0
+}
+//item_end
+#[duplicate_item(
+	lints(allow(single_group));
+	name		ty;
+	[allowed]	[u8];
+)]//duplicate_end
+pub fn name() -> ty
+{
+	0
+}
+//item_end
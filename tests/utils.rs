@@ -1,19 +1,39 @@
 use std::{
-	ffi::OsString,
+	ffi::{OsStr, OsString},
 	fs::File,
 	io::{BufRead, BufReader, Write},
-	path::Path,
+	ops::Range,
+	path::{Path, PathBuf},
+	sync::Mutex,
 };
 
 /// Whether the `pretty_errors` feature is enabled.
 pub const FEATURE_PRETTY_ERRORS: bool = cfg!(feature = "pretty_errors");
 /// Whether the `module_disambiguation` feature is enabled.
 pub const FEATURE_MODULE_DISAMBIGUATION: bool = cfg!(feature = "module_disambiguation");
+/// Whether the `nested_invocation` feature is enabled.
+pub const FEATURE_NESTED_INVOCATION: bool = cfg!(feature = "nested_invocation");
+/// Whether the `positions` feature is enabled.
+pub const FEATURE_POSITIONS: bool = cfg!(feature = "positions");
+/// Whether the `validate` feature is enabled.
+pub const FEATURE_VALIDATE: bool = cfg!(feature = "validate");
 /// The number of enabled features.
-pub const NR_FEATURES: usize =
-	0 + FEATURE_PRETTY_ERRORS as usize + FEATURE_MODULE_DISAMBIGUATION as usize;
+pub const NR_FEATURES: usize = 0
+	+ FEATURE_PRETTY_ERRORS as usize
+	+ FEATURE_MODULE_DISAMBIGUATION as usize
+	+ FEATURE_NESTED_INVOCATION as usize
+	+ FEATURE_POSITIONS as usize
+	+ FEATURE_VALIDATE as usize;
 /// A list of the enabled features.
 const FEATURES: [&'static str; NR_FEATURES] = get_features();
+/// A list of all features, enabled or not.
+const ALL_FEATURES: [&'static str; 5] = [
+	"pretty_errors",
+	"module_disambiguation",
+	"nested_invocation",
+	"positions",
+	"validate",
+];
 
 /// Returns a list of enabled features.
 const fn get_features() -> [&'static str; NR_FEATURES]
@@ -28,9 +48,663 @@ const fn get_features() -> [&'static str; NR_FEATURES]
 	{
 		features[FEATURE_PRETTY_ERRORS as usize] = "module_disambiguation";
 	}
+	#[cfg(feature = "nested_invocation")]
+	{
+		features[FEATURE_PRETTY_ERRORS as usize + FEATURE_MODULE_DISAMBIGUATION as usize] =
+			"nested_invocation";
+	}
+	#[cfg(feature = "positions")]
+	{
+		features[FEATURE_PRETTY_ERRORS as usize
+			+ FEATURE_MODULE_DISAMBIGUATION as usize
+			+ FEATURE_NESTED_INVOCATION as usize] = "positions";
+	}
+	#[cfg(feature = "validate")]
+	{
+		features[FEATURE_PRETTY_ERRORS as usize
+			+ FEATURE_MODULE_DISAMBIGUATION as usize
+			+ FEATURE_NESTED_INVOCATION as usize
+			+ FEATURE_POSITIONS as usize] = "validate";
+	}
 	features
 }
 
+/// Selects files based on which features are enabled and which variant of a
+/// test they are for.
+///
+/// A file with an extension that is a feature (e.g.
+/// `name.expanded.module_disambiguation.rs`) is only used when that feature is
+/// enabled, while one with an extension that is a feature prefixed with `no_`
+/// (e.g. `name.expanded.no_module_disambiguation.rs`) is only used when it is
+/// disabled.
+///
+/// Likewise, a file with the extension `inline` is only used for the inline
+/// variant of a test (see `ExpansionTester::duplicate_for_inline`), whose file
+/// names start with `inline_`, while one with the extension `attr` is only
+/// used for the attribute variant.
+///
+/// Such a file is used in place of the file without those extensions (e.g.
+/// `name.expanded.rs`).
+///
+/// Returns the name to use the file with and whether it is specific to the
+/// enabled features or the variant, or `None` if the file shouldn't be used.
+fn select_file(file_name: &OsStr) -> Option<(OsString, bool)>
+{
+	let file_name = file_name.to_str().unwrap();
+	let inline = file_name.starts_with("inline_");
+	let mut specific = false;
+	let mut parts = Vec::new();
+	for part in file_name.split('.')
+	{
+		let (feature, required) = match part.strip_prefix("no_")
+		{
+			Some(feature) => (feature, false),
+			None => (part, true),
+		};
+		let selected = if ALL_FEATURES.contains(&feature)
+		{
+			FEATURES.contains(&feature) == required
+		}
+		else if part == "inline" || part == "attr"
+		{
+			(part == "inline") == inline
+		}
+		else
+		{
+			parts.push(part);
+			continue;
+		};
+		if !selected
+		{
+			return None;
+		}
+		specific = true;
+	}
+	Some((parts.join(".").into(), specific))
+}
+
+/// Whether the given file name has no extensions requiring features that
+/// aren't enabled or excluding features that are (see `select_file`).
+pub fn for_enabled_features(file_name: &str) -> bool
+{
+	file_name.split('.').all(|part| {
+		let (feature, required) = match part.strip_prefix("no_")
+		{
+			Some(feature) => (feature, false),
+			None => (part, true),
+		};
+		!ALL_FEATURES.contains(&feature) || FEATURES.contains(&feature) == required
+	})
+}
+
+/// The start of the line that, in a source file of an expansion test, marks
+/// that its expected output shouldn't be type-checked (see
+/// `ExpansionTester::check_expected`), followed by the reason, e.g. that it
+/// intentionally references undefined items.
+const EXPECTED_UNCHECKED: &str = "//expected_unchecked";
+
+/// The file in the home directory of expansion tests whose contents are
+/// prepended to each of their source files (see `ExpansionTester`).
+const PRELUDE_FILE: &str = "prelude.rs";
+
+/// The line that, in a source file of an expansion test, marks that the
+/// prelude of its home directory shouldn't be prepended to it.
+const NO_PRELUDE: &str = "//no_prelude";
+
+/// The subdirectory of the testing directory holding the crate that
+/// type-checks expected outputs (see `ExpansionTester::check_expected`).
+const CHECK_EXPECTED_DIR: &str = "check_expected";
+
+/// The environment variable that, when set, makes expansion tests overwrite
+/// their expected outputs instead of failing (see `ExpansionTester`).
+const BLESS_VAR: &str = "DUPLICATE_BLESS";
+
+/// The extension of the expected outputs in testing directories.
+const EXPANDED_EXTENSION: &str = ".expanded.rs";
+
+/// The number of unchanged lines shown around each change in the diffs of
+/// expected and actual outputs.
+const DIFF_CONTEXT: usize = 3;
+
+/// The files written to testing directories by
+/// `ExpansionTester::copy_with_prefix_postfix`, each with the source file it
+/// was copied from, such that blessed outputs can be written back to it.
+///
+/// Later entries replace earlier ones for the same file, like the copies do.
+static COPIED_FROM: Mutex<Vec<(PathBuf, PathBuf)>> = Mutex::new(Vec::new());
+
+/// Returns the given section of the given combined expectation file of an
+/// error test that applies to the enabled features, if it has one.
+///
+/// Instead of separate files with the expected basic error message, hint, and
+/// highlight of a source file, a combined file has the line `//message`,
+/// `//hint`, or `//highlight` before each of those it checks, e.g.:
+///
+/// ```text
+/// //message
+/// Unknown position.
+/// //highlight
+/// 3 | positions(ty@field);
+///   |              ^^^^^
+/// ```
+///
+/// Like file names (see `select_file`), a label may have feature extensions,
+/// such that the section only applies with (or without) those features and
+/// takes precedence over the section without extensions. E.g. the basic
+/// message, which includes what was found when `pretty_errors` is disabled,
+/// can be given for both configurations:
+///
+/// ```text
+/// //message.pretty_errors
+/// Expected '['.
+/// //message.no_pretty_errors
+/// Expected '[' (found ';').
+/// ```
+pub fn expected_section(content: &str, section: &str) -> Option<String>
+{
+	let mut result: Option<(bool, String)> = None;
+	for (label, lines) in error_sections(content)
+	{
+		let mut parts = label.split('.');
+		if parts.next() != Some(section) || !for_enabled_features(label)
+		{
+			continue;
+		}
+		let specific = parts.next().is_some();
+		if result.as_ref().map_or(true, |(s, _)| specific && !s)
+		{
+			result = Some((specific, lines));
+		}
+	}
+	result.map(|(_, lines)| lines)
+}
+
+/// Whether the given combined expectation file of an error test has the given
+/// section for any features (see `expected_section`).
+pub fn declares_section(content: &str, section: &str) -> bool
+{
+	error_sections(content)
+		.iter()
+		.any(|(label, _)| label.split('.').next() == Some(section))
+}
+
+/// Splits the given combined expectation file of an error test into its
+/// sections, each with its label (without the `//`) and contents.
+fn error_sections(content: &str) -> Vec<(&str, String)>
+{
+	let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+	for line in content.lines()
+	{
+		let label = line
+			.strip_prefix("//")
+			.filter(|label| ERROR_SECTIONS.contains(&label.split('.').next().unwrap()));
+		match (label, sections.last_mut())
+		{
+			(Some(label), _) => sections.push((label, Vec::new())),
+			(None, Some((_, lines))) => lines.push(line),
+			(None, None) => (),
+		}
+	}
+	sections
+		.into_iter()
+		.map(|(label, lines)| (label, lines.join("\n").trim_end().to_owned()))
+		.collect()
+}
+
+/// The sections of combined expectation files of error tests (see
+/// `expected_section`).
+const ERROR_SECTIONS: [&str; 3] = ["message", "hint", "highlight"];
+
+/// Returns the path in the destination directory that the given file name
+/// should be written to, taking the enabled features and variant into account
+/// (see `select_file`).
+///
+/// Returns `None` if the file shouldn't be written, either because it is for
+/// other features or the other variant, or because a file specific to the
+/// enabled features or variant has already been written in its place.
+fn destination_for(destination: &Path, file_name: &OsStr) -> Option<PathBuf>
+{
+	let (file_name, specific) = select_file(file_name)?;
+	let mut destination_file = destination.to_path_buf();
+	destination_file.push(file_name);
+	if !specific && destination_file.exists()
+	{
+		None
+	}
+	else
+	{
+		Some(destination_file)
+	}
+}
+
+/// Returns the lines of the prelude in the home directory of the given
+/// testing directory, if it has one (see `ExpansionTester`).
+fn prelude_lines(destination: &Path) -> Vec<String>
+{
+	destination
+		.parent()
+		.and_then(|home| std::fs::read_to_string(home.join(PRELUDE_FILE)).ok())
+		.map_or_else(Vec::new, |prelude| {
+			prelude.lines().map(String::from).collect()
+		})
+}
+
+/// Writes the given lines of a source file to the given file name in the
+/// destination directory, and the inline version of them (see
+/// `ExpansionTester::duplicate_for_inline`) to the name prefixed with
+/// 'inline_'.
+///
+/// The lines are preceded by the prelude of the home directory, unless they
+/// include the `//no_prelude` marker.
+fn write_with_inline(lines: impl Iterator<Item = String>, file_name: &OsStr, destination: &Path)
+{
+	let mut inline_file_name = OsString::from("inline_");
+	inline_file_name.push(file_name);
+
+	// Written to the destination afterwards, as a file might only be needed in one
+	// variant (see 'select_file')
+	let mut dest_file = Vec::new();
+	let mut dest_inline_file = Vec::new();
+
+	let lines: Vec<_> = lines.collect();
+	let prelude = if lines.iter().any(|line| line.trim() == NO_PRELUDE)
+	{
+		Vec::new()
+	}
+	else
+	{
+		prelude_lines(destination)
+	};
+
+	for line in prelude.into_iter().chain(lines)
+	{
+		let line = line.trim();
+
+		match line
+		{
+			"#[duplicate_item(" =>
+			{
+				dest_file.write_all("#[duplicate_item(".as_bytes()).unwrap();
+				dest_inline_file
+					.write_all("duplicate!{[".as_bytes())
+					.unwrap();
+			},
+			"#[substitute_item(" =>
+			{
+				dest_file
+					.write_all("#[substitute_item(".as_bytes())
+					.unwrap();
+				dest_inline_file
+					.write_all("substitute!{[".as_bytes())
+					.unwrap();
+			},
+			")]//duplicate_end" =>
+			{
+				dest_file.write_all(")]".as_bytes()).unwrap();
+				dest_inline_file.write_all("]".as_bytes()).unwrap();
+			},
+			"//item_end" =>
+			{
+				dest_inline_file.write_all("}".as_bytes()).unwrap();
+			},
+			_ =>
+			{
+				dest_file.write_all(line.as_bytes()).unwrap();
+				dest_inline_file.write_all(line.as_bytes()).unwrap();
+			},
+		}
+		dest_file.write_all("\n".as_bytes()).unwrap();
+		dest_inline_file.write_all("\n".as_bytes()).unwrap();
+	}
+
+	if let Some(dest_file_path) = destination_for(destination, file_name)
+	{
+		std::fs::write(dest_file_path, dest_file).unwrap();
+	}
+	if let Some(dest_inline_file_path) = destination_for(destination, &inline_file_name)
+	{
+		std::fs::write(dest_inline_file_path, dest_inline_file).unwrap();
+	}
+}
+
+/// A token of an invocation, as found by `scan_tokens`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Scanned
+{
+	/// An identifier, keyword, or number.
+	Ident,
+	/// A group starting with the given delimiter, including its contents.
+	Group(u8),
+	/// A `;`.
+	Semicolon,
+	/// A `=>`.
+	Arrow,
+	/// Any other punctuation or literal.
+	Other,
+}
+
+/// If a comment, string literal, or character literal starts at the given
+/// index, returns the index after it.
+fn skip_non_code(bytes: &[u8], i: usize) -> Option<usize>
+{
+	let find = |from: usize, pattern: &[u8]| {
+		bytes[from..]
+			.windows(pattern.len())
+			.position(|window| window == pattern)
+			.map_or(bytes.len(), |pos| from + pos + pattern.len())
+	};
+	match (bytes[i], bytes.get(i + 1), bytes.get(i + 2))
+	{
+		(b'/', Some(b'/'), _) => Some(find(i, b"\n")),
+		(b'/', Some(b'*'), _) => Some(find(i + 2, b"*/")),
+		(b'"', ..) =>
+		{
+			let mut j = i + 1;
+			while j < bytes.len() && bytes[j] != b'"'
+			{
+				j += if bytes[j] == b'\\' { 2 } else { 1 };
+			}
+			Some((j + 1).min(bytes.len()))
+		},
+		(b'\'', Some(b'\\'), _) => Some(find(i + 3, b"'")),
+		(b'\'', Some(_), Some(b'\'')) => Some(i + 3),
+		_ => None,
+	}
+}
+
+/// Splits the given invocation into tokens, each with the range of its text.
+///
+/// Comments are skipped. Returns None if the delimiters aren't balanced.
+fn scan_tokens(text: &str) -> Option<Vec<(Scanned, Range<usize>)>>
+{
+	let bytes = text.as_bytes();
+	let is_ident = |b: u8| b == b'_' || b.is_ascii_alphanumeric() || !b.is_ascii();
+	let mut tokens = Vec::new();
+	let mut i = 0;
+	while i < bytes.len()
+	{
+		let start = i;
+		if bytes[i].is_ascii_whitespace()
+		{
+			i += 1;
+			continue;
+		}
+		if let Some(end) = skip_non_code(bytes, i)
+		{
+			i = end;
+			if bytes[start] != b'/'
+			{
+				tokens.push((Scanned::Other, start..i));
+			}
+			continue;
+		}
+		let kind = match bytes[i]
+		{
+			b'(' | b'[' | b'{' =>
+			{
+				let mut depth = 0;
+				loop
+				{
+					if let Some(end) = skip_non_code(bytes, i)
+					{
+						i = end;
+						continue;
+					}
+					match bytes.get(i)?
+					{
+						b'(' | b'[' | b'{' => depth += 1,
+						b')' | b']' | b'}' => depth -= 1,
+						_ => (),
+					}
+					i += 1;
+					if depth == 0
+					{
+						break;
+					}
+				}
+				Scanned::Group(bytes[start])
+			},
+			b')' | b']' | b'}' => return None,
+			b if is_ident(b) =>
+			{
+				while i < bytes.len() && is_ident(bytes[i])
+				{
+					i += 1;
+				}
+				Scanned::Ident
+			},
+			b';' =>
+			{
+				i += 1;
+				Scanned::Semicolon
+			},
+			b'=' if bytes.get(i + 1) == Some(&b'>') =>
+			{
+				i += 2;
+				Scanned::Arrow
+			},
+			_ =>
+			{
+				i += 1;
+				Scanned::Other
+			},
+		};
+		tokens.push((kind, start..i));
+	}
+	Some(tokens)
+}
+
+/// Converts the given body of a short syntax invocation into the verbose
+/// syntax.
+///
+/// Returns None if the body isn't the identifiers followed by the substitution
+/// groups, possibly preceded by invocation options and global substitutions,
+/// or uses options that require the short syntax. Also returns None if the
+/// body has doc comments, as they would be lost with the other comments.
+fn short_to_verbose(body: &str) -> Option<String>
+{
+	if body.contains("///")
+	{
+		return None;
+	}
+	let tokens = scan_tokens(body)?;
+	if tokens.iter().any(|(kind, _)| *kind == Scanned::Arrow)
+	{
+		return None;
+	}
+	let statements: Vec<_> = tokens
+		.split(|(kind, _)| *kind == Scanned::Semicolon)
+		.filter(|statement| !statement.is_empty())
+		.collect();
+	let is_substitution = |(kind, _): &(Scanned, Range<usize>)| {
+		matches!(kind, Scanned::Group(b'[') | Scanned::Group(b'{'))
+	};
+	let group_count = statements
+		.iter()
+		.rev()
+		.take_while(|statement| statement.iter().all(is_substitution))
+		.count();
+	if group_count == 0 || group_count == statements.len()
+	{
+		return None;
+	}
+	let (prefix, rest) = statements.split_at(statements.len() - group_count - 1);
+	let text = |range: &Range<usize>| &body[range.clone()];
+	if prefix.iter().any(|statement| {
+		statement[0].0 == Scanned::Ident
+			&& ["continues", "rows_from"].contains(&text(&statement[0].1))
+	})
+	{
+		return None;
+	}
+
+	// The identifiers, each with its parameters
+	let mut identifiers = Vec::new();
+	let mut iter = rest[0].iter().peekable();
+	while let Some((kind, range)) = iter.next()
+	{
+		if *kind != Scanned::Ident || body.as_bytes()[range.start].is_ascii_digit()
+		{
+			return None;
+		}
+		let mut end = range.end;
+		if let Some((Scanned::Group(b'('), parameters)) = iter.peek()
+		{
+			end = parameters.end;
+			iter.next();
+		}
+		identifiers.push(&body[range.start..end]);
+	}
+
+	let mut verbose = String::new();
+	for statement in prefix
+	{
+		verbose.push_str(&body[statement[0].1.start..statement.last().unwrap().1.end]);
+		verbose.push_str(";\n");
+	}
+	for group in &rest[1..]
+	{
+		if group.len() != identifiers.len()
+		{
+			return None;
+		}
+		verbose.push_str("[\n");
+		for (identifier, (_, substitution)) in identifiers.iter().zip(group.iter())
+		{
+			verbose.push_str(identifier);
+			verbose.push(' ');
+			verbose.push_str(text(substitution));
+			verbose.push('\n');
+		}
+		verbose.push_str("]\n");
+	}
+	Some(verbose)
+}
+
+/// Returns a unified diff of the given expected and actual texts, with the
+/// given number of unchanged lines around each change.
+///
+/// Lines only in `expected` are prefixed with '-' and those only in `actual`
+/// with '+'. Returns an empty string if the lines are the same.
+pub fn unified_diff(expected: &str, actual: &str, context: usize) -> String
+{
+	let old: Vec<_> = expected.lines().collect();
+	let new: Vec<_> = actual.lines().collect();
+
+	// The length of the longest common subsequence of the lines from each index
+	let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+	for i in (0..old.len()).rev()
+	{
+		for j in (0..new.len()).rev()
+		{
+			common[i][j] = if old[i] == new[j]
+			{
+				common[i + 1][j + 1] + 1
+			}
+			else
+			{
+				common[i + 1][j].max(common[i][j + 1])
+			};
+		}
+	}
+
+	// Each line with its kind (' ', '-', or '+') and its index in each text
+	let mut lines = Vec::new();
+	let (mut i, mut j) = (0, 0);
+	while i < old.len() || j < new.len()
+	{
+		if i < old.len() && j < new.len() && old[i] == new[j]
+		{
+			lines.push((' ', old[i], i, j));
+			i += 1;
+			j += 1;
+		}
+		else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1])
+		{
+			lines.push(('-', old[i], i, j));
+			i += 1;
+		}
+		else
+		{
+			lines.push(('+', new[j], i, j));
+			j += 1;
+		}
+	}
+
+	let mut result = String::new();
+	let mut start = 0;
+	while let Some(change) = lines[start..].iter().position(|(kind, ..)| *kind != ' ')
+	{
+		// Extend the hunk until more than twice the context of unchanged lines follow
+		let first = (start + change).saturating_sub(context);
+		let mut end = start + change;
+		let mut unchanged = 0;
+		for (index, (kind, ..)) in lines.iter().enumerate().skip(start + change)
+		{
+			if *kind == ' '
+			{
+				unchanged += 1;
+				if unchanged > 2 * context
+				{
+					break;
+				}
+			}
+			else
+			{
+				unchanged = 0;
+				end = index;
+			}
+		}
+		let last = (end + context).min(lines.len() - 1);
+		let hunk = &lines[first..=last];
+		let (_, _, old_start, new_start) = hunk[0];
+		let old_count = hunk.iter().filter(|(kind, ..)| *kind != '+').count();
+		let new_count = hunk.iter().filter(|(kind, ..)| *kind != '-').count();
+		result += &format!(
+			"@@ -{},{} +{},{} @@\n",
+			old_start + 1,
+			old_count,
+			new_start + 1,
+			new_count
+		);
+		for (kind, line, ..) in hunk
+		{
+			result.push(*kind);
+			result += line;
+			result.push('\n');
+		}
+		start = last + 1;
+	}
+	result
+}
+
+/// Runs the binary of the scratch crate in the given directory with the given
+/// features and asserts that it succeeds.
+///
+/// All scratch crates share a target directory, so 'duplicate' and its
+/// dependencies aren't rebuilt for each of them.
+pub fn run_fixture_crate(dir: &str, features: &[&str])
+{
+	let output = std::process::Command::new("cargo")
+		.arg("run")
+		.arg("--features")
+		.arg(features.join(","))
+		.env(
+			"CARGO_TARGET_DIR",
+			Path::new(env!("CARGO_MANIFEST_DIR")).join("target/fixtures"),
+		)
+		.current_dir(dir)
+		.output()
+		.unwrap();
+	assert!(
+		output.status.success(),
+		"Failed to run '{}' (features: {:?}): {:?}",
+		dir,
+		features,
+		output
+	);
+}
+
 /// Manages the setting up and running of expansion tests using macrotest
 ///
 /// Expansion test live in a home directory. This directory has a single
@@ -43,6 +717,40 @@ const fn get_features() -> [&'static str; NR_FEATURES]
 /// Various rules can be configured, e.g. a simple copy of files, or duplicating
 /// the source files a number of times in the testing directory with various
 /// names.
+///
+/// The actions provided by the tester support files that are specific to some
+/// features being enabled or disabled, or to the attribute or inline variant
+/// of a test (see `select_file`), such that e.g. the expected output of a test
+/// can differ depending on the features.
+///
+/// ### Prelude
+/// If the home directory has a `prelude.rs`, the actions generating test files
+/// from source files (e.g. `duplicate_for_inline`) prepend its contents to
+/// them, such that items shared by the tests, like `use duplicate::*;`, aren't
+/// repeated in every source file. Since the prelude is expanded with the rest
+/// of the file, it is part of the expected outputs too. Source files with a
+/// `//no_prelude` line are left as they are, e.g. because they test imports.
+///
+/// Expansion tests generate their outputs anew and fail with a unified diff
+/// against the expected output of each test that differs (see
+/// `unified_diff`), while the expected outputs are type-checked concurrently.
+///
+/// ### Blessing
+/// When the 'DUPLICATE_BLESS' environment variable is set, expansion tests
+/// don't compare against the expected outputs but overwrite them with the
+/// actual ones, e.g. after changing the output of many tests at once:
+///
+/// 1. Run e.g. `DUPLICATE_BLESS=1 cargo test --test tests -- --nocapture` with
+///    each relevant set of features, as outputs may be specific to them.
+/// 2. Read the printed summary of which expected files changed.
+/// 3. Review the changes with `git diff` before committing them.
+///
+/// Outputs are written back to the files in the source directories they were
+/// copied from, so all the variants generated from a file (e.g. with the
+/// 'inline_', 'short_', or 'verbose_' prefix) must agree. Otherwise, the file
+/// isn't blessed and a file specific to the variant must be added manually.
+/// Nothing is blessed if any test fails to compile, and error tests, whose
+/// expected outputs are excerpts, are never blessed.
 pub struct ExpansionTester<'a>
 {
 	/// The home directory for the tests
@@ -55,6 +763,9 @@ pub struct ExpansionTester<'a>
 
 	/// Whether this tester is testing errors, i.e. that expansions should fail
 	error_tests: bool,
+
+	/// Environment variables to set while expanding, as names and values
+	env: Vec<(&'a str, &'a str)>,
 }
 
 impl<'a> ExpansionTester<'a>
@@ -67,6 +778,7 @@ impl<'a> ExpansionTester<'a>
 			testing_dir,
 			source_dirs: Vec::new(),
 			error_tests: false,
+			env: Vec::new(),
 		}
 	}
 
@@ -80,6 +792,7 @@ impl<'a> ExpansionTester<'a>
 			testing_dir,
 			source_dirs: Vec::new(),
 			error_tests: true,
+			env: Vec::new(),
 		}
 	}
 
@@ -95,6 +808,13 @@ impl<'a> ExpansionTester<'a>
 		self.source_dirs.push((dir, actions));
 	}
 
+	/// Sets an environment variable for the expansions only, leaving the
+	/// environment of the tests themselves untouched.
+	pub fn set_env(&mut self, name: &'a str, value: &'a str)
+	{
+		self.env.push((name, value));
+	}
+
 	/// Executes the tests including first setting up the testing directory.
 	pub fn execute_tests(&self)
 	{
@@ -142,6 +862,17 @@ impl<'a> ExpansionTester<'a>
 			}
 			args.push(features.as_str());
 		}
+		// Passed to the expanding cargo through its '[env]' configuration
+		let env: Vec<_> = self
+			.env
+			.iter()
+			.map(|(name, value)| format!("env.{}={:?}", name, value))
+			.collect();
+		for var in env.iter()
+		{
+			args.push("--config");
+			args.push(var.as_str());
+		}
 
 		if self.error_tests
 		{
@@ -149,14 +880,337 @@ impl<'a> ExpansionTester<'a>
 				testing_dir + "/*.rs",
 				args.as_slice(),
 			);
+			return;
 		}
-		else
+
+		let bless = std::env::var_os(BLESS_VAR).is_some();
+		let previous = Self::take_expected(&testing_dir);
+		// The expected outputs are type-checked while the expansions run, as the
+		// checking crate has its own target directory, so cargo doesn't make either
+		// wait for the other
+		let checked = (!bless).then(|| {
+			let testing_dir = testing_dir.clone();
+			std::thread::spawn(move || Self::check_expected_crate(&testing_dir))
+		});
+		// The outputs are generated anew and compared afterwards, so the differences
+		// can be shown as diffs
+		let expanded = std::panic::catch_unwind(|| {
+			duplicate_macrotest::expand_args(testing_dir.clone() + "/*.rs", args.as_slice())
+		});
+
+		if bless
 		{
-			duplicate_macrotest::expand_without_refresh_args(
-				testing_dir + "/*.rs",
-				args.as_slice(),
+			assert!(
+				expanded.is_ok(),
+				"Refusing to bless the expected outputs in '{}' since expansion failed (see \
+				 above).",
+				self.dir
 			);
+			let previous: Vec<_> = previous.into_iter().map(|(path, _)| path).collect();
+			Self::bless(&testing_dir, &previous);
+			Self::keep_expected(&testing_dir);
+			Self::check_expected_crate(&testing_dir);
+			return;
+		}
+
+		let mismatches = Self::compare_expected(&testing_dir, &previous);
+		if let Some(Err(panic)) = checked.map(std::thread::JoinHandle::join)
+		{
+			std::panic::resume_unwind(panic);
+		}
+		assert!(
+			mismatches.is_empty(),
+			"{} expansion test(s) in '{}' differ from their expected outputs:\n{}",
+			mismatches.len(),
+			self.dir,
+			mismatches.join("\n")
+		);
+		if let Err(panic) = expanded
+		{
+			std::panic::resume_unwind(panic);
+		}
+	}
+
+	/// Removes the expected outputs from the given testing directory, such that
+	/// macrotest generates them anew, returning their paths and contents.
+	///
+	/// They are kept in the crate type-checking them (see `check_expected`).
+	fn take_expected(testing_dir: &str) -> Vec<(PathBuf, String)>
+	{
+		let kept = Self::keep_expected(testing_dir);
+		for (path, _) in kept.iter()
+		{
+			std::fs::remove_file(path).unwrap();
+		}
+		kept
+	}
+
+	/// Copies the expected outputs in the given testing directory to the crate
+	/// type-checking them (see `check_expected`), returning their paths and
+	/// contents.
+	fn keep_expected(testing_dir: &str) -> Vec<(PathBuf, String)>
+	{
+		let kept_dir = Path::new(testing_dir)
+			.join(CHECK_EXPECTED_DIR)
+			.join("expected");
+		std::fs::create_dir_all(&kept_dir).unwrap();
+		let mut kept = Vec::new();
+		for file in std::fs::read_dir(testing_dir).unwrap()
+		{
+			let path = file.unwrap().path();
+			if path.is_file() && path.to_str().unwrap().ends_with(EXPANDED_EXTENSION)
+			{
+				let content = std::fs::read_to_string(&path).unwrap();
+				std::fs::write(kept_dir.join(path.file_name().unwrap()), &content).unwrap();
+				kept.push((path, content));
+			}
+		}
+		kept
+	}
+
+	/// Compares the outputs generated in the given testing directory to the
+	/// previous expected outputs (see `take_expected`), returning a
+	/// description of each output that differs, with the test file it is for,
+	/// the source file it was copied from, and a unified diff.
+	fn compare_expected(testing_dir: &str, previous: &[(PathBuf, String)]) -> Vec<String>
+	{
+		let copied_from = COPIED_FROM.lock().unwrap_or_else(|err| err.into_inner());
+		let mut mismatches = Vec::new();
+		let mut outputs: Vec<_> = std::fs::read_dir(testing_dir)
+			.unwrap()
+			.map(|file| file.unwrap().path())
+			.filter(|path| path.is_file() && path.to_str().unwrap().ends_with(EXPANDED_EXTENSION))
+			.collect();
+		outputs.sort();
+		for path in outputs
+		{
+			let test = path.to_str().unwrap().replace(EXPANDED_EXTENSION, ".rs");
+			let expected = match previous.iter().find(|(p, _)| *p == path)
+			{
+				Some((_, expected)) => expected,
+				None =>
+				{
+					mismatches.push(format!("{} - expected output not found", test));
+					continue;
+				},
+			};
+			let actual = std::fs::read_to_string(&path).unwrap();
+			if !actual.lines().eq(expected.lines())
+			{
+				let source = copied_from
+					.iter()
+					.rev()
+					.find(|(copy, _)| *copy == path)
+					.map_or(path.clone(), |(_, source)| source.clone());
+				mismatches.push(format!(
+					"{} - different from {:?}:\n{}",
+					test,
+					source,
+					unified_diff(expected, &actual, DIFF_CONTEXT)
+				));
+			}
 		}
+		mismatches
+	}
+
+	/// Writes the outputs generated in the given testing directory back to the
+	/// source files that the previous expected outputs (see `take_expected`)
+	/// were copied from, and prints a summary of the changed source files.
+	///
+	/// Panics, after writing the rest, if the variants of a file disagree or a
+	/// test has no expected output to bless.
+	fn bless(testing_dir: &str, previous: &[PathBuf])
+	{
+		let copied_from = COPIED_FROM.lock().unwrap_or_else(|err| err.into_inner());
+		// Each source file with the outputs of the variants copied from it
+		let mut sources: Vec<(PathBuf, Vec<(PathBuf, String)>)> = Vec::new();
+		let mut problems = Vec::new();
+		for file in std::fs::read_dir(testing_dir).unwrap()
+		{
+			let path = file.unwrap().path();
+			if !path.is_file() || !path.to_str().unwrap().ends_with(EXPANDED_EXTENSION)
+			{
+				continue;
+			}
+			let source = copied_from
+				.iter()
+				.rev()
+				.find(|(copy, _)| *copy == path)
+				.map(|(_, source)| source.clone());
+			let source = match source
+			{
+				Some(source) if previous.contains(&path) => source,
+				_ =>
+				{
+					problems.push(format!("{:?} has no expected output to bless.", path));
+					continue;
+				},
+			};
+			let output = std::fs::read_to_string(&path).unwrap();
+			match sources.iter_mut().find(|(s, _)| *s == source)
+			{
+				Some((_, variants)) => variants.push((path, output)),
+				None => sources.push((source, vec![(path, output)])),
+			}
+		}
+
+		let mut blessed = Vec::new();
+		for (source, variants) in sources
+		{
+			let (_, output) = &variants[0];
+			if variants.iter().any(|(_, o)| !o.lines().eq(output.lines()))
+			{
+				problems.push(format!(
+					"{:?} wasn't blessed since its variants differ: {:?}",
+					source,
+					variants.iter().map(|(path, _)| path).collect::<Vec<_>>()
+				));
+			}
+			else if !std::fs::read_to_string(&source)
+				.unwrap()
+				.lines()
+				.eq(output.lines())
+			{
+				std::fs::write(&source, output).unwrap();
+				blessed.push(source);
+			}
+		}
+
+		println!(
+			"Blessed {} expected output(s) for '{}':",
+			blessed.len(),
+			testing_dir
+		);
+		for source in blessed
+		{
+			println!("\t{:?}", source);
+		}
+		assert!(problems.is_empty(), "{}", problems.join("\n"));
+	}
+
+	/// Type-checks the expected outputs added by `check_expected` to the crate
+	/// in the given testing directory, if any.
+	///
+	/// The crate includes each expected output in its own module, so they
+	/// can't conflict.
+	fn check_expected_crate(testing_dir: &str)
+	{
+		let crate_dir = Path::new(testing_dir).join(CHECK_EXPECTED_DIR);
+		let lib_path = crate_dir.join("src/lib.rs");
+		let modules = match std::fs::read_to_string(&lib_path)
+		{
+			Ok(modules) => modules,
+			Err(_) => return,
+		};
+		// Files specific to the enabled features are added in addition to the ones
+		// they replace
+		let mut lines: Vec<_> = modules.lines().collect();
+		lines.sort();
+		lines.dedup();
+		std::fs::write(&lib_path, lines.join("\n")).unwrap();
+
+		let features = FEATURES
+			.iter()
+			.map(|f| format!("{:?}", f))
+			.collect::<Vec<_>>()
+			.join(", ");
+		std::fs::write(
+			crate_dir.join("Cargo.toml"),
+			format!(
+				concat!(
+					"[package]\n",
+					"name = \"check_expected\"\n",
+					"version = \"0.0.0\"\n",
+					"edition = \"2021\"\n",
+					"\n",
+					"[dependencies]\n",
+					"duplicate = {{ path = {:?}, default-features = false, features = [{}] }}\n",
+				),
+				env!("CARGO_MANIFEST_DIR"),
+				features
+			),
+		)
+		.unwrap();
+
+		let output = std::process::Command::new("cargo")
+			.arg("check")
+			.arg("--quiet")
+			// Shared by all testing directories, so the crate isn't rebuilt every time
+			.env(
+				"CARGO_TARGET_DIR",
+				Path::new(env!("CARGO_MANIFEST_DIR")).join("target/check_expected"),
+			)
+			.env("RUSTFLAGS", "--cap-lints allow")
+			.current_dir(&crate_dir)
+			.output()
+			.unwrap();
+		assert!(
+			output.status.success(),
+			"Expected outputs in '{}' don't compile (mark their source files with '{}' if \
+			 intended):\n{}",
+			testing_dir,
+			EXPECTED_UNCHECKED,
+			String::from_utf8_lossy(&output.stderr)
+		);
+	}
+
+	/// Generates an action that adds the expected output of the given source
+	/// file to a crate in the testing directory, which is type-checked after
+	/// the expansion tests, so expected outputs that don't compile are caught.
+	///
+	/// The expected output is the file in the testing directory with the same
+	/// name as the source file but the `.expanded.rs` extension. Source files
+	/// with a line starting with `//expected_unchecked` (followed by the
+	/// reason) are skipped, e.g. because their expected output intentionally
+	/// references undefined items.
+	pub fn check_expected() -> Box<dyn Fn(&Path, &dyn AsRef<Path>)>
+	{
+		Box::new(|file, destination| {
+			let (file_name, _) = match select_file(file.file_name().unwrap())
+			{
+				Some(selected) => selected,
+				None => return,
+			};
+			if BufReader::new(File::open(file).unwrap())
+				.lines()
+				.any(|line| line.unwrap().trim().starts_with(EXPECTED_UNCHECKED))
+			{
+				return;
+			}
+			let stem = Path::new(&file_name)
+				.file_stem()
+				.unwrap()
+				.to_str()
+				.unwrap()
+				.to_owned();
+			let module: String = stem
+				.chars()
+				.map(|c| {
+					if c.is_alphanumeric()
+					{
+						c
+					}
+					else
+					{
+						'_'
+					}
+				})
+				.collect();
+
+			let src_dir = destination.as_ref().join(CHECK_EXPECTED_DIR).join("src");
+			std::fs::create_dir_all(&src_dir).unwrap();
+			let mut lib = std::fs::OpenOptions::new()
+				.create(true)
+				.append(true)
+				.open(src_dir.join("lib.rs"))
+				.unwrap();
+			writeln!(
+				lib,
+				"mod expected_{} {{ include!(\"../expected/{}.expanded.rs\"); }}",
+				module, stem
+			)
+			.unwrap();
+		})
 	}
 
 	/// Generates an action that copies the file given to the testing
@@ -176,12 +1230,56 @@ impl<'a> ExpansionTester<'a>
 		let prefix = OsString::from(prefix);
 		let postfix = OsString::from(postfix);
 		Box::new(move |file, destination| {
-			let mut destination_file = destination.as_ref().to_path_buf();
 			let mut file_name = prefix.clone();
 			file_name.push(file.file_name().unwrap());
 			file_name.push(postfix.clone());
-			destination_file.push(file_name);
-			std::fs::copy(file, &destination_file).unwrap();
+			if let Some(destination_file) = destination_for(destination.as_ref(), &file_name)
+			{
+				std::fs::copy(file, &destination_file).unwrap();
+				COPIED_FROM
+					.lock()
+					.unwrap_or_else(|err| err.into_inner())
+					.push((destination_file, file.to_path_buf()));
+			}
+		})
+	}
+
+	/// Generates an action that writes the given section of the given combined
+	/// expectation file of an error test (see `expected_section`) to the
+	/// testing directory with the given prefix and postfix added to its name.
+	///
+	/// Nothing is written if the file doesn't have the section, while it
+	/// panics if the file only has the section for other features, so an
+	/// expectation for the enabled features can't be missed.
+	pub fn copy_section_with_prefix_postfix<'b>(
+		section: &'b str,
+		prefix: &str,
+		postfix: &str,
+	) -> Box<dyn 'b + Fn(&Path, &dyn AsRef<Path>)>
+	{
+		let prefix = OsString::from(prefix);
+		let postfix = OsString::from(postfix);
+		Box::new(move |file, destination| {
+			let content = std::fs::read_to_string(file).unwrap();
+			let expected = match expected_section(&content, section)
+			{
+				Some(expected) => expected,
+				None if declares_section(&content, section) =>
+				{
+					panic!(
+						"{:?} has no '{}' section for the enabled features.",
+						file, section
+					)
+				},
+				None => return,
+			};
+			let mut file_name = prefix.clone();
+			file_name.push(file.file_name().unwrap());
+			file_name.push(postfix.clone());
+			if let Some(destination_file) = destination_for(destination.as_ref(), &file_name)
+			{
+				std::fs::write(&destination_file, expected).unwrap();
+			}
 		})
 	}
 
@@ -255,61 +1353,76 @@ impl<'a> ExpansionTester<'a>
 	) -> Box<dyn '_ + Fn(&Path, &dyn AsRef<Path>)>
 	{
 		Box::new(move |file, destination| {
-			let mut inline_file_name = OsString::from("inline_");
-			inline_file_name.push(prefix);
-			inline_file_name.push(file.file_name().unwrap());
 			let mut new_file_name = OsString::from(prefix);
 			new_file_name.push(file.file_name().unwrap());
+			let lines = BufReader::new(File::open(file).unwrap()).lines();
+			write_with_inline(
+				lines.map(Result::unwrap),
+				&new_file_name,
+				destination.as_ref(),
+			);
+		})
+	}
 
-			let mut dest_file_path = destination.as_ref().to_path_buf();
-			let mut dest_inline_file_path = destination.as_ref().to_path_buf();
-
-			dest_file_path.push(new_file_name);
-			dest_inline_file_path.push(inline_file_name);
-
-			let mut dest_file = File::create(dest_file_path).unwrap();
-			let mut dest_inline_file = File::create(dest_inline_file_path).unwrap();
+	/// Generates an action that converts the short syntax invocations of the
+	/// given file (as used by `duplicate_for_inline`) into the verbose syntax
+	/// and creates the attribute and inline versions of the result in the
+	/// testing directory, prefixed with 'verbose_'. The expected output of the
+	/// file therefore also needs to be copied with that prefix.
+	///
+	/// Only invocations whose short syntax is the identifiers followed by the
+	/// substitution groups, possibly preceded by invocation options and global
+	/// substitutions, are converted. Nothing is created for files without such
+	/// invocations or whose names start with 'short_' or 'verbose_', which
+	/// have hand-written variants.
+	pub fn duplicate_for_verbose() -> Box<dyn Fn(&Path, &dyn AsRef<Path>)>
+	{
+		Box::new(|file, destination| {
+			let file_name = file.file_name().unwrap();
+			let name = file_name.to_str().unwrap();
+			if name.starts_with("short_") || name.starts_with("verbose_")
+			{
+				return;
+			}
 
+			let mut lines = Vec::new();
+			let mut invocation: Option<String> = None;
+			let mut converted = false;
 			for line in BufReader::new(File::open(file).unwrap()).lines()
 			{
 				let line = line.unwrap();
-				let line = line.trim();
-
-				match line
+				match (&mut invocation, line.trim())
 				{
-					"#[duplicate_item(" =>
+					(None, "#[duplicate_item(") => invocation = Some(String::new()),
+					(Some(body), ")]//duplicate_end") =>
 					{
-						dest_file.write_all("#[duplicate_item(".as_bytes()).unwrap();
-						dest_inline_file
-							.write_all("duplicate!{[".as_bytes())
-							.unwrap();
-					},
-					"#[substitute_item(" =>
-					{
-						dest_file
-							.write_all("#[substitute_item(".as_bytes())
-							.unwrap();
-						dest_inline_file
-							.write_all("substitute!{[".as_bytes())
-							.unwrap();
-					},
-					")]//duplicate_end" =>
-					{
-						dest_file.write_all(")]".as_bytes()).unwrap();
-						dest_inline_file.write_all("]".as_bytes()).unwrap();
-					},
-					"//item_end" =>
-					{
-						dest_inline_file.write_all("}".as_bytes()).unwrap();
+						match short_to_verbose(body)
+						{
+							Some(verbose) =>
+							{
+								lines.extend(verbose.lines().map(String::from));
+								converted = true;
+							},
+							None => lines.extend(body.lines().map(String::from)),
+						}
+						invocation = None;
 					},
-					_ =>
+					(Some(body), _) =>
 					{
-						dest_file.write_all(line.as_bytes()).unwrap();
-						dest_inline_file.write_all(line.as_bytes()).unwrap();
+						body.push_str(&line);
+						body.push('\n');
+						continue;
 					},
+					_ => (),
 				}
-				dest_file.write_all("\n".as_bytes()).unwrap();
-				dest_inline_file.write_all("\n".as_bytes()).unwrap();
+				lines.push(line);
+			}
+
+			if converted
+			{
+				let mut verbose_file_name = OsString::from("verbose_");
+				verbose_file_name.push(file_name);
+				write_with_inline(lines.into_iter(), &verbose_file_name, destination.as_ref());
 			}
 		})
 	}
@@ -324,6 +1437,13 @@ impl<'a> ExpansionTester<'a>
 	/// Sets up and runs tests in a specific directory using our standard test
 	/// setup.
 	pub fn run_default_test_setup_errors(home_dir: &str, test_subdir: &str, test_errors: bool)
+	{
+		ExpansionTester::default_test_setup(home_dir, test_subdir, test_errors).execute_tests();
+	}
+
+	/// Sets up tests in a specific directory using our standard test setup,
+	/// without running them.
+	pub fn default_test_setup(home_dir: &'a str, test_subdir: &'a str, test_errors: bool) -> Self
 	{
 		let mut test = if test_errors
 		{
@@ -333,12 +1453,22 @@ impl<'a> ExpansionTester<'a>
 		{
 			ExpansionTester::new(home_dir, test_subdir)
 		};
-		test.add_source_dir("from", vec![ExpansionTester::duplicate_for_inline()]);
+		let mut from_actions = vec![
+			ExpansionTester::duplicate_for_inline(),
+			ExpansionTester::duplicate_for_verbose(),
+		];
+		if !test_errors
+		{
+			from_actions.push(ExpansionTester::check_expected());
+		}
+		test.add_source_dir("from", from_actions);
 		test.add_source_dir(
 			"expected",
 			vec![
 				ExpansionTester::copy(),
 				ExpansionTester::copy_with_prefix("inline_"),
+				ExpansionTester::copy_with_prefix("verbose_"),
+				ExpansionTester::copy_with_prefix("inline_verbose_"),
 			],
 		);
 		test.add_source_dir(
@@ -350,6 +1480,22 @@ impl<'a> ExpansionTester<'a>
 				ExpansionTester::copy_with_prefix("verbose_"),
 			],
 		);
-		test.execute_tests();
+		test
 	}
 }
+
+#[test]
+fn unified_diff_hunks()
+{
+	assert_eq!(unified_diff("a\nb\nc", "a\nb\nc\n", 3), "");
+	assert_eq!(
+		unified_diff("a\nb\nc\nd", "a\nx\nc\nd\ne", 1),
+		"@@ -1,4 +1,5 @@\n a\n-b\n+x\n c\n d\n+e\n"
+	);
+	// Changes further apart than twice the context are in separate hunks
+	assert_eq!(
+		unified_diff("1\n2\n3\n4\n5\n6\n7", "0\n2\n3\n4\n5\n6\n8", 1),
+		"@@ -1,2 +1,2 @@\n-1\n+0\n 2\n@@ -6,2 +6,2 @@\n 6\n-7\n+8\n"
+	);
+	assert_eq!(unified_diff("", "a", 3), "@@ -1,0 +1,1 @@\n+a\n");
+}